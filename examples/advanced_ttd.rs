@@ -12,22 +12,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Example dialogue inputs (2 speakers)
     let inputs = vec![
-        TTDInput {
-            text: "I started reading a book last night, couldn’t stop.".to_string(),
-            voice_id: voices::all_voices::ALICE.voice_id.to_string(),
-        },
-        TTDInput {
-            text: "That’s the best feeling, when pages pull you in.".to_string(),
-            voice_id: voices::all_voices::ANTONI.voice_id.to_string(),
-        },
-        TTDInput {
-            text: " It felt like living another life for a while.".to_string(),
-            voice_id: voices::all_voices::ALICE.voice_id.to_string(),
-        },
-        TTDInput {
-            text: "Books do that better than anything else.".to_string(),
-            voice_id: voices::all_voices::ANTONI.voice_id.to_string(),
-        },
+        TTDInput::new(
+            voices::all_voices::ALICE,
+            "I started reading a book last night, couldn’t stop.",
+        ),
+        TTDInput::new(
+            voices::all_voices::ANTONI,
+            "That’s the best feeling, when pages pull you in.",
+        ),
+        TTDInput::new(
+            voices::all_voices::ALICE,
+            " It felt like living another life for a while.",
+        ),
+        TTDInput::new(
+            voices::all_voices::ANTONI,
+            "Books do that better than anything else.",
+        ),
     ];
 
     // Custom speech settings
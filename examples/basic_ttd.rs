@@ -1,4 +1,4 @@
-use elevenlabs_ttd::{ElevenLabsTTDClient, TTDInput, voices};
+use elevenlabs_ttd::{ElevenLabsTTDClient, dialogue};
 use std::env;
 
 #[tokio::main]
@@ -11,16 +11,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let client = ElevenLabsTTDClient::new(api_key);
 
     // Example dialogue inputs (2 speakers)
-    let inputs = vec![
-        TTDInput {
-            text: "I saw the sky this morning, it looked like fire.".to_string(),
-            voice_id: voices::all_voices::ARNOLD.voice_id.to_string(),
-        },
-        TTDInput {
-            text: "I noticed that too, the sunrise was unreal.".to_string(),
-            voice_id: voices::all_voices::IVANA.voice_id.to_string(),
-        },
-    ];
+    let inputs = dialogue! {
+        ARNOLD: "I saw the sky this morning, it looked like fire.";
+        IVANA: "I noticed that too, the sunrise was unreal.";
+    };
 
     // Convert text to dialogue audio
     let audio = client.text_to_dialogue(inputs).execute().await?;
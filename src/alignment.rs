@@ -0,0 +1,187 @@
+//! Forced alignment of generated audio against its script, for captions.
+//! Enabled with the `alignment` feature.
+
+use std::fmt::Write;
+
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+use crate::ElevenLabsTTDClient;
+use crate::error::ElevenLabsTTDError;
+use crate::version::{ApiVersion, EndpointFamily};
+
+/// Words per caption cue when no sentence ends sooner
+pub const DEFAULT_WORDS_PER_CUE: usize = 8;
+
+/// A word with its position in the audio, in seconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlignedWord {
+    pub text: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// Word-level timestamps of a script in an audio file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alignment {
+    pub words: Vec<AlignedWord>,
+    // Average alignment loss; higher means the audio matched the script less well.
+    #[serde(default)]
+    pub loss: Option<f64>,
+}
+
+/// One caption: consecutive words shown together
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+impl Alignment {
+    /// Group words into cues of at most `max_words`, also breaking after
+    /// sentence-ending punctuation
+    pub fn cues(&self, max_words: usize) -> Vec<Cue> {
+        let mut cues = Vec::new();
+        let mut current: Vec<&AlignedWord> = Vec::new();
+
+        let words = self.words.iter().filter(|w| !w.text.trim().is_empty());
+        for word in words {
+            current.push(word);
+            let sentence_end = word.text.trim_end().ends_with(['.', '!', '?']);
+            if current.len() >= max_words.max(1) || sentence_end {
+                cues.push(cue(&current));
+                current.clear();
+            }
+        }
+        if !current.is_empty() {
+            cues.push(cue(&current));
+        }
+        cues
+    }
+
+    /// Captions in SubRip (`.srt`) format
+    pub fn to_srt(&self) -> String {
+        let mut out = String::new();
+        for (i, cue) in self.cues(DEFAULT_WORDS_PER_CUE).iter().enumerate() {
+            let _ = write!(
+                out,
+                "{}\n{} --> {}\n{}\n\n",
+                i + 1,
+                timestamp(cue.start, ','),
+                timestamp(cue.end, ','),
+                cue.text
+            );
+        }
+        out
+    }
+
+    /// Captions in WebVTT (`.vtt`) format
+    pub fn to_vtt(&self) -> String {
+        let mut out = String::from("WEBVTT\n\n");
+        for cue in self.cues(DEFAULT_WORDS_PER_CUE) {
+            let _ = write!(
+                out,
+                "{} --> {}\n{}\n\n",
+                timestamp(cue.start, '.'),
+                timestamp(cue.end, '.'),
+                cue.text
+            );
+        }
+        out
+    }
+}
+
+fn cue(words: &[&AlignedWord]) -> Cue {
+    Cue {
+        start: words[0].start,
+        end: words[words.len() - 1].end,
+        text: words
+            .iter()
+            .map(|w| w.text.trim())
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+/// `HH:MM:SS,mmm` (SRT) or `HH:MM:SS.mmm` (WebVTT)
+fn timestamp(seconds: f64, separator: char) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        separator,
+        millis % 1000
+    )
+}
+
+impl ElevenLabsTTDClient {
+    /// Align audio against the script it was generated from (`POST /v1/forced-alignment`).
+    /// `file_name` tells the API the audio type, e.g. `scene.mp3`.
+    pub async fn align(
+        &self,
+        audio: &[u8],
+        file_name: &str,
+        script: &str,
+    ) -> Result<Alignment, ElevenLabsTTDError> {
+        let form = reqwest::multipart::Form::new()
+            .part(
+                "file",
+                reqwest::multipart::Part::bytes(audio.to_vec()).file_name(file_name.to_string()),
+            )
+            .text("text", script.to_string());
+
+        let url = self.api_url(
+            EndpointFamily::ForcedAlignment,
+            ApiVersion::V1,
+            "/forced-alignment",
+        );
+        self.request(Method::POST, &url)
+            .multipart(form)
+            .send_json()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alignment() -> Alignment {
+        let word = |text: &str, start: f64, end: f64| AlignedWord {
+            text: text.to_string(),
+            start,
+            end,
+        };
+        Alignment {
+            words: vec![
+                word("Hello", 0.0, 0.4),
+                word(" ", 0.4, 0.5),
+                word("there.", 0.5, 1.0),
+                word("Who", 1.5, 1.7),
+                word("knocks?", 1.7, 62.25),
+            ],
+            loss: None,
+        }
+    }
+
+    #[test]
+    fn test_cues_break_at_sentences() {
+        let cues = alignment().cues(DEFAULT_WORDS_PER_CUE);
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].text, "Hello there.");
+        assert_eq!(cues[1].start, 1.5);
+    }
+
+    #[test]
+    fn test_srt_and_vtt_output() {
+        let srt = alignment().to_srt();
+        assert!(srt.starts_with("1\n00:00:00,000 --> 00:00:01,000\nHello there.\n\n2\n"));
+        assert!(srt.contains("00:00:01,500 --> 00:01:02,250"));
+
+        let vtt = alignment().to_vtt();
+        assert!(vtt.starts_with("WEBVTT\n\n00:00:00.000 --> 00:00:01.000\n"));
+    }
+}
@@ -0,0 +1,510 @@
+//! Handles for account resources (voices, history) grouped by endpoint family
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ElevenLabsTTDClient;
+use crate::endpoint::GetDefaultVoiceSettings;
+use crate::error::ElevenLabsTTDError;
+use crate::pagination::{Page, Paginated};
+use crate::types::{
+    HistoryItem, SharedVoice, Voice, VoiceId, VoicePreview, VoiceSample, VoiceSettings,
+};
+use crate::version::{ApiVersion, EndpointFamily};
+use reqwest::Method;
+
+/// Page size used when listing resources
+pub const DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// Sort order of list results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+/// Filters for [`VoicesApi::list_with`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VoiceListParams {
+    // Matches against name, description, labels and category.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+    // One of: premade | cloned | generated | professional.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    // One of: personal | community | default | workspace | non-default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voice_type: Option<String>,
+    // Field to sort by, "created_at_unix" or "name".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_direction: Option<SortDirection>,
+    // Voices fetched per request, at most 100. Default to 100.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<u32>,
+}
+
+impl VoiceListParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn search(mut self, search: impl Into<String>) -> Self {
+        self.search = Some(search.into());
+        self
+    }
+
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    pub fn voice_type(mut self, voice_type: impl Into<String>) -> Self {
+        self.voice_type = Some(voice_type.into());
+        self
+    }
+
+    pub fn sort(mut self, field: impl Into<String>, direction: SortDirection) -> Self {
+        self.sort = Some(field.into());
+        self.sort_direction = Some(direction);
+        self
+    }
+
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+}
+
+/// Filters for [`HistoryApi::list_with`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HistoryListParams {
+    // Matches against the generated text.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+    // Only generations made with this voice.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voice_id: Option<String>,
+    // Only generations made with this model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_id: Option<String>,
+    // Newest first by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_direction: Option<SortDirection>,
+    // Items fetched per request, at most 1000. Default to 100.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<u32>,
+}
+
+impl HistoryListParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn search(mut self, search: impl Into<String>) -> Self {
+        self.search = Some(search.into());
+        self
+    }
+
+    pub fn voice_id(mut self, voice_id: impl Into<String>) -> Self {
+        self.voice_id = Some(voice_id.into());
+        self
+    }
+
+    pub fn model_id(mut self, model_id: impl Into<String>) -> Self {
+        self.model_id = Some(model_id.into());
+        self
+    }
+
+    pub fn sort_direction(mut self, direction: SortDirection) -> Self {
+        self.sort_direction = Some(direction);
+        self
+    }
+
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+}
+
+/// Changes applied by [`VoicesApi::edit`]
+#[derive(Debug, Clone)]
+pub struct VoiceEditParams {
+    // The API requires the name on every edit, pass the current one to keep it.
+    pub name: String,
+    pub description: Option<String>,
+    // Replaces all existing labels when set.
+    pub labels: Option<HashMap<String, String>>,
+    // Additional samples to train the voice on.
+    pub samples: Vec<VoiceSample>,
+}
+
+impl VoiceEditParams {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            labels: None,
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn description<S: Into<String>>(mut self, description: S) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn labels(mut self, labels: HashMap<String, String>) -> Self {
+        self.labels = Some(labels);
+        self
+    }
+
+    pub fn sample(mut self, sample: VoiceSample) -> Self {
+        self.samples.push(sample);
+        self
+    }
+}
+
+/// Voice endpoints, see [`ElevenLabsTTDClient::voices`]
+pub struct VoicesApi {
+    client: ElevenLabsTTDClient,
+}
+
+/// History endpoints, see [`ElevenLabsTTDClient::history`]
+pub struct HistoryApi {
+    client: ElevenLabsTTDClient,
+}
+
+#[derive(Deserialize)]
+struct VoicesPage {
+    voices: Vec<Voice>,
+    next_page_token: Option<String>,
+    #[serde(default)]
+    has_more: bool,
+}
+
+#[derive(Deserialize)]
+struct CreatedVoice {
+    voice_id: VoiceId,
+}
+
+#[derive(Deserialize)]
+struct VoicePreviews {
+    previews: Vec<VoicePreview>,
+}
+
+#[derive(Deserialize)]
+struct SharedVoicesPage {
+    voices: Vec<SharedVoice>,
+    #[serde(default)]
+    has_more: bool,
+}
+
+#[derive(Deserialize)]
+struct HistoryPage {
+    history: Vec<HistoryItem>,
+    last_history_item_id: Option<String>,
+    #[serde(default)]
+    has_more: bool,
+}
+
+impl VoicesApi {
+    pub(crate) fn new(client: ElevenLabsTTDClient) -> Self {
+        Self { client }
+    }
+
+    /// All voices available to the account (`GET /v2/voices`)
+    pub fn list(&self) -> Paginated<Voice> {
+        self.list_with(VoiceListParams::default())
+    }
+
+    /// Voices matching `params`
+    pub fn list_with(&self, mut params: VoiceListParams) -> Paginated<Voice> {
+        params.page_size.get_or_insert(DEFAULT_PAGE_SIZE);
+        let client = self.client.clone();
+        Paginated::new(move |cursor: Option<String>| {
+            let client = client.clone();
+            let params = params.clone();
+            async move {
+                let mut request = client
+                    .request(
+                        Method::GET,
+                        &client.api_url(EndpointFamily::Voices, ApiVersion::V2, "/voices"),
+                    )
+                    .query(&params);
+                if let Some(token) = cursor {
+                    request = request.query(&[("next_page_token", token)]);
+                }
+
+                let page: VoicesPage = request.send_json().await?;
+
+                Ok(Page {
+                    items: page.voices,
+                    next_cursor: page.next_page_token.filter(|_| page.has_more),
+                })
+            }
+        })
+    }
+
+    /// Clone a voice from audio samples (`POST /v1/voices/add`, instant voice cloning).
+    /// The returned ID can be used as a [`crate::TTDInput`] voice right away.
+    pub async fn create<S: Into<String>>(
+        &self,
+        name: S,
+        samples: Vec<VoiceSample>,
+        labels: HashMap<String, String>,
+    ) -> Result<VoiceId, ElevenLabsTTDError> {
+        if samples.is_empty() {
+            return Err(ElevenLabsTTDError::ValidationError(
+                "At least one voice sample is required".to_string(),
+            ));
+        }
+
+        let mut form = reqwest::multipart::Form::new().text("name", name.into());
+        if !labels.is_empty() {
+            form = form.text("labels", serde_json::to_string(&labels)?);
+        }
+        for sample in samples {
+            form = form.part("files", sample_part(sample));
+        }
+
+        let created: CreatedVoice = self
+            .client
+            .request(
+                Method::POST,
+                &self
+                    .client
+                    .api_url(EndpointFamily::Voices, ApiVersion::V1, "/voices/add"),
+            )
+            .multipart(form)
+            .send_json()
+            .await?;
+        Ok(created.voice_id)
+    }
+
+    /// Update a voice's name, description, labels or samples (`POST /v1/voices/{id}/edit`)
+    pub async fn edit<V: AsRef<str>>(
+        &self,
+        voice_id: V,
+        params: VoiceEditParams,
+    ) -> Result<(), ElevenLabsTTDError> {
+        let mut form = reqwest::multipart::Form::new().text("name", params.name);
+        if let Some(description) = params.description {
+            form = form.text("description", description);
+        }
+        if let Some(labels) = params.labels {
+            form = form.text("labels", serde_json::to_string(&labels)?);
+        }
+        for sample in params.samples {
+            form = form.part("files", sample_part(sample));
+        }
+
+        self.client
+            .request(
+                Method::POST,
+                &self.client.api_url(
+                    EndpointFamily::Voices,
+                    ApiVersion::V1,
+                    &format!("/voices/{}/edit", voice_id.as_ref()),
+                ),
+            )
+            .multipart(form)
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    /// Delete a voice (`DELETE /v1/voices/{id}`)
+    pub async fn delete<V: AsRef<str>>(&self, voice_id: V) -> Result<(), ElevenLabsTTDError> {
+        self.client
+            .request(
+                Method::DELETE,
+                &self.client.api_url(
+                    EndpointFamily::Voices,
+                    ApiVersion::V1,
+                    &format!("/voices/{}", voice_id.as_ref()),
+                ),
+            )
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    /// Generate preview voices from a description (`POST /v1/text-to-voice/create-previews`).
+    /// `text` is what the previews say; when `None`, the API writes a fitting line.
+    pub async fn design<S: Into<String>>(
+        &self,
+        description: S,
+        text: Option<String>,
+    ) -> Result<Vec<VoicePreview>, ElevenLabsTTDError> {
+        let body = serde_json::json!({
+            "voice_description": description.into(),
+            "auto_generate_text": text.is_none(),
+            "text": text,
+        });
+
+        let previews: VoicePreviews = self
+            .client
+            .request(
+                Method::POST,
+                &self.client.api_url(
+                    EndpointFamily::VoiceDesign,
+                    ApiVersion::V1,
+                    "/text-to-voice/create-previews",
+                ),
+            )
+            .json(&body)
+            .send_json()
+            .await?;
+        Ok(previews.previews)
+    }
+
+    /// Save a designed voice to the account (`POST /v1/text-to-voice/create-voice-from-preview`)
+    pub async fn create_from_preview<S: Into<String>, D: Into<String>>(
+        &self,
+        name: S,
+        description: D,
+        preview: &VoicePreview,
+    ) -> Result<VoiceId, ElevenLabsTTDError> {
+        let body = serde_json::json!({
+            "voice_name": name.into(),
+            "voice_description": description.into(),
+            "generated_voice_id": preview.generated_voice_id,
+        });
+
+        let created: CreatedVoice = self
+            .client
+            .request(
+                Method::POST,
+                &self.client.api_url(
+                    EndpointFamily::VoiceDesign,
+                    ApiVersion::V1,
+                    "/text-to-voice/create-voice-from-preview",
+                ),
+            )
+            .json(&body)
+            .send_json()
+            .await?;
+        Ok(created.voice_id)
+    }
+
+    /// The settings ElevenLabs currently applies to voices by default
+    pub async fn default_settings(&self) -> Result<VoiceSettings, ElevenLabsTTDError> {
+        self.client.execute_endpoint(&GetDefaultVoiceSettings).await
+    }
+
+    /// Voices shared in the public voice library (`GET /v1/shared-voices`)
+    pub fn library(&self) -> Paginated<SharedVoice> {
+        let client = self.client.clone();
+        Paginated::new(move |cursor: Option<String>| {
+            let client = client.clone();
+            async move {
+                let page_number: u32 = cursor.and_then(|c| c.parse().ok()).unwrap_or(0);
+                let query = [
+                    ("page_size", DEFAULT_PAGE_SIZE.to_string()),
+                    ("page", page_number.to_string()),
+                ];
+
+                let page: SharedVoicesPage = client
+                    .request(
+                        Method::GET,
+                        &client.api_url(
+                            EndpointFamily::SharedVoices,
+                            ApiVersion::V1,
+                            "/shared-voices",
+                        ),
+                    )
+                    .query(&query)
+                    .send_json()
+                    .await?;
+
+                Ok(Page {
+                    items: page.voices,
+                    next_cursor: page.has_more.then(|| (page_number + 1).to_string()),
+                })
+            }
+        })
+    }
+}
+
+fn sample_part(sample: VoiceSample) -> reqwest::multipart::Part {
+    reqwest::multipart::Part::bytes(sample.data).file_name(sample.file_name)
+}
+
+impl HistoryApi {
+    pub(crate) fn new(client: ElevenLabsTTDClient) -> Self {
+        Self { client }
+    }
+
+    /// Past generations, newest first (`GET /v1/history`)
+    pub fn list(&self) -> Paginated<HistoryItem> {
+        self.list_with(HistoryListParams::default())
+    }
+
+    /// Past generations matching `params`
+    pub fn list_with(&self, mut params: HistoryListParams) -> Paginated<HistoryItem> {
+        params.page_size.get_or_insert(DEFAULT_PAGE_SIZE);
+        let client = self.client.clone();
+        Paginated::new(move |cursor: Option<String>| {
+            let client = client.clone();
+            let params = params.clone();
+            async move {
+                let mut request = client
+                    .request(
+                        Method::GET,
+                        &client.api_url(EndpointFamily::History, ApiVersion::V1, "/history"),
+                    )
+                    .query(&params);
+                if let Some(after) = cursor {
+                    request = request.query(&[("start_after_history_item_id", after)]);
+                }
+
+                let page: HistoryPage = request.send_json().await?;
+
+                Ok(Page {
+                    items: page.history,
+                    next_cursor: page.last_history_item_id.filter(|_| page.has_more),
+                })
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query_string<T: Serialize>(params: &T) -> String {
+        let request = reqwest::Client::new()
+            .get("http://localhost/")
+            .query(params)
+            .build()
+            .unwrap();
+        request.url().query().unwrap_or_default().to_string()
+    }
+
+    #[test]
+    fn test_voice_params_encode_query() {
+        let params = VoiceListParams::new()
+            .search("deep & calm")
+            .category("premade")
+            .sort("name", SortDirection::Asc);
+
+        assert_eq!(
+            query_string(&params),
+            "search=deep+%26+calm&category=premade&sort=name&sort_direction=asc"
+        );
+    }
+
+    #[test]
+    fn test_empty_history_params_encode_nothing() {
+        assert_eq!(query_string(&HistoryListParams::new()), "");
+        assert_eq!(
+            query_string(&HistoryListParams::new().voice_id("abc").page_size(10)),
+            "voice_id=abc&page_size=10"
+        );
+    }
+}
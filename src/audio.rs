@@ -0,0 +1,1217 @@
+//! Helpers for stitching generated audio together
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::error::ElevenLabsTTDError;
+use crate::formats::{Codec, OutputFormat};
+use crate::sandbox;
+
+/// Silence of `duration` in `format`, ready to be concatenated with generated audio.
+///
+/// PCM, μ-law and A-law silence is sample-exact; MP3 is rounded up to whole
+/// frames, and Opus is a complete Ogg stream that chains onto the previous one.
+/// WAV formats get raw PCM samples, to be concatenated before adding the header.
+pub fn silence(duration: Duration, format: OutputFormat) -> Vec<u8> {
+    sandbox::encode(&[(duration, None)], format)
+}
+
+/// Wrap 16-bit little-endian mono PCM in a WAV (RIFF) header
+pub fn wav(pcm: &[u8], sample_rate: u32) -> Vec<u8> {
+    wav_with_channels(pcm, sample_rate, 1)
+}
+
+/// Wrap interleaved 16-bit stereo samples, e.g. from [`mix_stems`], in a WAV header
+pub fn stereo_wav(samples: &[i16], sample_rate: u32) -> Vec<u8> {
+    let pcm: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+    wav_with_channels(&pcm, sample_rate, 2)
+}
+
+fn wav_with_channels(pcm: &[u8], sample_rate: u32, channels: u16) -> Vec<u8> {
+    let mut out = Vec::with_capacity(44 + pcm.len());
+    out.extend_from_slice(&wav_header(pcm.len() as u32, sample_rate, channels));
+    out.extend_from_slice(pcm);
+    out
+}
+
+/// The 44-byte header of a 16-bit PCM WAV file with `data_len` bytes of samples
+pub(crate) fn wav_header(data_len: u32, sample_rate: u32, channels: u16) -> Vec<u8> {
+    const BITS_PER_SAMPLE: u16 = 16;
+    let block_align = channels * BITS_PER_SAMPLE / 8;
+
+    let mut out = Vec::with_capacity(44);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes()); // integer PCM
+    out.extend_from_slice(&channels.to_le_bytes());
+    out.extend_from_slice(&sample_rate.to_le_bytes());
+    out.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    out.extend_from_slice(&block_align.to_le_bytes());
+    out.extend_from_slice(&BITS_PER_SAMPLE.to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+    out
+}
+
+/// Playback length of audio in `format`. MP3 is assumed to be constant bitrate,
+/// as ElevenLabs produces it; Opus is read from the last Ogg page.
+pub fn duration(audio: &[u8], format: OutputFormat) -> Option<Duration> {
+    let rate = format.sample_rate() as f64;
+    let seconds = match format.codec() {
+        Codec::Pcm if format.is_wav() => wav_data(audio).ok()?.len() as f64 / 2.0 / rate,
+        Codec::Pcm => audio.len() as f64 / 2.0 / rate,
+        Codec::Ulaw | Codec::Alaw => audio.len() as f64 / rate,
+        Codec::Mp3 => audio.len() as f64 * 8.0 / (format.bitrate_kbps()? as f64 * 1000.0),
+        Codec::Opus => {
+            let head = audio.windows(8).position(|w| w == b"OpusHead")?;
+            let pre_skip = u16::from_le_bytes(audio.get(head + 10..head + 12)?.try_into().ok()?);
+            let page = audio.windows(4).rposition(|w| w == b"OggS")?;
+            let granule = u64::from_le_bytes(audio.get(page + 6..page + 14)?.try_into().ok()?);
+            granule.saturating_sub(pre_skip as u64) as f64 / 48000.0
+        }
+    };
+    Some(Duration::from_secs_f64(seconds))
+}
+
+/// The samples of WAV audio, or the audio itself for other formats
+pub(crate) fn strip_container(
+    audio: &[u8],
+    format: OutputFormat,
+) -> Result<&[u8], ElevenLabsTTDError> {
+    if format.is_wav() {
+        wav_data(audio)
+    } else {
+        Ok(audio)
+    }
+}
+
+/// Concatenate audio chunks of the same `format`, with `gap` of silence between
+/// consecutive chunks. A zero gap concatenates them back to back.
+pub fn concat<I, B>(chunks: I, gap: Duration, format: OutputFormat) -> Vec<u8>
+where
+    I: IntoIterator<Item = B>,
+    B: AsRef<[u8]>,
+{
+    let gap = if gap.is_zero() {
+        Vec::new()
+    } else {
+        silence(gap, format)
+    };
+
+    let mut out = Vec::new();
+    for (i, chunk) in chunks.into_iter().enumerate() {
+        if i > 0 {
+            out.extend_from_slice(&gap);
+        }
+        out.extend_from_slice(chunk.as_ref());
+    }
+    out
+}
+
+/// Concatenate 16-bit PCM chunks, overlapping each boundary by `fade` with a linear
+/// crossfade. This masks the clicks straight concatenation of independently generated
+/// segments can produce; each overlap is shortened to fit the chunks on either side.
+pub fn crossfade<I, B>(
+    chunks: I,
+    fade: Duration,
+    format: OutputFormat,
+) -> Result<Vec<u8>, ElevenLabsTTDError>
+where
+    I: IntoIterator<Item = B>,
+    B: AsRef<[u8]>,
+{
+    if format.codec() != Codec::Pcm {
+        return Err(ElevenLabsTTDError::ValidationError(format!(
+            "Crossfading needs PCM audio, got {}",
+            format.as_str()
+        )));
+    }
+
+    let fade_samples = (fade.as_secs_f64() * format.sample_rate() as f64).round() as usize;
+    let mut out: Vec<i16> = Vec::new();
+    for chunk in chunks {
+        let samples = pcm_samples(chunk.as_ref());
+        let overlap = fade_samples.min(out.len()).min(samples.len());
+        let start = out.len() - overlap;
+
+        for i in 0..overlap {
+            let t = (i + 1) as f64 / (overlap + 1) as f64;
+            let mixed = out[start + i] as f64 * (1.0 - t) + samples[i] as f64 * t;
+            out[start + i] = mixed.round() as i16;
+        }
+        out.extend_from_slice(&samples[overlap..]);
+    }
+
+    Ok(out.iter().flat_map(|s| s.to_le_bytes()).collect())
+}
+
+/// Concatenate chunks that may have been generated in different formats, e.g. cached
+/// chunks from before an output format change. PCM chunks at another sample rate are
+/// resampled to `format`'s rate; any other mismatch is an error.
+#[cfg(feature = "resample")]
+pub fn concat_mixed<I, B>(
+    chunks: I,
+    gap: Duration,
+    format: OutputFormat,
+) -> Result<Vec<u8>, ElevenLabsTTDError>
+where
+    I: IntoIterator<Item = (B, OutputFormat)>,
+    B: AsRef<[u8]>,
+{
+    let chunks = chunks
+        .into_iter()
+        .map(|(chunk, chunk_format)| {
+            if chunk_format == format {
+                Ok(chunk.as_ref().to_vec())
+            } else if chunk_format.codec() == Codec::Pcm && format.codec() == Codec::Pcm {
+                resample(
+                    chunk.as_ref(),
+                    chunk_format.sample_rate(),
+                    format.sample_rate(),
+                )
+            } else {
+                Err(ElevenLabsTTDError::ValidationError(format!(
+                    "Can't convert {} audio to {}",
+                    chunk_format.as_str(),
+                    format.as_str()
+                )))
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(concat(chunks, gap, format))
+}
+
+/// Resample 16-bit PCM from `from_rate` to `to_rate` Hz with rubato's FFT
+/// resampler, which low-passes first when downsampling to avoid aliasing
+#[cfg(feature = "resample")]
+pub fn resample(pcm: &[u8], from_rate: u32, to_rate: u32) -> Result<Vec<u8>, ElevenLabsTTDError> {
+    use rubato::{FftFixedIn, Resampler};
+
+    // Input frames fed to the resampler per call
+    const CHUNK: usize = 1024;
+
+    let input: Vec<f64> = pcm_samples(pcm).into_iter().map(f64::from).collect();
+    if from_rate == to_rate || input.is_empty() {
+        return Ok(pcm.to_vec());
+    }
+
+    let failed = |e: &dyn std::fmt::Display| {
+        ElevenLabsTTDError::ValidationError(format!(
+            "Can't resample {} Hz audio to {} Hz: {}",
+            from_rate, to_rate, e
+        ))
+    };
+    let mut resampler = FftFixedIn::<f64>::new(from_rate as usize, to_rate as usize, CHUNK, 2, 1)
+        .map_err(|e| failed(&e))?;
+    let delay = resampler.output_delay();
+    let output_len = (input.len() as f64 * to_rate as f64 / from_rate as f64).round() as usize;
+
+    let mut output = Vec::with_capacity(delay + output_len + CHUNK);
+    let mut rest = input.as_slice();
+    while rest.len() >= resampler.input_frames_next() {
+        let (chunk, tail) = rest.split_at(resampler.input_frames_next());
+        let resampled = resampler.process(&[chunk], None).map_err(|e| failed(&e))?;
+        output.extend_from_slice(&resampled[0]);
+        rest = tail;
+    }
+    // Zero-pad the last chunk, then flush until the filter's delay is out
+    let resampled = resampler
+        .process_partial(Some(&[rest]), None)
+        .map_err(|e| failed(&e))?;
+    output.extend_from_slice(&resampled[0]);
+    while output.len() < delay + output_len {
+        let resampled = resampler
+            .process_partial(None::<&[&[f64]]>, None)
+            .map_err(|e| failed(&e))?;
+        output.extend_from_slice(&resampled[0]);
+    }
+
+    Ok(output[delay..delay + output_len]
+        .iter()
+        .flat_map(|value| {
+            let sample = value.round().clamp(i16::MIN as f64, i16::MAX as f64) as i16;
+            sample.to_le_bytes()
+        })
+        .collect())
+}
+
+/// Samples at or above this magnitude count as clipped
+pub const CLIP_LEVEL: i16 = 32_700;
+
+/// Clipped samples needed before a take is flagged
+pub const MIN_CLIPPED_SAMPLES: usize = 10;
+
+/// RMS level (of full scale) below which audio counts as silent, about -50 dBFS
+pub const SILENCE_LEVEL: f64 = 0.003;
+
+/// Shortest silence flagged as unexpected
+pub const MAX_SILENCE: Duration = Duration::from_secs(2);
+
+/// Overall RMS level below which a take counts as near-empty, about -45 dBFS
+pub const LOW_ENERGY_LEVEL: f64 = 0.0056;
+
+/// Symptom of a failed or damaged generation, found by [`detect_anomalies`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum AudioAnomaly {
+    /// Samples at or near full scale
+    Clipping { samples: usize },
+    /// A run of silence of at least [`MAX_SILENCE`]
+    LongSilence { start: Duration, duration: Duration },
+    /// The whole take is barely audible; `rms` is a fraction of full scale
+    LowEnergy { rms: f64 },
+}
+
+impl fmt::Display for AudioAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AudioAnomaly::Clipping { samples } => write!(f, "{} clipped samples", samples),
+            AudioAnomaly::LongSilence { start, duration } => write!(
+                f,
+                "{:.1}s of silence at {:.1}s",
+                duration.as_secs_f64(),
+                start.as_secs_f64()
+            ),
+            AudioAnomaly::LowEnergy { rms } => {
+                write!(f, "Near-zero energy (RMS {:.4} of full scale)", rms)
+            }
+        }
+    }
+}
+
+/// Scan decoded audio for clipping, long silences and near-zero energy.
+/// Nothing is reported for empty audio or a sample rate of zero.
+pub fn detect_anomalies(samples: &[i16], sample_rate: u32) -> Vec<AudioAnomaly> {
+    let mut anomalies = Vec::new();
+    if samples.is_empty() || sample_rate == 0 {
+        return anomalies;
+    }
+
+    let clipped = samples
+        .iter()
+        .filter(|s| s.unsigned_abs() >= CLIP_LEVEL as u16)
+        .count();
+    if clipped >= MIN_CLIPPED_SAMPLES {
+        anomalies.push(AudioAnomaly::Clipping { samples: clipped });
+    }
+
+    // Silence is measured over 10ms windows
+    let window = (sample_rate as usize / 100).max(1);
+    let window_duration =
+        |windows: usize| Duration::from_secs_f64((windows * window) as f64 / sample_rate as f64);
+    let mut silent_since = None;
+    let windows: Vec<bool> = samples
+        .chunks(window)
+        .map(|chunk| rms(chunk) < SILENCE_LEVEL)
+        .collect();
+    for (i, silent) in windows.iter().chain([&false]).enumerate() {
+        match (silent, silent_since) {
+            (true, None) => silent_since = Some(i),
+            (false, Some(start)) => {
+                silent_since = None;
+                // The last window may be partial
+                let total = Duration::from_secs_f64(samples.len() as f64 / sample_rate as f64);
+                let duration = window_duration(i - start).min(total - window_duration(start));
+                if duration >= MAX_SILENCE {
+                    anomalies.push(AudioAnomaly::LongSilence {
+                        start: window_duration(start),
+                        duration,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let level = rms(samples);
+    if level < LOW_ENERGY_LEVEL {
+        anomalies.push(AudioAnomaly::LowEnergy { rms: level });
+    }
+
+    anomalies
+}
+
+/// Root mean square level as a fraction of full scale
+fn rms(samples: &[i16]) -> f64 {
+    let sum: f64 = samples.iter().map(|s| (*s as f64 / 32768.0).powi(2)).sum();
+    (sum / samples.len().max(1) as f64).sqrt()
+}
+
+/// A mono track of one speaker, placed in the stereo field by [`mix_stems`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stem {
+    pub samples: Vec<i16>,
+    // Position from -1.0 (left) through 0.0 (center) to 1.0 (right).
+    pub pan: f32,
+    // Linear gain, 1.0 leaves the level unchanged.
+    pub gain: f32,
+}
+
+impl Stem {
+    /// A centered stem at its original level
+    pub fn new(samples: Vec<i16>) -> Self {
+        Self {
+            samples,
+            pan: 0.0,
+            gain: 1.0,
+        }
+    }
+
+    /// Set the pan position, clamped to -1.0 (left) ..= 1.0 (right)
+    pub fn pan(mut self, pan: f32) -> Self {
+        self.pan = pan.clamp(-1.0, 1.0);
+        self
+    }
+
+    /// Set the linear gain
+    pub fn gain(mut self, gain: f32) -> Self {
+        self.gain = gain.max(0.0);
+        self
+    }
+
+    /// Set the gain in decibels, e.g. -6.0 for roughly half the amplitude
+    pub fn gain_db(self, db: f32) -> Self {
+        self.gain(10f32.powf(db / 20.0))
+    }
+
+    /// Left and right gains, using a constant-power pan law so a
+    /// voice keeps its loudness as it moves across the field
+    fn channel_gains(&self) -> (f32, f32) {
+        let angle = (self.pan + 1.0) * std::f32::consts::FRAC_PI_4;
+        (self.gain * angle.cos(), self.gain * angle.sin())
+    }
+}
+
+/// Mix mono stems of the same sample rate into interleaved stereo samples (left, right),
+/// as long as the longest stem. Overloads are clamped rather than wrapped.
+pub fn mix_stems(stems: &[Stem]) -> Vec<i16> {
+    let len = stems.iter().map(|s| s.samples.len()).max().unwrap_or(0);
+    let mut mix = vec![0f32; len * 2];
+
+    for stem in stems {
+        add_stem(&mut mix, stem, 0, |_| 1.0);
+    }
+
+    to_i16(mix)
+}
+
+/// Default reduction of the music bed while dialogue is playing
+pub const DEFAULT_DUCKING_DB: f32 = -12.0;
+
+/// Default time constant for the music fading down when dialogue starts
+pub const DEFAULT_DUCKING_ATTACK: Duration = Duration::from_millis(50);
+
+/// Default time constant for the music coming back up after dialogue ends
+pub const DEFAULT_DUCKING_RELEASE: Duration = Duration::from_millis(500);
+
+/// How quickly the speech detector lets go after the last loud sample
+const SPEECH_HOLD: Duration = Duration::from_millis(10);
+
+/// A program timeline: dialogue stems and sound effects placed at offsets over a
+/// music bed that is ducked while anyone speaks, rendered to one stereo track.
+/// All stems must share the mixdown's sample rate.
+#[derive(Debug, Clone)]
+pub struct Mixdown {
+    sample_rate: u32,
+    dialogue: Vec<(Duration, Stem)>,
+    sfx: Vec<(Duration, Stem)>,
+    music: Option<Stem>,
+    loop_music: bool,
+    ducking_db: f32,
+    ducking_attack: Duration,
+    ducking_release: Duration,
+}
+
+impl Mixdown {
+    /// An empty program at `sample_rate`, which must be above zero
+    pub fn new(sample_rate: u32) -> Result<Self, ElevenLabsTTDError> {
+        if sample_rate == 0 {
+            return Err(ElevenLabsTTDError::ValidationError(
+                "Mixdown sample rate must be above zero".to_string(),
+            ));
+        }
+        Ok(Self {
+            sample_rate,
+            dialogue: Vec::new(),
+            sfx: Vec::new(),
+            music: None,
+            loop_music: false,
+            ducking_db: DEFAULT_DUCKING_DB,
+            ducking_attack: DEFAULT_DUCKING_ATTACK,
+            ducking_release: DEFAULT_DUCKING_RELEASE,
+        })
+    }
+
+    /// Place a dialogue stem starting `at` into the program
+    pub fn dialogue(mut self, at: Duration, stem: Stem) -> Self {
+        self.dialogue.push((at, stem));
+        self
+    }
+
+    /// Place a sound effect starting `at` into the program
+    pub fn sfx(mut self, at: Duration, stem: Stem) -> Self {
+        self.sfx.push((at, stem));
+        self
+    }
+
+    /// Play a music bed once from the start, cut off where the program ends
+    pub fn music(mut self, stem: Stem) -> Self {
+        self.music = Some(stem);
+        self.loop_music = false;
+        self
+    }
+
+    /// Loop a music bed from the start until the program ends
+    pub fn music_loop(mut self, stem: Stem) -> Self {
+        self.music = Some(stem);
+        self.loop_music = true;
+        self
+    }
+
+    /// How far the music is lowered under dialogue, in decibels (0.0 disables ducking)
+    pub fn ducking_db(mut self, db: f32) -> Self {
+        self.ducking_db = db.min(0.0);
+        self
+    }
+
+    /// Time constants of the ducking envelope: how fast the music fades down
+    /// when dialogue starts (`attack`) and back up after it ends (`release`)
+    pub fn ducking_times(mut self, attack: Duration, release: Duration) -> Self {
+        self.ducking_attack = attack;
+        self.ducking_release = release;
+        self
+    }
+
+    /// Length of the program: up to the end of the last dialogue stem or effect,
+    /// or the music alone when there is nothing else
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.len() as f64 / self.sample_rate as f64)
+    }
+
+    fn offset(&self, at: Duration) -> usize {
+        samples_for_rate(at, self.sample_rate)
+    }
+
+    fn len(&self) -> usize {
+        self.dialogue
+            .iter()
+            .chain(&self.sfx)
+            .map(|(at, stem)| self.offset(*at) + stem.samples.len())
+            .max()
+            .or_else(|| self.music.as_ref().map(|m| m.samples.len()))
+            .unwrap_or(0)
+    }
+
+    /// Render interleaved stereo samples (left, right)
+    pub fn render(&self) -> Vec<i16> {
+        let len = self.len();
+        let mut mix = vec![0f32; len * 2];
+        let mut speech = vec![0f32; len];
+
+        for (at, stem) in &self.dialogue {
+            let offset = self.offset(*at);
+            add_stem(&mut mix, stem, offset, |_| 1.0);
+            for (i, sample) in stem.samples.iter().enumerate() {
+                speech[offset + i] += *sample as f32 * stem.gain;
+            }
+        }
+        for (at, stem) in &self.sfx {
+            add_stem(&mut mix, stem, self.offset(*at), |_| 1.0);
+        }
+
+        if let Some(music) = &self.music {
+            let samples = if self.loop_music && !music.samples.is_empty() {
+                music.samples.iter().copied().cycle().take(len).collect()
+            } else {
+                music.samples[..music.samples.len().min(len)].to_vec()
+            };
+            let duck = self.ducking_envelope(&speech);
+            let music = Stem {
+                samples,
+                ..music.clone()
+            };
+            add_stem(&mut mix, &music, 0, |i| duck[i]);
+        }
+
+        to_i16(mix)
+    }
+
+    /// Render the program as a stereo WAV file
+    pub fn render_wav(&self) -> Vec<u8> {
+        stereo_wav(&self.render(), self.sample_rate)
+    }
+
+    /// Gain for each sample of the music bed. An envelope follower on the dialogue
+    /// detects speech; the gain then glides towards the ducked level with the
+    /// attack time constant, and back to full level with the release one.
+    fn ducking_envelope(&self, speech: &[f32]) -> Vec<f32> {
+        let coefficient =
+            |time: Duration| (-1.0 / (time.as_secs_f32() * self.sample_rate as f32).max(1.0)).exp();
+        let hold = coefficient(SPEECH_HOLD);
+        let attack = coefficient(self.ducking_attack);
+        let release = coefficient(self.ducking_release);
+        let ducked = 10f32.powf(self.ducking_db / 20.0);
+        let threshold = SILENCE_LEVEL as f32 * 32768.0;
+
+        let mut level = 0f32;
+        let mut gain = 1f32;
+        speech
+            .iter()
+            .map(|sample| {
+                level = sample.abs().max(level * hold);
+                let (target, coefficient) = if level >= threshold {
+                    (ducked, attack)
+                } else {
+                    (1.0, release)
+                };
+                gain = target + (gain - target) * coefficient;
+                gain
+            })
+            .collect()
+    }
+}
+
+/// Add a panned stem to interleaved stereo `mix` from sample `offset`,
+/// scaled by `envelope` of the stem's sample index
+fn add_stem(mix: &mut [f32], stem: &Stem, offset: usize, envelope: impl Fn(usize) -> f32) {
+    let (left, right) = stem.channel_gains();
+    for (i, sample) in stem.samples.iter().enumerate() {
+        let frame = 2 * (offset + i);
+        let sample = *sample as f32 * envelope(i);
+        mix[frame] += sample * left;
+        mix[frame + 1] += sample * right;
+    }
+}
+
+fn to_i16(mix: Vec<f32>) -> Vec<i16> {
+    mix.into_iter()
+        .map(|s| s.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16)
+        .collect()
+}
+
+fn samples_for_rate(duration: Duration, sample_rate: u32) -> usize {
+    (duration.as_secs_f64() * sample_rate as f64).round() as usize
+}
+
+/// G.711 μ-law byte to 16-bit linear sample
+pub const ULAW_TABLE: [i16; 256] = ulaw_table();
+
+/// G.711 A-law byte to 16-bit linear sample
+pub const ALAW_TABLE: [i16; 256] = alaw_table();
+
+const fn ulaw_table() -> [i16; 256] {
+    let mut table = [0i16; 256];
+    let mut i = 0;
+    while i < 256 {
+        let byte = !(i as u8);
+        let exponent = (byte >> 4) & 0x07;
+        let mantissa = (byte & 0x0F) as i16;
+        let magnitude = (((mantissa << 3) + 0x84) << exponent) - 0x84;
+        table[i] = if byte & 0x80 != 0 {
+            -magnitude
+        } else {
+            magnitude
+        };
+        i += 1;
+    }
+    table
+}
+
+const fn alaw_table() -> [i16; 256] {
+    let mut table = [0i16; 256];
+    let mut i = 0;
+    while i < 256 {
+        let byte = (i as u8) ^ 0x55;
+        let exponent = (byte >> 4) & 0x07;
+        let mantissa = (byte & 0x0F) as i16;
+        let magnitude = match exponent {
+            0 => (mantissa << 4) + 8,
+            _ => ((mantissa << 4) + 0x108) << (exponent - 1),
+        };
+        table[i] = if byte & 0x80 != 0 {
+            magnitude
+        } else {
+            -magnitude
+        };
+        i += 1;
+    }
+    table
+}
+
+/// Sample rate WebRTC audio tracks work at
+pub const WEBRTC_SAMPLE_RATE: u32 = 48000;
+
+/// A fixed-size chunk of 48kHz 16-bit PCM, as WebRTC and LiveKit audio tracks take it
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioFrame {
+    // Interleaved samples, `samples_per_channel * channels` of them.
+    pub samples: Vec<i16>,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub samples_per_channel: usize,
+    // Position of the frame's first sample in the audio.
+    pub timestamp: Duration,
+}
+
+impl AudioFrame {
+    /// The frame with its mono samples copied to both channels
+    pub fn to_stereo(&self) -> AudioFrame {
+        if self.channels == 2 {
+            return self.clone();
+        }
+        AudioFrame {
+            samples: self.samples.iter().flat_map(|s| [*s, *s]).collect(),
+            channels: 2,
+            ..self.clone()
+        }
+    }
+}
+
+/// Iterator over the [`AudioFrame`]s of some audio, see [`frames`]
+#[derive(Debug, Clone)]
+pub struct AudioFrames {
+    samples: Vec<i16>,
+    frame_len: usize,
+    position: usize,
+}
+
+impl Iterator for AudioFrames {
+    type Item = AudioFrame;
+
+    fn next(&mut self) -> Option<AudioFrame> {
+        if self.position >= self.samples.len() {
+            return None;
+        }
+        let end = (self.position + self.frame_len).min(self.samples.len());
+        let mut samples = self.samples[self.position..end].to_vec();
+        // The last frame is padded with silence, as tracks only take full frames
+        samples.resize(self.frame_len, 0);
+        let timestamp = Duration::from_secs_f64(self.position as f64 / WEBRTC_SAMPLE_RATE as f64);
+        self.position = end;
+
+        Some(AudioFrame {
+            samples,
+            sample_rate: WEBRTC_SAMPLE_RATE,
+            channels: 1,
+            samples_per_channel: self.frame_len,
+            timestamp,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = (self.samples.len() - self.position).div_ceil(self.frame_len);
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for AudioFrames {}
+
+/// Split PCM, WAV, μ-law or A-law audio into mono 48kHz frames of `duration`
+/// (10ms is what WebRTC expects). Audio at other rates is resampled, which
+/// needs the `resample` feature: without it only 48kHz audio (`pcm_48000`)
+/// can be split. MP3 and Opus audio can't be split into frames.
+pub fn frames(
+    audio: &[u8],
+    format: OutputFormat,
+    duration: Duration,
+) -> Result<AudioFrames, ElevenLabsTTDError> {
+    let scaled = duration.as_nanos() * WEBRTC_SAMPLE_RATE as u128;
+    let frame_len = scaled / 1_000_000_000;
+    if frame_len == 0 || !scaled.is_multiple_of(1_000_000_000) {
+        return Err(ElevenLabsTTDError::ValidationError(format!(
+            "Frame duration {:?} isn't a whole number of samples at 48kHz",
+            duration
+        )));
+    }
+
+    if matches!(format.codec(), Codec::Mp3 | Codec::Opus) {
+        return Err(ElevenLabsTTDError::ValidationError(format!(
+            "Can't split compressed {} audio into frames; request a PCM format such as pcm_48000",
+            format.as_str()
+        )));
+    }
+
+    let mut samples = i16_samples(audio, format)?;
+    if format.sample_rate() != WEBRTC_SAMPLE_RATE {
+        samples = resample_to_webrtc(&samples, format.sample_rate())?;
+    }
+    Ok(AudioFrames {
+        samples,
+        frame_len: frame_len as usize,
+        position: 0,
+    })
+}
+
+#[cfg(feature = "resample")]
+fn resample_to_webrtc(samples: &[i16], sample_rate: u32) -> Result<Vec<i16>, ElevenLabsTTDError> {
+    let pcm: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+    Ok(pcm_samples(&resample(
+        &pcm,
+        sample_rate,
+        WEBRTC_SAMPLE_RATE,
+    )?))
+}
+
+#[cfg(not(feature = "resample"))]
+fn resample_to_webrtc(_: &[i16], sample_rate: u32) -> Result<Vec<i16>, ElevenLabsTTDError> {
+    Err(ElevenLabsTTDError::ValidationError(format!(
+        "Resampling {}Hz audio to 48kHz frames needs the `resample` feature",
+        sample_rate
+    )))
+}
+
+/// Decode PCM, WAV, μ-law or A-law audio into 16-bit samples
+pub fn i16_samples(audio: &[u8], format: OutputFormat) -> Result<Vec<i16>, ElevenLabsTTDError> {
+    match format.codec() {
+        Codec::Pcm if format.is_wav() => Ok(pcm_samples(wav_data(audio)?)),
+        Codec::Pcm => Ok(pcm_samples(audio)),
+        Codec::Ulaw => Ok(audio.iter().map(|b| ULAW_TABLE[*b as usize]).collect()),
+        Codec::Alaw => Ok(audio.iter().map(|b| ALAW_TABLE[*b as usize]).collect()),
+        Codec::Mp3 | Codec::Opus => Err(ElevenLabsTTDError::ValidationError(format!(
+            "Can't read samples from compressed {} audio",
+            format.as_str()
+        ))),
+    }
+}
+
+/// Decode PCM, WAV, μ-law or A-law audio into samples between -1.0 and 1.0
+pub fn f32_samples(audio: &[u8], format: OutputFormat) -> Result<Vec<f32>, ElevenLabsTTDError> {
+    Ok(i16_samples(audio, format)?
+        .into_iter()
+        .map(|s| s as f32 / 32768.0)
+        .collect())
+}
+
+/// Read a 16-bit PCM WAV file into mono samples (averaging the channels)
+/// and its sample rate
+pub fn read_wav(file: &[u8]) -> Result<(Vec<i16>, u32), ElevenLabsTTDError> {
+    let fmt = wav_chunk(file, b"fmt ")?;
+    if fmt.len() < 16 {
+        return Err(ElevenLabsTTDError::ValidationError(
+            "Invalid WAV file".to_string(),
+        ));
+    }
+    let field = |at: usize| u16::from_le_bytes([fmt[at], fmt[at + 1]]);
+    let (encoding, channels, bits) = (field(0), field(2).max(1), field(14));
+    let sample_rate = u32::from_le_bytes([fmt[4], fmt[5], fmt[6], fmt[7]]);
+    // 0xFFFE is WAVE_FORMAT_EXTENSIBLE, used by some tools for plain PCM
+    if !matches!(encoding, 1 | 0xFFFE) || bits != 16 {
+        return Err(ElevenLabsTTDError::ValidationError(format!(
+            "Only 16-bit PCM WAV files are supported, got format {} with {} bits",
+            encoding, bits
+        )));
+    }
+    if sample_rate == 0 {
+        return Err(ElevenLabsTTDError::ValidationError(
+            "WAV file has a sample rate of zero".to_string(),
+        ));
+    }
+
+    let samples = pcm_samples(wav_chunk(file, b"data")?)
+        .chunks(channels as usize)
+        .map(|frame| (frame.iter().map(|s| *s as i32).sum::<i32>() / frame.len() as i32) as i16)
+        .collect();
+    Ok((samples, sample_rate))
+}
+
+/// Target level of [`normalize`], in dBFS RMS
+pub const DEFAULT_NORMALIZE_DB: f32 = -20.0;
+
+/// Highest peak [`normalize`] allows, in dBFS
+pub const NORMALIZE_PEAK_DB: f32 = -1.0;
+
+/// Scale samples so their RMS level is `target_db` dBFS, backing off if
+/// that would push a peak above [`NORMALIZE_PEAK_DB`]. Silence is left as is.
+pub fn normalize(samples: &mut [i16], target_db: f32) {
+    let level = rms(samples) as f32;
+    let peak = samples.iter().map(|s| s.unsigned_abs()).max().unwrap_or(0) as f32 / 32768.0;
+    if level <= f32::EPSILON || peak <= f32::EPSILON {
+        return;
+    }
+
+    let gain =
+        (10f32.powf(target_db / 20.0) / level).min(10f32.powf(NORMALIZE_PEAK_DB / 20.0) / peak);
+    for sample in samples.iter_mut() {
+        *sample = (*sample as f32 * gain)
+            .round()
+            .clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+    }
+}
+
+/// The `data` chunk of a WAV file
+fn wav_data(file: &[u8]) -> Result<&[u8], ElevenLabsTTDError> {
+    wav_chunk(file, b"data")
+}
+
+/// Body of the first chunk with `id` in a WAV file
+fn wav_chunk<'a>(file: &'a [u8], id: &[u8; 4]) -> Result<&'a [u8], ElevenLabsTTDError> {
+    let invalid = || ElevenLabsTTDError::ValidationError("Invalid WAV file".to_string());
+    if file.len() < 12 || &file[..4] != b"RIFF" || &file[8..12] != b"WAVE" {
+        return Err(invalid());
+    }
+
+    let mut rest = &file[12..];
+    while rest.len() >= 8 {
+        let len = u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+        let body = &rest[8..];
+        if &rest[..4] == id {
+            return Ok(&body[..len.min(body.len())]);
+        }
+        // Chunks are padded to an even length
+        rest = body.get(len + len % 2..).ok_or_else(invalid)?;
+    }
+    Err(invalid())
+}
+
+/// 16-bit little-endian samples, ignoring a trailing odd byte
+fn pcm_samples(bytes: &[u8]) -> Vec<i16> {
+    bytes
+        .chunks_exact(2)
+        .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pcm_silence_is_sample_exact() {
+        // 16-bit mono: two bytes per sample
+        let silence = silence(Duration::from_millis(250), OutputFormat::Pcm16000);
+        assert_eq!(silence.len(), 8000);
+        assert!(silence.iter().all(|b| *b == 0));
+
+        let ulaw = super::silence(Duration::from_millis(250), OutputFormat::Ulaw8000);
+        assert_eq!(ulaw, vec![0xFF; 2000]);
+    }
+
+    #[test]
+    fn test_wav_header() {
+        let file = wav(&[0, 0, 1, 0], 16000);
+        assert_eq!(file.len(), 48);
+        assert_eq!(&file[..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(file[4..8].try_into().unwrap()), 40);
+        assert_eq!(u32::from_le_bytes(file[24..28].try_into().unwrap()), 16000);
+        assert_eq!(u32::from_le_bytes(file[28..32].try_into().unwrap()), 32000);
+        assert_eq!(u32::from_le_bytes(file[40..44].try_into().unwrap()), 4);
+        assert_eq!(&file[44..], &[0, 0, 1, 0]);
+    }
+
+    #[test]
+    fn test_g711_expansion() {
+        // Reference values from the ITU-T G.711 tables
+        assert_eq!(ULAW_TABLE[0xFF], 0);
+        assert_eq!(ULAW_TABLE[0x7F], 0);
+        assert_eq!(ULAW_TABLE[0x00], -32124);
+        assert_eq!(ULAW_TABLE[0x80], 32124);
+        assert_eq!(ALAW_TABLE[0xD5], 8);
+        assert_eq!(ALAW_TABLE[0x55], -8);
+        assert_eq!(ALAW_TABLE[0xAA], 32256);
+        assert_eq!(ALAW_TABLE[0x2A], -32256);
+    }
+
+    #[test]
+    fn test_samples_by_format() {
+        let pcm = pcm(&[-32768, 0, 16384]);
+        assert_eq!(
+            i16_samples(&pcm, OutputFormat::Pcm16000).unwrap(),
+            vec![-32768, 0, 16384]
+        );
+        assert_eq!(
+            f32_samples(&wav(&pcm, 16000), OutputFormat::Wav { sample_rate: 16000 }).unwrap(),
+            vec![-1.0, 0.0, 0.5]
+        );
+        assert_eq!(
+            i16_samples(&[0xFF, 0x80], OutputFormat::Ulaw8000).unwrap(),
+            vec![0, 32124]
+        );
+        assert!(i16_samples(b"RIFF", OutputFormat::Wav { sample_rate: 16000 }).is_err());
+        assert!(i16_samples(&pcm, OutputFormat::Mp3_44100_128).is_err());
+    }
+
+    #[test]
+    fn test_detect_anomalies() {
+        let rate = 1000;
+        let tone: Vec<i16> = (0..rate)
+            .map(|n| if n % 2 == 0 { 8000 } else { -8000 })
+            .collect();
+        assert!(detect_anomalies(&tone, rate as u32).is_empty());
+
+        let mut gap = tone.clone();
+        gap.extend(vec![0; 3 * rate]);
+        gap.extend(&tone);
+        assert_eq!(
+            detect_anomalies(&gap, rate as u32),
+            vec![AudioAnomaly::LongSilence {
+                start: Duration::from_secs(1),
+                duration: Duration::from_secs(3),
+            }]
+        );
+
+        let clipped: Vec<i16> = tone.iter().map(|s| s.saturating_mul(5)).collect();
+        assert_eq!(
+            detect_anomalies(&clipped, rate as u32),
+            vec![AudioAnomaly::Clipping { samples: rate }]
+        );
+
+        let quiet = vec![10; rate];
+        assert!(matches!(
+            detect_anomalies(&quiet, rate as u32)[..],
+            [AudioAnomaly::LowEnergy { .. }]
+        ));
+        assert!(detect_anomalies(&gap, 0).is_empty());
+    }
+
+    #[test]
+    fn test_mix_stems_pans_speakers() {
+        let left = Stem::new(vec![10000; 2]).pan(-1.0);
+        let right = Stem::new(vec![10000; 3]).pan(1.0).gain(0.5);
+        assert_eq!(
+            mix_stems(&[left, right]),
+            vec![10000, 5000, 10000, 5000, 0, 5000]
+        );
+
+        // Constant power: about -3dB on each side when centered
+        let center = mix_stems(&[Stem::new(vec![10000])]);
+        assert_eq!(center, vec![7071, 7071]);
+
+        let loud = mix_stems(&[Stem::new(vec![30000]).gain_db(6.0).pan(-1.0)]);
+        assert_eq!(loud[0], i16::MAX);
+
+        let file = stereo_wav(&center, 8000);
+        assert_eq!(u16::from_le_bytes([file[22], file[23]]), 2);
+        assert_eq!(u32::from_le_bytes(file[28..32].try_into().unwrap()), 32000);
+    }
+
+    #[test]
+    fn test_mixdown_places_layers_and_ducks_music() {
+        let rate = 1000;
+        let voice = Stem::new(vec![8000; 500]).pan(-1.0);
+        let door = Stem::new(vec![4000; 100]).pan(1.0);
+        let music = Stem::new(vec![1000; 5000]).pan(1.0);
+
+        let mix = Mixdown::new(rate)
+            .unwrap()
+            .dialogue(Duration::from_millis(500), voice)
+            .sfx(Duration::from_millis(1200), door)
+            .music(music)
+            .ducking_db(-20.0);
+
+        assert_eq!(mix.duration(), Duration::from_millis(1300));
+        let out = mix.render();
+        assert_eq!(out.len(), 2 * 1300);
+
+        let frame = |ms: usize| (out[2 * ms], out[2 * ms + 1]);
+        // Music alone before the dialogue, at full level
+        assert_eq!(frame(100), (0, 1000));
+        // Dialogue on the left, music close to 20dB down on the right
+        let (voice, ducked) = frame(700);
+        assert_eq!(voice, 8000);
+        assert!((100..130).contains(&ducked));
+        // Music on its way back up, with the door on top
+        let (_, door) = frame(1250);
+        assert!((4300..4600).contains(&door));
+    }
+
+    #[test]
+    fn test_music_loop_fills_program() {
+        let ramp: Vec<i16> = (0..300).collect();
+        let out = Mixdown::new(1000)
+            .unwrap()
+            .sfx(Duration::from_secs(1), Stem::new(vec![0; 100]))
+            .music_loop(Stem::new(ramp).pan(-1.0))
+            .render();
+
+        assert_eq!(out.len(), 2 * 1100);
+        assert_eq!(out[2 * 1050], 150);
+        assert_eq!(out[2 * 299], 299);
+        assert_eq!(out[2 * 300], 0);
+
+        let once = Mixdown::new(1000)
+            .unwrap()
+            .sfx(Duration::from_secs(1), Stem::new(vec![0; 100]))
+            .music(Stem::new(vec![1000; 300]).pan(-1.0))
+            .render();
+        assert_eq!(once[2 * 1050], 0);
+    }
+
+    #[test]
+    fn test_mixdown_rejects_zero_sample_rate() {
+        assert!(Mixdown::new(0).is_err());
+    }
+
+    #[test]
+    fn test_read_wav_downmixes_stereo() {
+        let file = stereo_wav(&[100, 300, -100, -300], 22050);
+        assert_eq!(read_wav(&file).unwrap(), (vec![200, -200], 22050));
+        assert_eq!(
+            read_wav(&wav(&pcm(&[1, 2]), 8000)).unwrap(),
+            (vec![1, 2], 8000)
+        );
+        assert!(read_wav(b"RIFF").is_err());
+        assert!(read_wav(&wav(&pcm(&[1, 2]), 0)).is_err());
+    }
+
+    #[test]
+    fn test_normalize_levels_and_limits_peaks() {
+        let mut quiet = vec![100i16, -100, 100, -100];
+        normalize(&mut quiet, -20.0);
+        // -20dBFS is a tenth of full scale
+        assert_eq!(quiet, vec![3277, -3277, 3277, -3277]);
+
+        // A lone spike keeps the gain below the target
+        let mut spiky = vec![0i16; 99];
+        spiky.push(10000);
+        normalize(&mut spiky, -6.0);
+        assert_eq!(spiky[99], 29205);
+
+        let mut silent = vec![0i16; 4];
+        normalize(&mut silent, -20.0);
+        assert_eq!(silent, vec![0; 4]);
+    }
+
+    #[test]
+    fn test_concat_inserts_gaps_between_chunks() {
+        let audio = concat(
+            [vec![1u8; 4], vec![2u8; 4], vec![3u8; 4]],
+            Duration::from_millis(1),
+            OutputFormat::Pcm16000,
+        );
+
+        // 1ms at 16kHz is 16 samples, 32 bytes, per gap
+        assert_eq!(audio.len(), 12 + 2 * 32);
+        assert_eq!(&audio[..4], &[1; 4]);
+        assert_eq!(&audio[36..40], &[2; 4]);
+        assert!(audio[4..36].iter().all(|b| *b == 0));
+
+        let back_to_back = concat([b"ab", b"cd"], Duration::ZERO, OutputFormat::Pcm16000);
+        assert_eq!(back_to_back, b"abcd");
+    }
+
+    fn pcm(samples: &[i16]) -> Vec<u8> {
+        samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn test_crossfade_blends_boundaries() {
+        // 3 samples at 16kHz
+        let fade = Duration::from_micros(187);
+        let audio = crossfade(
+            [pcm(&[1000; 5]), pcm(&[-1000; 5])],
+            fade,
+            OutputFormat::Pcm16000,
+        )
+        .unwrap();
+
+        assert_eq!(
+            pcm_samples(&audio),
+            vec![1000, 1000, 500, 0, -500, -1000, -1000]
+        );
+
+        // Overlap shrinks to fit short chunks
+        let short = crossfade([pcm(&[10]), pcm(&[10])], fade, OutputFormat::Pcm16000).unwrap();
+        assert_eq!(pcm_samples(&short), vec![10]);
+
+        assert!(crossfade([b"ab"], fade, OutputFormat::Mp3_44100_128).is_err());
+    }
+
+    #[cfg(feature = "resample")]
+    #[test]
+    fn test_resample_keeps_duration_and_level() {
+        let steady = pcm(&[1000; 1600]);
+        // The resampler zero-pads past both ends, so the edges ring a little
+        let settled = |samples: &[i16]| {
+            let edge = samples.len() / 20;
+            samples[edge..samples.len() - edge]
+                .iter()
+                .all(|s| (*s - 1000).abs() <= 2)
+        };
+
+        let down = pcm_samples(&resample(&steady, 16000, 8000).unwrap());
+        assert_eq!(down.len(), 800);
+        assert!(settled(&down));
+
+        let up = pcm_samples(&resample(&steady, 16000, 44100).unwrap());
+        assert_eq!(up.len(), 4410);
+        assert!(settled(&up));
+    }
+
+    #[cfg(feature = "resample")]
+    #[test]
+    fn test_concat_mixed_resamples_pcm() {
+        let audio = concat_mixed(
+            [
+                (pcm(&[0; 160]), OutputFormat::Pcm16000),
+                (pcm(&[0; 441]), OutputFormat::Pcm44100),
+            ],
+            Duration::ZERO,
+            OutputFormat::Pcm16000,
+        )
+        .unwrap();
+        assert_eq!(audio.len(), 2 * 320);
+
+        let mismatch = concat_mixed(
+            [(b"mp3".to_vec(), OutputFormat::Mp3_44100_128)],
+            Duration::ZERO,
+            OutputFormat::Pcm16000,
+        );
+        assert!(mismatch.is_err());
+    }
+
+    #[test]
+    fn test_frames_are_fixed_size_and_padded() {
+        let audio = pcm(&[100; 1000]);
+        let frames: Vec<AudioFrame> =
+            frames(&audio, OutputFormat::Pcm48000, Duration::from_millis(10))
+                .unwrap()
+                .collect();
+
+        assert_eq!(frames.len(), 3);
+        assert!(frames.iter().all(|f| f.samples.len() == 480));
+        assert_eq!(frames[1].timestamp, Duration::from_millis(10));
+        assert_eq!(frames[2].samples[39], 100);
+        assert_eq!(frames[2].samples[40], 0);
+
+        let stereo = frames[0].to_stereo();
+        assert_eq!(stereo.samples.len(), 960);
+        assert_eq!(stereo.samples_per_channel, 480);
+
+        assert!(frames_fail(Duration::from_micros(10_010)));
+        assert!(frames_fail(Duration::ZERO));
+    }
+
+    fn frames_fail(duration: Duration) -> bool {
+        frames(&pcm(&[0; 10]), OutputFormat::Pcm48000, duration).is_err()
+    }
+
+    #[cfg(feature = "resample")]
+    #[test]
+    fn test_frames_resample_to_48khz() {
+        let audio = pcm(&[1000; 1600]);
+        let frames = frames(&audio, OutputFormat::Pcm16000, Duration::from_millis(20)).unwrap();
+        // 100ms at 48kHz in 20ms frames
+        assert_eq!(frames.len(), 5);
+    }
+
+    #[cfg(not(feature = "resample"))]
+    #[test]
+    fn test_frames_need_resample_feature_for_other_rates() {
+        let audio = pcm(&[1000; 1600]);
+        let error = frames(&audio, OutputFormat::Pcm16000, Duration::from_millis(20)).unwrap_err();
+        assert!(error.to_string().contains("`resample` feature"));
+    }
+
+    #[test]
+    fn test_frames_reject_compressed_audio() {
+        let error = frames(
+            b"mp3",
+            OutputFormat::Mp3_44100_128,
+            Duration::from_millis(10),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("request a PCM format"));
+    }
+}
@@ -0,0 +1,91 @@
+//! Central record of every generation, for compliance logging, and of the
+//! errors calls end with
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::error::ElevenLabsTTDError;
+use crate::tenant::TenantId;
+
+/// One Text-to-Dialogue call, successful or not
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEvent {
+    pub timestamp: DateTime<Utc>,
+    // Tenant the request was made for, if any.
+    pub tenant: Option<TenantId>,
+    pub correlation_id: String,
+    // Characters submitted across all turns.
+    pub input_characters: u64,
+    // Distinct voice IDs used, in order of first appearance.
+    pub voices: Vec<String>,
+    // Estimated cost of the billed characters, if a price was configured.
+    pub cost: Option<f64>,
+    // Request ID assigned by ElevenLabs (the history item ID), if known.
+    pub request_id: Option<String>,
+    // Error kind (see `ElevenLabsTTDError::kind`) when the call failed.
+    pub error: Option<&'static str>,
+}
+
+/// Receives an [`AuditEvent`] after every Text-to-Dialogue call.
+/// Called inline, so implementations should hand events off rather than block.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, event: &AuditEvent);
+}
+
+impl<F> AuditSink for F
+where
+    F: Fn(&AuditEvent) + Send + Sync,
+{
+    fn record(&self, event: &AuditEvent) {
+        self(event)
+    }
+}
+
+/// What was being done when an error reached an [`ErrorSink`]
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorContext {
+    // "text_to_dialogue" for a single request, "render_dialogue" for a rendered
+    // script, "request" for other endpoints.
+    pub operation: &'static str,
+    // The chapter, batch item or scene being rendered, if any.
+    pub item: Option<String>,
+    pub correlation_id: Option<String>,
+    // Request ID assigned by ElevenLabs, when the API answered.
+    pub request_id: Option<String>,
+    pub endpoint: Option<String>,
+    // Attempts made, including the one that failed last.
+    pub attempts: u32,
+}
+
+impl ErrorContext {
+    pub(crate) fn new(
+        error: &ElevenLabsTTDError,
+        operation: &'static str,
+        item: Option<&str>,
+        attempts: u32,
+    ) -> Self {
+        Self {
+            operation,
+            item: item.map(str::to_string),
+            correlation_id: error.correlation_id().map(str::to_string),
+            request_id: error.request_id().map(str::to_string),
+            endpoint: error.endpoint().map(str::to_string),
+            attempts,
+        }
+    }
+}
+
+/// Receives every error a client call finally fails with, after any retries,
+/// e.g. to forward it to Sentry or Rollbar. Called inline, like [`AuditSink`].
+pub trait ErrorSink: Send + Sync {
+    fn report(&self, error: &ElevenLabsTTDError, context: &ErrorContext);
+}
+
+impl<F> ErrorSink for F
+where
+    F: Fn(&ElevenLabsTTDError, &ErrorContext) + Send + Sync,
+{
+    fn report(&self, error: &ElevenLabsTTDError, context: &ErrorContext) {
+        self(error, context)
+    }
+}
@@ -0,0 +1,143 @@
+//! Threshold callbacks that fire as character usage approaches a limit
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Which limit a budget alert refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetScope {
+    /// The character budget configured on the client for this session
+    Session,
+    /// The account's subscription character limit
+    Subscription,
+}
+
+/// Passed to budget callbacks when usage crosses a configured threshold
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetAlert {
+    pub scope: BudgetScope,
+    // The threshold that was crossed, in percent.
+    pub threshold_percent: f64,
+    // Characters used so far.
+    pub used: u64,
+    // Characters allowed in total.
+    pub limit: u64,
+}
+
+impl BudgetAlert {
+    /// Percentage of the limit used so far
+    pub fn used_percent(&self) -> f64 {
+        self.used as f64 / self.limit as f64 * 100.0
+    }
+}
+
+pub(crate) type BudgetCallback = Arc<dyn Fn(&BudgetAlert) + Send + Sync>;
+
+struct Threshold {
+    percent: f64,
+    callback: BudgetCallback,
+    fired_session: AtomicBool,
+    fired_subscription: AtomicBool,
+}
+
+/// Registered thresholds, shared by every clone of a client.
+/// Each threshold fires at most once per scope until it is reset.
+#[derive(Clone)]
+pub(crate) struct BudgetMonitor {
+    thresholds: Arc<Vec<Threshold>>,
+    session_budget: Option<u64>,
+}
+
+impl BudgetMonitor {
+    pub(crate) fn new(thresholds: Vec<(f64, BudgetCallback)>, session_budget: Option<u64>) -> Self {
+        let thresholds = thresholds
+            .into_iter()
+            .map(|(percent, callback)| Threshold {
+                percent,
+                callback,
+                fired_session: AtomicBool::new(false),
+                fired_subscription: AtomicBool::new(false),
+            })
+            .collect();
+
+        Self {
+            thresholds: Arc::new(thresholds),
+            session_budget,
+        }
+    }
+
+    /// Check session usage against the configured character budget
+    pub(crate) fn check_session(&self, used: u64) {
+        if let Some(limit) = self.session_budget {
+            self.check(BudgetScope::Session, used, limit);
+        }
+    }
+
+    /// Check subscription usage reported by the API
+    pub(crate) fn check_subscription(&self, used: u64, limit: u64) {
+        self.check(BudgetScope::Subscription, used, limit);
+    }
+
+    /// Allow session thresholds to fire again, e.g. after usage was reset
+    pub(crate) fn reset_session(&self) {
+        for threshold in self.thresholds.iter() {
+            threshold.fired_session.store(false, Ordering::SeqCst);
+        }
+    }
+
+    fn check(&self, scope: BudgetScope, used: u64, limit: u64) {
+        if limit == 0 {
+            return;
+        }
+
+        let used_percent = used as f64 / limit as f64 * 100.0;
+        for threshold in self.thresholds.iter() {
+            if used_percent < threshold.percent {
+                continue;
+            }
+
+            let fired = match scope {
+                BudgetScope::Session => &threshold.fired_session,
+                BudgetScope::Subscription => &threshold.fired_subscription,
+            };
+            if !fired.swap(true, Ordering::SeqCst) {
+                (threshold.callback)(&BudgetAlert {
+                    scope,
+                    threshold_percent: threshold.percent,
+                    used,
+                    limit,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_thresholds_fire_once_when_crossed() {
+        let alerts = Arc::new(Mutex::new(Vec::new()));
+        let sink = alerts.clone();
+        let callback: BudgetCallback =
+            Arc::new(move |alert| sink.lock().unwrap().push(alert.clone()));
+        let monitor =
+            BudgetMonitor::new(vec![(50.0, callback.clone()), (90.0, callback)], Some(1000));
+
+        monitor.check_session(400);
+        assert!(alerts.lock().unwrap().is_empty());
+
+        monitor.check_session(600);
+        monitor.check_session(700);
+        assert_eq!(alerts.lock().unwrap().len(), 1);
+
+        monitor.check_session(950);
+        monitor.check_subscription(95, 100);
+        let alerts = alerts.lock().unwrap();
+        assert_eq!(alerts.len(), 4);
+        assert_eq!(alerts[1].threshold_percent, 90.0);
+        assert_eq!(alerts[2].scope, BudgetScope::Subscription);
+    }
+}
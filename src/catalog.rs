@@ -0,0 +1,120 @@
+//! Cached view of the account's voices for repeated lookups
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::ElevenLabsTTDClient;
+use crate::error::ElevenLabsTTDError;
+use crate::types::Voice;
+use crate::voices::match_name;
+
+/// How long fetched voices are served before a refresh
+pub const DEFAULT_CATALOG_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// The account's voices, fetched once and cached for a TTL.
+///
+/// The first lookup fetches the voices; once the TTL expires, lookups keep
+/// answering from the cached voices while a refresh runs in the background.
+/// Clones share the same cache.
+#[derive(Clone)]
+pub struct VoiceCatalog {
+    client: ElevenLabsTTDClient,
+    ttl: Duration,
+    state: Arc<Mutex<CatalogState>>,
+    refreshing: Arc<AtomicBool>,
+}
+
+#[derive(Default)]
+struct CatalogState {
+    voices: Arc<Vec<Voice>>,
+    fetched_at: Option<Instant>,
+}
+
+impl VoiceCatalog {
+    pub(crate) fn new(client: ElevenLabsTTDClient) -> Self {
+        Self {
+            client,
+            ttl: DEFAULT_CATALOG_TTL,
+            state: Arc::new(Mutex::new(CatalogState::default())),
+            refreshing: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Set how long fetched voices are considered fresh
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// All voices, fetching them on first use
+    pub async fn voices(&self) -> Result<Arc<Vec<Voice>>, ElevenLabsTTDError> {
+        let (voices, fetched_at) = {
+            let state = self.state.lock().unwrap();
+            (state.voices.clone(), state.fetched_at)
+        };
+
+        match fetched_at {
+            None => self.refresh().await,
+            Some(at) if at.elapsed() >= self.ttl => {
+                self.refresh_in_background();
+                Ok(voices)
+            }
+            Some(_) => Ok(voices),
+        }
+    }
+
+    /// Fetch the voices now, replacing the cached ones
+    pub async fn refresh(&self) -> Result<Arc<Vec<Voice>>, ElevenLabsTTDError> {
+        let voices = Arc::new(self.client.voices().list().collect_all().await?);
+        let mut state = self.state.lock().unwrap();
+        state.voices = voices.clone();
+        state.fetched_at = Some(Instant::now());
+        Ok(voices)
+    }
+
+    /// Find a voice by its voice ID
+    pub async fn find_by_id(&self, voice_id: &str) -> Result<Option<Voice>, ElevenLabsTTDError> {
+        Ok(self
+            .voices()
+            .await?
+            .iter()
+            .find(|v| v.voice_id == voice_id)
+            .cloned())
+    }
+
+    /// Find a voice by name (case-insensitive)
+    pub async fn find_by_name(&self, name: &str) -> Result<Option<Voice>, ElevenLabsTTDError> {
+        Ok(self
+            .voices()
+            .await?
+            .iter()
+            .find(|v| v.name.to_lowercase() == name.to_lowercase())
+            .cloned())
+    }
+
+    /// Search voices by name, best matches first, like [`crate::voices::all_voices::search`]
+    pub async fn search(&self, query: &str) -> Result<Vec<Voice>, ElevenLabsTTDError> {
+        let voices = self.voices().await?;
+        let mut matches: Vec<_> = voices
+            .iter()
+            .filter_map(|v| match_name(query, &v.name).map(|kind| (kind, v)))
+            .collect();
+        matches.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.name.cmp(&b.1.name)));
+        Ok(matches.into_iter().map(|(_, v)| v.clone()).collect())
+    }
+
+    fn refresh_in_background(&self) {
+        if self.client.is_shut_down() || self.refreshing.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let catalog = self.clone();
+        tokio::spawn(async move {
+            if let Err(error) = catalog.refresh().await {
+                tracing::warn!(%error, "voice catalog refresh failed");
+            }
+            catalog.refreshing.store(false, Ordering::Release);
+        });
+    }
+}
@@ -0,0 +1,213 @@
+//! Fail fast while the API is persistently failing
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::ElevenLabsTTDError;
+
+/// State of the client's circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    // Requests are sent.
+    Closed,
+    // Requests fail locally until the cooldown ends.
+    Open,
+    // The cooldown ended; one probe request is in flight and decides whether to close.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct Breaker {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    probing: bool,
+}
+
+/// Opens after `threshold` consecutive server errors, timeouts or connection
+/// failures; after `cooldown` one request is let through as a probe, which
+/// closes the circuit if it succeeds and reopens it otherwise.
+/// Shared by every clone of a client.
+#[derive(Debug, Clone)]
+pub(crate) struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    state: Arc<Mutex<Breaker>>,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            cooldown,
+            state: Arc::new(Mutex::new(Breaker {
+                consecutive_failures: 0,
+                opened_at: None,
+                probing: false,
+            })),
+        }
+    }
+
+    /// Allow a request through, or fail with the time left until the next probe.
+    /// The outcome is recorded with [`CircuitPermit::record`].
+    pub(crate) fn acquire(&self) -> Result<CircuitPermit, ElevenLabsTTDError> {
+        let mut breaker = self.state.lock().unwrap();
+        let Some(opened_at) = breaker.opened_at else {
+            return Ok(self.permit(false));
+        };
+        let elapsed = opened_at.elapsed();
+        if elapsed >= self.cooldown && !breaker.probing {
+            breaker.probing = true;
+            return Ok(self.permit(true));
+        }
+        Err(ElevenLabsTTDError::CircuitOpen {
+            retry_in: self.cooldown.saturating_sub(elapsed),
+        })
+    }
+
+    fn permit(&self, probe: bool) -> CircuitPermit {
+        CircuitPermit {
+            breaker: self.clone(),
+            probe,
+            recorded: false,
+        }
+    }
+
+    /// Record the outcome of a request let through by [`Self::acquire`]
+    fn record<T>(&self, result: &Result<T, ElevenLabsTTDError>) {
+        let upstream_failed = match result {
+            Ok(_) => false,
+            Err(e) => match e {
+                ElevenLabsTTDError::ServerError { .. }
+                | ElevenLabsTTDError::Timeout { .. }
+                | ElevenLabsTTDError::StreamInterrupted { .. }
+                | ElevenLabsTTDError::CorruptAudio { .. } => true,
+                ElevenLabsTTDError::RequestError(e) => e.is_connect() || e.is_timeout(),
+                // The API answered, so it's up
+                _ => false,
+            },
+        };
+
+        let mut breaker = self.state.lock().unwrap();
+        breaker.probing = false;
+        if !upstream_failed {
+            breaker.consecutive_failures = 0;
+            breaker.opened_at = None;
+            return;
+        }
+        breaker.consecutive_failures += 1;
+        if breaker.opened_at.is_some() || breaker.consecutive_failures >= self.threshold {
+            if breaker.opened_at.is_none() {
+                tracing::warn!(
+                    failures = breaker.consecutive_failures,
+                    "circuit breaker opened"
+                );
+            }
+            breaker.opened_at = Some(Instant::now());
+        }
+    }
+
+    /// A probe ended without an outcome, e.g. its request was cancelled: count it
+    /// as a failure so the circuit reopens and the next cooldown sends a new probe
+    fn abandon_probe(&self) {
+        let mut breaker = self.state.lock().unwrap();
+        breaker.probing = false;
+        breaker.consecutive_failures += 1;
+        breaker.opened_at = Some(Instant::now());
+    }
+
+    pub(crate) fn state(&self) -> CircuitState {
+        let breaker = self.state.lock().unwrap();
+        match breaker.opened_at {
+            None => CircuitState::Closed,
+            Some(_) if breaker.probing => CircuitState::HalfOpen,
+            Some(opened_at) if opened_at.elapsed() >= self.cooldown => CircuitState::HalfOpen,
+            Some(_) => CircuitState::Open,
+        }
+    }
+}
+
+/// A request let through by [`CircuitBreaker::acquire`]. Dropping it without
+/// recording an outcome counts a probe as failed, so a cancelled probe can't
+/// leave the circuit half-open forever.
+#[derive(Debug)]
+#[must_use]
+pub(crate) struct CircuitPermit {
+    breaker: CircuitBreaker,
+    probe: bool,
+    recorded: bool,
+}
+
+impl CircuitPermit {
+    /// Record the outcome of the request
+    pub(crate) fn record<T>(mut self, result: &Result<T, ElevenLabsTTDError>) {
+        self.recorded = true;
+        self.breaker.record(result);
+    }
+}
+
+impl Drop for CircuitPermit {
+    fn drop(&mut self) {
+        if self.probe && !self.recorded {
+            self.breaker.abandon_probe();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server_error() -> Result<(), ElevenLabsTTDError> {
+        Err(ElevenLabsTTDError::ServerError {
+            status: 503,
+            message: "overloaded".to_string(),
+            correlation_id: None,
+            response: None,
+        })
+    }
+
+    #[test]
+    fn test_opens_and_probes() {
+        let breaker = CircuitBreaker::new(2, Duration::from_millis(20));
+        breaker.acquire().unwrap().record(&server_error());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.acquire().unwrap().record(&server_error());
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert_eq!(breaker.acquire().unwrap_err().kind(), "circuit_open");
+
+        // One probe after the cooldown; a failure reopens the circuit
+        std::thread::sleep(Duration::from_millis(25));
+        let probe = breaker.acquire().unwrap();
+        assert!(breaker.acquire().is_err());
+        probe.record(&server_error());
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // A successful probe closes it
+        std::thread::sleep(Duration::from_millis(25));
+        breaker.acquire().unwrap().record(&Ok(()));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_probe_reopens_circuit() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(20));
+        breaker.acquire().unwrap().record(&server_error());
+        std::thread::sleep(Duration::from_millis(25));
+
+        // The probe's request is cancelled before an outcome is recorded
+        let probing = breaker.clone();
+        let cancelled = tokio::time::timeout(Duration::from_millis(5), async move {
+            let permit = probing.acquire().unwrap();
+            std::future::pending::<()>().await;
+            permit.record(&Ok(()));
+        })
+        .await;
+        assert!(cancelled.is_err());
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // The next cooldown lets a new probe through
+        std::thread::sleep(Duration::from_millis(25));
+        breaker.acquire().unwrap().record(&Ok(()));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
@@ -0,0 +1,151 @@
+//! Pre-send content checks, so platforms can enforce their own policies before
+//! any text reaches the API
+
+use crate::types::TTDInput;
+
+/// Inspects every turn before a request is sent; returning `Err(reason)` rejects
+/// the request with [`crate::ElevenLabsTTDError::ContentRejected`].
+/// Called inline, so implementations should be quick.
+pub trait ContentFilter: Send + Sync {
+    fn check(&self, input: &TTDInput) -> Result<(), String>;
+}
+
+impl<F> ContentFilter for F
+where
+    F: Fn(&TTDInput) -> Result<(), String> + Send + Sync,
+{
+    fn check(&self, input: &TTDInput) -> Result<(), String> {
+        self(input)
+    }
+}
+
+/// Rejects turns containing any word from a list, ignoring case.
+///
+/// Entries match whole words; `*` at the start or end of an entry matches any
+/// prefix or suffix (`scam*` matches "scammer"), and entries with spaces match phrases.
+#[derive(Debug, Clone, Default)]
+pub struct WordListFilter {
+    patterns: Vec<Vec<Pattern>>,
+}
+
+#[derive(Debug, Clone)]
+struct Pattern {
+    word: String,
+    any_prefix: bool,
+    any_suffix: bool,
+}
+
+impl Pattern {
+    fn matches(&self, word: &str) -> bool {
+        match (self.any_prefix, self.any_suffix) {
+            (true, true) => word.contains(&self.word),
+            (true, false) => word.ends_with(&self.word),
+            (false, true) => word.starts_with(&self.word),
+            (false, false) => word == self.word,
+        }
+    }
+}
+
+impl WordListFilter {
+    pub fn new<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self::default().words(words)
+    }
+
+    /// One entry per line, ignoring blank lines and `#` comments
+    pub fn from_list(list: &str) -> Self {
+        Self::new(
+            list.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#')),
+        )
+    }
+
+    /// Add more entries
+    pub fn words<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for entry in words {
+            let phrase: Vec<Pattern> = normalized_words(entry.as_ref())
+                .into_iter()
+                .map(|word| Pattern {
+                    any_prefix: word.starts_with('*'),
+                    any_suffix: word.ends_with('*'),
+                    word: word.trim_matches('*').to_string(),
+                })
+                .filter(|pattern| !pattern.word.is_empty())
+                .collect();
+            if !phrase.is_empty() {
+                self.patterns.push(phrase);
+            }
+        }
+        self
+    }
+
+    /// The first entry found in `text`, as written in the text
+    pub fn find(&self, text: &str) -> Option<String> {
+        let words = normalized_words(text);
+        for phrase in &self.patterns {
+            for candidate in words.windows(phrase.len()) {
+                if candidate
+                    .iter()
+                    .zip(phrase)
+                    .all(|(word, pattern)| pattern.matches(word))
+                {
+                    return Some(candidate.join(" "));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl ContentFilter for WordListFilter {
+    fn check(&self, input: &TTDInput) -> Result<(), String> {
+        match self.find(&input.text) {
+            Some(found) => Err(format!("prohibited term {:?}", found)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Lowercase words, split on anything but letters, digits, `'` and `*`
+fn normalized_words(text: &str) -> Vec<String> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '\'' || c == '*'))
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(text: &str) -> TTDInput {
+        TTDInput {
+            text: text.to_string(),
+            voice_id: "voice".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_word_list_matches_words_wildcards_and_phrases() {
+        let filter = WordListFilter::from_list("# policy\nscam*\n\nwire transfer\n*bomb");
+
+        assert!(filter.check(&turn("A perfectly fine sentence.")).is_ok());
+        assert!(filter.check(&turn("Scampi for dinner?")).is_err());
+        assert_eq!(filter.find("He's a SCAMMER!").as_deref(), Some("scammer"));
+        assert_eq!(
+            filter.find("Send a wire  transfer now").as_deref(),
+            Some("wire transfer")
+        );
+        assert_eq!(filter.find("wire the transfer"), None);
+        assert_eq!(filter.find("a photobomb"), Some("photobomb".to_string()));
+        assert_eq!(filter.find("bombastic"), None);
+    }
+}
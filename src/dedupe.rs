@@ -0,0 +1,272 @@
+//! Deduplication of repeated requests: an in-memory cache keyed by idempotency key,
+//! and an optional on-disk store keyed by request hash that survives process restarts
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::ElevenLabsTTDError;
+use crate::types::TTDAudio;
+
+/// File name of the index kept inside a [`DedupeStore`] directory
+const INDEX_FILE: &str = "index.json";
+
+/// Version of the index format, bumped whenever the keys change meaning.
+/// Version 2 keys entries by [`crate::TTDRequest::canonical_hash`]; older
+/// indexes were an unversioned map keyed by a hash of the request body.
+const INDEX_VERSION: u32 = 2;
+
+/// The index as written to disk
+#[derive(Serialize, Deserialize)]
+struct IndexFile {
+    version: u32,
+    entries: HashMap<String, StoredEntry>,
+}
+
+/// Default number of idempotent responses kept in memory
+pub const DEFAULT_DEDUPE_CAPACITY: usize = 256;
+
+/// Store key for a request hash, scoped to the tenant or overridden API key so
+/// accounts sharing a store can't load each other's audio
+pub(crate) fn scoped_hash(hash: String, scope: Option<&str>) -> String {
+    match scope {
+        Some(scope) => Sha256::digest(format!("{}:{}", scope, hash).as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect(),
+        None => hash,
+    }
+}
+
+/// Shared cache of successful responses keyed by idempotency key.
+/// Clones share the same storage, so every clone of a client dedupes together.
+#[derive(Clone)]
+pub(crate) struct DedupeCache {
+    inner: Arc<Mutex<DedupeState>>,
+}
+
+struct DedupeState {
+    capacity: usize,
+    entries: HashMap<String, TTDAudio>,
+    // Insertion order, used to evict the oldest entry once capacity is reached
+    order: VecDeque<String>,
+}
+
+impl DedupeCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(DedupeState {
+                capacity,
+                entries: HashMap::new(),
+                order: VecDeque::new(),
+            })),
+        }
+    }
+
+    /// Get a previously stored response for this key
+    pub(crate) fn get(&self, key: &str) -> Option<TTDAudio> {
+        let state = self.inner.lock().unwrap();
+        state.entries.get(key).cloned()
+    }
+
+    /// Store a successful response under this key
+    pub(crate) fn insert(&self, key: String, audio: TTDAudio) {
+        let mut state = self.inner.lock().unwrap();
+        if state.capacity == 0 {
+            return;
+        }
+
+        if state.entries.insert(key.clone(), audio).is_none() {
+            state.order.push_back(key);
+        }
+
+        while state.order.len() > state.capacity {
+            if let Some(oldest) = state.order.pop_front() {
+                state.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// A previously generated output recorded in a [`DedupeStore`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredEntry {
+    // Path of the stored audio file.
+    pub path: PathBuf,
+    // History item ID returned by ElevenLabs for the original generation, if any.
+    pub history_item_id: Option<String>,
+}
+
+/// Persistent request-hash → output index, so re-running an unchanged pipeline
+/// only regenerates the requests whose inputs actually changed
+#[derive(Debug, Clone)]
+pub struct DedupeStore {
+    dir: PathBuf,
+    index: Arc<Mutex<HashMap<String, StoredEntry>>>,
+}
+
+impl DedupeStore {
+    /// Open (or create) a store in the given directory. An index written by an
+    /// older version of the crate is keyed differently and can't be migrated, so
+    /// it is started over: those requests are generated again on their next run.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self, ElevenLabsTTDError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let index_path = dir.join(INDEX_FILE);
+        let index = if index_path.exists() {
+            let file: serde_json::Value = serde_json::from_slice(&fs::read(&index_path)?)?;
+            match file.get("version").and_then(serde_json::Value::as_u64) {
+                Some(version) if version == INDEX_VERSION as u64 => {
+                    serde_json::from_value::<IndexFile>(file)?.entries
+                }
+                Some(version) if version > INDEX_VERSION as u64 => {
+                    return Err(ElevenLabsTTDError::ValidationError(format!(
+                        "Dedupe store index version {} is newer than supported version {}",
+                        version, INDEX_VERSION
+                    )));
+                }
+                _ => {
+                    tracing::warn!(
+                        dir = %dir.display(),
+                        "dedupe store index is from an older version; starting it over"
+                    );
+                    HashMap::new()
+                }
+            }
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self {
+            dir,
+            index: Arc::new(Mutex::new(index)),
+        })
+    }
+
+    /// Look up the stored entry for a request hash
+    pub fn get(&self, hash: &str) -> Option<StoredEntry> {
+        self.index.lock().unwrap().get(hash).cloned()
+    }
+
+    /// Number of stored outputs
+    pub fn len(&self) -> usize {
+        self.index.lock().unwrap().len()
+    }
+
+    /// Whether the store has no outputs yet
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Load the stored audio for a request hash, if its file still exists
+    pub(crate) fn load(&self, hash: &str) -> Option<(Vec<u8>, StoredEntry)> {
+        let entry = self.get(hash)?;
+        let audio = fs::read(&entry.path).ok()?;
+        Some((audio, entry))
+    }
+
+    /// Write the audio next to the index and record it under the request hash
+    pub(crate) fn save(&self, hash: &str, audio: &TTDAudio) -> Result<(), ElevenLabsTTDError> {
+        let path = self.dir.join(format!("{}.bin", hash));
+        fs::write(&path, &audio.audio)?;
+
+        let mut index = self.index.lock().unwrap();
+        index.insert(
+            hash.to_string(),
+            StoredEntry {
+                path,
+                history_item_id: audio.history_item_id.clone(),
+            },
+        );
+        self.write_index(&index)
+    }
+
+    /// Write the index to disk, e.g. before the process exits
+    pub fn flush(&self) -> Result<(), ElevenLabsTTDError> {
+        self.write_index(&self.index.lock().unwrap())
+    }
+
+    fn write_index(&self, index: &HashMap<String, StoredEntry>) -> Result<(), ElevenLabsTTDError> {
+        // Write to a temporary file first so a crash never leaves a truncated index
+        let tmp_path = self.dir.join(format!("{}.tmp", INDEX_FILE));
+        let file = serde_json::json!({ "version": INDEX_VERSION, "entries": index });
+        fs::write(&tmp_path, serde_json::to_vec_pretty(&file)?)?;
+        fs::rename(&tmp_path, self.dir.join(INDEX_FILE))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn audio(bytes: &[u8]) -> TTDAudio {
+        TTDAudio::new(bytes.to_vec(), "test".to_string())
+    }
+
+    #[test]
+    fn test_get_returns_inserted_response() {
+        let cache = DedupeCache::new(4);
+        cache.insert("key-1".to_string(), audio(b"abc"));
+
+        assert_eq!(cache.get("key-1").unwrap().audio, b"abc");
+        assert!(cache.get("key-2").is_none());
+    }
+
+    #[test]
+    fn test_oldest_entry_is_evicted() {
+        let cache = DedupeCache::new(2);
+        cache.insert("a".to_string(), audio(b"1"));
+        cache.insert("b".to_string(), audio(b"2"));
+        cache.insert("c".to_string(), audio(b"3"));
+
+        assert!(cache.get("a").is_none());
+        assert!(cache.get("b").is_some());
+        assert!(cache.get("c").is_some());
+    }
+
+    #[test]
+    fn test_store_persists_across_reopen() {
+        let dir = std::env::temp_dir().join(format!("ttd-dedupe-{}", uuid::Uuid::new_v4()));
+
+        let store = DedupeStore::open(&dir).unwrap();
+        assert!(store.is_empty());
+        store.save("hash-1", &audio(b"turn one")).unwrap();
+
+        let reopened = DedupeStore::open(&dir).unwrap();
+        let (bytes, entry) = reopened.load("hash-1").unwrap();
+        assert_eq!(bytes, b"turn one");
+        assert!(entry.path.starts_with(&dir));
+        assert!(reopened.load("hash-2").is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_store_starts_over_from_unversioned_index() {
+        let dir = std::env::temp_dir().join(format!("ttd-dedupe-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let legacy = serde_json::json!({
+            "old-hash": { "path": dir.join("old-hash.bin"), "history_item_id": null }
+        });
+        fs::write(dir.join(INDEX_FILE), legacy.to_string()).unwrap();
+
+        let store = DedupeStore::open(&dir).unwrap();
+        assert!(store.is_empty());
+        store.save("hash-1", &audio(b"turn one")).unwrap();
+        let index: serde_json::Value =
+            serde_json::from_slice(&fs::read(dir.join(INDEX_FILE)).unwrap()).unwrap();
+        assert_eq!(index["version"], INDEX_VERSION);
+        assert!(index["entries"]["hash-1"].is_object());
+
+        fs::write(dir.join(INDEX_FILE), r#"{"version":99,"entries":{}}"#).unwrap();
+        assert!(DedupeStore::open(&dir).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,911 @@
+//! Scripts mixing dialogue turns with sound-effect cues, rendered with
+//! [`crate::ElevenLabsTTDClient::render_dialogue`]
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ElevenLabsTTDClient;
+use crate::error::ElevenLabsTTDError;
+use crate::formats::{Codec, OutputFormat};
+use crate::models::{elevanlabs_models, supports_dialogue};
+use crate::types::{Model, TTDInput, Voice, VoiceRef};
+use crate::voices::all_voices;
+
+/// Length of a sound effect in sandbox mode when the cue doesn't set one
+pub const DEFAULT_SFX_DURATION: Duration = Duration::from_secs(2);
+
+/// Longest pause a single pause tag stands for; longer pauses get several tags
+pub const MAX_BREAK: Duration = Duration::from_secs(3);
+
+/// Speaker names (matched case-insensitively) to voice IDs, used when importing scripts
+pub type SpeakerMap = HashMap<String, String>;
+
+/// Why a speaker of a [`SpeakerMap`] can't be generated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CastingProblem {
+    // The voice isn't in the account's voices.
+    UnknownVoice,
+    // The voice is fine-tuned for other models only.
+    UnsupportedModel(String),
+}
+
+/// A speaker whose voice failed [`CastValidation::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CastingError {
+    pub speaker: String,
+    pub voice_id: String,
+    pub problem: CastingProblem,
+}
+
+impl std::fmt::Display for CastingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.problem {
+            CastingProblem::UnknownVoice => write!(
+                f,
+                "{}: voice {} not found in the account",
+                self.speaker, self.voice_id
+            ),
+            CastingProblem::UnsupportedModel(model_id) => write!(
+                f,
+                "{}: voice {} can't be used with {}",
+                self.speaker, self.voice_id, model_id
+            ),
+        }
+    }
+}
+
+/// Checks of a [`SpeakerMap`]'s voices against the account, to catch casting
+/// mistakes before any audio is generated
+pub trait CastValidation {
+    /// Check every voice against the account's voices and the default model,
+    /// returning one error per speaker that can't be generated
+    fn validate<'a>(
+        &'a self,
+        client: &'a ElevenLabsTTDClient,
+    ) -> impl Future<Output = Result<Vec<CastingError>, ElevenLabsTTDError>> + Send + 'a {
+        self.validate_for_model(client, elevanlabs_models::ELEVEN_V3)
+    }
+
+    /// Like [`Self::validate`], for `model_id`. Fails if the model doesn't exist
+    /// or can't generate dialogue.
+    fn validate_for_model<'a>(
+        &'a self,
+        client: &'a ElevenLabsTTDClient,
+        model_id: &'a str,
+    ) -> impl Future<Output = Result<Vec<CastingError>, ElevenLabsTTDError>> + Send + 'a;
+}
+
+impl CastValidation for SpeakerMap {
+    async fn validate_for_model(
+        &self,
+        client: &ElevenLabsTTDClient,
+        model_id: &str,
+    ) -> Result<Vec<CastingError>, ElevenLabsTTDError> {
+        let models = client.models().await?;
+        check_model(&models, model_id)?;
+        let voices = client.voices().list().collect_all().await?;
+        Ok(casting_errors(self, &voices, model_id))
+    }
+}
+
+/// Fail unless `model_id` is one of `models` and can generate dialogue
+fn check_model(models: &[Model], model_id: &str) -> Result<(), ElevenLabsTTDError> {
+    let Some(model) = models.iter().find(|model| model.model_id == model_id) else {
+        return Err(ElevenLabsTTDError::ValidationError(format!(
+            "Model {} is not available to the account",
+            model_id
+        )));
+    };
+    if !model.can_do_text_to_speech || !supports_dialogue(model_id) {
+        return Err(ElevenLabsTTDError::ValidationError(format!(
+            "Model {} can't generate dialogue",
+            model_id
+        )));
+    }
+    Ok(())
+}
+
+/// Speakers of `cast` whose voice is missing from `voices`, or is a professional
+/// clone fine-tuned only for models other than `model_id`, ordered by speaker
+fn casting_errors(cast: &SpeakerMap, voices: &[Voice], model_id: &str) -> Vec<CastingError> {
+    let mut errors: Vec<CastingError> = cast
+        .iter()
+        .filter_map(|(speaker, voice_id)| {
+            let problem = match voices.iter().find(|voice| &voice.voice_id == voice_id) {
+                None => CastingProblem::UnknownVoice,
+                Some(voice) if !voice.supports_model(model_id) => {
+                    CastingProblem::UnsupportedModel(model_id.to_string())
+                }
+                Some(_) => return None,
+            };
+            Some(CastingError {
+                speaker: speaker.clone(),
+                voice_id: voice_id.clone(),
+                problem,
+            })
+        })
+        .collect();
+    errors.sort_by(|a, b| a.speaker.cmp(&b.speaker));
+    errors
+}
+
+/// A sound effect rendered with the sound-generation endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SoundCue {
+    // What the effect should sound like, e.g. "door slams".
+    pub description: String,
+    // Length of the effect; when not set, the API picks one.
+    pub duration: Option<Duration>,
+}
+
+/// One line of a [`Dialogue`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DialogueLine {
+    Turn(TTDInput),
+    // A turn in the given ISO 639-1 language, e.g. "fr".
+    LocalizedTurn(TTDInput, String),
+    Sfx(SoundCue),
+    Pause(Duration),
+}
+
+/// A radio-drama style script: dialogue turns interleaved with sound effects
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Dialogue {
+    pub lines: Vec<DialogueLine>,
+}
+
+/// Machine-readable record of a dialogue, written next to its audio
+#[derive(Debug, Clone, Serialize)]
+pub struct Transcript {
+    pub lines: Vec<TranscriptLine>,
+}
+
+/// One line of a [`Transcript`]
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptLine {
+    // "turn", "sfx" or "pause".
+    pub kind: &'static str,
+    // Language of the turn, when tagged with one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    // Voice ID of the speaker, for turns.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+    // Name of the speaker, when it is a pre-built voice.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker_name: Option<String>,
+    // The spoken text, the sound effect description, or the pause length, e.g. "1.5s".
+    pub text: String,
+    // Audio tags in the text, e.g. "laughs" for `[laughs]`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    // Position in the rendered audio in seconds, when known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<f64>,
+}
+
+impl Transcript {
+    /// Pretty-printed JSON
+    pub fn to_json(&self) -> Result<String, ElevenLabsTTDError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Write the transcript next to an audio file (`scene.mp3` → `scene.json`)
+    pub fn write_sidecar<P: AsRef<Path>>(
+        &self,
+        audio_path: P,
+    ) -> Result<PathBuf, ElevenLabsTTDError> {
+        let path = audio_path.as_ref().with_extension("json");
+        std::fs::write(&path, self.to_json()?)?;
+        Ok(path)
+    }
+}
+
+/// Speaker and text of a `**Name:** text` or `**Name**: text` line
+fn markdown_turn(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix("**")?;
+    let (name, text) = rest.split_once("**")?;
+    let (name, text) = match name.strip_suffix(':') {
+        Some(name) => (name, text),
+        None => (name, text.strip_prefix(':')?),
+    };
+    let name = name.trim();
+    (!name.is_empty()).then_some((name, text.trim()))
+}
+
+fn lowercase_cast(cast: &SpeakerMap) -> HashMap<String, &String> {
+    cast.iter()
+        .map(|(name, voice_id)| (name.to_lowercase(), voice_id))
+        .collect()
+}
+
+/// Records of RFC 4180 CSV: comma-separated, fields optionally quoted with `"`,
+/// `""` for a quote inside a quoted field, which may also span lines
+fn csv_records(text: &str) -> Result<Vec<Vec<String>>, ElevenLabsTTDError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{FEFF}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            _ if quoted => field.push(c),
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if quoted {
+        return Err(ElevenLabsTTDError::ValidationError(
+            "CSV has an unterminated quoted field".to_string(),
+        ));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Text between square brackets, e.g. `[whispers]`
+fn audio_tags(text: &str) -> Vec<String> {
+    text.split('[')
+        .skip(1)
+        .filter_map(|part| part.split_once(']'))
+        .map(|(tag, _)| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+impl From<Vec<TTDInput>> for Dialogue {
+    /// A dialogue of plain turns
+    fn from(inputs: Vec<TTDInput>) -> Self {
+        Self {
+            lines: inputs.into_iter().map(DialogueLine::Turn).collect(),
+        }
+    }
+}
+
+/// Consecutive lines rendered by one API call
+pub(crate) enum Segment<'a> {
+    Turns(Vec<TTDInput>, Option<&'a str>),
+    Sfx(&'a SoundCue),
+    Silence(Duration),
+}
+
+impl Dialogue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a spoken turn
+    pub fn line<V: VoiceRef, T: Into<String>>(mut self, voice: V, text: T) -> Self {
+        self.lines
+            .push(DialogueLine::Turn(TTDInput::new(voice, text)));
+        self
+    }
+
+    /// Add a spoken turn in another language, given as an ISO 639-1 code, e.g. "fr".
+    /// Turns in different languages are generated in separate requests.
+    pub fn line_in<V: VoiceRef, L: Into<String>, T: Into<String>>(
+        mut self,
+        voice: V,
+        language: L,
+        text: T,
+    ) -> Self {
+        self.lines.push(DialogueLine::LocalizedTurn(
+            TTDInput::new(voice, text),
+            language.into(),
+        ));
+        self
+    }
+
+    /// Add a sound effect of API-chosen length
+    pub fn sfx<S: Into<String>>(mut self, description: S) -> Self {
+        self.lines.push(DialogueLine::Sfx(SoundCue {
+            description: description.into(),
+            duration: None,
+        }));
+        self
+    }
+
+    /// Add a sound effect of a set length
+    pub fn sfx_for<S: Into<String>>(mut self, description: S, duration: Duration) -> Self {
+        self.lines.push(DialogueLine::Sfx(SoundCue {
+            description: description.into(),
+            duration: Some(duration),
+        }));
+        self
+    }
+
+    /// Add a pause of `ms` milliseconds. PCM, µ-law and A-law output get exact
+    /// client-side silence; compressed formats get `[pause]`-style audio tags on the
+    /// preceding turn.
+    pub fn pause(mut self, ms: u64) -> Self {
+        self.lines
+            .push(DialogueLine::Pause(Duration::from_millis(ms)));
+        self
+    }
+
+    /// Parse a script of `NAME: text` lines and `[sound effect]` cues.
+    /// `cast` maps speaker names (case-insensitive) to voice IDs; blank lines are skipped.
+    pub fn parse(script: &str, cast: &SpeakerMap) -> Result<Self, ElevenLabsTTDError> {
+        let cast = lowercase_cast(cast);
+
+        let mut dialogue = Dialogue::new();
+        for (number, line) in script.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(cue) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                dialogue = dialogue.sfx(cue.trim());
+                continue;
+            }
+
+            let (speaker, text) = line.split_once(':').ok_or_else(|| {
+                ElevenLabsTTDError::ValidationError(format!(
+                    "Line {} is neither `NAME: text` nor a `[cue]`",
+                    number + 1
+                ))
+            })?;
+            let voice_id = cast.get(&speaker.trim().to_lowercase()).ok_or_else(|| {
+                ElevenLabsTTDError::ValidationError(format!(
+                    "Line {}: no voice cast for {}",
+                    number + 1,
+                    speaker.trim()
+                ))
+            })?;
+            dialogue = dialogue.line(voice_id.as_str(), text.trim());
+        }
+
+        Ok(dialogue)
+    }
+
+    /// Read a CSV spreadsheet with `speaker` and `text` columns (other columns are
+    /// ignored). Speakers are looked up in `speakers`; a row with no speaker and a
+    /// `[cue]` as text is a sound effect.
+    pub fn from_csv<R: Read>(reader: R, speakers: &SpeakerMap) -> Result<Self, ElevenLabsTTDError> {
+        let mut dialogues = Self::read_csv(reader, speakers, None)?;
+        Ok(dialogues
+            .pop()
+            .map(|(_, dialogue)| dialogue)
+            .unwrap_or_default())
+    }
+
+    /// Like [`Self::from_csv`], but rows are split into separate dialogues by the
+    /// value of `group_column` (e.g. `scene`), in order of first appearance
+    pub fn batch_from_csv<R: Read>(
+        reader: R,
+        speakers: &SpeakerMap,
+        group_column: &str,
+    ) -> Result<Vec<(String, Self)>, ElevenLabsTTDError> {
+        Self::read_csv(reader, speakers, Some(group_column))
+    }
+
+    fn read_csv<R: Read>(
+        mut reader: R,
+        speakers: &SpeakerMap,
+        group_column: Option<&str>,
+    ) -> Result<Vec<(String, Self)>, ElevenLabsTTDError> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        let mut records = csv_records(&text)?.into_iter();
+
+        let header = records.next().unwrap_or_default();
+        let column = |name: &str| {
+            header
+                .iter()
+                .position(|h| h.trim().eq_ignore_ascii_case(name))
+                .ok_or_else(|| {
+                    ElevenLabsTTDError::ValidationError(format!("CSV has no `{}` column", name))
+                })
+        };
+        let speaker_column = column("speaker")?;
+        let text_column = column("text")?;
+        let group_column = group_column.map(column).transpose()?;
+
+        let cast = lowercase_cast(speakers);
+        let mut groups: Vec<(String, Dialogue)> = Vec::new();
+        // The header is row 1
+        for (row, record) in records.enumerate().map(|(i, r)| (i + 2, r)) {
+            let field = |i: usize| record.get(i).map(|f| f.trim()).unwrap_or_default();
+            let (speaker, text) = (field(speaker_column), field(text_column));
+            if speaker.is_empty() && text.is_empty() {
+                continue;
+            }
+
+            let group = group_column.map(field).unwrap_or_default();
+            let index = match groups.iter().position(|(name, _)| name == group) {
+                Some(index) => index,
+                None => {
+                    groups.push((group.to_string(), Dialogue::new()));
+                    groups.len() - 1
+                }
+            };
+            let dialogue = &mut groups[index].1;
+
+            if speaker.is_empty()
+                && let Some(cue) = text.strip_prefix('[').and_then(|t| t.strip_suffix(']'))
+            {
+                *dialogue = std::mem::take(dialogue).sfx(cue.trim());
+                continue;
+            }
+            let voice_id = cast.get(&speaker.to_lowercase()).ok_or_else(|| {
+                ElevenLabsTTDError::ValidationError(format!(
+                    "Row {}: no voice cast for {:?}",
+                    row, speaker
+                ))
+            })?;
+            *dialogue = std::mem::take(dialogue).line(voice_id.as_str(), text);
+        }
+
+        Ok(groups)
+    }
+
+    /// Read a Markdown script as one dialogue, ignoring scene headings.
+    /// See [`Self::scenes_from_markdown`] for the format.
+    pub fn from_markdown(
+        markdown: &str,
+        speakers: &SpeakerMap,
+    ) -> Result<Self, ElevenLabsTTDError> {
+        let lines = Self::scenes_from_markdown(markdown, speakers)?
+            .into_iter()
+            .flat_map(|(_, scene)| scene.lines)
+            .collect();
+        Ok(Dialogue { lines })
+    }
+
+    /// Read a Markdown script into scenes, named by their headings:
+    ///
+    /// ```markdown
+    /// # The kitchen
+    ///
+    /// **Ann:** Did you hear that?
+    /// **Bob**: Hear what? Lines wrapped onto the next line
+    /// continue the same turn.
+    ///
+    /// [door slams]
+    ///
+    /// Any other paragraph is a stage direction and is skipped.
+    /// ```
+    ///
+    /// Every heading level starts a new scene; turns before the first heading
+    /// form a scene with an empty name.
+    pub fn scenes_from_markdown(
+        markdown: &str,
+        speakers: &SpeakerMap,
+    ) -> Result<Vec<(String, Self)>, ElevenLabsTTDError> {
+        let cast = lowercase_cast(speakers);
+        let mut scenes: Vec<(String, Dialogue)> = Vec::new();
+        // Whether the previous line was part of a turn, so wrapped lines continue it
+        let mut in_turn = false;
+
+        for (number, line) in markdown.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                in_turn = false;
+                continue;
+            }
+
+            if line.starts_with('#') {
+                let title = line.trim_start_matches('#').trim();
+                scenes.push((title.to_string(), Dialogue::new()));
+                in_turn = false;
+                continue;
+            }
+
+            if scenes.is_empty() {
+                scenes.push((String::new(), Dialogue::new()));
+            }
+            let scene = &mut scenes.last_mut().unwrap().1;
+
+            if let Some((speaker, text)) = markdown_turn(line) {
+                let voice_id = cast.get(&speaker.to_lowercase()).ok_or_else(|| {
+                    ElevenLabsTTDError::ValidationError(format!(
+                        "Line {}: no voice cast for {}",
+                        number + 1,
+                        speaker
+                    ))
+                })?;
+                *scene = std::mem::take(scene).line(voice_id.as_str(), text);
+                in_turn = true;
+            } else if let Some(cue) = line
+                .trim_matches(|c| c == '*' || c == '_')
+                .strip_prefix('[')
+                .and_then(|l| l.strip_suffix(']'))
+            {
+                *scene = std::mem::take(scene).sfx(cue.trim());
+                in_turn = false;
+            } else if in_turn && let Some(DialogueLine::Turn(turn)) = scene.lines.last_mut() {
+                turn.text.push(' ');
+                turn.text.push_str(line);
+            }
+        }
+
+        scenes.retain(|(_, scene)| !scene.lines.is_empty());
+        Ok(scenes)
+    }
+
+    /// Speakers, lines and tags of the dialogue, without timings
+    pub fn transcript(&self) -> Transcript {
+        let lines = self
+            .lines
+            .iter()
+            .map(|line| match line {
+                DialogueLine::Turn(input) | DialogueLine::LocalizedTurn(input, _) => {
+                    TranscriptLine {
+                        kind: "turn",
+                        language: match line {
+                            DialogueLine::LocalizedTurn(_, language) => Some(language.clone()),
+                            _ => None,
+                        },
+                        speaker: Some(input.voice_id.clone()),
+                        speaker_name: all_voices::name_for_id(&input.voice_id).map(str::to_string),
+                        text: input.text.clone(),
+                        tags: audio_tags(&input.text),
+                        start: None,
+                        end: None,
+                    }
+                }
+                DialogueLine::Sfx(cue) => TranscriptLine {
+                    kind: "sfx",
+                    language: None,
+                    speaker: None,
+                    speaker_name: None,
+                    text: cue.description.clone(),
+                    tags: Vec::new(),
+                    start: None,
+                    end: None,
+                },
+                DialogueLine::Pause(duration) => TranscriptLine {
+                    kind: "pause",
+                    language: None,
+                    speaker: None,
+                    speaker_name: None,
+                    text: format!("{}s", duration.as_secs_f64()),
+                    tags: Vec::new(),
+                    start: None,
+                    end: None,
+                },
+            })
+            .collect();
+        Transcript { lines }
+    }
+
+    /// The transcript as pretty-printed JSON
+    pub fn to_transcript_json(&self) -> Result<String, ElevenLabsTTDError> {
+        self.transcript().to_json()
+    }
+
+    /// Group consecutive turns in the same language so each run is generated in one request.
+    /// Pauses become silence for uncompressed formats, and audio tags
+    /// otherwise unless there's no turn before them to carry the tag.
+    pub(crate) fn segments(&self, output_format: OutputFormat) -> Vec<Segment<'_>> {
+        let raw_samples = matches!(
+            output_format.codec(),
+            Codec::Pcm | Codec::Ulaw | Codec::Alaw
+        );
+
+        let mut segments = Vec::new();
+        for line in &self.lines {
+            match line {
+                DialogueLine::Turn(input) | DialogueLine::LocalizedTurn(input, _) => {
+                    let language = match line {
+                        DialogueLine::LocalizedTurn(_, language) => Some(language.as_str()),
+                        _ => None,
+                    };
+                    match segments.last_mut() {
+                        Some(Segment::Turns(turns, current)) if *current == language => {
+                            turns.push(input.clone())
+                        }
+                        _ => segments.push(Segment::Turns(vec![input.clone()], language)),
+                    }
+                }
+                DialogueLine::Sfx(cue) => segments.push(Segment::Sfx(cue)),
+                DialogueLine::Pause(duration) => match segments.last_mut() {
+                    Some(Segment::Turns(turns, _)) if !raw_samples => {
+                        if let Some(last) = turns.last_mut() {
+                            last.text.push_str(&pause_tags(*duration));
+                        }
+                    }
+                    Some(Segment::Silence(silence)) => *silence += *duration,
+                    _ => segments.push(Segment::Silence(*duration)),
+                },
+            }
+        }
+        segments
+    }
+}
+
+/// eleven_v3 pause tags adding up to about `duration`, each standing for at most
+/// [`MAX_BREAK`]. Audio tags aren't billed, unlike SSML `<break>` markup.
+fn pause_tags(duration: Duration) -> String {
+    let mut tags = String::new();
+    let mut left = duration;
+    while !left.is_zero() {
+        let step = left.min(MAX_BREAK);
+        let tag = crate::ssml::pause_for_millis(step.as_secs_f64() * 1000.0);
+        if !tag.is_empty() {
+            tags.push(' ');
+            tags.push_str(tag);
+        }
+        left -= step;
+    }
+    tags
+}
+
+/// A `Vec<TTDInput>` of turns spoken by pre-built voices, named by their
+/// [`all_voices`] constant. A speaker that isn't in the catalog fails to compile.
+///
+/// ```rust
+/// use elevenlabs_ttd::dialogue;
+///
+/// let inputs = dialogue! {
+///     ARNOLD: "I saw the sky this morning, it looked like fire.";
+///     IVANA: "I noticed that too, the sunrise was unreal.";
+/// };
+/// assert_eq!(inputs.len(), 2);
+/// ```
+///
+/// ```compile_fail
+/// let inputs = elevenlabs_ttd::dialogue! { NOBODY: "Hello?" };
+/// ```
+#[macro_export]
+macro_rules! dialogue {
+    ($($voice:ident : $text:expr);* $(;)?) => {{
+        let inputs: ::std::vec::Vec<$crate::TTDInput> = ::std::vec![
+            $($crate::TTDInput::new(&$crate::voices::all_voices::$voice, $text)),*
+        ];
+        inputs
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_script_with_cues() {
+        let cast = HashMap::from([
+            ("Ann".to_string(), "voice-a".to_string()),
+            ("Bob".to_string(), "voice-b".to_string()),
+        ]);
+        let script = "ANN: Did you hear that?\nBob: Hear what?\n\n[door slams]\nAnn: That.";
+
+        let dialogue = Dialogue::parse(script, &cast).unwrap();
+        let segments = dialogue.segments(OutputFormat::default());
+
+        assert_eq!(dialogue.lines.len(), 4);
+        assert_eq!(segments.len(), 3);
+        assert!(matches!(&segments[0], Segment::Turns(turns, None) if turns.len() == 2));
+        assert!(matches!(segments[1], Segment::Sfx(cue) if cue.description == "door slams"));
+    }
+
+    #[test]
+    fn test_dialogue_macro_uses_catalog_voices() {
+        let line = String::from("Hey");
+        let inputs = crate::dialogue! {
+            ARNOLD: "Hi";
+            IVANA: line
+        };
+        assert_eq!(inputs.len(), 2);
+        assert_eq!(inputs[0].voice_id, all_voices::ARNOLD.voice_id);
+        assert_eq!(inputs[1].voice_id, all_voices::IVANA.voice_id);
+        assert_eq!(inputs[1].text, "Hey");
+        assert!(crate::dialogue! {}.is_empty());
+    }
+
+    #[test]
+    fn test_pause_rendering_depends_on_format() {
+        let dialogue = Dialogue::new()
+            .line("voice-a", "Wait.")
+            .pause(4500)
+            .line("voice-b", "What?");
+
+        let compressed = dialogue.segments(OutputFormat::Mp3_44100_128);
+        assert_eq!(compressed.len(), 1);
+        assert!(matches!(&compressed[0], Segment::Turns(turns, _)
+            if turns[0].text == "Wait. [long pause] [long pause]"));
+        let billable = match &compressed[0] {
+            Segment::Turns(turns, _) => crate::text::billable_len(&turns[0].text),
+            _ => unreachable!(),
+        };
+        assert_eq!(billable, crate::text::billable_len("Wait."));
+
+        let short = Dialogue::new().line("voice-a", "Wait.").pause(300);
+        let short = short.segments(OutputFormat::Mp3_44100_128);
+        assert!(matches!(&short[0], Segment::Turns(turns, _)
+            if turns[0].text == "Wait. [short pause]"));
+
+        let pcm = dialogue.segments(OutputFormat::Pcm16000);
+        assert_eq!(pcm.len(), 3);
+        assert!(matches!(pcm[1], Segment::Silence(d) if d == Duration::from_millis(4500)));
+    }
+
+    #[test]
+    fn test_languages_split_requests() {
+        let dialogue = Dialogue::new()
+            .line("voice-a", "Ready?")
+            .line_in("voice-b", "fr", "Oui.")
+            .line_in("voice-a", "fr", "Allons-y.")
+            .line("voice-b", "Go.");
+
+        let segments = dialogue.segments(OutputFormat::default());
+        assert_eq!(segments.len(), 3);
+        assert!(matches!(&segments[0], Segment::Turns(turns, None) if turns.len() == 1));
+        assert!(matches!(&segments[1], Segment::Turns(turns, Some("fr")) if turns.len() == 2));
+        assert_eq!(
+            dialogue.transcript().lines[1].language.as_deref(),
+            Some("fr")
+        );
+    }
+
+    #[test]
+    fn test_transcript_json() {
+        let dialogue = Dialogue::new()
+            .line(
+                all_voices::RACHEL.voice_id,
+                "[whispers] Quiet. [pause] Listen.",
+            )
+            .sfx("owl hoots");
+
+        let json: serde_json::Value =
+            serde_json::from_str(&dialogue.to_transcript_json().unwrap()).unwrap();
+
+        assert_eq!(json["lines"][0]["speaker_name"], "Rachel");
+        assert_eq!(
+            json["lines"][0]["tags"],
+            serde_json::json!(["whispers", "pause"])
+        );
+        assert_eq!(json["lines"][1]["kind"], "sfx");
+        assert!(json["lines"][1].get("start").is_none());
+    }
+
+    #[test]
+    fn test_from_csv() {
+        let speakers = SpeakerMap::from([
+            ("Ann".to_string(), "voice-a".to_string()),
+            ("Bob".to_string(), "voice-b".to_string()),
+        ]);
+        let csv = "Speaker,Text,Notes\r\nann,\"Well, \"\"hello\"\".\"\r\n,[door slams],\nBOB,\"Two\nlines\",loud\n";
+
+        let dialogue = Dialogue::from_csv(csv.as_bytes(), &speakers).unwrap();
+
+        assert_eq!(dialogue.lines.len(), 3);
+        assert!(matches!(&dialogue.lines[0], DialogueLine::Turn(t)
+            if t.voice_id == "voice-a" && t.text == "Well, \"hello\"."));
+        assert!(
+            matches!(&dialogue.lines[1], DialogueLine::Sfx(c) if c.description == "door slams")
+        );
+        assert!(matches!(&dialogue.lines[2], DialogueLine::Turn(t) if t.text == "Two\nlines"));
+
+        let error = Dialogue::from_csv("speaker,text\nEve,Hi".as_bytes(), &speakers).unwrap_err();
+        assert!(error.to_string().contains("Row 2"));
+    }
+
+    #[test]
+    fn test_batch_from_csv_groups_rows() {
+        let speakers = SpeakerMap::from([("Ann".to_string(), "voice-a".to_string())]);
+        let csv = "scene,speaker,text\n2,Ann,Later.\n1,Ann,First.\n2,Ann,Still later.\n";
+
+        let scenes = Dialogue::batch_from_csv(csv.as_bytes(), &speakers, "scene").unwrap();
+
+        assert_eq!(scenes.len(), 2);
+        assert_eq!(scenes[0].0, "2");
+        assert_eq!(scenes[0].1.lines.len(), 2);
+        assert_eq!(scenes[1].0, "1");
+        assert!(Dialogue::batch_from_csv(csv.as_bytes(), &speakers, "act").is_err());
+    }
+
+    #[test]
+    fn test_scenes_from_markdown() {
+        let speakers = SpeakerMap::from([
+            ("Ann".to_string(), "voice-a".to_string()),
+            ("Bob".to_string(), "voice-b".to_string()),
+        ]);
+        let markdown = "\
+Cold open, no heading.
+
+**Ann:** Hello?
+
+# Scene 1: The kitchen
+
+*Ann enters.*
+
+**Ann:** Did you hear that?
+**bob**: Hear what? It
+was nothing.
+
+_[door slams]_
+
+## Scene 2
+**Bob:** Oh.
+";
+
+        let scenes = Dialogue::scenes_from_markdown(markdown, &speakers).unwrap();
+
+        let names: Vec<_> = scenes.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["", "Scene 1: The kitchen", "Scene 2"]);
+        let kitchen = &scenes[1].1.lines;
+        assert_eq!(kitchen.len(), 3);
+        assert!(matches!(&kitchen[1], DialogueLine::Turn(t)
+            if t.voice_id == "voice-b" && t.text == "Hear what? It was nothing."));
+        assert!(matches!(&kitchen[2], DialogueLine::Sfx(c) if c.description == "door slams"));
+
+        let whole = Dialogue::from_markdown(markdown, &speakers).unwrap();
+        assert_eq!(whole.lines.len(), 5);
+        assert!(Dialogue::from_markdown("**Eve:** Hi", &speakers).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_uncast_speaker() {
+        let error = Dialogue::parse("Eve: Hi", &HashMap::new()).unwrap_err();
+        assert!(error.to_string().contains("Eve"));
+    }
+
+    #[test]
+    fn test_casting_errors_per_speaker() {
+        let voice = |id: &str, category: &str, models: &[&str]| -> Voice {
+            serde_json::from_value(serde_json::json!({
+                "voice_id": id,
+                "name": id,
+                "category": category,
+                "high_quality_base_model_ids": models,
+            }))
+            .unwrap()
+        };
+        let voices = [
+            voice("voice-a", "premade", &[]),
+            voice("voice-p", "professional", &["eleven_multilingual_v2"]),
+        ];
+        let cast = HashMap::from([
+            ("Ann".to_string(), "voice-a".to_string()),
+            ("Bob".to_string(), "voice-x".to_string()),
+            ("Cy".to_string(), "voice-p".to_string()),
+        ]);
+
+        let errors = casting_errors(&cast, &voices, "eleven_v3");
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].speaker, "Bob");
+        assert_eq!(errors[0].problem, CastingProblem::UnknownVoice);
+        assert_eq!(
+            errors[1].problem,
+            CastingProblem::UnsupportedModel("eleven_v3".to_string())
+        );
+        assert!(
+            errors[1]
+                .to_string()
+                .contains("can't be used with eleven_v3")
+        );
+
+        let models: Vec<Model> = serde_json::from_str(
+            r#"[{"model_id": "eleven_v3", "can_do_text_to_speech": true},
+                {"model_id": "eleven_english_sts_v2", "can_do_text_to_speech": false}]"#,
+        )
+        .unwrap();
+        assert!(check_model(&models, "eleven_v3").is_ok());
+        assert!(check_model(&models, "eleven_english_sts_v2").is_err());
+        assert!(check_model(&models, "eleven_v4").is_err());
+    }
+}
@@ -0,0 +1,69 @@
+//! Extension point for typed wrappers around additional ElevenLabs endpoints
+
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+
+use crate::types::{Subscription, VoiceSettings};
+
+/// A typed description of an API call, executed with
+/// [`crate::ElevenLabsTTDClient::execute_endpoint`].
+///
+/// Other crates can implement this to layer endpoints on top of this client:
+///
+/// ```rust
+/// use elevenlabs_ttd::endpoint::Endpoint;
+///
+/// struct GetModels;
+///
+/// impl Endpoint for GetModels {
+///     type Response = Vec<serde_json::Value>;
+///
+///     fn path(&self) -> String {
+///         "/models".to_string()
+///     }
+/// }
+/// ```
+pub trait Endpoint {
+    /// Type the JSON response body is parsed into
+    type Response: DeserializeOwned;
+
+    /// HTTP method, GET by default
+    fn method(&self) -> Method {
+        Method::GET
+    }
+
+    /// Path relative to the client's base URL, e.g. `/models`
+    fn path(&self) -> String;
+
+    /// URL query parameters
+    fn query(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    /// JSON request body
+    fn body(&self) -> Option<serde_json::Value> {
+        None
+    }
+}
+
+/// `GET /user/subscription`
+pub struct GetSubscription;
+
+impl Endpoint for GetSubscription {
+    type Response = Subscription;
+
+    fn path(&self) -> String {
+        "/user/subscription".to_string()
+    }
+}
+
+/// `GET /voices/settings/default`
+pub struct GetDefaultVoiceSettings;
+
+impl Endpoint for GetDefaultVoiceSettings {
+    type Response = VoiceSettings;
+
+    fn path(&self) -> String {
+        "/voices/settings/default".to_string()
+    }
+}
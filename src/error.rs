@@ -1,69 +1,451 @@
-use std::fmt;
+use std::time::{Duration, Instant};
+
+use serde::{Serialize, Serializer};
+use thiserror::Error;
+
+use crate::rate_limit::RateLimitInfo;
 
 /// All possible errors that can occur when using the ElevenLabs API
-#[derive(Debug)]
+#[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum ElevenLabsTTDError {
     /// HTTP request failed (network issues, timeout, etc.)
-    RequestError(reqwest::Error),
+    #[error("Request failed: {0}")]
+    RequestError(#[source] reqwest::Error),
 
     /// API returned an error status code
-    ApiError { status: u16, message: String },
+    #[error("API error ({status}){}: {message}", context_suffix(.correlation_id, .response))]
+    ApiError {
+        status: u16,
+        message: String,
+        correlation_id: Option<String>,
+        // The raw response, useful when a gateway or proxy returned a non-JSON body.
+        response: Option<Box<RawErrorResponse>>,
+    },
+
+    /// The API failed on its side (5xx); the request may or may not have been processed
+    #[error("Server error ({status}){}: {message}", context_suffix(.correlation_id, .response))]
+    ServerError {
+        status: u16,
+        message: String,
+        correlation_id: Option<String>,
+        response: Option<Box<RawErrorResponse>>,
+    },
 
     /// Failed to parse JSON response
-    ParseError(serde_json::Error),
+    #[error("Failed to parse response: {0}")]
+    ParseError(#[from] serde_json::Error),
 
     /// Invalid API key or authentication failed
-    AuthenticationError(String),
+    #[error("Authentication failed{}: {message}", context_suffix(.correlation_id, .response))]
+    AuthenticationError {
+        message: String,
+        correlation_id: Option<String>,
+        // `None` when the failure was detected locally, e.g. no key for a tenant.
+        response: Option<Box<RawErrorResponse>>,
+    },
 
     /// Rate limit exceeded
+    #[error("Rate limit exceeded{}{}: {message}", retry_suffix(.retry_after), context_suffix(.correlation_id, .response))]
     RateLimitError {
         retry_after: Option<u64>, // seconds
         message: String,
+        correlation_id: Option<String>,
+        // The raw response, with the rate-limit headers, when it came from the API.
+        response: Option<Box<RawErrorResponse>>,
     },
 
     /// Quota exceeded (not enough credits)
-    QuotaExceededError(String),
+    #[error("Quota exceeded{}: {message}", context_suffix(.correlation_id, .response))]
+    QuotaExceededError {
+        message: String,
+        correlation_id: Option<String>,
+        // `None` when the failure was detected locally, e.g. the daily ceiling.
+        response: Option<Box<RawErrorResponse>>,
+    },
 
     /// Invalid input parameters
+    #[error("Validation error: {0}")]
     ValidationError(String),
+
+    /// Reading or writing local files failed
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    /// A turn was rejected by the client's [`crate::content::ContentFilter`]
+    #[error("Content rejected in turn {index}: {reason}")]
+    ContentRejected { index: usize, reason: String },
+
+    /// The connection dropped while the audio was downloading; `received` holds
+    /// the bytes that arrived, which may end mid-frame
+    #[error("Response interrupted after {} bytes: {source}", .received.len())]
+    StreamInterrupted {
+        received: Vec<u8>,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// Downloaded MP3 data failed frame validation at byte `offset`, see
+    /// [`crate::ClientBuilder::validate_mp3`]
+    #[error("Corrupt audio at byte {offset}: {reason}")]
+    CorruptAudio { offset: u64, reason: String },
+
+    /// The client's circuit breaker is open after repeated upstream failures,
+    /// so the request wasn't sent
+    #[error("Circuit open after repeated upstream failures (next probe in {}ms)", .retry_in.as_millis())]
+    CircuitOpen { retry_in: Duration },
+
+    /// The request did not complete within the configured timeout
+    #[error("Request timed out after {}ms", .elapsed.as_millis())]
+    Timeout { elapsed: Duration },
+
+    /// The client was shut down with [`crate::ElevenLabsTTDClient::shutdown`],
+    /// so the request wasn't sent
+    #[error("Client is shutting down")]
+    ShuttingDown,
+}
+
+/// Raw body and headers of a failed API response
+#[derive(Debug, Clone)]
+pub struct RawErrorResponse {
+    pub body: Vec<u8>,
+    pub headers: reqwest::header::HeaderMap,
+    // Path of the endpoint that failed, e.g. "/v1/text-to-dialogue".
+    pub endpoint: Option<String>,
+}
+
+impl RawErrorResponse {
+    /// The ID ElevenLabs assigned to the request (the `request-id` header)
+    pub fn request_id(&self) -> Option<&str> {
+        ["request-id", "x-request-id"]
+            .iter()
+            .find_map(|name| self.headers.get(*name)?.to_str().ok())
+    }
 }
 
-impl fmt::Display for ElevenLabsTTDError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+/// `[endpoint: ..., request id: ..., correlation id: ...]`, with whichever are known
+fn context_suffix(
+    correlation_id: &Option<String>,
+    response: &Option<Box<RawErrorResponse>>,
+) -> String {
+    let response = response.as_deref();
+    let context: Vec<String> = [
+        response
+            .and_then(|r| r.endpoint.as_deref())
+            .map(|endpoint| format!("endpoint: {}", endpoint)),
+        response
+            .and_then(RawErrorResponse::request_id)
+            .map(|id| format!("request id: {}", id)),
+        correlation_id
+            .as_deref()
+            .map(|id| format!("correlation id: {}", id)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    if context.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", context.join(", "))
+    }
+}
+
+fn retry_suffix(retry_after: &Option<u64>) -> String {
+    match retry_after {
+        Some(seconds) => format!(" (retry in {}s)", seconds),
+        None => String::new(),
+    }
+}
+
+impl ElevenLabsTTDError {
+    /// The correlation ID of the request that produced this error, if known
+    pub fn correlation_id(&self) -> Option<&str> {
+        match self {
+            ElevenLabsTTDError::ApiError { correlation_id, .. }
+            | ElevenLabsTTDError::ServerError { correlation_id, .. }
+            | ElevenLabsTTDError::AuthenticationError { correlation_id, .. }
+            | ElevenLabsTTDError::RateLimitError { correlation_id, .. }
+            | ElevenLabsTTDError::QuotaExceededError { correlation_id, .. } => {
+                correlation_id.as_deref()
+            }
+            _ => None,
+        }
+    }
+
+    /// A stable, machine-readable name for the kind of error
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ElevenLabsTTDError::RequestError(_) => "request_error",
+            ElevenLabsTTDError::ApiError { .. } => "api_error",
+            ElevenLabsTTDError::ServerError { .. } => "server_error",
+            ElevenLabsTTDError::ParseError(_) => "parse_error",
+            ElevenLabsTTDError::AuthenticationError { .. } => "authentication_error",
+            ElevenLabsTTDError::RateLimitError { .. } => "rate_limit_error",
+            ElevenLabsTTDError::QuotaExceededError { .. } => "quota_exceeded_error",
+            ElevenLabsTTDError::ValidationError(_) => "validation_error",
+            ElevenLabsTTDError::IoError(_) => "io_error",
+            ElevenLabsTTDError::ContentRejected { .. } => "content_rejected",
+            ElevenLabsTTDError::StreamInterrupted { .. } => "stream_interrupted",
+            ElevenLabsTTDError::CorruptAudio { .. } => "corrupt_audio",
+            ElevenLabsTTDError::CircuitOpen { .. } => "circuit_open",
+            ElevenLabsTTDError::Timeout { .. } => "timeout",
+            ElevenLabsTTDError::ShuttingDown => "shutting_down",
+        }
+    }
+
+    /// The HTTP status code, if the error came from an API response; `None` for
+    /// refusals made locally, such as the daily ceiling
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ElevenLabsTTDError::ApiError { status, .. }
+            | ElevenLabsTTDError::ServerError { status, .. } => Some(*status),
+            ElevenLabsTTDError::AuthenticationError { response, .. } => {
+                response.as_ref().map(|_| 401)
+            }
+            ElevenLabsTTDError::QuotaExceededError { response, .. } => {
+                response.as_ref().map(|_| 402)
+            }
+            ElevenLabsTTDError::RateLimitError { response, .. } => response.as_ref().map(|_| 429),
+            ElevenLabsTTDError::RequestError(e) => e.status().map(|s| s.as_u16()),
+            _ => None,
+        }
+    }
+
+    /// The ElevenLabs error code (the `detail.status` field of the error body),
+    /// e.g. `quota_exceeded` or `voice_not_found`
+    pub fn code(&self) -> Option<String> {
+        match self {
+            ElevenLabsTTDError::ApiError { message, .. }
+            | ElevenLabsTTDError::ServerError { message, .. }
+            | ElevenLabsTTDError::RateLimitError { message, .. }
+            | ElevenLabsTTDError::AuthenticationError { message, .. }
+            | ElevenLabsTTDError::QuotaExceededError { message, .. } => {
+                let body: serde_json::Value = serde_json::from_str(message).ok()?;
+                body.get("detail")?
+                    .get("status")?
+                    .as_str()
+                    .map(str::to_string)
+            }
+            _ => None,
+        }
+    }
+
+    /// Audio received before the download was interrupted
+    pub fn partial_audio(&self) -> Option<&[u8]> {
+        match self {
+            ElevenLabsTTDError::StreamInterrupted { received, .. } => Some(received),
+            _ => None,
+        }
+    }
+
+    /// Seconds to wait before retrying, if the API said so
+    pub fn retry_after(&self) -> Option<u64> {
         match self {
-            ElevenLabsTTDError::RequestError(e) => write!(f, "Request failed: {}", e),
-            ElevenLabsTTDError::ApiError { status, message } => {
-                write!(f, "API error ({}): {}", status, message)
+            ElevenLabsTTDError::RateLimitError { retry_after, .. } => *retry_after,
+            ElevenLabsTTDError::CircuitOpen { retry_in } => {
+                Some(retry_in.as_secs_f64().ceil() as u64)
             }
-            ElevenLabsTTDError::ParseError(e) => write!(f, "Failed to parse response: {}", e),
-            ElevenLabsTTDError::AuthenticationError(msg) => {
-                write!(f, "Authentication failed: {}", msg)
+            _ => None,
+        }
+    }
+
+    /// Whether sending the same request again may succeed: rate limits,
+    /// timeouts, connection failures, interrupted or corrupt downloads and server-side errors.
+    ///
+    /// Some of these may have been generated (and billed) before failing; see
+    /// [`Self::is_safe_to_retry`] for the ones that certainly weren't.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ElevenLabsTTDError::RateLimitError { .. }
+            | ElevenLabsTTDError::CircuitOpen { .. }
+            | ElevenLabsTTDError::Timeout { .. }
+            | ElevenLabsTTDError::StreamInterrupted { .. }
+            | ElevenLabsTTDError::CorruptAudio { .. } => true,
+            ElevenLabsTTDError::RequestError(e) => e.is_connect() || e.is_timeout(),
+            _ => self.status().is_some_and(|status| status >= 500),
+        }
+    }
+
+    /// Whether the request certainly wasn't processed, so retrying it can't bill
+    /// twice: connection failures, rate limits (429), unavailability (503) and
+    /// an open circuit. Timeouts and other server errors may have happened after generation.
+    pub fn is_safe_to_retry(&self) -> bool {
+        match self {
+            ElevenLabsTTDError::RequestError(e) => e.is_connect(),
+            ElevenLabsTTDError::RateLimitError { .. } | ElevenLabsTTDError::CircuitOpen { .. } => {
+                true
             }
-            ElevenLabsTTDError::RateLimitError {
+            _ => self.status() == Some(503),
+        }
+    }
+}
+
+impl ElevenLabsTTDError {
+    /// Build an error from a non-success response, keeping its raw body and headers
+    pub(crate) async fn from_response(
+        response: reqwest::Response,
+        correlation_id: Option<String>,
+    ) -> Self {
+        let status = response.status().as_u16();
+        let headers = response.headers().clone();
+        let endpoint = Some(response.url().path().to_string());
+        let body = response
+            .bytes()
+            .await
+            .map(|b| b.to_vec())
+            .unwrap_or_default();
+
+        Self::from_parts(status, headers, body, correlation_id, endpoint)
+    }
+
+    /// Build an error from an already-read response: 401, 402 and 429 map to
+    /// their dedicated variants, 5xx to [`ElevenLabsTTDError::ServerError`] and
+    /// anything else to [`ElevenLabsTTDError::ApiError`]
+    pub(crate) fn from_parts(
+        status: u16,
+        headers: reqwest::header::HeaderMap,
+        body: Vec<u8>,
+        correlation_id: Option<String>,
+        endpoint: Option<String>,
+    ) -> Self {
+        let message = String::from_utf8_lossy(&body).into_owned();
+        let retry_after = headers
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok());
+        let response = Some(Box::new(RawErrorResponse {
+            body,
+            headers,
+            endpoint,
+        }));
+        match status {
+            401 => ElevenLabsTTDError::AuthenticationError {
+                message,
+                correlation_id,
+                response,
+            },
+            402 => ElevenLabsTTDError::QuotaExceededError {
+                message,
+                correlation_id,
+                response,
+            },
+            429 => ElevenLabsTTDError::RateLimitError {
                 retry_after,
                 message,
-            } => match retry_after {
-                Some(seconds) => write!(
-                    f,
-                    "Rate limit exceeded (retry in {}s): {}",
-                    seconds, message
-                ),
-                None => write!(f, "Rate limit exceeded: {}", message),
+                correlation_id,
+                response,
+            },
+            500..=599 => ElevenLabsTTDError::ServerError {
+                status,
+                message,
+                correlation_id,
+                response,
+            },
+            _ => ElevenLabsTTDError::ApiError {
+                status,
+                message,
+                correlation_id,
+                response,
             },
-            ElevenLabsTTDError::QuotaExceededError(msg) => write!(f, "Quota exceeded: {}", msg),
-            ElevenLabsTTDError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
         }
     }
-}
 
-impl std::error::Error for ElevenLabsTTDError {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    /// The raw response of a failed API call, if it was captured
+    pub fn raw_response(&self) -> Option<&RawErrorResponse> {
         match self {
-            ElevenLabsTTDError::RequestError(e) => Some(e),
-            ElevenLabsTTDError::ParseError(e) => Some(e),
+            ElevenLabsTTDError::ApiError { response, .. }
+            | ElevenLabsTTDError::ServerError { response, .. }
+            | ElevenLabsTTDError::RateLimitError { response, .. }
+            | ElevenLabsTTDError::AuthenticationError { response, .. }
+            | ElevenLabsTTDError::QuotaExceededError { response, .. } => response.as_deref(),
             _ => None,
         }
     }
+
+    /// Path of the endpoint that failed, e.g. "/v1/text-to-dialogue", if known
+    pub fn endpoint(&self) -> Option<&str> {
+        self.raw_response()?.endpoint.as_deref()
+    }
+
+    /// The ID ElevenLabs assigned to the failed request, if it sent one
+    pub fn request_id(&self) -> Option<&str> {
+        self.raw_response()?.request_id()
+    }
+
+    /// Rate-limit state reported in the headers of the failed response
+    pub fn rate_limit(&self) -> Option<RateLimitInfo> {
+        RateLimitInfo::from_headers(&self.raw_response()?.headers)
+    }
+
+    /// The error message with anything that looks like an API key masked
+    pub(crate) fn redacted_message(&self) -> String {
+        redact(&self.to_string())
+    }
+
+    /// Map a transport error, reporting timeouts with the time elapsed since `started`
+    pub(crate) fn from_transport(error: reqwest::Error, started: Instant) -> Self {
+        if error.is_timeout() {
+            ElevenLabsTTDError::Timeout {
+                elapsed: started.elapsed(),
+            }
+        } else {
+            error.into()
+        }
+    }
+}
+
+/// Mask anything that looks like an ElevenLabs API key (`sk_...`)
+fn redact(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("sk_") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 3..];
+        let key_len = after
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(after.len());
+        out.push_str(if key_len > 0 { "sk_***" } else { "sk_" });
+        rest = &after[key_len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Errors serialize to a flat object with the fields logging pipelines and
+/// job queues need; messages are redacted and sources are not included
+impl Serialize for ElevenLabsTTDError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct SerializedError<'a> {
+            kind: &'static str,
+            message: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            status: Option<u16>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            code: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            correlation_id: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            endpoint: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            request_id: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            retry_after: Option<u64>,
+        }
+
+        SerializedError {
+            kind: self.kind(),
+            message: self.redacted_message(),
+            status: self.status(),
+            code: self.code(),
+            correlation_id: self.correlation_id(),
+            endpoint: self.endpoint(),
+            request_id: self.request_id(),
+            retry_after: self.retry_after(),
+        }
+        .serialize(serializer)
+    }
 }
 
 impl From<reqwest::Error> for ElevenLabsTTDError {
@@ -71,19 +453,45 @@ impl From<reqwest::Error> for ElevenLabsTTDError {
         // Check if it's a specific HTTP status error
         if let Some(status) = error.status() {
             let status_code = status.as_u16();
+            // The API answered, though only the status is left of its response
+            let response = || {
+                Some(Box::new(RawErrorResponse {
+                    body: Vec::new(),
+                    headers: reqwest::header::HeaderMap::new(),
+                    endpoint: error.url().map(|url| url.path().to_string()),
+                }))
+            };
             match status_code {
-                401 => ElevenLabsTTDError::AuthenticationError("Invalid API key".to_string()),
+                401 => ElevenLabsTTDError::AuthenticationError {
+                    message: "Invalid API key".to_string(),
+                    correlation_id: None,
+                    response: response(),
+                },
                 429 => {
                     // Try to extract retry-after header if available
                     ElevenLabsTTDError::RateLimitError {
                         retry_after: None, // Could be enhanced to parse Retry-After header
                         message: "Too many requests".to_string(),
+                        correlation_id: None,
+                        response: response(),
                     }
                 }
-                402 => ElevenLabsTTDError::QuotaExceededError("Insufficient credits".to_string()),
+                402 => ElevenLabsTTDError::QuotaExceededError {
+                    message: "Insufficient credits".to_string(),
+                    correlation_id: None,
+                    response: response(),
+                },
+                500..=599 => ElevenLabsTTDError::ServerError {
+                    status: status_code,
+                    message: error.to_string(),
+                    correlation_id: None,
+                    response: None,
+                },
                 _ => ElevenLabsTTDError::ApiError {
                     status: status_code,
                     message: error.to_string(),
+                    correlation_id: None,
+                    response: None,
                 },
             }
         } else {
@@ -91,9 +499,3 @@ impl From<reqwest::Error> for ElevenLabsTTDError {
         }
     }
 }
-
-impl From<serde_json::Error> for ElevenLabsTTDError {
-    fn from(error: serde_json::Error) -> Self {
-        ElevenLabsTTDError::ParseError(error)
-    }
-}
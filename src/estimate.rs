@@ -0,0 +1,26 @@
+//! Rough, offline estimates of how long generated dialogue will be
+
+use std::time::Duration;
+
+use crate::types::TTDInput;
+
+/// Average speaking rate used for estimates, in characters per second
+pub const CHARS_PER_SECOND: f64 = 15.0;
+
+/// Shortest duration estimated for any non-empty turn
+pub const MIN_TURN_DURATION: Duration = Duration::from_millis(500);
+
+/// Estimate the spoken duration of a single line of text, not counting audio tags
+pub fn turn_duration(text: &str) -> Duration {
+    let chars = crate::text::billable_len(text);
+    if chars == 0 {
+        return Duration::ZERO;
+    }
+
+    Duration::from_secs_f64(chars as f64 / CHARS_PER_SECOND).max(MIN_TURN_DURATION)
+}
+
+/// Estimate the spoken duration of a whole dialogue
+pub fn dialogue_duration(inputs: &[TTDInput]) -> Duration {
+    inputs.iter().map(|input| turn_duration(&input.text)).sum()
+}
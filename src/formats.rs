@@ -0,0 +1,267 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::ElevenLabsTTDError;
+use crate::types::SubscriptionTier;
+
+/// Audio codec family of an output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Codec {
+    Mp3,
+    Pcm,
+    Ulaw,
+    Alaw,
+    Opus,
+}
+
+/// Output formats supported by the Text-to-Dialogue API.
+/// Formatted as codec_sample_rate_bitrate, e.g. `mp3_44100_128`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum OutputFormat {
+    Mp3_22050_32,
+    Mp3_44100_32,
+    Mp3_44100_64,
+    Mp3_44100_96,
+    #[default]
+    Mp3_44100_128,
+    Mp3_44100_192,
+    Pcm8000,
+    Pcm16000,
+    Pcm22050,
+    Pcm24000,
+    Pcm44100,
+    Pcm48000,
+    Ulaw8000,
+    Alaw8000,
+    Opus48000_32,
+    Opus48000_64,
+    Opus48000_96,
+    /// PCM wrapped in a WAV header: requests the `pcm_*` format at the same
+    /// sample rate and adds the header to the returned audio
+    Wav {
+        sample_rate: u32,
+    },
+}
+
+impl OutputFormat {
+    /// Every output format accepted by the API
+    pub const ALL: [OutputFormat; 17] = [
+        OutputFormat::Mp3_22050_32,
+        OutputFormat::Mp3_44100_32,
+        OutputFormat::Mp3_44100_64,
+        OutputFormat::Mp3_44100_96,
+        OutputFormat::Mp3_44100_128,
+        OutputFormat::Mp3_44100_192,
+        OutputFormat::Pcm8000,
+        OutputFormat::Pcm16000,
+        OutputFormat::Pcm22050,
+        OutputFormat::Pcm24000,
+        OutputFormat::Pcm44100,
+        OutputFormat::Pcm48000,
+        OutputFormat::Ulaw8000,
+        OutputFormat::Alaw8000,
+        OutputFormat::Opus48000_32,
+        OutputFormat::Opus48000_64,
+        OutputFormat::Opus48000_96,
+    ];
+
+    /// WAV variants of every PCM format
+    pub const WAV: [OutputFormat; 6] = [
+        OutputFormat::Wav { sample_rate: 8000 },
+        OutputFormat::Wav { sample_rate: 16000 },
+        OutputFormat::Wav { sample_rate: 22050 },
+        OutputFormat::Wav { sample_rate: 24000 },
+        OutputFormat::Wav { sample_rate: 44100 },
+        OutputFormat::Wav { sample_rate: 48000 },
+    ];
+
+    /// The API name of this format, as sent in the `output_format` query parameter.
+    /// WAV formats are named `wav_<sample rate>` and sent as their [`Self::api_format`].
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            OutputFormat::Mp3_22050_32 => "mp3_22050_32",
+            OutputFormat::Mp3_44100_32 => "mp3_44100_32",
+            OutputFormat::Mp3_44100_64 => "mp3_44100_64",
+            OutputFormat::Mp3_44100_96 => "mp3_44100_96",
+            OutputFormat::Mp3_44100_128 => "mp3_44100_128",
+            OutputFormat::Mp3_44100_192 => "mp3_44100_192",
+            OutputFormat::Pcm8000 => "pcm_8000",
+            OutputFormat::Pcm16000 => "pcm_16000",
+            OutputFormat::Pcm22050 => "pcm_22050",
+            OutputFormat::Pcm24000 => "pcm_24000",
+            OutputFormat::Pcm44100 => "pcm_44100",
+            OutputFormat::Pcm48000 => "pcm_48000",
+            OutputFormat::Ulaw8000 => "ulaw_8000",
+            OutputFormat::Alaw8000 => "alaw_8000",
+            OutputFormat::Opus48000_32 => "opus_48000_32",
+            OutputFormat::Opus48000_64 => "opus_48000_64",
+            OutputFormat::Opus48000_96 => "opus_48000_96",
+            OutputFormat::Wav { sample_rate } => match sample_rate {
+                8000 => "wav_8000",
+                16000 => "wav_16000",
+                22050 => "wav_22050",
+                24000 => "wav_24000",
+                44100 => "wav_44100",
+                48000 => "wav_48000",
+                _ => "wav",
+            },
+        }
+    }
+
+    /// Look up a format by its API name (e.g. `pcm_16000`), or `wav_<sample rate>`
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .chain(Self::WAV)
+            .find(|f| f.as_str() == name)
+    }
+
+    /// Usual file extension for audio in this format
+    pub const fn extension(&self) -> &'static str {
+        match self.codec() {
+            Codec::Pcm if self.is_wav() => "wav",
+            Codec::Mp3 => "mp3",
+            Codec::Pcm => "pcm",
+            Codec::Ulaw => "ulaw",
+            Codec::Alaw => "alaw",
+            Codec::Opus => "opus",
+        }
+    }
+
+    /// Whether this is a [`OutputFormat::Wav`] format
+    pub const fn is_wav(&self) -> bool {
+        matches!(self, OutputFormat::Wav { .. })
+    }
+
+    /// The format requested from the API: the PCM format at the same sample
+    /// rate for WAV, otherwise the format itself
+    pub const fn api_format(&self) -> OutputFormat {
+        match self {
+            OutputFormat::Wav { sample_rate } => match sample_rate {
+                8000 => OutputFormat::Pcm8000,
+                16000 => OutputFormat::Pcm16000,
+                22050 => OutputFormat::Pcm22050,
+                24000 => OutputFormat::Pcm24000,
+                44100 => OutputFormat::Pcm44100,
+                48000 => OutputFormat::Pcm48000,
+                _ => *self,
+            },
+            _ => *self,
+        }
+    }
+
+    /// Fail for a [`OutputFormat::Wav`] at a sample rate no PCM format has,
+    /// since WAV is requested as PCM
+    pub fn check_supported(&self) -> Result<(), ElevenLabsTTDError> {
+        match self {
+            OutputFormat::Wav { sample_rate } if self.api_format().is_wav() => {
+                Err(unsupported_wav(sample_rate))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Codec family of this format
+    pub const fn codec(&self) -> Codec {
+        match self {
+            OutputFormat::Mp3_22050_32
+            | OutputFormat::Mp3_44100_32
+            | OutputFormat::Mp3_44100_64
+            | OutputFormat::Mp3_44100_96
+            | OutputFormat::Mp3_44100_128
+            | OutputFormat::Mp3_44100_192 => Codec::Mp3,
+            OutputFormat::Pcm8000
+            | OutputFormat::Pcm16000
+            | OutputFormat::Pcm22050
+            | OutputFormat::Pcm24000
+            | OutputFormat::Pcm44100
+            | OutputFormat::Pcm48000
+            | OutputFormat::Wav { .. } => Codec::Pcm,
+            OutputFormat::Ulaw8000 => Codec::Ulaw,
+            OutputFormat::Alaw8000 => Codec::Alaw,
+            OutputFormat::Opus48000_32
+            | OutputFormat::Opus48000_64
+            | OutputFormat::Opus48000_96 => Codec::Opus,
+        }
+    }
+
+    /// Sample rate in Hz
+    pub const fn sample_rate(&self) -> u32 {
+        match self {
+            OutputFormat::Pcm8000 | OutputFormat::Ulaw8000 | OutputFormat::Alaw8000 => 8000,
+            OutputFormat::Pcm16000 => 16000,
+            OutputFormat::Mp3_22050_32 | OutputFormat::Pcm22050 => 22050,
+            OutputFormat::Pcm24000 => 24000,
+            OutputFormat::Mp3_44100_32
+            | OutputFormat::Mp3_44100_64
+            | OutputFormat::Mp3_44100_96
+            | OutputFormat::Mp3_44100_128
+            | OutputFormat::Mp3_44100_192
+            | OutputFormat::Pcm44100 => 44100,
+            OutputFormat::Pcm48000
+            | OutputFormat::Opus48000_32
+            | OutputFormat::Opus48000_64
+            | OutputFormat::Opus48000_96 => 48000,
+            OutputFormat::Wav { sample_rate } => *sample_rate,
+        }
+    }
+
+    /// Bitrate in kbps for compressed formats
+    pub const fn bitrate_kbps(&self) -> Option<u32> {
+        match self {
+            OutputFormat::Mp3_22050_32
+            | OutputFormat::Mp3_44100_32
+            | OutputFormat::Opus48000_32 => Some(32),
+            OutputFormat::Mp3_44100_64 | OutputFormat::Opus48000_64 => Some(64),
+            OutputFormat::Mp3_44100_96 | OutputFormat::Opus48000_96 => Some(96),
+            OutputFormat::Mp3_44100_128 => Some(128),
+            OutputFormat::Mp3_44100_192 => Some(192),
+            _ => None,
+        }
+    }
+
+    /// Lowest subscription tier allowed to request this format
+    pub const fn required_tier(&self) -> SubscriptionTier {
+        match self.api_format() {
+            OutputFormat::Mp3_44100_192 => SubscriptionTier::Creator,
+            OutputFormat::Pcm44100 => SubscriptionTier::Pro,
+            _ => SubscriptionTier::Free,
+        }
+    }
+}
+
+impl From<OutputFormat> for String {
+    fn from(format: OutputFormat) -> Self {
+        format.as_str().to_string()
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Wav { sample_rate } => write!(f, "wav_{}", sample_rate),
+            _ => f.write_str(self.as_str()),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = ElevenLabsTTDError;
+
+    /// Accepts the names [`Self::from_name`] does. WAV is only available at the
+    /// sample rates of the PCM formats, since it's requested as PCM.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Self::from_name(s).ok_or_else(|| match s.strip_prefix("wav_") {
+            Some(rate) if rate.parse::<u32>().is_ok() => unsupported_wav(rate),
+            _ => ElevenLabsTTDError::ValidationError(format!("Unknown output format: {}", s)),
+        })
+    }
+}
+
+pub(crate) fn unsupported_wav(sample_rate: impl fmt::Display) -> ElevenLabsTTDError {
+    ElevenLabsTTDError::ValidationError(format!(
+        "No PCM format at {} Hz to request WAV as",
+        sample_rate
+    ))
+}
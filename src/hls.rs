@@ -0,0 +1,203 @@
+//! HLS export of long MP3 output: fixed-duration packed audio segments plus an
+//! `.m3u8` playlist, so web players can stream it progressively from static storage
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::error::ElevenLabsTTDError;
+use crate::formats::{Codec, OutputFormat};
+use crate::mp3;
+
+/// Segment length Apple recommends for HLS
+pub const DEFAULT_SEGMENT_DURATION: Duration = Duration::from_secs(6);
+
+/// Name of the playlist written by [`export`]
+pub const PLAYLIST_NAME: &str = "playlist.m3u8";
+
+/// ID3 PRIV owner HLS packed audio uses for the segment's start time
+const TIMESTAMP_OWNER: &[u8] = b"com.apple.streaming.transportStreamTimestamp\0";
+
+/// One packed audio segment: MP3 frames behind an ID3 tag with their start time
+#[derive(Debug, Clone)]
+pub struct HlsSegment {
+    pub data: Vec<u8>,
+    pub duration: Duration,
+    // Start time of the segment in the whole output.
+    pub start: Duration,
+}
+
+/// Split MP3 `audio` at frame boundaries into segments of about `target` duration
+pub fn segment(
+    audio: &[u8],
+    format: OutputFormat,
+    target: Duration,
+) -> Result<Vec<HlsSegment>, ElevenLabsTTDError> {
+    if format.codec() != Codec::Mp3 {
+        return Err(ElevenLabsTTDError::ValidationError(format!(
+            "HLS export needs MP3 audio, got {}",
+            format.as_str()
+        )));
+    }
+    if target.is_zero() {
+        return Err(ElevenLabsTTDError::ValidationError(
+            "HLS segment duration must be positive".to_string(),
+        ));
+    }
+
+    let frames = mp3::frames(audio)?;
+    let sample_rate = frames.first().map_or(1, |(_, frame)| frame.sample_rate) as u64;
+    let target_samples = (target.as_secs_f64() * sample_rate as f64).round() as u64;
+
+    let mut segments = Vec::new();
+    let mut elapsed = 0u64;
+    let mut start = 0;
+    while start < frames.len() {
+        let mut samples = 0u64;
+        let mut end = start;
+        while end < frames.len() && (samples < target_samples || end == start) {
+            samples += frames[end].1.samples as u64;
+            end += 1;
+        }
+
+        let from = frames[start].0;
+        let to = frames
+            .get(end)
+            .map_or_else(|| frame_end(&frames[end - 1]), |(offset, _)| *offset);
+        let mut data = timestamp_tag(elapsed * 90_000 / sample_rate);
+        data.extend_from_slice(&audio[from..to]);
+        segments.push(HlsSegment {
+            data,
+            duration: Duration::from_secs_f64(samples as f64 / sample_rate as f64),
+            start: Duration::from_secs_f64(elapsed as f64 / sample_rate as f64),
+        });
+
+        elapsed += samples;
+        start = end;
+    }
+    Ok(segments)
+}
+
+/// VOD media playlist for `segments`, with `uri` naming each segment by index
+pub fn playlist<F: Fn(usize) -> String>(segments: &[HlsSegment], uri: F) -> String {
+    let target = segments
+        .iter()
+        .map(|segment| segment.duration.as_secs_f64().ceil() as u64)
+        .max()
+        .unwrap_or(0);
+
+    let mut out = format!(
+        "#EXTM3U\n#EXT-X-VERSION:3\n#EXT-X-TARGETDURATION:{}\n#EXT-X-MEDIA-SEQUENCE:0\n#EXT-X-PLAYLIST-TYPE:VOD\n",
+        target
+    );
+    for (index, segment) in segments.iter().enumerate() {
+        out.push_str(&format!(
+            "#EXTINF:{:.3},\n{}\n",
+            segment.duration.as_secs_f64(),
+            uri(index)
+        ));
+    }
+    out.push_str("#EXT-X-ENDLIST\n");
+    out
+}
+
+/// Write MP3 `audio` to `dir` as `segment_00000.mp3`, … segments of about
+/// `target` duration and a [`PLAYLIST_NAME`] playlist referencing them.
+/// Returns the playlist path.
+pub fn export<P: AsRef<Path>>(
+    audio: &[u8],
+    format: OutputFormat,
+    dir: P,
+    target: Duration,
+) -> Result<PathBuf, ElevenLabsTTDError> {
+    let dir = dir.as_ref();
+    let segments = segment(audio, format, target)?;
+    std::fs::create_dir_all(dir)?;
+
+    let name = |index: usize| format!("segment_{:05}.mp3", index);
+    for (index, segment) in segments.iter().enumerate() {
+        std::fs::write(dir.join(name(index)), &segment.data)?;
+    }
+    let path = dir.join(PLAYLIST_NAME);
+    std::fs::write(&path, playlist(&segments, name))?;
+    Ok(path)
+}
+
+fn frame_end((offset, frame): &(usize, mp3::FrameHeader)) -> usize {
+    offset + frame.len as usize
+}
+
+/// ID3v2.4 tag with the PRIV frame carrying a segment's 90kHz start time
+fn timestamp_tag(pts: u64) -> Vec<u8> {
+    let mut frame = TIMESTAMP_OWNER.to_vec();
+    frame.extend_from_slice(&(pts & 0x1_FFFF_FFFF).to_be_bytes());
+
+    let mut tag = b"ID3\x04\x00\x00".to_vec();
+    tag.extend_from_slice(&synchsafe(10 + frame.len() as u32));
+    tag.extend_from_slice(b"PRIV");
+    tag.extend_from_slice(&synchsafe(frame.len() as u32));
+    tag.extend_from_slice(&[0, 0]);
+    tag.extend_from_slice(&frame);
+    tag
+}
+
+fn synchsafe(size: u32) -> [u8; 4] {
+    [
+        (size >> 21) as u8 & 0x7F,
+        (size >> 14) as u8 & 0x7F,
+        (size >> 7) as u8 & 0x7F,
+        size as u8 & 0x7F,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments_cover_audio_with_timestamps() {
+        // 100 frames of 1152 samples at 44.1kHz, about 2.6s
+        let audio = crate::mp3::tests::frames(100);
+        let segments =
+            segment(&audio, OutputFormat::Mp3_44100_128, Duration::from_secs(1)).unwrap();
+
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[0].duration.as_millis(), 1018);
+        assert_eq!(segments[1].start, segments[0].duration);
+        for segment in &segments {
+            assert!(segment.data.starts_with(b"ID3"));
+            assert!(crate::mp3::validate(&segment.data).is_ok());
+        }
+        let frames: u64 = segments
+            .iter()
+            .map(|s| crate::mp3::validate(&s.data).unwrap())
+            .sum();
+        assert_eq!(frames, 100);
+
+        let second = &segments[1].data;
+        let pts = u64::from_be_bytes(second[65..73].try_into().unwrap());
+        assert_eq!(pts, 39 * 1152 * 90_000 / 44100);
+    }
+
+    #[test]
+    fn test_export_writes_playlist() {
+        let dir = std::env::temp_dir().join(format!("ttd-hls-{}", uuid::Uuid::new_v4()));
+        let audio = crate::mp3::tests::frames(100);
+        let path = export(
+            &audio,
+            OutputFormat::Mp3_44100_128,
+            &dir,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+
+        let playlist = std::fs::read_to_string(&path).unwrap();
+        assert!(playlist.starts_with("#EXTM3U\n"));
+        assert!(playlist.contains("#EXT-X-TARGETDURATION:2\n"));
+        assert!(playlist.contains("#EXTINF:1.019,\nsegment_00000.mp3\n"));
+        assert!(playlist.ends_with("segment_00002.mp3\n#EXT-X-ENDLIST\n"));
+        assert!(dir.join("segment_00002.mp3").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+
+        assert!(segment(&audio, OutputFormat::Pcm16000, Duration::from_secs(1)).is_err());
+    }
+}
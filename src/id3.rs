@@ -0,0 +1,178 @@
+//! ID3v2.4 tags for MP3 output, so podcast and audiobook files carry their
+//! metadata. Written by [`crate::TTDAudio::write_mp3_with_tags`].
+
+use std::time::Duration;
+
+/// Metadata written to an MP3 file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tags {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    // Chapter markers, in playback order.
+    pub chapters: Vec<Chapter>,
+}
+
+/// A chapter marker (ID3 `CHAP` frame)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+    pub title: String,
+    pub start: Duration,
+    pub end: Duration,
+}
+
+impl Tags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the title (`TIT2`)
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Set the artist (`TPE1`)
+    pub fn artist<S: Into<String>>(mut self, artist: S) -> Self {
+        self.artist = Some(artist.into());
+        self
+    }
+
+    /// Set the album (`TALB`)
+    pub fn album<S: Into<String>>(mut self, album: S) -> Self {
+        self.album = Some(album.into());
+        self
+    }
+
+    /// Add a chapter marker
+    pub fn chapter<S: Into<String>>(mut self, title: S, start: Duration, end: Duration) -> Self {
+        self.chapters.push(Chapter {
+            title: title.into(),
+            start,
+            end,
+        });
+        self
+    }
+
+    /// The encoded ID3v2.4 tag, to be written before the MP3 frames
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut frames = Vec::new();
+        for (id, value) in [
+            (b"TIT2", &self.title),
+            (b"TPE1", &self.artist),
+            (b"TALB", &self.album),
+        ] {
+            if let Some(value) = value {
+                frames.extend(text_frame(id, value));
+            }
+        }
+
+        if !self.chapters.is_empty() {
+            // Table of contents: top-level and ordered
+            let mut toc = b"toc\0".to_vec();
+            toc.push(0x03);
+            toc.push(self.chapters.len().min(u8::MAX as usize) as u8);
+            for i in 0..self.chapters.len().min(u8::MAX as usize) {
+                toc.extend(chapter_id(i));
+            }
+            frames.extend(frame(b"CTOC", &toc));
+
+            for (i, chapter) in self.chapters.iter().enumerate() {
+                let mut body = chapter_id(i);
+                body.extend(millis(chapter.start).to_be_bytes());
+                body.extend(millis(chapter.end).to_be_bytes());
+                // Byte offsets unused
+                body.extend(u32::MAX.to_be_bytes());
+                body.extend(u32::MAX.to_be_bytes());
+                body.extend(text_frame(b"TIT2", &chapter.title));
+                frames.extend(frame(b"CHAP", &body));
+            }
+        }
+
+        let mut tag = b"ID3".to_vec();
+        tag.extend([4, 0, 0]); // version 2.4.0, no flags
+        tag.extend(synchsafe(frames.len() as u32));
+        tag.extend(frames);
+        tag
+    }
+}
+
+fn frame(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut out = id.to_vec();
+    out.extend(synchsafe(body.len() as u32));
+    out.extend([0, 0]); // flags
+    out.extend_from_slice(body);
+    out
+}
+
+/// A UTF-8 text frame
+fn text_frame(id: &[u8; 4], text: &str) -> Vec<u8> {
+    let mut body = vec![0x03];
+    body.extend_from_slice(text.as_bytes());
+    frame(id, &body)
+}
+
+/// Null-terminated element ID of the `index`th chapter
+fn chapter_id(index: usize) -> Vec<u8> {
+    format!("ch{}\0", index).into_bytes()
+}
+
+fn millis(duration: Duration) -> u32 {
+    duration.as_millis().min(u32::MAX as u128) as u32
+}
+
+/// 28-bit size spread over four bytes with the high bit clear
+fn synchsafe(size: u32) -> [u8; 4] {
+    [
+        (size >> 21) as u8 & 0x7F,
+        (size >> 14) as u8 & 0x7F,
+        (size >> 7) as u8 & 0x7F,
+        size as u8 & 0x7F,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_frames() {
+        let tag = Tags::new().title("Episode 1").artist("Narrator").to_bytes();
+
+        assert_eq!(&tag[..6], b"ID3\x04\x00\x00");
+        // Two frames: 10-byte headers plus an encoding byte and the text
+        assert_eq!(&tag[6..10], &[0, 0, 0, 10 + 10 + 10 + 9]);
+        assert_eq!(&tag[10..14], b"TIT2");
+        assert_eq!(&tag[20..30], b"\x03Episode 1");
+        assert_eq!(&tag[30..34], b"TPE1");
+    }
+
+    #[test]
+    fn test_chapters() {
+        let tag = Tags::new()
+            .chapter("Intro", Duration::ZERO, Duration::from_millis(1500))
+            .chapter(
+                "Scene",
+                Duration::from_millis(1500),
+                Duration::from_secs(60),
+            )
+            .to_bytes();
+
+        let body = &tag[10..];
+        assert_eq!(&body[..4], b"CTOC");
+        assert_eq!(&body[10..20], b"toc\0\x03\x02ch0\0");
+        assert_eq!(&body[20..24], b"ch1\0");
+
+        let chap = &body[24..];
+        assert_eq!(&chap[..4], b"CHAP");
+        assert_eq!(&chap[10..14], b"ch0\0");
+        assert_eq!(&chap[18..22], &1500u32.to_be_bytes());
+        assert_eq!(&chap[30..34], b"TIT2");
+    }
+
+    #[test]
+    fn test_synchsafe() {
+        assert_eq!(synchsafe(257), [0, 0, 2, 1]);
+        assert_eq!(synchsafe(0x0FFF_FFFF), [0x7F; 4]);
+    }
+}
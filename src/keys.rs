@@ -0,0 +1,119 @@
+//! Routing requests across several API keys based on their remaining quota
+
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+/// Known remaining quota of one configured API key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KeyQuota {
+    // The last four characters of the key, safe to log.
+    pub key_hint: String,
+    // Characters remaining, or `None` if not known yet.
+    pub remaining: Option<u64>,
+}
+
+/// All configured keys with their last known remaining quota.
+/// Clones share the same quota state.
+#[derive(Debug, Clone)]
+pub(crate) struct KeyPool {
+    keys: Arc<Vec<String>>,
+    remaining: Arc<Mutex<Vec<Option<u64>>>>,
+}
+
+impl KeyPool {
+    pub(crate) fn new(keys: Vec<String>) -> Self {
+        let remaining = vec![None; keys.len()];
+        Self {
+            keys: Arc::new(keys),
+            remaining: Arc::new(Mutex::new(remaining)),
+        }
+    }
+
+    pub(crate) fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// Pick the key with the most headroom for a request of `characters`.
+    /// Keys with unknown quota are assumed to have headroom.
+    pub(crate) fn select(&self, characters: u64) -> &str {
+        let remaining = self.remaining.lock().unwrap();
+        let headroom = |i: usize| remaining[i].unwrap_or(u64::MAX);
+
+        let best = (0..self.keys.len())
+            .filter(|i| headroom(*i) >= characters)
+            .max_by_key(|i| (headroom(*i), std::cmp::Reverse(*i)))
+            .or_else(|| (0..self.keys.len()).max_by_key(|i| (headroom(*i), std::cmp::Reverse(*i))))
+            .unwrap_or(0);
+
+        &self.keys[best]
+    }
+
+    /// Update a key's remaining quota, e.g. from its subscription
+    pub(crate) fn set_remaining(&self, key: &str, characters: u64) {
+        if let Some(i) = self.keys.iter().position(|k| k == key) {
+            self.remaining.lock().unwrap()[i] = Some(characters);
+        }
+    }
+
+    /// Subtract characters billed to a key
+    pub(crate) fn record_usage(&self, key: &str, characters: u64) {
+        if let Some(i) = self.keys.iter().position(|k| k == key) {
+            let mut remaining = self.remaining.lock().unwrap();
+            if let Some(left) = remaining[i].as_mut() {
+                *left = left.saturating_sub(characters);
+            }
+        }
+    }
+
+    pub(crate) fn quotas(&self) -> Vec<KeyQuota> {
+        let remaining = self.remaining.lock().unwrap();
+        self.keys
+            .iter()
+            .zip(remaining.iter())
+            .map(|(key, remaining)| KeyQuota {
+                key_hint: key_hint(key),
+                remaining: *remaining,
+            })
+            .collect()
+    }
+}
+
+/// The last four characters of a key
+pub(crate) fn key_hint(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    chars[chars.len().saturating_sub(4)..].iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool() -> KeyPool {
+        KeyPool::new(vec!["key-aaaa".to_string(), "key-bbbb".to_string()])
+    }
+
+    #[test]
+    fn test_select_prefers_key_with_headroom() {
+        let pool = pool();
+        // Unknown quotas: first key wins
+        assert_eq!(pool.select(100), "key-aaaa");
+
+        pool.set_remaining("key-aaaa", 50);
+        pool.set_remaining("key-bbbb", 5000);
+        assert_eq!(pool.select(100), "key-bbbb");
+
+        pool.record_usage("key-bbbb", 4990);
+        assert_eq!(pool.select(20), "key-aaaa");
+    }
+
+    #[test]
+    fn test_quotas_redact_keys() {
+        let pool = pool();
+        pool.set_remaining("key-bbbb", 10);
+        let quotas = pool.quotas();
+        assert_eq!(quotas[0].key_hint, "aaaa");
+        assert_eq!(quotas[0].remaining, None);
+        assert_eq!(quotas[1].remaining, Some(10));
+    }
+}
@@ -12,14 +12,8 @@
 //!     let client = ElevenLabsTTDClient::new("your-api-key");
 //!
 //!      let inputs = vec![
-//!        TTDInput {
-//!            text: "I saw the sky this morning, it looked like fire.".to_string(),
-//!            voice_id: voices::all_voices::ARNOLD.voice_id.to_string(),
-//!        },
-//!        TTDInput {
-//!            text: "I noticed that too, the sunrise was unreal.".to_string(),
-//!            voice_id: voices::all_voices::IVANA.voice_id.to_string(),
-//!        },
+//!        TTDInput::new(voices::all_voices::ARNOLD, "I saw the sky this morning, it looked like fire."),
+//!        TTDInput::new(voices::all_voices::IVANA, "I noticed that too, the sunrise was unreal."),
 //!     ];
 //!     
 //!     let audio = client
@@ -27,47 +21,451 @@
 //!         .execute()
 //!         .await?;
 //!     
-//!     // audio is Vec<u8> - raw audio data
+//!     // audio derefs to the raw audio bytes
+//!     println!("correlation id: {}", audio.correlation_id);
 //!     std::fs::write("output.mp3", audio)?;
 //!     Ok(())
 //! }
 //! ```
 
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
 use reqwest::Client;
+use tracing::Instrument;
 
+#[cfg(feature = "alignment")]
+pub mod alignment;
+pub mod api;
+pub mod audio;
+pub mod audit;
+mod budget;
+pub mod catalog;
+mod circuit;
+pub mod content;
+mod dedupe;
+pub mod dialogue;
+pub mod endpoint;
 pub mod error;
+pub mod estimate;
+pub mod formats;
+#[cfg(feature = "streaming-export")]
+pub mod hls;
+#[cfg(feature = "id3")]
+pub mod id3;
+mod keys;
+pub mod manifest;
+pub mod mock;
 pub mod models;
+pub mod mp3;
+pub mod pagination;
+pub mod project;
+mod rate_limit;
+pub mod raw;
+pub mod relay;
+pub mod retry;
+mod sandbox;
+mod shutdown;
+pub mod ssml;
+mod stats;
+pub mod sweep;
+pub mod tenant;
+pub mod text;
 pub mod types;
+mod usage;
+pub mod validation;
+mod version;
 pub mod voices;
+mod warning;
+pub mod writer;
 
-pub use error::ElevenLabsTTDError;
+pub use api::{
+    HistoryApi, HistoryListParams, SortDirection, VoiceEditParams, VoiceListParams, VoicesApi,
+};
+pub use audit::{AuditEvent, AuditSink, ErrorContext, ErrorSink};
+pub use budget::{BudgetAlert, BudgetScope};
+pub use catalog::VoiceCatalog;
+pub use circuit::CircuitState;
+pub use content::{ContentFilter, WordListFilter};
+pub use dedupe::{DEFAULT_DEDUPE_CAPACITY, DedupeStore, StoredEntry};
+pub use dialogue::{
+    CastValidation, CastingError, Dialogue, DialogueLine, SoundCue, SpeakerMap, Transcript,
+    TranscriptLine,
+};
+pub use endpoint::Endpoint;
+pub use error::{ElevenLabsTTDError, RawErrorResponse};
+pub use formats::OutputFormat;
+pub use keys::KeyQuota;
+pub use manifest::GenerationManifest;
+pub use mock::MockResponse;
+pub use pagination::{Page, Paginated};
+pub use rate_limit::RateLimitInfo;
+pub use raw::{RawRequestBuilder, RawResponse};
+pub use reqwest::Method;
+pub use retry::{Jitter, RetryPolicy};
+pub use shutdown::ShutdownReport;
+pub use stats::LatencyStats;
+pub use tenant::{KeyProvider, Secret, TenantId};
 pub use types::*;
+pub use usage::{DailyUsage, UsageSummary};
+pub use validation::{ValidationMode, ValidationWarning};
+pub use version::{ApiVersion, EndpointFamily};
+pub use warning::Warning;
+
+/// Default ElevenLabs API base URL. Endpoints this crate targets on another
+/// version are sent there instead, see [`ClientBuilder::endpoint_version`].
+pub const DEFAULT_BASE_URL: &str = "https://api.elevenlabs.io/v1";
+
+/// Default header used to send the client-generated correlation ID
+pub const DEFAULT_CORRELATION_HEADER: &str = "x-correlation-id";
+
+/// Default header used to send the caller-supplied idempotency key
+pub const DEFAULT_IDEMPOTENCY_HEADER: &str = "idempotency-key";
+
+/// Default header used to scope requests to an enterprise workspace
+pub const DEFAULT_WORKSPACE_HEADER: &str = "xi-workspace-id";
 
 /// Main client for interacting with ElevenLabs API
 #[derive(Clone)]
 pub struct ElevenLabsTTDClient {
     client: Client,
     api_key: String,
-    base_url: String,
+    versions: version::ApiVersions,
+    correlation_header: String,
+    idempotency_header: String,
+    workspace_header: String,
+    workspace_id: Option<String>,
+    dedupe: dedupe::DedupeCache,
+    dedupe_store: Option<DedupeStore>,
+    sandbox: bool,
+    usage: Option<usage::UsageTracker>,
+    budget: Option<budget::BudgetMonitor>,
+    keys: keys::KeyPool,
+    validation_mode: ValidationMode,
+    validation_warning: Option<ValidationWarningCallback>,
+    check_format_tier: bool,
+    text_to_speech_fallback: bool,
+    timeout: Option<Duration>,
+    mock_transport: Option<mock::MockTransport>,
+    key_provider: Option<tenant::CachedKeyProvider>,
+    audit_sink: Option<std::sync::Arc<dyn AuditSink>>,
+    content_filter: Option<std::sync::Arc<dyn ContentFilter>>,
+    error_sink: Option<std::sync::Arc<dyn ErrorSink>>,
+    cost_per_thousand_characters: Option<f64>,
+    daily_ceiling: Option<usage::DailyCeiling>,
+    sanitize_text: bool,
+    split_long_turns: bool,
+    segment_gap: Duration,
+    detect_anomalies: bool,
+    validate_mp3: bool,
+    circuit_breaker: Option<circuit::CircuitBreaker>,
+    log_requests: bool,
+    log_request_text: bool,
+    latency: Option<stats::LatencyRecorder>,
+    // Tier of the primary key's account, fetched on first use when `check_format_tier` is set.
+    tier: std::sync::Arc<std::sync::OnceLock<Option<SubscriptionTier>>>,
+    lifecycle: shutdown::Lifecycle,
+}
+
+type ValidationWarningCallback = std::sync::Arc<dyn Fn(&ValidationWarning) + Send + Sync>;
+
+impl std::fmt::Debug for ElevenLabsTTDClient {
+    /// Shows where requests go, never the API keys
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ElevenLabsTTDClient")
+            .field("base_url", &self.versions.base_url())
+            .field("workspace_id", &self.workspace_id)
+            .field("sandbox", &self.sandbox)
+            .field("mock_transport", &self.mock_transport.is_some())
+            .field("validation_mode", &self.validation_mode)
+            .finish_non_exhaustive()
+    }
 }
 
 impl ElevenLabsTTDClient {
     /// Create a new ElevenLabs client with API key
     pub fn new<S: Into<String>>(api_key: S) -> Self {
-        Self {
-            client: Client::new(),
-            api_key: api_key.into(),
-            base_url: "https://api.elevenlabs.io/v1".to_string(),
-        }
+        Self::builder(api_key).build()
     }
 
     /// Create a new client with custom base URL (for testing/enterprise)
     pub fn with_base_url<S: Into<String>>(api_key: S, base_url: S) -> Self {
-        Self {
-            client: Client::new(),
-            api_key: api_key.into(),
-            base_url: base_url.into(),
+        Self::builder(api_key).base_url(base_url).build()
+    }
+
+    /// Create an offline client that never hits the network and returns
+    /// deterministic placeholder audio sized to the estimated dialogue duration
+    pub fn sandbox() -> Self {
+        Self::builder("sandbox").sandbox(true).build()
+    }
+
+    /// Start configuring a client with non-default options
+    pub fn builder<S: Into<String>>(api_key: S) -> ClientBuilder {
+        ClientBuilder::new(api_key)
+    }
+
+    /// Usage recorded since the client was built or last reset,
+    /// or `None` if usage tracking wasn't enabled on the builder
+    pub fn usage_summary(&self) -> Option<UsageSummary> {
+        self.usage.as_ref().map(|usage| usage.summary())
+    }
+
+    /// Characters and requests counted against the daily ceiling today (UTC),
+    /// or `None` if no ceiling was set on the builder. Shared by all clones.
+    pub fn daily_usage(&self) -> Option<DailyUsage> {
+        self.daily_ceiling.as_ref().map(|ceiling| ceiling.usage())
+    }
+
+    /// Latency of successful requests per endpoint and output format, or `None`
+    /// if latency tracking wasn't enabled on the builder. Shared by all clones.
+    pub fn stats(&self) -> Option<Vec<LatencyStats>> {
+        self.latency.as_ref().map(|latency| latency.stats())
+    }
+
+    /// State of the circuit breaker, or `None` if none was set on the builder.
+    /// Shared by all clones.
+    pub fn circuit_state(&self) -> Option<CircuitState> {
+        self.circuit_breaker.as_ref().map(|breaker| breaker.state())
+    }
+
+    /// Clear the latency histograms
+    pub fn reset_stats(&self) {
+        if let Some(latency) = &self.latency {
+            latency.reset();
+        }
+    }
+
+    /// Reset the recorded usage to zero
+    pub fn reset_usage(&self) {
+        if let Some(usage) = &self.usage {
+            usage.reset();
+        }
+        if let Some(budget) = &self.budget {
+            budget.reset_session();
+        }
+    }
+
+    /// Probe DNS, TLS and authentication against the base URL with a cheap
+    /// `GET /models`, for readiness checks. Never fails; problems are reported
+    /// in the returned status. Sandbox clients are always healthy.
+    pub async fn health_check(&self) -> HealthCheck {
+        let started = Instant::now();
+        let result = if self.sandbox {
+            Ok(())
+        } else {
+            // Problems are the result here, not a failure to report
+            self.without_error_sink()
+                .request(Method::GET, "/models")
+                .send()
+                .await
+                .map(|_| ())
+        };
+        let latency = started.elapsed();
+
+        match result {
+            Ok(()) => HealthCheck {
+                status: HealthStatus::Healthy,
+                latency,
+                error: None,
+            },
+            Err(e) => {
+                let status = match &e {
+                    ElevenLabsTTDError::AuthenticationError { .. } => HealthStatus::Unauthorized,
+                    ElevenLabsTTDError::RateLimitError { .. } => HealthStatus::RateLimited,
+                    ElevenLabsTTDError::RequestError(_) | ElevenLabsTTDError::Timeout { .. } => {
+                        HealthStatus::Unreachable
+                    }
+                    _ => HealthStatus::Degraded,
+                };
+                HealthCheck {
+                    status,
+                    latency,
+                    error: Some(e.redacted_message()),
+                }
+            }
+        }
+    }
+
+    /// Fetch the subscription of the account that owns the API key.
+    /// Also checks subscription usage against any budget thresholds.
+    pub async fn subscription(&self) -> Result<Subscription, ElevenLabsTTDError> {
+        let subscription = self.subscription_for_key(&self.api_key).await?;
+
+        if let Some(budget) = &self.budget {
+            budget.check_subscription(subscription.character_count, subscription.character_limit);
+        }
+
+        Ok(subscription)
+    }
+
+    /// Models available to the account (`GET /v1/models`)
+    pub async fn models(&self) -> Result<Vec<Model>, ElevenLabsTTDError> {
+        self.request(Method::GET, "/models")
+            .operation("models")
+            .send_json()
+            .await
+    }
+
+    /// Whether the account's subscription tier allows requesting `format`.
+    /// Unknown tiers are assumed to allow every format.
+    pub async fn can_use(&self, format: OutputFormat) -> Result<bool, ElevenLabsTTDError> {
+        let tier = self.subscription().await?.tier_level();
+        Ok(tier.is_none_or(|tier| tier >= format.required_tier()))
+    }
+
+    /// Reject formats the account's tier can't use, fetching the tier once per client
+    async fn check_format_tier(&self, request: &TTDRequest) -> Result<(), ElevenLabsTTDError> {
+        let Some(format) = request
+            .output_format
+            .as_deref()
+            .and_then(OutputFormat::from_name)
+        else {
+            return Ok(());
+        };
+
+        let tier = match self.tier.get() {
+            Some(tier) => *tier,
+            None => {
+                // Failures are reported once, by the caller
+                let tier = self.without_error_sink().subscription().await?.tier_level();
+                *self.tier.get_or_init(|| tier)
+            }
+        };
+
+        match tier {
+            Some(tier) if tier < format.required_tier() => {
+                Err(ElevenLabsTTDError::ValidationError(format!(
+                    "Output format {} requires the {:?} tier or above, the account is on {:?}",
+                    format.as_str(),
+                    format.required_tier(),
+                    tier
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Fetch the subscription of every configured API key and record their
+    /// remaining quota, so new requests are routed to the key with most headroom
+    pub async fn refresh_key_quotas(&self) -> Result<Vec<KeyQuota>, ElevenLabsTTDError> {
+        for key in self.keys.keys() {
+            self.subscription_for_key(key).await?;
+        }
+        Ok(self.key_quotas())
+    }
+
+    /// Last known remaining quota of each configured API key
+    pub fn key_quotas(&self) -> Vec<KeyQuota> {
+        self.keys.quotas()
+    }
+
+    async fn subscription_for_key(&self, key: &str) -> Result<Subscription, ElevenLabsTTDError> {
+        let subscription = self
+            .execute_endpoint_with_key(&endpoint::GetSubscription, key)
+            .await?;
+        self.keys.set_remaining(
+            key,
+            subscription
+                .character_limit
+                .saturating_sub(subscription.character_count),
+        );
+        Ok(subscription)
+    }
+
+    /// Voice endpoints: listing account and library voices
+    pub fn voices(&self) -> VoicesApi {
+        VoicesApi::new(self.clone())
+    }
+
+    /// A cached catalog of the account's voices for repeated lookups
+    pub fn voice_catalog(&self) -> VoiceCatalog {
+        VoiceCatalog::new(self.clone())
+    }
+
+    /// History endpoints: listing past generations
+    pub fn history(&self) -> HistoryApi {
+        HistoryApi::new(self.clone())
+    }
+
+    /// Absolute URL of an endpoint in `family`, at the version configured on the
+    /// builder or else `version`, the one this crate targets for the endpoint
+    pub(crate) fn api_url(
+        &self,
+        family: EndpointFamily,
+        version: ApiVersion,
+        path: &str,
+    ) -> String {
+        self.versions.url(family, version, path)
+    }
+
+    /// Build a request to any ElevenLabs endpoint, with authentication applied.
+    /// `path` is relative to the base URL and default API version (e.g. `/models`).
+    /// Absolute URLs are used as-is, but only on the base URL's origin, so the API
+    /// key is never sent to another host; others fail with a validation error.
+    pub fn request(&self, method: Method, path: &str) -> RawRequestBuilder {
+        self.request_as(method, path, &self.api_key, self.workspace_id.as_deref())
+    }
+
+    fn request_as(
+        &self,
+        method: Method,
+        path: &str,
+        api_key: &str,
+        workspace_id: Option<&str>,
+    ) -> RawRequestBuilder {
+        let base_url = self.versions.base_url();
+        let url = if path.starts_with("http://") || path.starts_with("https://") {
+            if !same_origin(path, &base_url) {
+                return RawRequestBuilder::rejected(
+                    self.client.request(method, path),
+                    ElevenLabsTTDError::ValidationError(format!(
+                        "{} is not on the API's origin ({}), so the API key won't be sent to it",
+                        path, base_url
+                    )),
+                )
+                .error_sink(self.error_sink.clone());
+            }
+            path.to_string()
+        } else {
+            format!("{}{}", base_url, path)
+        };
+
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("xi-api-key", api_key);
+        if let Some(workspace_id) = workspace_id {
+            request = request.header(self.workspace_header.as_str(), workspace_id);
+        }
+        RawRequestBuilder::new(request).error_sink(self.error_sink.clone())
+    }
+
+    /// Execute a typed [`Endpoint`], e.g. one defined by another crate
+    pub async fn execute_endpoint<E: Endpoint>(
+        &self,
+        endpoint: &E,
+    ) -> Result<E::Response, ElevenLabsTTDError> {
+        self.execute_endpoint_with_key(endpoint, &self.api_key)
+            .await
+    }
+
+    async fn execute_endpoint_with_key<E: Endpoint>(
+        &self,
+        endpoint: &E,
+        api_key: &str,
+    ) -> Result<E::Response, ElevenLabsTTDError> {
+        let mut request = self
+            .request_as(
+                endpoint.method(),
+                &endpoint.path(),
+                api_key,
+                self.workspace_id.as_deref(),
+            )
+            .query(&endpoint.query());
+        if let Some(body) = endpoint.body() {
+            request = request.json(&body);
         }
+        request.send_json().await
     }
 
     /// Start building a Text-to-Dialogue request
@@ -75,136 +473,3465 @@ impl ElevenLabsTTDClient {
         TextToDialogueBuilder::new(self.clone(), inputs.into())
     }
 
+    /// Stop accepting requests, wait up to `deadline` for running generations to
+    /// finish, then flush the [`DedupeStore`] index. Applies to every clone of the
+    /// client: later requests fail with [`ElevenLabsTTDError::ShuttingDown`] and
+    /// voice catalogs stop refreshing in the background.
+    pub async fn shutdown(&self, deadline: Duration) -> Result<ShutdownReport, ElevenLabsTTDError> {
+        let report = self.lifecycle.close(deadline).await;
+        if !report.is_clean() {
+            tracing::warn!(
+                abandoned = report.abandoned,
+                "shutdown deadline passed with requests still running"
+            );
+        }
+        if let Some(store) = &self.dedupe_store {
+            store.flush()?;
+        }
+        Ok(report)
+    }
+
+    /// Whether [`Self::shutdown`] has been called on this client or a clone
+    pub fn is_shut_down(&self) -> bool {
+        self.lifecycle.is_closed()
+    }
+
+    /// Generate a sound effect from a description with the sound-generation endpoint
+    pub async fn sound_effect(
+        &self,
+        cue: &SoundCue,
+        output_format: OutputFormat,
+    ) -> Result<Vec<u8>, ElevenLabsTTDError> {
+        self.sound_effect_with(cue, output_format, &RequestOptions::default())
+            .await
+    }
+
+    /// [`Self::sound_effect`] with the per-request options that apply to it
+    pub(crate) async fn sound_effect_with(
+        &self,
+        cue: &SoundCue,
+        output_format: OutputFormat,
+        options: &RequestOptions,
+    ) -> Result<Vec<u8>, ElevenLabsTTDError> {
+        let _in_flight = self.lifecycle.enter()?;
+        output_format.check_supported()?;
+        let api_format = output_format.api_format();
+        let audio = if self.sandbox {
+            let duration = cue.duration.unwrap_or(dialogue::DEFAULT_SFX_DURATION);
+            audio::silence(duration, api_format)
+        } else {
+            let body = serde_json::json!({
+                "text": cue.description,
+                "duration_seconds": cue.duration.map(|d| d.as_secs_f64()),
+            });
+            let url = self.api_url(
+                EndpointFamily::SoundEffects,
+                ApiVersion::V1,
+                "/sound-generation",
+            );
+            let workspace_id = options
+                .workspace_id
+                .as_deref()
+                .or(self.workspace_id.as_deref());
+            let mut request = self
+                .request_as(Method::POST, &url, &self.api_key, workspace_id)
+                .operation("sound_effect")
+                .query(&[("output_format", api_format.as_str())])
+                .json(&body);
+            if let Some(key) = &options.idempotency_key {
+                request = request.header(&self.idempotency_header, key);
+            }
+            request.send().await?.body
+        };
+
+        Ok(match output_format {
+            OutputFormat::Wav { sample_rate } => audio::wav(&audio, sample_rate),
+            _ => audio,
+        })
+    }
+
+    /// Render a [`Dialogue`] into one audio file: each run of consecutive turns is
+    /// generated in one Text-to-Dialogue request (split where the turns' language
+    /// changes), each cue with [`Self::sound_effect`],
+    /// and the parts are concatenated in script order.
+    ///
+    /// PCM, μ-law, A-law and MP3 concatenate seamlessly; Opus yields a chained Ogg stream.
+    /// Parts are separated by the builder's [`ClientBuilder::segment_gap`], if set.
+    pub async fn render_dialogue(
+        &self,
+        dialogue: &Dialogue,
+        output_format: OutputFormat,
+    ) -> Result<TTDAudio, ElevenLabsTTDError> {
+        let result = self
+            .render_dialogue_with(
+                dialogue,
+                output_format,
+                None,
+                None,
+                &RequestOptions::default(),
+            )
+            .await;
+        if let Err(e) = &result {
+            self.report_error(e, "render_dialogue", None, 1);
+        }
+        result
+    }
+
+    /// [`Self::render_dialogue`] with the given settings, seed and options for every
+    /// request. With an idempotency key, each request sends it suffixed with its
+    /// position, e.g. `chapter-1-2` for the second.
+    pub(crate) async fn render_dialogue_with(
+        &self,
+        dialogue: &Dialogue,
+        output_format: OutputFormat,
+        settings: Option<&TTDSettings>,
+        seed: Option<u32>,
+        options: &RequestOptions,
+    ) -> Result<TTDAudio, ElevenLabsTTDError> {
+        output_format.check_supported()?;
+        // Failures are reported once, by the caller
+        let client = self.without_error_sink();
+        // WAV is stitched as PCM, with the header added at the end
+        let api_format = output_format.api_format();
+        let mut parts = Vec::new();
+        let mut character_count = None;
+        let mut warnings = Vec::new();
+        for (index, segment) in dialogue.segments(api_format).into_iter().enumerate() {
+            let options = RequestOptions {
+                idempotency_key: options
+                    .idempotency_key
+                    .as_ref()
+                    .map(|key| format!("{}-{}", key, index + 1)),
+                ..options.clone()
+            };
+            match segment {
+                dialogue::Segment::Turns(turns, language) => {
+                    let mut builder = client.text_to_dialogue(turns).output_format(api_format);
+                    if let Some(language) = language {
+                        builder = builder.language_code(language);
+                    }
+                    if let Some(settings) = settings {
+                        builder = builder.settings(settings.clone());
+                    }
+                    if let Some(seed) = seed {
+                        builder = builder.seed(seed);
+                    }
+                    builder.options = options;
+                    let mut part = builder.execute().await?;
+                    if let Some(count) = part.character_count {
+                        *character_count.get_or_insert(0) += count;
+                    }
+                    warnings.append(&mut part.warnings);
+                    parts.push(part.into_bytes());
+                }
+                dialogue::Segment::Sfx(cue) => {
+                    parts.push(client.sound_effect_with(cue, api_format, &options).await?);
+                }
+                dialogue::Segment::Silence(duration) => {
+                    parts.push(audio::silence(duration, api_format));
+                }
+            }
+        }
+        let mut audio = audio::concat(parts, self.segment_gap, api_format);
+        if let OutputFormat::Wav { sample_rate } = output_format {
+            audio = audio::wav(&audio, sample_rate);
+        }
+
+        let mut audio = TTDAudio {
+            character_count,
+            output_format: Some(output_format),
+            warnings,
+            ..TTDAudio::new(audio, uuid::Uuid::new_v4().to_string())
+        };
+        self.check_anomalies(&mut audio);
+        Ok(audio)
+    }
+
+    /// Start building a single-speaker Text-to-Speech request.
+    /// Takes the same options as [`Self::text_to_dialogue`].
+    pub fn text_to_speech<V: VoiceRef, T: Into<String>>(
+        &self,
+        voice: V,
+        text: T,
+    ) -> TextToDialogueBuilder {
+        let mut builder =
+            TextToDialogueBuilder::new(self.clone(), vec![TTDInput::new(voice, text)]);
+        builder.options.text_to_speech = true;
+        builder
+    }
+
     /// Internal method to execute TTD request
     pub(crate) async fn execute_ttd(
         &self,
         request: TTDRequest,
-    ) -> Result<Vec<u8>, ElevenLabsTTDError> {
-        let mut url = format!("{}/text-to-dialogue", self.base_url);
+        options: RequestOptions,
+    ) -> Result<TTDAudio, ElevenLabsTTDError> {
+        self.generate_in_format(request, options)
+            .await
+            .inspect_err(|e| self.report_error(e, "text_to_dialogue", None, 1))
+    }
 
-        if request.output_format.is_some() {
-            url = format!(
-                "{}?output_format={}",
-                url,
-                request.output_format.clone().unwrap()
-            );
+    /// [`Self::generate`] with WAV wrapped from PCM and anomalies checked. Errors
+    /// aren't reported, so callers that retry can report once at the end.
+    async fn generate_in_format(
+        &self,
+        mut request: TTDRequest,
+        options: RequestOptions,
+    ) -> Result<TTDAudio, ElevenLabsTTDError> {
+        let output_format = request
+            .output_format
+            .as_deref()
+            .and_then(OutputFormat::from_name);
+        // What an `OutputFormat::Wav` without a PCM equivalent turns into
+        if request.output_format.as_deref() == Some("wav") {
+            return Err(ElevenLabsTTDError::ValidationError(
+                "WAV needs a sample rate one of the PCM formats has".to_string(),
+            ));
         }
 
-        let response = self
-            .client
-            .post(&url)
-            .header("xi-api-key", &self.api_key)
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
+        // WAV is requested as PCM and wrapped on the way out
+        let wav = output_format.filter(OutputFormat::is_wav);
+        if let Some(format) = wav {
+            request.output_format = Some(format.api_format().into());
+        }
 
-        if !response.status().is_success() {
-            return Err(ElevenLabsTTDError::ApiError {
-                status: response.status().as_u16(),
-                message: response.text().await.unwrap_or_default(),
-            });
+        let mut audio = self.generate(request, options).await?;
+        if let Some(format) = wav {
+            audio.audio = audio::wav(&audio.audio, format.sample_rate());
         }
+        audio.output_format = output_format;
+        self.check_anomalies(&mut audio);
+        Ok(audio)
+    }
 
-        Ok(response.bytes().await?.to_vec())
+    /// This client without its error sink, for calls nested in one that reports itself
+    pub(crate) fn without_error_sink(&self) -> Self {
+        Self {
+            error_sink: None,
+            ..self.clone()
+        }
     }
-}
 
-/// Builder for Text-to-Dialogue requests
-pub struct TextToDialogueBuilder {
-    client: ElevenLabsTTDClient,
-    inputs: Vec<TTDInput>,
-    output_format: Option<String>,
-    model_id: Option<String>,
-    settings: Option<TTDSettings>,
-    pronunciation_dictionary_locators: Option<TTDPronunciationDictionaryLocators>,
-    seed: Option<u32>,
-}
+    /// Pass an error a call finally failed with to the error sink, if any
+    pub(crate) fn report_error(
+        &self,
+        error: &ElevenLabsTTDError,
+        operation: &'static str,
+        item: Option<&str>,
+        attempts: u32,
+    ) {
+        if let Some(sink) = &self.error_sink {
+            sink.report(error, &ErrorContext::new(error, operation, item, attempts));
+        }
+    }
 
-impl TextToDialogueBuilder {
-    fn new(client: ElevenLabsTTDClient, inputs: Vec<TTDInput>) -> Self {
-        Self {
-            client,
-            inputs,
-            output_format: None,
-            model_id: None,
-            settings: None,
-            pronunciation_dictionary_locators: None,
-            seed: None,
+    /// Fill in [`TTDAudio::anomalies`] when enabled, for formats that can be decoded
+    fn check_anomalies(&self, audio: &mut TTDAudio) {
+        if !self.detect_anomalies {
+            return;
+        }
+        if let Ok(anomalies) = audio.detect_anomalies() {
+            for anomaly in &anomalies {
+                tracing::warn!(correlation_id = %audio.correlation_id, %anomaly, "audio anomaly detected");
+            }
+            audio.anomalies = anomalies;
         }
     }
 
-    /// Set the output format to use
-    pub fn output_format<S: Into<String>>(mut self, output_format: S) -> Self {
-        self.output_format = Some(output_format.into());
-        self
+    fn notify_validation_warning(&self, warning: &ValidationWarning) {
+        if let Some(callback) = &self.validation_warning {
+            callback(warning);
+        }
     }
 
-    /// Set the model to use
-    pub fn model<S: Into<String>>(mut self, model_id: S) -> Self {
-        self.model_id = Some(model_id.into());
-        self
+    async fn generate(
+        &self,
+        mut request: TTDRequest,
+        mut options: RequestOptions,
+    ) -> Result<TTDAudio, ElevenLabsTTDError> {
+        let _in_flight = self.lifecycle.enter()?;
+        let mut warnings = std::mem::take(&mut options.warnings);
+        if self.sanitize_text {
+            for input in &mut request.inputs {
+                input.text = text::sanitize(&input.text);
+            }
+        }
+        if self.split_long_turns {
+            validation::split_long_turns(&mut request);
+        }
+        if let Some(filter) = &self.content_filter {
+            for (index, input) in request.inputs.iter().enumerate() {
+                filter
+                    .check(input)
+                    .map_err(|reason| ElevenLabsTTDError::ContentRejected { index, reason })?;
+            }
+        }
+
+        for warning in validation::validate(&mut request, self.validation_mode)? {
+            tracing::warn!(%warning, "request adjusted by lenient validation");
+            self.notify_validation_warning(&warning);
+            warnings.push(warning.into());
+        }
+
+        let correlation_id = options
+            .correlation_id
+            .take()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+        if self.check_format_tier && !self.sandbox {
+            self.check_format_tier(&request).await?;
+        }
+
+        let mut manifest = GenerationManifest::for_request(&request, &correlation_id);
+
+        if self.sandbox {
+            tracing::debug!(correlation_id = %correlation_id, "returning sandbox placeholder audio");
+            return Ok(TTDAudio {
+                manifest: Some(manifest),
+                warnings,
+                ..TTDAudio::new(sandbox::placeholder_audio(&request)?, correlation_id)
+            });
+        }
+
+        let fallback = (!options.text_to_speech && self.text_to_speech_fallback)
+            .then(|| text_to_speech_reason(&request))
+            .flatten();
+        let text_to_speech = options.text_to_speech || fallback.is_some();
+        if let Some(reason) = fallback {
+            warnings.push(Warning::FallbackUsed {
+                fallback: "text-to-speech".to_string(),
+                reason: reason.to_string(),
+            });
+        }
+
+        // Scope idempotency keys to the account and workspace when they're
+        // overridden, so tenants sharing the client can't receive each other's
+        // audio; hashed like stored responses, so the cache holds no API keys
+        let scope = options.scope();
+        let idempotency_key = options
+            .idempotency_key
+            .clone()
+            .map(|key| dedupe::scoped_hash(key, scope.as_deref()));
+
+        if let Some(key) = &idempotency_key
+            && let Some(audio) = self.dedupe.get(key)
+        {
+            tracing::debug!(
+                idempotency_key = options.idempotency_key.as_deref(),
+                "returning deduplicated response"
+            );
+            return Ok(TTDAudio { warnings, ..audio });
+        }
+
+        // Scoped like idempotency keys, so tenants sharing a store stay apart, and
+        // to the endpoint, so Text-to-Speech audio isn't reused for dialogue
+        let request_hash = self.dedupe_store.as_ref().map(|_| {
+            let hash = dedupe::scoped_hash(request.canonical_hash(), scope.as_deref());
+            if text_to_speech {
+                dedupe::scoped_hash(hash, Some("text-to-speech"))
+            } else {
+                hash
+            }
+        });
+
+        if let (Some(store), Some(hash)) = (&self.dedupe_store, &request_hash)
+            && let Some((audio, entry)) = store.load(hash)
+        {
+            tracing::debug!(request_hash = %hash, "returning stored response");
+            return Ok(TTDAudio {
+                history_item_id: entry.history_item_id,
+                output_format: request
+                    .output_format
+                    .as_deref()
+                    .and_then(OutputFormat::from_name),
+                warnings,
+                ..TTDAudio::new(audio, correlation_id)
+            });
+        }
+
+        let input_characters: u64 = request
+            .inputs
+            .iter()
+            .map(|input| text::billable_len(&input.text))
+            .sum();
+
+        let api_key = match (options.api_key, &options.tenant) {
+            (Some(api_key), _) => api_key.expose().to_string(),
+            (None, Some(tenant)) => self.tenant_key(tenant)?,
+            (None, None) => self.keys.select(input_characters).to_string(),
+        };
+
+        let reservation = self
+            .daily_ceiling
+            .as_ref()
+            .map(|ceiling| ceiling.reserve(input_characters))
+            .transpose()?;
+        let permit = self
+            .circuit_breaker
+            .as_ref()
+            .map(|breaker| breaker.acquire())
+            .transpose()?;
+
+        let span = tracing::info_span!(
+            "text_to_dialogue",
+            correlation_id = %correlation_id,
+            text_to_speech
+        );
+
+        let headers = CallHeaders {
+            api_key: &api_key,
+            correlation_id,
+            idempotency_key: options.idempotency_key.as_deref(),
+            workspace_id: options
+                .workspace_id
+                .as_deref()
+                .or(self.workspace_id.as_deref()),
+        };
+        let result = self
+            .send_ttd(
+                request,
+                text_to_speech,
+                options.base_url.as_deref(),
+                headers,
+            )
+            .instrument(span)
+            .await;
+
+        if let Some(permit) = permit {
+            permit.record(&result);
+        }
+
+        match &result {
+            Ok(audio) => self
+                .keys
+                .record_usage(&api_key, audio.character_count.unwrap_or(input_characters)),
+            Err(ElevenLabsTTDError::QuotaExceededError { .. }) => {
+                self.keys.set_remaining(&api_key, 0)
+            }
+            Err(ElevenLabsTTDError::ApiError { message, .. })
+                if message.contains("quota_exceeded") =>
+            {
+                self.keys.set_remaining(&api_key, 0)
+            }
+            Err(_) => {}
+        }
+
+        if let Some(reservation) = reservation {
+            let billed = match &result {
+                Ok(audio) => audio.character_count.unwrap_or(input_characters),
+                Err(_) => 0,
+            };
+            reservation.settle(billed);
+        }
+
+        if let Some(usage) = &self.usage {
+            match &result {
+                Ok(audio) => {
+                    usage.record_success(audio.character_count.unwrap_or(input_characters))
+                }
+                Err(_) => usage.record_error(),
+            }
+            if let Some(budget) = &self.budget {
+                budget.check_session(usage.summary().characters);
+            }
+        }
+
+        if let Some(sink) = &self.audit_sink {
+            let billed = match &result {
+                Ok(audio) => audio.character_count.unwrap_or(input_characters),
+                Err(_) => 0,
+            };
+            sink.record(&AuditEvent {
+                timestamp: chrono::Utc::now(),
+                tenant: options.tenant.clone(),
+                correlation_id: manifest.correlation_id.clone(),
+                input_characters,
+                voices: manifest.voice_ids.clone(),
+                cost: self
+                    .cost_per_thousand_characters
+                    .map(|price| billed as f64 / 1000.0 * price),
+                request_id: result.as_ref().ok().and_then(|a| a.history_item_id.clone()),
+                error: result.as_ref().err().map(|e| e.kind()),
+            });
+        }
+
+        let mut audio = result?;
+        manifest.history_item_id = audio.history_item_id.clone();
+        audio.manifest = Some(manifest);
+        audio.warnings = warnings;
+
+        if let Some(key) = idempotency_key {
+            self.dedupe.insert(key, audio.clone());
+        }
+
+        if let (Some(store), Some(hash)) = (&self.dedupe_store, &request_hash)
+            && let Err(e) = store.save(hash, &audio)
+        {
+            // The audio is already generated and billed, so don't fail the call over it
+            tracing::warn!(error = %e, "failed to persist response to dedupe store");
+        }
+
+        Ok(audio)
     }
 
-    /// Set the settings to use
-    pub fn settings(mut self, settings: TTDSettings) -> Self {
-        self.settings = Some(settings);
-        self
+    /// The API key of a tenant, from the configured [`KeyProvider`]
+    fn tenant_key(&self, tenant: &TenantId) -> Result<String, ElevenLabsTTDError> {
+        let provider = self.key_provider.as_ref().ok_or_else(|| {
+            ElevenLabsTTDError::ValidationError(
+                "A tenant was set but the client has no key provider".to_string(),
+            )
+        })?;
+        provider
+            .key_for(tenant)
+            .map(|secret| secret.expose().to_string())
+            .ok_or_else(|| ElevenLabsTTDError::AuthenticationError {
+                message: format!("No API key for tenant {}", tenant),
+                correlation_id: None,
+                response: None,
+            })
     }
 
-    /// Set the pronunciation dictionary locators to use
-    pub fn pronunciation_dictionary_locators(
-        mut self,
-        pronunciation_dictionary_locators: TTDPronunciationDictionaryLocators,
-    ) -> Self {
-        self.pronunciation_dictionary_locators = Some(pronunciation_dictionary_locators);
-        self
+    /// Send a request to the Text-to-Dialogue endpoint, or to the single-speaker
+    /// Text-to-Speech endpoint with the turns joined into one text
+    async fn send_ttd(
+        &self,
+        request: TTDRequest,
+        text_to_speech: bool,
+        base_url: Option<&str>,
+        headers: CallHeaders<'_>,
+    ) -> Result<TTDAudio, ElevenLabsTTDError> {
+        let (url, body) = ttd_url_and_body(&request, text_to_speech, &self.versions, base_url)?;
+        let correlation = headers.correlation_id.clone();
+        let started = Instant::now();
+        let result = self.transmit(&request, &url, body, headers).await;
+        let elapsed = started.elapsed();
+
+        if let (Some(latency), Ok(_)) = (&self.latency, &result) {
+            let endpoint = if text_to_speech {
+                "text_to_speech"
+            } else {
+                "text_to_dialogue"
+            };
+            latency.record(endpoint, request.output_format.as_deref(), elapsed);
+        }
+        if self.log_requests {
+            self.log_request(&request, &url, &correlation, elapsed, &result);
+        }
+        result
     }
 
-    /// Set seeds to use
-    pub fn seed(mut self, seed: u32) -> Self {
-        self.seed = Some(seed);
-        self
+    /// Emit the [`ClientBuilder::log_requests`] event for a finished request
+    fn log_request(
+        &self,
+        request: &TTDRequest,
+        url: &str,
+        correlation_id: &str,
+        elapsed: Duration,
+        result: &Result<TTDAudio, ElevenLabsTTDError>,
+    ) {
+        let path = reqwest::Url::parse(url)
+            .map(|url| url.path().to_string())
+            .unwrap_or_default();
+        let characters: u64 = request
+            .inputs
+            .iter()
+            .map(|input| text::billable_len(&input.text))
+            .sum();
+        let text = self.log_request_text.then(|| {
+            request
+                .inputs
+                .iter()
+                .map(|input| input.text.as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        });
+        let latency_ms = elapsed.as_millis() as u64;
+
+        match result {
+            Ok(_) => tracing::info!(
+                target: "elevenlabs_ttd::requests",
+                method = "POST",
+                path,
+                inputs = request.inputs.len(),
+                characters,
+                status = 200,
+                latency_ms,
+                correlation_id,
+                text,
+                "request completed"
+            ),
+            Err(e) => tracing::info!(
+                target: "elevenlabs_ttd::requests",
+                method = "POST",
+                path,
+                inputs = request.inputs.len(),
+                characters,
+                status = e.status(),
+                error = e.kind(),
+                latency_ms,
+                correlation_id,
+                text,
+                "request failed"
+            ),
+        }
     }
 
-    /// Execute the Text-to-Dialogue request
-    pub async fn execute(self) -> Result<Vec<u8>, ElevenLabsTTDError> {
-        let output_format = self
-            .output_format
-            .unwrap_or_else(|| "mp3_44100_128".to_string()); // Default to: mp3_44100_128
+    /// Send a Text-to-Dialogue or Text-to-Speech request over HTTP, or to the mock transport
+    async fn transmit(
+        &self,
+        request: &TTDRequest,
+        url: &str,
+        body: serde_json::Value,
+        headers: CallHeaders<'_>,
+    ) -> Result<TTDAudio, ElevenLabsTTDError> {
+        let CallHeaders {
+            api_key,
+            correlation_id,
+            idempotency_key,
+            workspace_id,
+        } = headers;
+        let started = Instant::now();
+        let mut validator = (self.validate_mp3
+            && request
+                .output_format
+                .as_deref()
+                .and_then(OutputFormat::from_name)
+                .is_some_and(|format| format.codec() == formats::Codec::Mp3))
+        .then(mp3::Mp3Validator::new);
+        let (headers, audio) = match &self.mock_transport {
+            Some(transport) => {
+                let response = transport(request)?;
+                if let Some(delay) = response.delay {
+                    match self.timeout {
+                        Some(timeout) if delay > timeout => {
+                            tokio::time::sleep(timeout).await;
+                            return Err(ElevenLabsTTDError::Timeout {
+                                elapsed: started.elapsed(),
+                            });
+                        }
+                        _ => tokio::time::sleep(delay).await,
+                    }
+                }
+
+                if !(200..300).contains(&response.status) {
+                    tracing::warn!(status = response.status, "text-to-dialogue request failed");
+                    return Err(ElevenLabsTTDError::from_parts(
+                        response.status,
+                        response.headers,
+                        response.body,
+                        Some(correlation_id),
+                        reqwest::Url::parse(url)
+                            .ok()
+                            .map(|url| url.path().to_string()),
+                    ));
+                }
+                if let Some(bytes) = response.interrupt_after {
+                    let mut received = response.body;
+                    received.truncate(bytes);
+                    return Err(ElevenLabsTTDError::StreamInterrupted {
+                        received,
+                        source: Box::new(std::io::Error::new(
+                            std::io::ErrorKind::UnexpectedEof,
+                            "connection closed",
+                        )),
+                    });
+                }
+                if let Some(validator) = &mut validator {
+                    validator.push(&response.body)?;
+                }
+                (response.headers, response.body)
+            }
+            None => {
+                let mut http_request = self
+                    .client
+                    .post(url)
+                    .header("xi-api-key", api_key)
+                    .header("Content-Type", "application/json")
+                    .header(self.correlation_header.as_str(), &correlation_id);
+
+                if let Some(key) = idempotency_key {
+                    http_request = http_request.header(self.idempotency_header.as_str(), key);
+                }
+                if let Some(workspace_id) = workspace_id {
+                    http_request =
+                        http_request.header(self.workspace_header.as_str(), workspace_id);
+                }
+
+                let mut response = http_request
+                    .json(&body)
+                    .send()
+                    .await
+                    .map_err(|e| ElevenLabsTTDError::from_transport(e, started))?;
+
+                if !response.status().is_success() {
+                    let status = response.status().as_u16();
+                    tracing::warn!(status, "text-to-dialogue request failed");
+                    return Err(
+                        ElevenLabsTTDError::from_response(response, Some(correlation_id)).await,
+                    );
+                }
+
+                let headers = response.headers().clone();
+                let mut audio = Vec::new();
+                loop {
+                    match response.chunk().await {
+                        Ok(Some(chunk)) => {
+                            if let Some(validator) = &mut validator {
+                                validator.push(&chunk)?;
+                            }
+                            audio.extend_from_slice(&chunk);
+                        }
+                        Ok(None) => break,
+                        Err(e) if audio.is_empty() => {
+                            return Err(ElevenLabsTTDError::from_transport(e, started));
+                        }
+                        Err(e) => {
+                            tracing::warn!(received = audio.len(), error = %e, "audio download interrupted");
+                            return Err(ElevenLabsTTDError::StreamInterrupted {
+                                received: audio,
+                                source: Box::new(e),
+                            });
+                        }
+                    }
+                }
+                (headers, audio)
+            }
+        };
+        if let Some(validator) = validator {
+            validator.finish()?;
+        }
 
-        let request = TTDRequest {
-            inputs: self.inputs,
-            output_format: Some(output_format.clone()),
-            seed: self.seed.or(None),
-            model_id: self
-                .model_id
-                .unwrap_or_else(|| models::elevanlabs_models::ELEVEN_V3.to_string()), // Default to: eleven_v3
-            settings: self.settings.or(None),
-            pronunciation_dictionary_locators: self.pronunciation_dictionary_locators.or(None),
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
         };
+        let history_item_id = header("history-item-id");
+        let character_count = header("x-character-count").and_then(|count| count.parse().ok());
+        let rate_limit = RateLimitInfo::from_headers(&headers);
 
-        self.client.execute_ttd(request).await
+        Ok(TTDAudio {
+            history_item_id,
+            character_count,
+            rate_limit,
+            headers,
+            ..TTDAudio::new(audio, correlation_id)
+        })
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// URL and JSON body of a Text-to-Dialogue request, or of the equivalent
+/// Text-to-Speech request when `text_to_speech` is set
+fn ttd_url_and_body(
+    request: &TTDRequest,
+    text_to_speech: bool,
+    versions: &version::ApiVersions,
+    base_url: Option<&str>,
+) -> Result<(String, serde_json::Value), ElevenLabsTTDError> {
+    let (mut url, body) = if text_to_speech {
+        let voice_id = request
+            .inputs
+            .first()
+            .map(|input| input.voice_id.as_str())
+            .unwrap_or_default();
+        let text: Vec<&str> = request.inputs.iter().map(|i| i.text.as_str()).collect();
+        let body = serde_json::json!({
+            "text": text.join("\n"),
+            "model_id": request.model_id,
+            "voice_settings": request.settings,
+            "pronunciation_dictionary_locators": request.pronunciation_dictionary_locators.as_slice(),
+            "seed": request.seed,
+            "language_code": request.language_code,
+        });
+        let path = format!("/text-to-speech/{}", voice_id);
+        let url = versions.url_at(
+            base_url,
+            EndpointFamily::TextToSpeech,
+            ApiVersion::V1,
+            &path,
+        );
+        (url, body)
+    } else {
+        (
+            versions.url_at(
+                base_url,
+                EndpointFamily::TextToDialogue,
+                ApiVersion::V1,
+                "/text-to-dialogue",
+            ),
+            serde_json::to_value(request)?,
+        )
+    };
 
-    #[tokio::test]
-    async fn test_client_creation() {
-        let client = ElevenLabsTTDClient::new("test-key");
-        assert_eq!(client.api_key, "test-key");
+    if request.output_format.is_some() {
+        url = format!(
+            "{}?output_format={}",
+            url,
+            request.output_format.clone().unwrap()
+        );
+    }
+    Ok((url, body))
+}
+
+/// Whether two absolute URLs share scheme, host and port
+fn same_origin(url: &str, other: &str) -> bool {
+    match (reqwest::Url::parse(url), reqwest::Url::parse(other)) {
+        (Ok(url), Ok(other)) => url.origin() == other.origin(),
+        _ => false,
+    }
+}
+
+/// Why a request is better served by Text-to-Speech, if it is: it has a single
+/// speaker, or its model can't generate dialogue
+fn text_to_speech_reason(request: &TTDRequest) -> Option<&'static str> {
+    let single_speaker = request
+        .inputs
+        .windows(2)
+        .all(|pair| pair[0].voice_id == pair[1].voice_id);
+    let dialogue_model = request
+        .model_id
+        .as_deref()
+        .is_none_or(models::supports_dialogue);
+    if single_speaker {
+        Some("single speaker")
+    } else if !dialogue_model {
+        Some("model doesn't support dialogue")
+    } else {
+        None
+    }
+}
+
+/// Builder for configuring an [`ElevenLabsTTDClient`]
+pub struct ClientBuilder {
+    api_key: String,
+    additional_api_keys: Vec<String>,
+    base_url: String,
+    api_version: Option<ApiVersion>,
+    endpoint_versions: HashMap<EndpointFamily, ApiVersion>,
+    timeout: Option<Duration>,
+    correlation_header: String,
+    idempotency_header: String,
+    workspace_header: String,
+    workspace_id: Option<String>,
+    dedupe_capacity: usize,
+    dedupe_store: Option<DedupeStore>,
+    sandbox: bool,
+    track_usage: bool,
+    cost_per_thousand_characters: Option<f64>,
+    character_budget: Option<u64>,
+    budget_thresholds: Vec<(f64, budget::BudgetCallback)>,
+    validation_mode: ValidationMode,
+    validation_warning: Option<ValidationWarningCallback>,
+    check_format_tier: bool,
+    text_to_speech_fallback: bool,
+    mock_transport: Option<mock::MockTransport>,
+    key_provider: Option<std::sync::Arc<dyn KeyProvider>>,
+    key_cache_ttl: Duration,
+    audit_sink: Option<std::sync::Arc<dyn AuditSink>>,
+    content_filter: Option<std::sync::Arc<dyn ContentFilter>>,
+    error_sink: Option<std::sync::Arc<dyn ErrorSink>>,
+    daily_character_limit: Option<u64>,
+    sanitize_text: bool,
+    split_long_turns: bool,
+    segment_gap: Duration,
+    detect_anomalies: bool,
+    validate_mp3: bool,
+    circuit_breaker: Option<(u32, Duration)>,
+    log_requests: bool,
+    log_request_text: bool,
+    track_latency: bool,
+}
+
+impl ClientBuilder {
+    fn new<S: Into<String>>(api_key: S) -> Self {
+        Self {
+            api_key: api_key.into(),
+            additional_api_keys: Vec::new(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            api_version: None,
+            endpoint_versions: HashMap::new(),
+            timeout: None,
+            correlation_header: DEFAULT_CORRELATION_HEADER.to_string(),
+            idempotency_header: DEFAULT_IDEMPOTENCY_HEADER.to_string(),
+            workspace_header: DEFAULT_WORKSPACE_HEADER.to_string(),
+            workspace_id: None,
+            dedupe_capacity: DEFAULT_DEDUPE_CAPACITY,
+            dedupe_store: None,
+            sandbox: false,
+            track_usage: false,
+            cost_per_thousand_characters: None,
+            character_budget: None,
+            budget_thresholds: Vec::new(),
+            validation_mode: ValidationMode::default(),
+            validation_warning: None,
+            check_format_tier: false,
+            text_to_speech_fallback: false,
+            mock_transport: None,
+            key_provider: None,
+            key_cache_ttl: tenant::DEFAULT_KEY_CACHE_TTL,
+            audit_sink: None,
+            content_filter: None,
+            error_sink: None,
+            daily_character_limit: None,
+            sanitize_text: false,
+            split_long_turns: false,
+            segment_gap: Duration::ZERO,
+            detect_anomalies: false,
+            validate_mp3: false,
+            circuit_breaker: None,
+            log_requests: false,
+            log_request_text: false,
+            track_latency: false,
+        }
+    }
+
+    /// Set a custom base URL (for testing/enterprise). A trailing version
+    /// segment such as `/v1` is accepted and sets [`Self::api_version`]. A URL
+    /// without one, e.g. a gateway, gets v1 paths appended as they are; set
+    /// [`Self::api_version`] to have the segment added.
+    pub fn base_url<S: Into<String>>(mut self, base_url: S) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// API version that raw requests and [`Endpoint`]s are sent to, v1 by default
+    pub fn api_version(mut self, version: ApiVersion) -> Self {
+        self.api_version = Some(version);
+        self
+    }
+
+    /// Send every endpoint of `family` to `version` instead of the version this
+    /// crate targets for it, e.g. to migrate one family ahead of the others
+    pub fn endpoint_version(mut self, family: EndpointFamily, version: ApiVersion) -> Self {
+        self.endpoint_versions.insert(family, version);
+        self
+    }
+
+    /// Fail requests that take longer than this with [`ElevenLabsTTDError::Timeout`]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Pool additional API keys with the primary one. Requests are routed to
+    /// the key with the most remaining quota, see [`ElevenLabsTTDClient::refresh_key_quotas`]
+    pub fn api_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.additional_api_keys
+            .extend(keys.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set the header name used to send the per-request correlation ID
+    pub fn correlation_header<S: Into<String>>(mut self, header: S) -> Self {
+        self.correlation_header = header.into();
+        self
+    }
+
+    /// Set the header name used to send idempotency keys
+    pub fn idempotency_header<S: Into<String>>(mut self, header: S) -> Self {
+        self.idempotency_header = header.into();
+        self
+    }
+
+    /// Set the header name used to send the workspace ID
+    pub fn workspace_header<S: Into<String>>(mut self, header: S) -> Self {
+        self.workspace_header = header.into();
+        self
+    }
+
+    /// Scope every request to an enterprise workspace, so usage is billed to it
+    pub fn workspace_id<S: Into<String>>(mut self, workspace_id: S) -> Self {
+        self.workspace_id = Some(workspace_id.into());
+        self
+    }
+
+    /// Set how many idempotent responses are kept for deduplication (0 disables it)
+    pub fn dedupe_capacity(mut self, capacity: usize) -> Self {
+        self.dedupe_capacity = capacity;
+        self
+    }
+
+    /// Persist generated outputs in a store, so identical requests are served
+    /// from disk instead of regenerated, even across process restarts
+    pub fn dedupe_store(mut self, store: DedupeStore) -> Self {
+        self.dedupe_store = Some(store);
+        self
+    }
+
+    /// Never hit the network; return placeholder audio instead
+    pub fn sandbox(mut self, enabled: bool) -> Self {
+        self.sandbox = enabled;
+        self
+    }
+
+    /// Record requests, errors and billed characters, see [`ElevenLabsTTDClient::usage_summary`]
+    pub fn track_usage(mut self, enabled: bool) -> Self {
+        self.track_usage = enabled;
+        self
+    }
+
+    /// Price per 1000 billed characters, used to estimate cost in the usage summary.
+    /// Setting a price also enables usage tracking.
+    pub fn cost_per_thousand_characters(mut self, price: f64) -> Self {
+        self.track_usage = true;
+        self.cost_per_thousand_characters = Some(price);
+        self
+    }
+
+    /// Characters this client may use in a session; budget thresholds fire
+    /// relative to it. Setting a budget also enables usage tracking.
+    pub fn character_budget(mut self, characters: u64) -> Self {
+        self.track_usage = true;
+        self.character_budget = Some(characters);
+        self
+    }
+
+    /// Call `callback` once when session usage (against [`ClientBuilder::character_budget`])
+    /// or subscription usage (checked on [`ElevenLabsTTDClient::subscription`])
+    /// reaches `percent` of its limit
+    pub fn on_budget_threshold<F>(mut self, percent: f64, callback: F) -> Self
+    where
+        F: Fn(&BudgetAlert) + Send + Sync + 'static,
+    {
+        self.budget_thresholds
+            .push((percent, std::sync::Arc::new(callback)));
+        self
+    }
+
+    /// Choose between failing fast on invalid requests (default) and fixing them up
+    pub fn validation_mode(mut self, mode: ValidationMode) -> Self {
+        self.validation_mode = mode;
+        self
+    }
+
+    /// Call `callback` for every fix applied in [`ValidationMode::Lenient`]
+    pub fn on_validation_warning<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&ValidationWarning) + Send + Sync + 'static,
+    {
+        self.validation_warning = Some(std::sync::Arc::new(callback));
+        self
+    }
+
+    /// Check the requested output format against the account's subscription tier
+    /// before sending, failing locally instead of with an API error
+    pub fn check_format_tier(mut self, enabled: bool) -> Self {
+        self.check_format_tier = enabled;
+        self
+    }
+
+    /// Send single-speaker dialogues, and dialogues using a model without dialogue
+    /// support, to the Text-to-Speech endpoint instead
+    pub fn text_to_speech_fallback(mut self, enabled: bool) -> Self {
+        self.text_to_speech_fallback = enabled;
+        self
+    }
+
+    /// Answer Text-to-Dialogue requests with `transport` instead of HTTP, so code built
+    /// on the client can be tested against success, each error class and slow responses.
+    /// Validation, usage tracking and deduplication still apply.
+    pub fn mock_transport<F>(mut self, transport: F) -> Self
+    where
+        F: Fn(&TTDRequest) -> Result<MockResponse, ElevenLabsTTDError> + Send + Sync + 'static,
+    {
+        self.mock_transport = Some(std::sync::Arc::new(transport));
+        self
+    }
+
+    /// Look up per-tenant API keys for requests made with
+    /// [`TextToDialogueBuilder::tenant`]
+    pub fn key_provider<P: KeyProvider + 'static>(mut self, provider: P) -> Self {
+        self.key_provider = Some(std::sync::Arc::new(provider));
+        self
+    }
+
+    /// How long keys from the key provider are reused before asking again
+    pub fn key_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.key_cache_ttl = ttl;
+        self
+    }
+
+    /// Report every Text-to-Dialogue call to `sink`: who generated what, with which
+    /// voices, and at what estimated cost (see [`Self::cost_per_thousand_characters`])
+    pub fn audit_sink<S: AuditSink + 'static>(mut self, sink: S) -> Self {
+        self.audit_sink = Some(std::sync::Arc::new(sink));
+        self
+    }
+
+    /// Report every error a client call finally fails with to `sink`, once per
+    /// call: renders that retry report only their last error. This covers
+    /// generation, the other endpoints and [`ElevenLabsTTDClient::request`];
+    /// [`ElevenLabsTTDClient::health_check`] returns its problems instead.
+    pub fn error_sink<S: ErrorSink + 'static>(mut self, sink: S) -> Self {
+        self.error_sink = Some(std::sync::Arc::new(sink));
+        self
+    }
+
+    /// Check every turn with `filter` before sending, refusing the request with
+    /// [`ElevenLabsTTDError::ContentRejected`] if any turn fails
+    pub fn content_filter<F: ContentFilter + 'static>(mut self, filter: F) -> Self {
+        self.content_filter = Some(std::sync::Arc::new(filter));
+        self
+    }
+
+    /// Refuse requests once `characters` have been billed today (UTC), counted
+    /// across every clone of the client, e.g. a whole worker pool
+    pub fn daily_character_limit(mut self, characters: u64) -> Self {
+        self.daily_character_limit = Some(characters);
+        self
+    }
+
+    /// Clean up every turn's text with [`text::sanitize`] before validation,
+    /// for scripts pasted from word processors
+    pub fn sanitize_text(mut self, enabled: bool) -> Self {
+        self.sanitize_text = enabled;
+        self
+    }
+
+    /// Split turns over [`validation::MAX_TURN_CHARACTERS`] at sentence boundaries
+    /// into consecutive turns with the same voice instead of rejecting or truncating them
+    pub fn split_long_turns(mut self, enabled: bool) -> Self {
+        self.split_long_turns = enabled;
+        self
+    }
+
+    /// Silence inserted between the parts stitched together by
+    /// [`ElevenLabsTTDClient::render_dialogue`], for breathing room between runs of turns
+    pub fn segment_gap(mut self, gap: Duration) -> Self {
+        self.segment_gap = gap;
+        self
+    }
+
+    /// Scan every PCM, WAV, μ-law or A-law response for clipping, long silences and
+    /// near-zero energy, reporting them in [`TTDAudio::anomalies`] so bad takes can be flagged
+    pub fn detect_anomalies(mut self, enabled: bool) -> Self {
+        self.detect_anomalies = enabled;
+        self
+    }
+
+    /// Stop sending requests after `failures` consecutive server errors, timeouts or
+    /// connection failures, failing fast with [`ElevenLabsTTDError::CircuitOpen`].
+    /// After `cooldown` one probe request is sent; it closes the circuit if it succeeds.
+    pub fn circuit_breaker(mut self, failures: u32, cooldown: Duration) -> Self {
+        self.circuit_breaker = Some((failures, cooldown));
+        self
+    }
+
+    /// Log every Text-to-Dialogue request as a `tracing` event with target
+    /// `elevenlabs_ttd::requests`: method, path, input and character counts, status
+    /// and latency. The API key and the text are never included.
+    pub fn log_requests(mut self, enabled: bool) -> Self {
+        self.log_requests = enabled;
+        self
+    }
+
+    /// Keep latency histograms of successful requests per endpoint and output
+    /// format, see [`ElevenLabsTTDClient::stats`]
+    pub fn track_latency(mut self, enabled: bool) -> Self {
+        self.track_latency = enabled;
+        self
+    }
+
+    /// Include the full text of every request in the [`Self::log_requests`] events.
+    /// Scripts may contain personal data, so only enable this where logs are private.
+    pub fn log_request_text(mut self, enabled: bool) -> Self {
+        self.log_request_text = enabled;
+        if enabled {
+            self.log_requests = true;
+        }
+        self
+    }
+
+    /// Check the frame sync of MP3 responses as they download, failing with
+    /// [`ElevenLabsTTDError::CorruptAudio`] at the first broken or truncated frame
+    pub fn validate_mp3(mut self, enabled: bool) -> Self {
+        self.validate_mp3 = enabled;
+        self
+    }
+
+    /// Build the client
+    pub fn build(self) -> ElevenLabsTTDClient {
+        let mut keys = vec![self.api_key.clone()];
+        for key in self.additional_api_keys {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+
+        let mut http = Client::builder();
+        if let Some(timeout) = self.timeout {
+            http = http.timeout(timeout);
+        }
+
+        ElevenLabsTTDClient {
+            client: http.build().expect("failed to build HTTP client"),
+            api_key: self.api_key,
+            versions: version::ApiVersions::new(
+                &self.base_url,
+                self.api_version,
+                self.endpoint_versions,
+            ),
+            correlation_header: self.correlation_header,
+            idempotency_header: self.idempotency_header,
+            workspace_header: self.workspace_header,
+            workspace_id: self.workspace_id,
+            dedupe: dedupe::DedupeCache::new(self.dedupe_capacity),
+            dedupe_store: self.dedupe_store,
+            sandbox: self.sandbox,
+            usage: self
+                .track_usage
+                .then(|| usage::UsageTracker::new(self.cost_per_thousand_characters)),
+            budget: (!self.budget_thresholds.is_empty())
+                .then(|| budget::BudgetMonitor::new(self.budget_thresholds, self.character_budget)),
+            keys: keys::KeyPool::new(keys),
+            validation_mode: self.validation_mode,
+            validation_warning: self.validation_warning,
+            check_format_tier: self.check_format_tier,
+            text_to_speech_fallback: self.text_to_speech_fallback,
+            timeout: self.timeout,
+            mock_transport: self.mock_transport,
+            audit_sink: self.audit_sink,
+            content_filter: self.content_filter,
+            error_sink: self.error_sink,
+            daily_ceiling: self.daily_character_limit.map(usage::DailyCeiling::new),
+            sanitize_text: self.sanitize_text,
+            split_long_turns: self.split_long_turns,
+            segment_gap: self.segment_gap,
+            detect_anomalies: self.detect_anomalies,
+            validate_mp3: self.validate_mp3,
+            circuit_breaker: self
+                .circuit_breaker
+                .map(|(failures, cooldown)| circuit::CircuitBreaker::new(failures, cooldown)),
+            log_requests: self.log_requests,
+            log_request_text: self.log_request_text,
+            latency: self.track_latency.then(stats::LatencyRecorder::default),
+            cost_per_thousand_characters: self.cost_per_thousand_characters,
+            key_provider: self
+                .key_provider
+                .map(|provider| tenant::CachedKeyProvider::new(provider, self.key_cache_ttl)),
+            tier: Default::default(),
+            lifecycle: Default::default(),
+        }
+    }
+}
+
+/// Headers of one Text-to-Dialogue call, resolved from the client and [`RequestOptions`]
+struct CallHeaders<'a> {
+    api_key: &'a str,
+    correlation_id: String,
+    idempotency_key: Option<&'a str>,
+    workspace_id: Option<&'a str>,
+}
+
+/// Per-request options that are sent alongside, but not inside, the request body
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RequestOptions {
+    pub(crate) correlation_id: Option<String>,
+    pub(crate) idempotency_key: Option<String>,
+    // Send to the single-speaker Text-to-Speech endpoint.
+    pub(crate) text_to_speech: bool,
+    // Bill this key instead of one from the client's key pool.
+    pub(crate) api_key: Option<Secret>,
+    pub(crate) base_url: Option<String>,
+    // Bill the key the client's KeyProvider returns for this tenant.
+    pub(crate) tenant: Option<TenantId>,
+    // Workspace the usage is attributed to, instead of the client's.
+    pub(crate) workspace_id: Option<String>,
+    // Found while building the request, to be attached to the response.
+    pub(crate) warnings: Vec<Warning>,
+}
+
+impl RequestOptions {
+    /// Account and workspace overridden for this request, which responses
+    /// may only be shared within
+    fn scope(&self) -> Option<String> {
+        let account = match (&self.api_key, &self.tenant) {
+            (Some(api_key), _) => Some(format!("api_key:{}", api_key.expose())),
+            (None, Some(tenant)) => Some(format!("tenant:{}", tenant)),
+            (None, None) => None,
+        };
+        match (account, &self.workspace_id) {
+            (account, None) => account,
+            (None, Some(workspace)) => Some(format!("workspace:{}", workspace)),
+            (Some(account), Some(workspace)) => {
+                Some(format!("{}:workspace:{}", account, workspace))
+            }
+        }
+    }
+}
+
+/// Builder for Text-to-Dialogue requests.
+///
+/// Cloning a builder gives an independent request template: set the model, format
+/// and settings once, then clone it per dialogue.
+#[derive(Debug, Clone)]
+pub struct TextToDialogueBuilder {
+    client: ElevenLabsTTDClient,
+    inputs: Vec<TTDInput>,
+    output_format: Option<String>,
+    model_id: Option<String>,
+    settings: Option<TTDSettings>,
+    pronunciation_dictionary_locators: Option<TTDPronunciationDictionaryLocators>,
+    seed: Option<u32>,
+    language_code: Option<String>,
+    use_server_defaults: bool,
+    // Account voices to check the cast against, see `voice_metadata`.
+    voice_metadata: Vec<Voice>,
+    options: RequestOptions,
+}
+
+impl TextToDialogueBuilder {
+    fn new(client: ElevenLabsTTDClient, inputs: Vec<TTDInput>) -> Self {
+        Self {
+            client,
+            inputs,
+            output_format: None,
+            model_id: None,
+            settings: None,
+            pronunciation_dictionary_locators: None,
+            seed: None,
+            language_code: None,
+            use_server_defaults: false,
+            voice_metadata: Vec::new(),
+            options: RequestOptions::default(),
+        }
+    }
+
+    /// Set the output format to use
+    pub fn output_format<S: Into<String>>(mut self, output_format: S) -> Self {
+        self.output_format = Some(output_format.into());
+        self
+    }
+
+    /// Set the model to use
+    pub fn model<S: Into<String>>(mut self, model_id: S) -> Self {
+        self.model_id = Some(model_id.into());
+        self
+    }
+
+    /// Set the settings to use
+    pub fn settings(mut self, settings: TTDSettings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    /// Set stability, keeping any other settings already set
+    pub fn stability(mut self, stability: f32) -> Self {
+        self.settings = Some(self.settings.unwrap_or_default().stability(stability));
+        self
+    }
+
+    /// Enable or disable speaker boost, keeping any other settings already set
+    pub fn speaker_boost(mut self, enabled: bool) -> Self {
+        self.settings = Some(self.settings.unwrap_or_default().speaker_boost(enabled));
+        self
+    }
+
+    /// Set the pronunciation dictionary locators to use
+    pub fn pronunciation_dictionary_locators(
+        mut self,
+        pronunciation_dictionary_locators: TTDPronunciationDictionaryLocators,
+    ) -> Self {
+        self.pronunciation_dictionary_locators = Some(pronunciation_dictionary_locators);
+        self
+    }
+
+    /// Set seeds to use
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Set the language of the text as an ISO 639-1 code, e.g. "fr"
+    pub fn language_code<S: Into<String>>(mut self, language_code: S) -> Self {
+        self.language_code = Some(language_code.into());
+        self
+    }
+
+    /// Don't fill in `mp3_44100_128` / `eleven_v3` when no output format or model
+    /// was set; leave them out and let ElevenLabs apply its current defaults
+    pub fn use_server_defaults(mut self) -> Self {
+        self.use_server_defaults = true;
+        self
+    }
+
+    /// Take every option of `template`, replacing the ones already set
+    pub fn apply_template(mut self, template: TTDRequestTemplate) -> Self {
+        self.output_format = template.output_format;
+        self.model_id = template.model_id;
+        self.settings = template.settings;
+        self.pronunciation_dictionary_locators = template.pronunciation_dictionary_locators;
+        self.seed = template.seed;
+        self.language_code = template.language_code;
+        self.use_server_defaults = template.use_server_defaults;
+        self
+    }
+
+    /// The options set so far, to reuse with other inputs
+    pub fn template(&self) -> TTDRequestTemplate {
+        TTDRequestTemplate {
+            output_format: self.output_format.clone(),
+            model_id: self.model_id.clone(),
+            settings: self.settings.clone(),
+            pronunciation_dictionary_locators: self.pronunciation_dictionary_locators.clone(),
+            seed: self.seed,
+            language_code: self.language_code.clone(),
+            use_server_defaults: self.use_server_defaults,
+        }
+    }
+
+    /// Send this request with another API key, e.g. to bill a customer's own
+    /// ElevenLabs account from a shared client
+    pub fn api_key<S: Into<String>>(mut self, api_key: S) -> Self {
+        self.options.api_key = Some(Secret::new(api_key));
+        self
+    }
+
+    /// Send this request on behalf of a tenant, with the API key the client's
+    /// [`KeyProvider`] returns for it
+    pub fn tenant<T: Into<TenantId>>(mut self, tenant: T) -> Self {
+        self.options.tenant = Some(tenant.into());
+        self
+    }
+
+    /// Scope this request to another workspace than the client's
+    pub fn workspace_id<S: Into<String>>(mut self, workspace_id: S) -> Self {
+        self.options.workspace_id = Some(workspace_id.into());
+        self
+    }
+
+    /// Send this request to another base URL
+    pub fn base_url<S: Into<String>>(mut self, base_url: S) -> Self {
+        self.options.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Use a caller-supplied correlation ID instead of generating one
+    pub fn correlation_id<S: Into<String>>(mut self, correlation_id: S) -> Self {
+        self.options.correlation_id = Some(correlation_id.into());
+        self
+    }
+
+    /// Set an idempotency key, so a retried submission returns the original
+    /// response instead of generating (and billing) the dialogue twice
+    pub fn idempotency_key<S: Into<String>>(mut self, key: S) -> Self {
+        self.options.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Check the cast against these account voices (e.g. from the
+    /// [`VoiceCatalog`]) when the request is built, reporting each turn whose
+    /// voice the model doesn't support as a [`ValidationWarning::UnsupportedVoice`]
+    pub fn voice_metadata<I: IntoIterator<Item = Voice>>(mut self, voices: I) -> Self {
+        self.voice_metadata = voices.into_iter().collect();
+        self
+    }
+
+    /// Turns of the dialogue
+    pub fn inputs(&self) -> &[TTDInput] {
+        &self.inputs
+    }
+
+    /// Turns of the dialogue, to add, edit or drop turns before sending
+    pub fn inputs_mut(&mut self) -> &mut Vec<TTDInput> {
+        &mut self.inputs
+    }
+
+    /// Model set with [`Self::model`]; when unset, [`Self::dry_run`] shows the default
+    pub fn model_id(&self) -> Option<&str> {
+        self.model_id.as_deref()
+    }
+
+    /// Output format set with [`Self::output_format`]; when unset,
+    /// [`Self::dry_run`] shows the default
+    pub fn format(&self) -> Option<&str> {
+        self.output_format.as_deref()
+    }
+
+    /// Settings set with [`Self::settings`] or its shorthands
+    pub fn current_settings(&self) -> Option<&TTDSettings> {
+        self.settings.as_ref()
+    }
+
+    /// The request [`Self::execute`] would send, without sending it, e.g. to
+    /// compare with [`TTDRequest::diff`], and the warnings found building it.
+    /// Nothing is logged or passed to [`ClientBuilder::on_validation_warning`].
+    pub fn dry_run(&self) -> (TTDRequest, Vec<Warning>) {
+        let request = self.build_request();
+        let warnings = self
+            .unsupported_voices(&request)
+            .into_iter()
+            .map(Warning::from)
+            .collect();
+        (request, warnings)
+    }
+
+    /// Execute the Text-to-Dialogue request
+    pub async fn execute(mut self) -> Result<TTDAudio, ElevenLabsTTDError> {
+        let request = self.build_request();
+        self.options.warnings = self.check_voices(&request);
+        self.client.execute_ttd(request, self.options).await
+    }
+
+    /// Render the dialogue `count` times with a different seed each, one take after
+    /// the other, so the best read can be picked and reproduced with [`Self::seed`].
+    /// Seeds count up from the builder's seed if one is set, and are random otherwise.
+    /// Rate-limited takes are tried again under `retry`. A take that fails doesn't
+    /// stop the others, so the result holds one entry per take, in seed order.
+    pub async fn takes(
+        mut self,
+        count: usize,
+        retry: &RetryPolicy,
+    ) -> Vec<Result<Take, ElevenLabsTTDError>> {
+        let request = self.build_request();
+        self.options.warnings = self.check_voices(&request);
+        let first_seed = self
+            .seed
+            .unwrap_or_else(|| uuid::Uuid::new_v4().as_u128() as u32);
+
+        let mut takes = Vec::with_capacity(count);
+        for take in 0..count {
+            let seed = first_seed.wrapping_add(take as u32);
+            let mut options = self.options.clone();
+            // Caller-supplied IDs would otherwise collide across takes
+            for id in [&mut options.correlation_id, &mut options.idempotency_key]
+                .into_iter()
+                .flatten()
+            {
+                id.push_str(&format!("-take{}", take + 1));
+            }
+
+            let started = Instant::now();
+            let mut attempt = 1;
+            let result = loop {
+                let request = TTDRequest {
+                    seed: Some(seed),
+                    ..request.clone()
+                };
+                let error = match self
+                    .client
+                    .generate_in_format(request, options.clone())
+                    .await
+                {
+                    Ok(audio) => break Ok(Take { seed, audio }),
+                    Err(e @ ElevenLabsTTDError::RateLimitError { .. }) => e,
+                    Err(e) => {
+                        self.client
+                            .report_error(&e, "text_to_dialogue", None, attempt);
+                        break Err(e);
+                    }
+                };
+                let delay = error
+                    .retry_after()
+                    .map(Duration::from_secs)
+                    .unwrap_or_else(|| retry.delay(attempt));
+                if !retry.should_retry(attempt, started.elapsed(), delay) {
+                    self.client
+                        .report_error(&error, "text_to_dialogue", None, attempt);
+                    break Err(error);
+                }
+                tracing::warn!(take = take + 1, attempt, %error, "take rate limited");
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            };
+            takes.push(result);
+        }
+        takes
+    }
+
+    /// Report turns whose voice the model doesn't support, returning them as
+    /// warnings for the response
+    fn check_voices(&self, request: &TTDRequest) -> Vec<Warning> {
+        self.unsupported_voices(request)
+            .into_iter()
+            .map(|warning| {
+                tracing::warn!(%warning, "voice not supported by the model");
+                self.client.notify_validation_warning(&warning);
+                warning.into()
+            })
+            .collect()
+    }
+
+    /// Turns whose voice, per [`Self::voice_metadata`], the request's model
+    /// (eleven_v3 when left to the server) doesn't support
+    fn unsupported_voices(&self, request: &TTDRequest) -> Vec<ValidationWarning> {
+        let model_id = request
+            .model_id
+            .as_deref()
+            .unwrap_or(models::elevanlabs_models::ELEVEN_V3);
+        request
+            .inputs
+            .iter()
+            .enumerate()
+            .filter(|(_, input)| {
+                self.voice_metadata.iter().any(|voice| {
+                    voice.voice_id == input.voice_id && !voice.supports_model(model_id)
+                })
+            })
+            .map(|(index, input)| ValidationWarning::UnsupportedVoice {
+                index,
+                voice_id: input.voice_id.clone(),
+                model_id: model_id.to_string(),
+            })
+            .collect()
+    }
+
+    fn build_request(&self) -> TTDRequest {
+        self.template().with_inputs(self.inputs.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_inputs() -> Vec<TTDInput> {
+        vec![TTDInput {
+            text: "Hello there.".to_string(),
+            voice_id: voices::all_voices::RACHEL.voice_id.to_string(),
+        }]
+    }
+
+    #[tokio::test]
+    async fn test_client_creation() {
+        let client = ElevenLabsTTDClient::new("test-key");
+        assert_eq!(client.api_key, "test-key");
+    }
+
+    #[test]
+    fn test_builder_pattern() {
+        let client = ElevenLabsTTDClient::new("test-key");
+        let builder = client.text_to_dialogue([]).model("model-456");
+
+        // Builder pattern works
+        assert!(builder.inputs().is_empty());
+        assert_eq!(builder.model_id(), Some("model-456"));
+        assert_eq!(builder.format(), None);
+        assert!(builder.current_settings().is_none());
+
+        let mut builder = builder.output_format(OutputFormat::Pcm16000).stability(1.0);
+        builder.inputs_mut().extend(sample_inputs());
+        assert_eq!(builder.inputs().len(), 1);
+        assert_eq!(builder.format(), Some("pcm_16000"));
+        assert_eq!(
+            builder.current_settings().and_then(|s| s.stability),
+            Some(1.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_template_from_config() {
+        let template: TTDRequestTemplate = serde_json::from_str(
+            r#"{"output_format": "pcm_16000", "settings": {"stability": 1.0}, "seed": 7}"#,
+        )
+        .unwrap();
+        let request = template.with_inputs(sample_inputs());
+        assert_eq!(request.model_id.as_deref(), Some("eleven_v3"));
+        assert_eq!(request.seed, Some(7));
+
+        let client = ElevenLabsTTDClient::sandbox();
+        let builder = client
+            .text_to_dialogue(sample_inputs())
+            .apply_template(template.clone());
+        assert!(builder.dry_run().0.diff(&request).is_empty());
+        assert_eq!(builder.template().seed, Some(7));
+
+        let audio = request.execute(&client).await.unwrap();
+        assert_eq!(audio.output_format, Some(OutputFormat::Pcm16000));
+        let manifest = audio.manifest.unwrap();
+        assert_eq!(manifest.seed, Some(7));
+
+        let unset = TTDRequestTemplate::new()
+            .use_server_defaults()
+            .with_inputs(sample_inputs());
+        assert!(unset.model_id.is_none() && unset.output_format.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_refuses_new_requests_and_flushes_store() {
+        let dir = std::env::temp_dir().join(format!("ttd-shutdown-{}", uuid::Uuid::new_v4()));
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .sandbox(true)
+            .dedupe_store(DedupeStore::open(&dir).unwrap())
+            .build();
+        let clone = client.clone();
+        client
+            .text_to_dialogue(sample_inputs())
+            .execute()
+            .await
+            .unwrap();
+
+        let report = client.shutdown(Duration::from_secs(1)).await.unwrap();
+        assert!(report.is_clean());
+        assert!(clone.is_shut_down());
+        assert!(dir.join("index.json").exists());
+
+        let error = clone
+            .text_to_dialogue(sample_inputs())
+            .execute()
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ElevenLabsTTDError::ShuttingDown));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_builder_template_clone_and_debug() {
+        let client = ElevenLabsTTDClient::new("client-secret-key");
+        let template = client
+            .text_to_dialogue([])
+            .model("eleven_v3")
+            .output_format(OutputFormat::Pcm16000)
+            .api_key("override-secret-key");
+
+        let mut first = template.clone();
+        first.inputs_mut().extend(sample_inputs());
+        let second = template.clone().stability(1.0);
+        assert_eq!(first.inputs().len(), 1);
+        assert!(template.inputs().is_empty());
+        assert_eq!(second.format(), Some("pcm_16000"));
+        assert!(template.current_settings().is_none());
+
+        let debug = format!("{:?}", first);
+        assert!(debug.contains("TextToDialogueBuilder"));
+        assert!(debug.contains("Hello there."));
+        assert!(!debug.contains("client-secret-key"));
+        assert!(!debug.contains("override-secret-key"));
+    }
+
+    #[test]
+    fn test_settings_shorthands_merge() {
+        let client = ElevenLabsTTDClient::new("test-key");
+        let request = client
+            .text_to_dialogue(sample_inputs())
+            .speaker_boost(false)
+            .stability(1.0)
+            .build_request();
+
+        let settings = request.settings.unwrap();
+        assert_eq!(settings.stability, Some(1.0));
+        assert_eq!(settings.use_speaker_boost, Some(false));
+    }
+
+    #[test]
+    fn test_dry_run_diff_lists_changed_fields() {
+        let client = ElevenLabsTTDClient::new("test-key");
+        let (local, _) = client.text_to_dialogue(sample_inputs()).seed(7).dry_run();
+        assert!(local.diff(&local.clone()).is_empty());
+
+        let mut inputs = sample_inputs();
+        inputs[0].text.push('\n');
+        inputs.push(TTDInput {
+            text: "Hi.".to_string(),
+            voice_id: "voice-b".to_string(),
+        });
+        let (ci, _) = client
+            .text_to_dialogue(inputs)
+            .stability(1.0)
+            .use_server_defaults()
+            .dry_run();
+
+        let diff: Vec<String> = local.diff(&ci).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            diff,
+            [
+                "inputs[0].text: \"Hello there.\" -> \"Hello there.\\n\"",
+                "inputs[1]: (unset) -> {\"text\":\"Hi.\",\"voice_id\":\"voice-b\"}",
+                "model_id: \"eleven_v3\" -> (unset)",
+                "output_format: \"mp3_44100_128\" -> (unset)",
+                "seed: 7 -> (unset)",
+                "settings: (unset) -> {\"stability\":1.0,\"use_speaker_boost\":true}",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_canonical_hash_ignores_formatting() {
+        let client = ElevenLabsTTDClient::new("test-key");
+        let (request, _) = client.text_to_dialogue(sample_inputs()).dry_run();
+
+        let mut reformatted = request.clone();
+        reformatted.inputs[0].text = "  Hello   there. \r\n".to_string();
+        assert_eq!(reformatted.canonical_hash(), request.canonical_hash());
+        assert_eq!(request.canonical_hash().len(), 64);
+
+        let mut changed = request.clone();
+        changed.inputs[0].text = "Hello\nthere.".to_string();
+        assert_ne!(changed.canonical_hash(), request.canonical_hash());
+        let mut seeded = request.clone();
+        seeded.seed = Some(1);
+        assert_ne!(seeded.canonical_hash(), request.canonical_hash());
+    }
+
+    #[test]
+    fn test_server_defaults_omit_format_and_model() {
+        let client = ElevenLabsTTDClient::new("test-key");
+
+        let request = client.text_to_dialogue(sample_inputs()).build_request();
+        assert_eq!(request.output_format.as_deref(), Some("mp3_44100_128"));
+        assert_eq!(request.model_id.as_deref(), Some("eleven_v3"));
+
+        let request = client
+            .text_to_dialogue(sample_inputs())
+            .use_server_defaults()
+            .build_request();
+        assert!(request.output_format.is_none());
+        let body = serde_json::to_value(&request).unwrap();
+        assert!(body.get("model_id").is_none());
+        assert!(body.get("output_format").is_none());
+    }
+
+    #[test]
+    fn test_client_builder_correlation_header() {
+        let client = ElevenLabsTTDClient::new("test-key");
+        assert_eq!(client.correlation_header, DEFAULT_CORRELATION_HEADER);
+
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .correlation_header("x-request-trace")
+            .build();
+        assert_eq!(client.correlation_header, "x-request-trace");
+        assert_eq!(client.versions.base_url(), "https://api.elevenlabs.io/v1");
+    }
+
+    #[test]
+    fn test_builder_correlation_id() {
+        let client = ElevenLabsTTDClient::new("test-key");
+        let builder = client.text_to_dialogue([]).correlation_id("job-42");
+        assert_eq!(builder.options.correlation_id, Some("job-42".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_returns_deduplicated_response() {
+        // Unroutable base URL: any real network call would fail
+        let client = ElevenLabsTTDClient::with_base_url("test-key", "http://127.0.0.1:9");
+        client.dedupe.insert(
+            "order-7".to_string(),
+            TTDAudio::new(b"cached".to_vec(), "first-call".to_string()),
+        );
+
+        let audio = client
+            .clone()
+            .text_to_dialogue(sample_inputs())
+            .idempotency_key("order-7")
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(audio.audio, b"cached");
+        assert_eq!(audio.correlation_id, "first-call");
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_scope_is_hashed() {
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .mock_transport(|_| Ok(MockResponse::audio(b"audio".to_vec())))
+            .build();
+
+        client
+            .text_to_dialogue(sample_inputs())
+            .api_key("other-key")
+            .idempotency_key("order-7")
+            .execute()
+            .await
+            .unwrap();
+
+        let scoped = dedupe::scoped_hash("order-7".to_string(), Some("api_key:other-key"));
+        assert!(client.dedupe.get(&scoped).is_some());
+        assert!(client.dedupe.get("order-7").is_none());
+        assert!(client.dedupe.get("api_key:other-key:order-7").is_none());
+    }
+
+    /// Serve `count` requests with empty audio, returning what each request sent
+    async fn capture_requests(count: usize) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for _ in 0..count {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 16 * 1024];
+                let read = socket.read(&mut buf).await.unwrap();
+                requests.push(String::from_utf8_lossy(&buf[..read]).to_lowercase());
+                socket
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\n[]",
+                    )
+                    .await
+                    .unwrap();
+            }
+            requests
+        });
+        (format!("http://{}", addr), handle)
+    }
+
+    #[tokio::test]
+    async fn test_workspace_header_with_per_request_override() {
+        let (base_url, requests) = capture_requests(3).await;
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .base_url(base_url)
+            .workspace_id("ws-client")
+            .build();
+
+        client
+            .text_to_dialogue(sample_inputs())
+            .execute()
+            .await
+            .unwrap();
+        client
+            .text_to_dialogue(sample_inputs())
+            .workspace_id("ws-request")
+            .execute()
+            .await
+            .unwrap();
+        client.request(Method::GET, "/models").send().await.unwrap();
+
+        let requests = requests.await.unwrap();
+        assert!(requests[0].contains("xi-workspace-id: ws-client"));
+        assert!(requests[1].contains("xi-workspace-id: ws-request"));
+        assert!(!requests[1].contains("ws-client"));
+        assert!(requests[2].contains("xi-workspace-id: ws-client"));
+    }
+
+    #[tokio::test]
+    async fn test_sound_effect_uses_request_workspace() {
+        let (base_url, requests) = capture_requests(1).await;
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .base_url(base_url)
+            .workspace_id("ws-client")
+            .build();
+        let cue = SoundCue {
+            description: "door slams".to_string(),
+            duration: None,
+        };
+        let options = RequestOptions {
+            workspace_id: Some("ws-request".to_string()),
+            ..RequestOptions::default()
+        };
+
+        client
+            .sound_effect_with(&cue, OutputFormat::Pcm16000, &options)
+            .await
+            .unwrap();
+
+        let requests = requests.await.unwrap();
+        assert!(requests[0].contains("xi-workspace-id: ws-request"));
+        assert!(!requests[0].contains("ws-client"));
+    }
+
+    #[tokio::test]
+    async fn test_responses_are_scoped_to_workspace() {
+        let dir = std::env::temp_dir().join(format!("ttd-workspace-{}", uuid::Uuid::new_v4()));
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = calls.clone();
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .dedupe_store(DedupeStore::open(&dir).unwrap())
+            .mock_transport(move |_| {
+                let call = counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Ok(MockResponse::audio(format!("audio-{}", call).into_bytes()))
+            })
+            .build();
+        let generate = |workspace: &str, key: Option<&str>| {
+            let mut builder = client
+                .text_to_dialogue(sample_inputs())
+                .workspace_id(workspace);
+            if let Some(key) = key {
+                builder = builder.idempotency_key(key);
+            }
+            builder.execute()
+        };
+
+        // Neither the idempotency key nor the stored response crosses workspaces
+        assert_eq!(
+            generate("ws-a", Some("k")).await.unwrap().as_ref(),
+            b"audio-0"
+        );
+        assert_eq!(
+            generate("ws-b", Some("k")).await.unwrap().as_ref(),
+            b"audio-1"
+        );
+        assert_eq!(generate("ws-c", None).await.unwrap().as_ref(), b"audio-2");
+        assert_eq!(
+            generate("ws-a", Some("k")).await.unwrap().as_ref(),
+            b"audio-0"
+        );
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stored_responses_are_scoped_to_text_to_speech_fallback() {
+        let dir = std::env::temp_dir().join(format!("ttd-fallback-{}", uuid::Uuid::new_v4()));
+        let store = DedupeStore::open(&dir).unwrap();
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let client = |fallback: bool| {
+            let counter = calls.clone();
+            ElevenLabsTTDClient::builder("test-key")
+                .dedupe_store(store.clone())
+                .text_to_speech_fallback(fallback)
+                .mock_transport(move |_| {
+                    let call = counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    Ok(MockResponse::audio(format!("audio-{}", call).into_bytes()))
+                })
+                .build()
+        };
+
+        // The single-speaker request goes to Text-to-Speech only with the fallback
+        let dialogue = client(false).text_to_dialogue(sample_inputs()).execute();
+        assert_eq!(dialogue.await.unwrap().as_ref(), b"audio-0");
+        let speech = client(true).text_to_dialogue(sample_inputs()).execute();
+        assert_eq!(speech.await.unwrap().as_ref(), b"audio-1");
+        let again = client(true).text_to_dialogue(sample_inputs()).execute();
+        assert_eq!(again.await.unwrap().as_ref(), b"audio-1");
+        assert_eq!(store.len(), 2);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_download_keeps_received_bytes() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Promises 100 bytes, sends 10, then hangs up
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 4096];
+            let _ = socket.read(&mut request).await;
+            let _ = socket
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 100\r\n\r\n0123456789")
+                .await;
+        });
+
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .base_url(format!("http://{}", addr))
+            .build();
+        let error = client
+            .text_to_dialogue(sample_inputs())
+            .execute()
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), "stream_interrupted");
+        assert_eq!(error.partial_audio(), Some(&b"0123456789"[..]));
+
+        let mock = ElevenLabsTTDClient::builder("test-key")
+            .mock_transport(|_| Ok(MockResponse::audio(b"audio".to_vec()).interrupt_after(2)))
+            .build();
+        let error = mock
+            .text_to_dialogue(sample_inputs())
+            .execute()
+            .await
+            .unwrap_err();
+        assert_eq!(error.partial_audio(), Some(&b"au"[..]));
+    }
+
+    #[tokio::test]
+    async fn test_slow_response_maps_to_timeout() {
+        // Accepts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                held.push(socket);
+            }
+        });
+
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .base_url(format!("http://{}", addr))
+            .timeout(Duration::from_millis(100))
+            .build();
+        let error = client
+            .text_to_dialogue(sample_inputs())
+            .execute()
+            .await
+            .unwrap_err();
+
+        match error {
+            ElevenLabsTTDError::Timeout { elapsed } => {
+                assert!(elapsed >= Duration::from_millis(100))
+            }
+            other => panic!("expected timeout, got {:?}", other),
+        }
+    }
+
+    /// Serve a single canned HTTP response on a local port and return its base URL
+    async fn serve_once(status: &str, headers: &[(&str, &str)], body: &[u8]) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let mut response = format!("HTTP/1.1 {}\r\n", status).into_bytes();
+        for (name, value) in headers {
+            response.extend_from_slice(format!("{}: {}\r\n", name, value).as_bytes());
+        }
+        response.extend_from_slice(
+            format!(
+                "content-length: {}\r\nconnection: close\r\n\r\n",
+                body.len()
+            )
+            .as_bytes(),
+        );
+        response.extend_from_slice(body);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 16 * 1024];
+            let _ = socket.read(&mut buf).await;
+            socket.write_all(&response).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_api_error_keeps_raw_response() {
+        let base_url = serve_once(
+            "502 Bad Gateway",
+            &[
+                ("content-type", "text/html"),
+                ("x-proxy", "edge-3"),
+                ("request-id", "req-9"),
+            ],
+            b"<h1>upstream</h1>\n",
+        )
+        .await;
+
+        let client = ElevenLabsTTDClient::with_base_url("test-key".to_string(), base_url);
+        let error = client
+            .text_to_dialogue(sample_inputs())
+            .execute()
+            .await
+            .unwrap_err();
+
+        let raw = error.raw_response().unwrap();
+        assert_eq!(raw.body, b"<h1>upstream</h1>\n");
+        assert_eq!(raw.headers["x-proxy"], "edge-3");
+        assert_eq!(error.status(), Some(502));
+        assert!(error.correlation_id().is_some());
+
+        assert_eq!(error.endpoint(), Some("/text-to-dialogue"));
+        assert_eq!(error.request_id(), Some("req-9"));
+        let message = error.to_string();
+        assert!(message.starts_with(
+            "Server error (502) [endpoint: /text-to-dialogue, request id: req-9, correlation id: "
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_dedicated_errors_keep_context() {
+        let cases = [
+            (
+                "401 Unauthorized",
+                "authentication_error",
+                "Authentication failed",
+            ),
+            (
+                "402 Payment Required",
+                "quota_exceeded_error",
+                "Quota exceeded",
+            ),
+            (
+                "429 Too Many Requests",
+                "rate_limit_error",
+                "Rate limit exceeded (retry in 3s)",
+            ),
+        ];
+        for (status, kind, display) in cases {
+            let code = &status[..3];
+            let request_id = format!("req-{}", code);
+            let base_url = serve_once(
+                status,
+                &[("request-id", &request_id), ("retry-after", "3")],
+                b"refused",
+            )
+            .await;
+
+            let client = ElevenLabsTTDClient::with_base_url("test-key".to_string(), base_url);
+            let error = client
+                .text_to_dialogue(sample_inputs())
+                .execute()
+                .await
+                .unwrap_err();
+
+            assert_eq!(error.kind(), kind);
+            assert_eq!(error.status(), code.parse().ok());
+            assert_eq!(error.raw_response().unwrap().body, b"refused");
+            assert_eq!(error.endpoint(), Some("/text-to-dialogue"));
+            assert_eq!(error.request_id(), Some(request_id.as_str()));
+            assert!(error.correlation_id().is_some());
+            assert!(error.to_string().starts_with(&format!(
+                "{} [endpoint: /text-to-dialogue, request id: {}, correlation id: ",
+                display, request_id
+            )));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_response_exposes_rate_limit() {
+        let base_url = serve_once(
+            "200 OK",
+            &[
+                ("content-type", "audio/mpeg"),
+                ("current-concurrent-requests", "1"),
+                ("maximum-concurrent-requests", "4"),
+            ],
+            b"audio",
+        )
+        .await;
+
+        let client = ElevenLabsTTDClient::with_base_url("test-key".to_string(), base_url);
+        let audio = client
+            .text_to_dialogue(sample_inputs())
+            .execute()
+            .await
+            .unwrap();
+
+        let rate_limit = audio.rate_limit.unwrap();
+        assert_eq!(rate_limit.maximum_concurrent_requests, Some(4));
+        assert_eq!(rate_limit.available_concurrency(), Some(3));
+        assert_eq!(audio.headers["content-type"], "audio/mpeg");
+    }
+
+    #[tokio::test]
+    async fn test_format_tier_check_rejects_locally() {
+        let base_url = serve_once(
+            "200 OK",
+            &[("content-type", "application/json")],
+            br#"{"tier":"free","character_count":0,"character_limit":10000}"#,
+        )
+        .await;
+
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .base_url(base_url)
+            .check_format_tier(true)
+            .build();
+        let error = client
+            .text_to_dialogue(sample_inputs())
+            .output_format(OutputFormat::Pcm44100)
+            .execute()
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, ElevenLabsTTDError::ValidationError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_lenient_validation_reports_fixes() {
+        let warnings = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = warnings.clone();
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .sandbox(true)
+            .validation_mode(ValidationMode::Lenient)
+            .on_validation_warning(move |w| sink.lock().unwrap().push(w.clone()))
+            .build();
+
+        let mut inputs = sample_inputs();
+        inputs.push(TTDInput {
+            text: "   ".to_string(),
+            voice_id: voices::all_voices::ADAM.voice_id.to_string(),
+        });
+        client.text_to_dialogue(inputs).execute().await.unwrap();
+
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            vec![ValidationWarning::EmptyTurnRemoved { index: 1 }]
+        );
+
+        let strict = ElevenLabsTTDClient::sandbox();
+        let error = strict.text_to_dialogue([]).execute().await.unwrap_err();
+        assert!(matches!(error, ElevenLabsTTDError::ValidationError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_takes_record_distinct_seeds() {
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = calls.clone();
+        // The second request is rate limited once
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .mock_transport(move |request| {
+                match counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+                    1 => Ok(MockResponse::error(429, "slow down").header("retry-after", "0")),
+                    _ => Ok(MockResponse::audio(
+                        request.seed.unwrap().to_le_bytes().to_vec(),
+                    )),
+                }
+            })
+            .build();
+
+        let takes = client
+            .text_to_dialogue(sample_inputs())
+            .seed(5)
+            .correlation_id("scene-1")
+            .takes(3, &RetryPolicy::default())
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let seeds: Vec<_> = takes.iter().map(|take| take.seed).collect();
+        assert_eq!(seeds, [5, 6, 7]);
+        assert_eq!(takes[1].audio.audio, 6u32.to_le_bytes());
+        assert_eq!(takes[2].audio.correlation_id, "scene-1-take3");
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_takes_report_once_after_retries() {
+        let reports = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .error_sink(move |error: &ElevenLabsTTDError, context: &ErrorContext| {
+                sink.lock().unwrap().push((error.kind(), context.attempts))
+            })
+            .mock_transport(
+                |_| Ok(MockResponse::error(429, "slow down").header("retry-after", "0")),
+            )
+            .build();
+
+        let takes = client
+            .text_to_dialogue(sample_inputs())
+            .takes(2, &RetryPolicy::default().max_attempts(2))
+            .await;
+        assert_eq!(takes.len(), 2);
+        assert!(
+            takes
+                .iter()
+                .all(|take| take.as_ref().unwrap_err().kind() == "rate_limit_error")
+        );
+        assert_eq!(
+            *reports.lock().unwrap(),
+            [("rate_limit_error", 2), ("rate_limit_error", 2)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_takes_keep_completed_takes_after_a_failure() {
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = calls.clone();
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .mock_transport(move |request| {
+                match counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+                    1 => Ok(MockResponse::error(500, "boom")),
+                    _ => Ok(MockResponse::audio(
+                        request.seed.unwrap().to_le_bytes().to_vec(),
+                    )),
+                }
+            })
+            .build();
+
+        let takes = client
+            .text_to_dialogue(sample_inputs())
+            .seed(1)
+            .takes(3, &RetryPolicy::default())
+            .await;
+        assert_eq!(takes[0].as_ref().unwrap().seed, 1);
+        assert!(takes[1].is_err());
+        assert_eq!(takes[2].as_ref().unwrap().seed, 3);
     }
 
     #[test]
-    fn test_builder_pattern() {
+    fn test_static_voice_serde_resolves_catalog() {
+        let rachel = &voices::all_voices::RACHEL;
+        let json = serde_json::to_value(rachel).unwrap();
+        assert_eq!(json["name"], rachel.name);
+
+        let entry: &'static StaticVoice = serde_json::from_value(json.clone()).unwrap();
+        assert!(std::ptr::eq(entry, rachel));
+        let by_id: &'static StaticVoice =
+            serde_json::from_value(serde_json::json!(rachel.voice_id)).unwrap();
+        assert_eq!(by_id, rachel);
+        assert!(serde_json::from_str::<&'static StaticVoice>(r#""not-a-voice""#).is_err());
+
+        // Owned data deserializes into Voice
+        let owned: Voice = serde_json::from_value(json).unwrap();
+        assert_eq!(owned.voice_id, rachel.voice_id);
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_wav_rate_is_rejected_before_sending() {
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .mock_transport(|_| panic!("nothing should be sent"))
+            .build();
+        let wav_11025 = OutputFormat::Wav { sample_rate: 11025 };
+
+        let error = client
+            .text_to_dialogue(sample_inputs())
+            .output_format(wav_11025)
+            .execute()
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), "validation_error");
+
+        let dialogue = Dialogue::new().line("voice-a", "Hi.");
+        let error = client
+            .render_dialogue(&dialogue, wav_11025)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), "validation_error");
+
+        let cue = SoundCue {
+            description: "door".to_string(),
+            duration: None,
+        };
+        assert!(client.sound_effect(&cue, wav_11025).await.is_err());
+    }
+
+    #[test]
+    fn test_display_from_str_round_trip() {
+        for format in OutputFormat::ALL.into_iter().chain(OutputFormat::WAV) {
+            assert_eq!(format.to_string().parse::<OutputFormat>().unwrap(), format);
+            assert!(!format.api_format().is_wav());
+        }
+        assert!("mp3_1_2".parse::<OutputFormat>().is_err());
+        let wav_11025 = OutputFormat::Wav { sample_rate: 11025 };
+        assert!(wav_11025.check_supported().is_err());
+        assert!(
+            OutputFormat::Wav { sample_rate: 16000 }
+                .check_supported()
+                .is_ok()
+        );
+        let unsupported = "wav_11025".parse::<OutputFormat>().unwrap_err();
+        assert_eq!(
+            unsupported.to_string(),
+            "Validation error: No PCM format at 11025 Hz to request WAV as"
+        );
+
+        for stability in [Stability::Creative, Stability::Natural, Stability::Robust] {
+            assert_eq!(
+                stability.to_string().parse::<Stability>().unwrap(),
+                stability
+            );
+        }
+        assert_eq!("1.0".parse::<Stability>().unwrap(), Stability::Robust);
+        assert_eq!(
+            " Natural ".parse::<Stability>().unwrap(),
+            Stability::Natural
+        );
+        assert_eq!(f32::from(Stability::Creative), 0.0);
+        assert!("0.7".parse::<Stability>().is_err());
+
+        let model: ModelId = "eleven_v3".parse().unwrap();
+        assert_eq!(model, ModelId::default());
+        assert_eq!(model.to_string(), "eleven_v3");
+        let voice: VoiceId = voices::all_voices::RACHEL.voice_id.parse().unwrap();
+        assert_eq!(voice.to_string(), voices::all_voices::RACHEL.voice_id);
+        assert!(" ".parse::<VoiceId>().is_err());
+    }
+
+    #[test]
+    fn test_voice_ref_conversions() {
+        let rachel = &voices::all_voices::RACHEL;
+        let owned = Voice::from(rachel);
+        assert_eq!(owned.gender(), rachel.gender);
+        assert_eq!(owned.display_name(), rachel.name);
+        let back: &'static StaticVoice = (&owned).try_into().unwrap();
+        assert!(std::ptr::eq(back, rachel));
+
+        let id = VoiceId(rachel.voice_id.to_string());
+        assert_eq!(id.display_name(), rachel.name);
+        assert_eq!("custom-voice".display_name(), "custom-voice");
+        for input in [
+            TTDInput::new(rachel, "Hi"),
+            TTDInput::new(&owned, "Hi"),
+            TTDInput::new(&id, "Hi"),
+            TTDInput::new(rachel.voice_id, "Hi"),
+        ] {
+            assert_eq!(input.voice_id, rachel.voice_id);
+        }
+
+        let custom = Voice {
+            voice_id: "custom-voice".to_string(),
+            ..owned
+        };
+        assert!(<&'static StaticVoice>::try_from(&custom).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_voice_warns_at_build() {
+        let warnings = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = warnings.clone();
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .sandbox(true)
+            .on_validation_warning(move |w| sink.lock().unwrap().push(w.clone()))
+            .build();
+        let professional: Voice = serde_json::from_value(serde_json::json!({
+            "voice_id": voices::all_voices::RACHEL.voice_id,
+            "name": "Rachel (PVC)",
+            "category": "professional",
+            "high_quality_base_model_ids": ["eleven_multilingual_v2"],
+        }))
+        .unwrap();
+        assert_eq!(
+            professional.supported_models(),
+            Some(&["eleven_multilingual_v2".to_string()][..])
+        );
+
+        let builder = client
+            .text_to_dialogue(sample_inputs())
+            .voice_metadata([professional]);
+        let unsupported = ValidationWarning::UnsupportedVoice {
+            index: 0,
+            voice_id: voices::all_voices::RACHEL.voice_id.to_string(),
+            model_id: "eleven_v3".to_string(),
+        };
+        // A dry run returns the warnings without reporting them
+        let (_, found) = builder.dry_run();
+        assert_eq!(found, vec![Warning::from(unsupported.clone())]);
+        assert!(warnings.lock().unwrap().is_empty());
+
+        let audio = builder.clone().execute().await.unwrap();
+        assert_eq!(audio.warnings, found);
+        assert_eq!(*warnings.lock().unwrap(), vec![unsupported]);
+
+        let (_, found) = builder.model("eleven_multilingual_v2").dry_run();
+        assert!(found.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_raw_request_returns_json() {
+        let base_url = serve_once(
+            "200 OK",
+            &[("content-type", "application/json")],
+            br#"[{"model_id":"eleven_v3"}]"#,
+        )
+        .await;
+
+        let client = ElevenLabsTTDClient::with_base_url("test-key".to_string(), base_url);
+        let models: serde_json::Value = client
+            .request(Method::GET, "/models")
+            .query(&[("page_size", "10")])
+            .send_json()
+            .await
+            .unwrap();
+
+        assert_eq!(models[0]["model_id"], "eleven_v3");
+    }
+
+    #[tokio::test]
+    async fn test_raw_request_keeps_key_on_api_origin() {
+        let base_url = serve_once("200 OK", &[("content-type", "application/json")], b"[]").await;
+
+        let client = ElevenLabsTTDClient::with_base_url("test-key".to_string(), base_url.clone());
+        let error = client
+            .request(Method::GET, "https://attacker.example/x")
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), "validation_error");
+
+        let models: serde_json::Value = client
+            .request(Method::GET, &format!("{}/v1/models", base_url))
+            .send_json()
+            .await
+            .unwrap();
+        assert_eq!(models, serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_execute_endpoint_parses_response() {
+        struct CreateThing {
+            name: String,
+        }
+
+        impl Endpoint for CreateThing {
+            type Response = serde_json::Value;
+
+            fn method(&self) -> Method {
+                Method::POST
+            }
+
+            fn path(&self) -> String {
+                "/things".to_string()
+            }
+
+            fn body(&self) -> Option<serde_json::Value> {
+                Some(serde_json::json!({ "name": self.name }))
+            }
+        }
+
+        let base_url = serve_once("200 OK", &[], br#"{"id":"thing-1"}"#).await;
+        let client = ElevenLabsTTDClient::with_base_url("test-key".to_string(), base_url);
+        let created = client
+            .execute_endpoint(&CreateThing {
+                name: "narrator".to_string(),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(created["id"], "thing-1");
+    }
+
+    #[tokio::test]
+    async fn test_history_list_reads_page() {
+        let base_url = serve_once(
+            "200 OK",
+            &[("content-type", "application/json")],
+            br#"{"history":[{"history_item_id":"h1","date_unix":1700000000}],"last_history_item_id":"h1","has_more":false}"#,
+        )
+        .await;
+
+        let client = ElevenLabsTTDClient::with_base_url("test-key".to_string(), base_url);
+        let items = client.history().list().collect_all().await.unwrap();
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].history_item_id, "h1");
+    }
+
+    #[tokio::test]
+    async fn test_voice_catalog_caches_voices() {
+        let base_url = serve_once(
+            "200 OK",
+            &[("content-type", "application/json")],
+            br#"{"voices":[{"voice_id":"v1","name":"Narrator"}],"has_more":false}"#,
+        )
+        .await;
+
+        let client = ElevenLabsTTDClient::with_base_url("test-key".to_string(), base_url);
+        let catalog = client.voice_catalog();
+
+        let voice = catalog.find_by_name("narrator").await.unwrap().unwrap();
+        assert_eq!(voice.voice_id, "v1");
+        // The server only answers once, so this must come from the cache
+        assert!(catalog.find_by_id("v1").await.unwrap().is_some());
+        assert_eq!(catalog.search("narr").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_create_voice_returns_id() {
+        let base_url = serve_once(
+            "200 OK",
+            &[("content-type", "application/json")],
+            br#"{"voice_id":"cloned-1","requires_verification":false}"#,
+        )
+        .await;
+
+        let client = ElevenLabsTTDClient::with_base_url("test-key".to_string(), base_url);
+        let voice_id = client
+            .voices()
+            .create(
+                "Captain",
+                vec![VoiceSample::new("sample.mp3", b"audio".to_vec())],
+                Default::default(),
+            )
+            .await
+            .unwrap();
+
+        let input = TTDInput {
+            text: "Aye".to_string(),
+            voice_id: voice_id.into(),
+        };
+        assert_eq!(input.voice_id, "cloned-1");
+    }
+
+    #[tokio::test]
+    async fn test_delete_voice_surfaces_api_error() {
+        let base_url = serve_once(
+            "404 Not Found",
+            &[("content-type", "application/json")],
+            br#"{"detail":{"status":"voice_not_found","message":"No voice"}}"#,
+        )
+        .await;
+
+        let client = ElevenLabsTTDClient::with_base_url("test-key".to_string(), base_url);
+        let error = client.voices().delete("missing").await.unwrap_err();
+
+        assert_eq!(error.status(), Some(404));
+        assert_eq!(error.code().as_deref(), Some("voice_not_found"));
+    }
+
+    #[tokio::test]
+    async fn test_settings_from_api_defaults() {
+        let base_url = serve_once(
+            "200 OK",
+            &[("content-type", "application/json")],
+            br#"{"stability":1.0,"similarity_boost":0.75,"style":0.0,"use_speaker_boost":false,"speed":1.0}"#,
+        )
+        .await;
+
+        let client = ElevenLabsTTDClient::with_base_url("test-key".to_string(), base_url);
+        let settings = TTDSettings::from_api_defaults(&client).await.unwrap();
+
+        assert_eq!(settings.stability, Some(1.0));
+        assert_eq!(settings.use_speaker_boost, Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_design_returns_decodable_previews() {
+        let base_url = serve_once(
+            "200 OK",
+            &[("content-type", "application/json")],
+            br#"{"previews":[{"generated_voice_id":"gen-1","audio_base_64":"YXVkaW8=","media_type":"audio/mpeg","duration_secs":1.5}],"text":"Hello"}"#,
+        )
+        .await;
+
+        let client = ElevenLabsTTDClient::with_base_url("test-key".to_string(), base_url);
+        let previews = client
+            .voices()
+            .design("A gravelly old sea captain", None)
+            .await
+            .unwrap();
+
+        assert_eq!(previews[0].generated_voice_id, "gen-1");
+        assert_eq!(previews[0].audio().unwrap(), b"audio");
+    }
+
+    #[tokio::test]
+    async fn test_render_dialogue_interleaves_sfx() {
+        let client = ElevenLabsTTDClient::sandbox();
+        let dialogue = Dialogue::new()
+            .line("voice-a", "Who's there?")
+            .sfx_for("door creaks", Duration::from_secs(1))
+            .line("voice-b", "Only me.");
+
+        let audio = client
+            .render_dialogue(&dialogue, OutputFormat::Pcm16000)
+            .await
+            .unwrap();
+
+        // 16-bit PCM: the one-second cue alone is 32000 bytes
+        let turns = estimate::turn_duration("Who's there?") + estimate::turn_duration("Only me.");
+        let expected = (turns.as_secs_f64() + 1.0) * 16000.0 * 2.0;
+        assert!((audio.len() as f64 - expected).abs() < 8.0);
+    }
+
+    #[tokio::test]
+    async fn test_wav_requests_pcm_and_adds_header() {
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .mock_transport(|request| {
+                assert_eq!(request.output_format.as_deref(), Some("pcm_24000"));
+                Ok(MockResponse::audio(vec![0; 480]))
+            })
+            .build();
+
+        let audio = client
+            .text_to_dialogue(sample_inputs())
+            .output_format(OutputFormat::Wav { sample_rate: 24000 })
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(&audio.audio[..4], b"RIFF");
+        assert_eq!(audio.audio.len(), 44 + 480);
+        assert_eq!(audio.as_i16_samples().unwrap(), vec![0; 240]);
+    }
+
+    #[tokio::test]
+    async fn test_detect_anomalies_flags_silent_take() {
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .detect_anomalies(true)
+            .mock_transport(|_| Ok(MockResponse::audio(vec![0; 16000 * 2 * 3])))
+            .build();
+
+        let audio = client
+            .text_to_dialogue(sample_inputs())
+            .output_format(OutputFormat::Pcm16000)
+            .execute()
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            audio.anomalies[..],
+            [
+                audio::AudioAnomaly::LongSilence { .. },
+                audio::AudioAnomaly::LowEnergy { .. }
+            ]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_render_dialogue_inserts_segment_gaps() {
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .sandbox(true)
+            .segment_gap(Duration::from_millis(500))
+            .build();
+        let dialogue = Dialogue::new()
+            .line("voice-a", "Ready?")
+            .sfx_for("whistle", Duration::from_secs(1))
+            .line("voice-b", "Go!");
+
+        let audio = client
+            .render_dialogue(&dialogue, OutputFormat::Pcm16000)
+            .await
+            .unwrap();
+
+        // Two half-second gaps on top of the turns and the one-second cue
+        let turns = estimate::turn_duration("Ready?") + estimate::turn_duration("Go!");
+        let expected = (turns.as_secs_f64() + 2.0) * 16000.0 * 2.0;
+        assert!((audio.len() as f64 - expected).abs() < 8.0);
+    }
+
+    #[test]
+    fn test_text_to_speech_fallback_detection() {
         let client = ElevenLabsTTDClient::new("test-key");
-        let builder = client.text_to_dialogue([]).model("model-456");
+        let mut inputs = sample_inputs();
+        inputs.push(TTDInput {
+            text: "Hi!".to_string(),
+            voice_id: "another-voice".to_string(),
+        });
 
-        // Builder pattern works
-        assert!(builder.inputs.is_empty());
-        assert_eq!(builder.model_id, Some("model-456".to_string()));
+        let builder = client.text_to_dialogue(inputs);
+        assert_eq!(text_to_speech_reason(&builder.build_request()), None);
+
+        let builder = builder.model("eleven_multilingual_v2");
+        assert_eq!(
+            text_to_speech_reason(&builder.build_request()),
+            Some("model doesn't support dialogue")
+        );
+
+        let single = client.text_to_dialogue(sample_inputs());
+        assert_eq!(
+            text_to_speech_reason(&single.build_request()),
+            Some("single speaker")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fallback_and_fixes_collected_as_warnings() {
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .validation_mode(ValidationMode::Lenient)
+            .text_to_speech_fallback(true)
+            .mock_transport(|_| Ok(MockResponse::audio(b"speech".to_vec())))
+            .build();
+        let mut inputs = sample_inputs();
+        inputs.push(TTDInput {
+            text: " ".to_string(),
+            voice_id: voices::all_voices::RACHEL.voice_id.to_string(),
+        });
+
+        let audio = client
+            .text_to_dialogue(inputs)
+            .stability(0.4)
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            audio.warnings,
+            vec![
+                Warning::Validation(ValidationWarning::EmptyTurnRemoved { index: 1 }),
+                Warning::Validation(ValidationWarning::StabilitySnapped { from: 0.4, to: 0.5 }),
+                Warning::FallbackUsed {
+                    fallback: "text-to-speech".to_string(),
+                    reason: "single speaker".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            audio.warnings[2].to_string(),
+            "Used text-to-speech instead: single speaker"
+        );
+        assert_eq!(
+            audio.warnings[1].to_string(),
+            "Stability 0.4 snapped to 0.5"
+        );
+
+        let json = serde_json::to_value(&audio.warnings[0]).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"kind": "validation", "warning": "empty_turn_removed", "index": 1})
+        );
+        assert_eq!(
+            serde_json::from_value::<Warning>(json).unwrap(),
+            audio.warnings[0]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_text_to_speech_returns_audio() {
+        let base_url = serve_once("200 OK", &[("content-type", "audio/mpeg")], b"speech").await;
+
+        let client = ElevenLabsTTDClient::with_base_url("test-key".to_string(), base_url);
+        let audio = client
+            .text_to_speech("voice-a", "Hello")
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(audio.as_ref(), b"speech");
+    }
+
+    #[tokio::test]
+    async fn test_per_request_base_url_and_key() {
+        let base_url = serve_once("200 OK", &[("content-type", "audio/mpeg")], b"tenant").await;
+
+        // The client's own base URL is unreachable; the override must be used
+        let client = ElevenLabsTTDClient::with_base_url("shared-key", "http://127.0.0.1:9");
+        let audio = client
+            .text_to_dialogue(sample_inputs())
+            .api_key("customer-key")
+            .base_url(base_url)
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(audio.as_ref(), b"tenant");
+    }
+
+    #[tokio::test]
+    async fn test_tenant_key_from_provider() {
+        let client = ElevenLabsTTDClient::builder("shared-key")
+            .key_provider(|tenant: &TenantId| (tenant.0 == "acme").then(|| Secret::new("acme-key")))
+            .mock_transport(|_| Ok(MockResponse::audio(b"audio".to_vec())))
+            .build();
+
+        let audio = client
+            .text_to_dialogue(sample_inputs())
+            .tenant("acme")
+            .execute()
+            .await;
+        assert!(audio.is_ok());
+
+        let error = client
+            .text_to_dialogue(sample_inputs())
+            .tenant("globex")
+            .execute()
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), "authentication_error");
+    }
+
+    #[tokio::test]
+    async fn test_dedupe_store_is_scoped_to_tenant() {
+        let dir = std::env::temp_dir().join(format!("ttd-scoped-{}", uuid::Uuid::new_v4()));
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = calls.clone();
+        let client = ElevenLabsTTDClient::builder("shared-key")
+            .key_provider(|tenant: &TenantId| Some(Secret::new(format!("{}-key", tenant.0))))
+            .dedupe_store(DedupeStore::open(&dir).unwrap())
+            .mock_transport(move |_| {
+                let call = counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Ok(MockResponse::audio(format!("audio-{}", call).into_bytes()))
+            })
+            .build();
+        let generate = |tenant: &str| {
+            client
+                .text_to_dialogue(sample_inputs())
+                .tenant(tenant)
+                .execute()
+        };
+
+        let acme = generate("acme").await.unwrap();
+        let globex = generate("globex").await.unwrap();
+        assert_eq!(acme.as_ref(), b"audio-0");
+        assert_eq!(globex.as_ref(), b"audio-1");
+
+        let again = generate("acme").await.unwrap();
+        assert_eq!(again.as_ref(), b"audio-0");
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stored_response_keeps_output_format() {
+        let dir = std::env::temp_dir().join(format!("ttd-format-{}", uuid::Uuid::new_v4()));
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .dedupe_store(DedupeStore::open(&dir).unwrap())
+            .mock_transport(|_| Ok(MockResponse::audio(vec![0; 64])))
+            .build();
+        let request = client
+            .text_to_dialogue(sample_inputs())
+            .output_format(OutputFormat::Pcm16000)
+            .build_request();
+
+        client
+            .generate(request.clone(), RequestOptions::default())
+            .await
+            .unwrap();
+        let stored = client
+            .generate(request, RequestOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(stored.output_format, Some(OutputFormat::Pcm16000));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_content_filter_rejects_before_sending() {
+        let sent = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let flag = sent.clone();
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .content_filter(WordListFilter::new(["there"]))
+            .mock_transport(move |_| {
+                flag.store(true, std::sync::atomic::Ordering::SeqCst);
+                Ok(MockResponse::audio(b"audio".to_vec()))
+            })
+            .build();
+
+        let error = client
+            .text_to_dialogue(sample_inputs())
+            .execute()
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), "content_rejected");
+        assert!(matches!(
+            error,
+            ElevenLabsTTDError::ContentRejected { index: 0, .. }
+        ));
+        assert!(!sent.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_health_check_classifies_responses() {
+        let base_url = serve_once("200 OK", &[], b"[]").await;
+        let client = ElevenLabsTTDClient::with_base_url("test-key", &base_url);
+        let health = client.health_check().await;
+        assert!(health.is_healthy());
+        assert_eq!(health.error, None);
+
+        let base_url = serve_once("401 Unauthorized", &[], b"invalid key sk_abc123").await;
+        let client = ElevenLabsTTDClient::with_base_url("test-key", &base_url);
+        let health = client.health_check().await;
+        assert_eq!(health.status, HealthStatus::Unauthorized);
+        assert!(!health.error.unwrap().contains("sk_abc123"));
+
+        let base_url = serve_once("503 Service Unavailable", &[], b"down").await;
+        let client = ElevenLabsTTDClient::with_base_url("test-key", &base_url);
+        assert_eq!(client.health_check().await.status, HealthStatus::Degraded);
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let client = ElevenLabsTTDClient::with_base_url("test-key", &base_url);
+        assert_eq!(
+            client.health_check().await.status,
+            HealthStatus::Unreachable
+        );
+
+        assert!(
+            ElevenLabsTTDClient::sandbox()
+                .health_check()
+                .await
+                .is_healthy()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_mp3_rejects_truncated_audio() {
+        let truncated = |_: &TTDRequest| {
+            let mut audio = mp3::tests::frames(4);
+            audio.truncate(1000);
+            Ok(MockResponse::audio(audio))
+        };
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .validate_mp3(true)
+            .mock_transport(truncated)
+            .build();
+        let error = client
+            .text_to_dialogue(sample_inputs())
+            .execute()
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), "corrupt_audio");
+        assert!(error.is_retryable());
+
+        // Only known MP3 formats are checked
+        let pcm = client
+            .text_to_dialogue(sample_inputs())
+            .output_format("pcm_16000")
+            .execute()
+            .await;
+        assert!(pcm.is_ok());
+        let unchecked = ElevenLabsTTDClient::builder("test-key")
+            .mock_transport(truncated)
+            .build();
+        assert!(
+            unchecked
+                .text_to_dialogue(sample_inputs())
+                .execute()
+                .await
+                .is_ok()
+        );
+
+        let valid = ElevenLabsTTDClient::builder("test-key")
+            .validate_mp3(true)
+            .mock_transport(|_| Ok(MockResponse::audio(mp3::tests::frames(4))))
+            .build();
+        assert!(
+            valid
+                .text_to_dialogue(sample_inputs())
+                .execute()
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stats_track_successful_requests() {
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = calls.clone();
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .track_latency(true)
+            .mock_transport(move |_| {
+                if counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 2 {
+                    Ok(MockResponse::error(500, "internal error"))
+                } else {
+                    Ok(MockResponse::audio(b"audio".to_vec()))
+                }
+            })
+            .build();
+        assert_eq!(client.stats(), Some(Vec::new()));
+
+        for _ in 0..3 {
+            let _ = client
+                .text_to_dialogue(sample_inputs())
+                .output_format("mp3_44100_128")
+                .execute()
+                .await;
+        }
+
+        let stats = client.clone().stats().unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].endpoint, "text_to_dialogue");
+        assert_eq!(stats[0].output_format.as_deref(), Some("mp3_44100_128"));
+        assert_eq!(stats[0].count, 2);
+        assert!(stats[0].p50 <= stats[0].p95 && stats[0].p95 <= stats[0].max);
+
+        client.reset_stats();
+        assert_eq!(client.stats(), Some(Vec::new()));
+        assert_eq!(ElevenLabsTTDClient::sandbox().stats(), None);
+    }
+
+    #[tokio::test]
+    async fn test_error_sink_reports_once_per_call() {
+        let reports = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .error_sink(move |error: &ElevenLabsTTDError, context: &ErrorContext| {
+                sink.lock().unwrap().push((error.kind(), context.clone()))
+            })
+            .mock_transport(|_| {
+                Ok(MockResponse::error(500, "internal error").header("request-id", "req-1"))
+            })
+            .build();
+
+        client
+            .text_to_dialogue(sample_inputs())
+            .execute()
+            .await
+            .unwrap_err();
+        let dialogue = Dialogue::new()
+            .line("voice-a", "Hi.")
+            .line("voice-b", "Hello.");
+        client
+            .render_dialogue(&dialogue, OutputFormat::default())
+            .await
+            .unwrap_err();
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].0, "server_error");
+        assert_eq!(reports[0].1.operation, "text_to_dialogue");
+        assert_eq!(reports[0].1.request_id.as_deref(), Some("req-1"));
+        assert_eq!(reports[0].1.attempts, 1);
+        assert!(reports[0].1.correlation_id.is_some());
+        assert_eq!(reports[1].1.operation, "render_dialogue");
+    }
+
+    #[tokio::test]
+    async fn test_error_sink_reports_other_endpoints() {
+        let reports = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        // Unroutable base URL: every call fails to connect
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .base_url("http://127.0.0.1:9")
+            .error_sink(move |_: &ElevenLabsTTDError, context: &ErrorContext| {
+                sink.lock().unwrap().push(context.operation)
+            })
+            .build();
+        let cue = SoundCue {
+            description: "door slams".to_string(),
+            duration: None,
+        };
+
+        client.models().await.unwrap_err();
+        client
+            .request(Method::GET, "/history")
+            .send()
+            .await
+            .unwrap_err();
+        client
+            .sound_effect(&cue, OutputFormat::Pcm16000)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            client.health_check().await.status,
+            HealthStatus::Unreachable
+        );
+
+        assert_eq!(
+            *reports.lock().unwrap(),
+            ["models", "request", "sound_effect"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_audit_sink_records_calls() {
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .cost_per_thousand_characters(0.30)
+            .audit_sink(move |event: &AuditEvent| sink.lock().unwrap().push(event.clone()))
+            .mock_transport(|_| {
+                Ok(MockResponse::audio(b"audio".to_vec())
+                    .header("history-item-id", "h1")
+                    .header("x-character-count", "1000"))
+            })
+            .build();
+
+        client
+            .text_to_dialogue(sample_inputs())
+            .execute()
+            .await
+            .unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].request_id.as_deref(), Some("h1"));
+        assert_eq!(events[0].voices, vec![voices::all_voices::RACHEL.voice_id]);
+        assert!((events[0].cost.unwrap() - 0.30).abs() < 1e-9);
+        assert!(events[0].error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sanitize_text_before_sending() {
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .sanitize_text(true)
+            .mock_transport(|request| {
+                assert_eq!(request.inputs[0].text, "\"Hi\" there");
+                Ok(MockResponse::audio(b"audio".to_vec()))
+            })
+            .build();
+
+        client
+            .text_to_dialogue(vec![TTDInput {
+                text: "\u{201C}Hi\u{201D}\u{00A0} there\u{200B}".to_string(),
+                voice_id: "voice".to_string(),
+            }])
+            .execute()
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_transport_simulates_responses() {
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .timeout(Duration::from_millis(50))
+            .mock_transport(|request| match request.inputs[0].text.as_str() {
+                "ok" => Ok(MockResponse::audio(b"audio".to_vec()).header("history-item-id", "h1")),
+                "slow" => Ok(MockResponse::audio(Vec::new()).delay(Duration::from_secs(5))),
+                _ => Ok(MockResponse::error(
+                    401,
+                    r#"{"detail":{"status":"invalid_api_key"}}"#,
+                )),
+            })
+            .build();
+        let say = |text: &str| {
+            client.text_to_dialogue(vec![TTDInput {
+                text: text.to_string(),
+                voice_id: "voice".to_string(),
+            }])
+        };
+
+        let audio = say("ok").execute().await.unwrap();
+        assert_eq!(audio.as_ref(), b"audio");
+        assert_eq!(audio.history_item_id.as_deref(), Some("h1"));
+
+        let error = say("slow").execute().await.unwrap_err();
+        assert_eq!(error.kind(), "timeout");
+
+        let error = say("denied").execute().await.unwrap_err();
+        assert_eq!(error.code().as_deref(), Some("invalid_api_key"));
+    }
+
+    #[tokio::test]
+    async fn test_statuses_map_to_dedicated_errors() {
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .mock_transport(|request| {
+                Ok(match request.inputs[0].text.as_str() {
+                    "401" => MockResponse::error(401, r#"{"detail":{"status":"invalid_api_key"}}"#),
+                    "402" => MockResponse::error(402, "out of credits"),
+                    "429" => MockResponse::error(429, "slow down").header("retry-after", "7"),
+                    "503" => MockResponse::error(503, "overloaded"),
+                    _ => MockResponse::error(422, "unprocessable"),
+                })
+            })
+            .build();
+        let error = |status: &str| {
+            client
+                .text_to_dialogue(vec![TTDInput {
+                    text: status.to_string(),
+                    voice_id: "voice".to_string(),
+                }])
+                .execute()
+        };
+
+        let denied = error("401").await.unwrap_err();
+        assert_eq!(denied.kind(), "authentication_error");
+        assert_eq!(denied.code().as_deref(), Some("invalid_api_key"));
+        assert_eq!(
+            error("402").await.unwrap_err().kind(),
+            "quota_exceeded_error"
+        );
+
+        let limited = error("429").await.unwrap_err();
+        assert_eq!(limited.retry_after(), Some(7));
+        assert_eq!(limited.raw_response().unwrap().body, b"slow down");
+
+        let unavailable = error("503").await.unwrap_err();
+        assert_eq!(unavailable.kind(), "server_error");
+        assert_eq!(unavailable.status(), Some(503));
+        assert!(unavailable.correlation_id().is_some());
+        assert_eq!(error("422").await.unwrap_err().kind(), "api_error");
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_fails_fast() {
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = calls.clone();
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .circuit_breaker(2, Duration::from_secs(60))
+            .mock_transport(move |_| {
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Ok(MockResponse::error(503, "overloaded"))
+            })
+            .build();
+
+        for _ in 0..2 {
+            let error = client
+                .text_to_dialogue(sample_inputs())
+                .execute()
+                .await
+                .unwrap_err();
+            assert_eq!(error.kind(), "server_error");
+        }
+        assert_eq!(client.circuit_state(), Some(CircuitState::Open));
+
+        let error = client
+            .text_to_dialogue(sample_inputs())
+            .execute()
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), "circuit_open");
+        assert_eq!(error.retry_after(), Some(60));
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    /// Collects the fields of `elevenlabs_ttd::requests` events as text
+    struct RequestLogCollector(std::sync::Arc<std::sync::Mutex<Vec<String>>>);
+
+    impl tracing::Subscriber for RequestLogCollector {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            tracing::span::Id::from_u64(1)
+        }
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+        fn event(&self, event: &tracing::Event<'_>) {
+            struct Fields(String);
+            impl tracing::field::Visit for Fields {
+                fn record_debug(
+                    &mut self,
+                    field: &tracing::field::Field,
+                    value: &dyn std::fmt::Debug,
+                ) {
+                    self.0.push_str(&format!("{}={:?} ", field.name(), value));
+                }
+            }
+            if event.metadata().target() == "elevenlabs_ttd::requests" {
+                let mut fields = Fields(String::new());
+                event.record(&mut fields);
+                self.0.lock().unwrap().push(fields.0);
+            }
+        }
+        fn enter(&self, _: &tracing::span::Id) {}
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[tokio::test]
+    async fn test_request_logging_redacts_text_by_default() {
+        let lines = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let _guard = tracing::subscriber::set_default(RequestLogCollector(lines.clone()));
+        let mock = |_: &TTDRequest| Ok(MockResponse::audio(b"audio".to_vec()));
+
+        let client = ElevenLabsTTDClient::builder("sk_secret")
+            .log_requests(true)
+            .mock_transport(mock)
+            .build();
+        client
+            .text_to_dialogue(sample_inputs())
+            .execute()
+            .await
+            .unwrap();
+
+        let verbose = ElevenLabsTTDClient::builder("sk_secret")
+            .log_request_text(true)
+            .mock_transport(mock)
+            .build();
+        verbose
+            .text_to_dialogue(sample_inputs())
+            .execute()
+            .await
+            .unwrap();
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("path=\"/v1/text-to-dialogue\""));
+        assert!(lines[0].contains("characters=12"));
+        assert!(lines[0].contains("status=200"));
+        assert!(!lines[0].contains("Hello there"));
+        assert!(lines[1].contains("Hello there"));
+        assert!(lines.iter().all(|line| !line.contains("sk_secret")));
+    }
+
+    #[test]
+    fn test_api_versions_from_builder() {
+        let client = ElevenLabsTTDClient::new("test-key");
+        assert_eq!(
+            client.api_url(EndpointFamily::Voices, ApiVersion::V2, "/voices"),
+            "https://api.elevenlabs.io/v2/voices"
+        );
+
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .base_url("https://proxy.example.com/v1")
+            .api_version(ApiVersion::V2)
+            .endpoint_version(EndpointFamily::Voices, ApiVersion::V1)
+            .build();
+        assert_eq!(client.versions.base_url(), "https://proxy.example.com/v2");
+        assert_eq!(
+            client.api_url(EndpointFamily::Voices, ApiVersion::V2, "/voices"),
+            "https://proxy.example.com/v1/voices"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_usage_records_failed_requests() {
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .base_url("http://127.0.0.1:9")
+            .track_usage(true)
+            .build();
+        assert!(
+            ElevenLabsTTDClient::new("test-key")
+                .usage_summary()
+                .is_none()
+        );
+
+        let result = client.text_to_dialogue(sample_inputs()).execute().await;
+        assert!(result.is_err());
+
+        let summary = client.usage_summary().unwrap();
+        assert_eq!(summary.requests, 1);
+        assert_eq!(summary.errors, 1);
+        assert_eq!(summary.characters, 0);
+
+        client.reset_usage();
+        assert_eq!(client.usage_summary().unwrap().requests, 0);
     }
 }
@@ -0,0 +1,68 @@
+//! Records of the exact parameters behind a generation
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::error::ElevenLabsTTDError;
+use crate::types::{TTDPronunciationDictionaryLocators, TTDRequest, TTDSettings};
+
+/// Everything needed to trace an audio file back to the request that produced it
+#[derive(Debug, Clone, Serialize)]
+pub struct GenerationManifest {
+    // Version of this crate that sent the request.
+    pub crate_version: String,
+    pub model_id: Option<String>,
+    pub output_format: Option<String>,
+    // SHA-256 of the inputs (texts and voice IDs), to check a script against the audio.
+    pub inputs_hash: String,
+    pub voice_ids: Vec<String>,
+    pub settings: Option<TTDSettings>,
+    pub pronunciation_dictionary_locators: Option<TTDPronunciationDictionaryLocators>,
+    pub seed: Option<u32>,
+    pub correlation_id: String,
+    // Request ID assigned by ElevenLabs, if the response carried one.
+    pub history_item_id: Option<String>,
+    // RFC 3339 time the request was sent.
+    pub created_at: String,
+}
+
+impl GenerationManifest {
+    pub(crate) fn for_request(request: &TTDRequest, correlation_id: &str) -> Self {
+        let inputs = serde_json::to_vec(&request.inputs).unwrap_or_default();
+        let mut voice_ids: Vec<String> = Vec::new();
+        for input in &request.inputs {
+            if !voice_ids.contains(&input.voice_id) {
+                voice_ids.push(input.voice_id.clone());
+            }
+        }
+
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            model_id: request.model_id.clone(),
+            output_format: request.output_format.clone(),
+            inputs_hash: Sha256::digest(&inputs)
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect(),
+            voice_ids,
+            settings: request.settings.clone(),
+            pronunciation_dictionary_locators: request.pronunciation_dictionary_locators.clone(),
+            seed: request.seed,
+            correlation_id: correlation_id.to_string(),
+            history_item_id: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Write the manifest next to an audio file (`scene.mp3` → `scene.manifest.json`)
+    pub fn write_sidecar<P: AsRef<Path>>(
+        &self,
+        audio_path: P,
+    ) -> Result<PathBuf, ElevenLabsTTDError> {
+        let path = audio_path.as_ref().with_extension("manifest.json");
+        std::fs::write(&path, serde_json::to_vec_pretty(self)?)?;
+        Ok(path)
+    }
+}
@@ -0,0 +1,68 @@
+//! In-process transport for testing code built on this client without HTTP
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::error::ElevenLabsTTDError;
+use crate::types::TTDRequest;
+
+/// A canned response returned by a mock transport,
+/// see [`crate::ClientBuilder::mock_transport`]
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+    // Wait this long before responding, to simulate slow responses.
+    // Delays beyond the client's timeout produce a timeout error.
+    pub delay: Option<Duration>,
+    // Drop the connection after sending this many bytes of the body.
+    pub interrupt_after: Option<usize>,
+}
+
+impl MockResponse {
+    /// A successful response carrying `audio`
+    pub fn audio<B: Into<Vec<u8>>>(audio: B) -> Self {
+        Self {
+            status: 200,
+            headers: HeaderMap::new(),
+            body: audio.into(),
+            delay: None,
+            interrupt_after: None,
+        }
+    }
+
+    /// A failed response with a JSON or text body
+    pub fn error<B: Into<Vec<u8>>>(status: u16, body: B) -> Self {
+        Self {
+            status,
+            ..Self::audio(body)
+        }
+    }
+
+    /// Add a response header, e.g. `history-item-id` or `x-character-count`
+    pub fn header(mut self, name: &'static str, value: &str) -> Self {
+        if let Ok(value) = HeaderValue::from_str(value) {
+            self.headers.insert(HeaderName::from_static(name), value);
+        }
+        self
+    }
+
+    /// Respond after `delay`
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Drop the connection after sending `bytes` bytes of the body,
+    /// producing [`ElevenLabsTTDError::StreamInterrupted`]
+    pub fn interrupt_after(mut self, bytes: usize) -> Self {
+        self.interrupt_after = Some(bytes);
+        self
+    }
+}
+
+pub(crate) type MockTransport =
+    Arc<dyn Fn(&TTDRequest) -> Result<MockResponse, ElevenLabsTTDError> + Send + Sync>;
@@ -3,3 +3,8 @@
 pub mod elevanlabs_models {
     pub const ELEVEN_V3: &str = "eleven_v3";
 }
+
+/// Whether a model can generate multi-speaker dialogue (the Eleven V3 family)
+pub fn supports_dialogue(model_id: &str) -> bool {
+    model_id.starts_with(elevanlabs_models::ELEVEN_V3)
+}
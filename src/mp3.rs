@@ -0,0 +1,251 @@
+//! MP3 frame sync validation, to catch truncated or corrupted downloads
+//! before they reach a player
+
+use crate::error::ElevenLabsTTDError;
+
+/// Layer III bitrates in kbps by bitrate index, for MPEG-1 and MPEG-2/2.5
+const BITRATES_V1: [u32; 16] = [
+    0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 0,
+];
+const BITRATES_V2: [u32; 16] = [
+    0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160, 0,
+];
+
+/// Size of an ID3v1 trailer
+const ID3V1_LEN: u64 = 128;
+
+/// Checks that MP3 data is an unbroken chain of Layer III frames, optionally
+/// preceded by an ID3v2 tag and followed by an ID3v1 tag. Data can be pushed
+/// as it downloads, so corruption is reported at the first bad frame.
+#[derive(Debug, Default)]
+pub struct Mp3Validator {
+    // Bytes of a header that straddles pushed chunks.
+    pending: Vec<u8>,
+    // Bytes of the current frame or tag still to be skipped.
+    skip: u64,
+    // Whether the current block is a frame rather than a tag.
+    in_frame: bool,
+    // Total bytes consumed, for error offsets.
+    offset: u64,
+    frames: u64,
+    // An ID3v1 trailer was found; nothing may follow it.
+    trailer: bool,
+}
+
+impl Mp3Validator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of complete frames seen so far
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Validate the next chunk of data
+    pub fn push(&mut self, mut chunk: &[u8]) -> Result<(), ElevenLabsTTDError> {
+        while !chunk.is_empty() {
+            if self.skip > 0 {
+                let n = self.skip.min(chunk.len() as u64);
+                self.skip -= n;
+                self.offset += n;
+                chunk = &chunk[n as usize..];
+                if self.skip == 0 && self.in_frame {
+                    self.frames += 1;
+                }
+                continue;
+            }
+            if self.trailer {
+                return Err(self.corrupt("data after the ID3v1 tag"));
+            }
+
+            // Gather enough bytes to read the next header: 4 for a frame,
+            // 10 once they turn out to start an ID3v2 tag
+            let needed: usize = if self.at_id3v2() { 10 } else { 4 };
+            let take = needed.saturating_sub(self.pending.len()).min(chunk.len());
+            self.pending.extend_from_slice(&chunk[..take]);
+            chunk = &chunk[take..];
+            if self.pending.len() < needed || (needed == 4 && self.at_id3v2()) {
+                continue;
+            }
+
+            let header = std::mem::take(&mut self.pending);
+            let (len, in_frame) = self.block_len(&header)?;
+            // The header bytes are part of the block
+            self.skip = len - header.len() as u64;
+            self.offset += header.len() as u64;
+            self.in_frame = in_frame;
+            if self.skip == 0 && in_frame {
+                self.frames += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Finish validation, failing if the data ended mid-frame or held no frames.
+    /// Returns the number of frames.
+    pub fn finish(self) -> Result<u64, ElevenLabsTTDError> {
+        if self.skip > 0 || !self.pending.is_empty() {
+            return Err(self.corrupt("truncated in the middle of a frame"));
+        }
+        if self.frames == 0 {
+            return Err(self.corrupt("no MPEG audio frames"));
+        }
+        Ok(self.frames)
+    }
+
+    /// Whether the pending bytes start an ID3v2 tag, only allowed up front
+    fn at_id3v2(&self) -> bool {
+        self.offset == 0 && self.pending.starts_with(b"ID3")
+    }
+
+    /// Length of the frame or tag starting with `header`, including the header,
+    /// and whether it is a frame
+    fn block_len(&mut self, header: &[u8]) -> Result<(u64, bool), ElevenLabsTTDError> {
+        if self.offset == 0 && header.starts_with(b"ID3") {
+            let size = header[6..10]
+                .iter()
+                .fold(0u64, |size, byte| (size << 7) | (byte & 0x7F) as u64);
+            let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+            return Ok((10 + size + footer, false));
+        }
+        if header.starts_with(b"TAG") && self.frames > 0 {
+            self.trailer = true;
+            return Ok((ID3V1_LEN, false));
+        }
+
+        match FrameHeader::parse(header) {
+            Ok(frame) => Ok((frame.len, true)),
+            Err(reason) => Err(self.corrupt(reason)),
+        }
+    }
+
+    fn corrupt(&self, reason: &str) -> ElevenLabsTTDError {
+        ElevenLabsTTDError::CorruptAudio {
+            offset: self.offset,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Fields of a Layer III frame header needed to walk and time a stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FrameHeader {
+    // Frame length in bytes, including the header.
+    pub(crate) len: u64,
+    pub(crate) samples: u32,
+    pub(crate) sample_rate: u32,
+}
+
+impl FrameHeader {
+    /// Parse the 4 byte header at the start of `header`
+    pub(crate) fn parse(header: &[u8]) -> Result<Self, &'static str> {
+        if header.len() < 4 || header[0] != 0xFF || header[1] & 0xE0 != 0xE0 {
+            return Err("lost frame sync");
+        }
+        let version = (header[1] >> 3) & 0x03;
+        let layer = (header[1] >> 1) & 0x03;
+        let bitrate_index = (header[2] >> 4) as usize;
+        let rate_index = ((header[2] >> 2) & 0x03) as usize;
+        let padding = ((header[2] >> 1) & 0x01) as u64;
+        if version == 0b01 || layer != 0b01 || rate_index == 3 {
+            return Err("invalid frame header");
+        }
+
+        let (bitrates, rates, samples) = match version {
+            0b11 => (&BITRATES_V1, [44100, 48000, 32000], 1152),
+            0b10 => (&BITRATES_V2, [22050, 24000, 16000], 576),
+            _ => (&BITRATES_V2, [11025, 12000, 8000], 576),
+        };
+        let bitrate = bitrates[bitrate_index] as u64 * 1000;
+        if bitrate == 0 {
+            return Err("unsupported frame bitrate");
+        }
+        let sample_rate = rates[rate_index];
+        Ok(Self {
+            len: samples as u64 / 8 * bitrate / sample_rate as u64 + padding,
+            samples,
+            sample_rate,
+        })
+    }
+}
+
+/// Offsets and headers of the frames of complete MP3 `audio`, skipping ID3 tags
+#[cfg(feature = "streaming-export")]
+pub(crate) fn frames(audio: &[u8]) -> Result<Vec<(usize, FrameHeader)>, ElevenLabsTTDError> {
+    validate(audio)?;
+    let mut pos = 0;
+    if audio.starts_with(b"ID3") {
+        let size = audio[6..10]
+            .iter()
+            .fold(0usize, |size, byte| (size << 7) | (byte & 0x7F) as usize);
+        let footer = if audio[5] & 0x10 != 0 { 10 } else { 0 };
+        pos = 10 + size + footer;
+    }
+
+    let mut frames = Vec::new();
+    while pos < audio.len() && !audio[pos..].starts_with(b"TAG") {
+        let frame = FrameHeader::parse(&audio[pos..]).map_err(|reason| {
+            ElevenLabsTTDError::CorruptAudio {
+                offset: pos as u64,
+                reason: reason.to_string(),
+            }
+        })?;
+        frames.push((pos, frame));
+        pos += frame.len as usize;
+    }
+    Ok(frames)
+}
+
+/// Check that `audio` is complete, uncorrupted MP3, returning its number of frames
+pub fn validate(audio: &[u8]) -> Result<u64, ElevenLabsTTDError> {
+    let mut validator = Mp3Validator::new();
+    validator.push(audio)?;
+    validator.finish()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// `count` silent MPEG-1 Layer III frames at 128kbps / 44.1kHz, 417 bytes each
+    pub(crate) fn frames(count: usize) -> Vec<u8> {
+        let mut frame = vec![0u8; 417];
+        frame[..4].copy_from_slice(&[0xFF, 0xFB, 0x90, 0x00]);
+        frame.repeat(count)
+    }
+
+    #[test]
+    fn test_valid_stream_in_any_chunking() {
+        let mut audio = b"ID3\x04\x00\x00\x00\x00\x00\x05hello".to_vec();
+        audio.extend(frames(3));
+        audio.extend(b"TAG");
+        audio.extend([0u8; 125]);
+
+        assert_eq!(validate(&audio).unwrap(), 3);
+        for size in [1, 3, 7, 500] {
+            let mut validator = Mp3Validator::new();
+            for chunk in audio.chunks(size) {
+                validator.push(chunk).unwrap();
+            }
+            assert_eq!(validator.finish().unwrap(), 3);
+        }
+    }
+
+    #[test]
+    fn test_truncated_and_corrupt_frames() {
+        let audio = frames(3);
+        let error = validate(&audio[..1000]).unwrap_err();
+        assert_eq!(error.kind(), "corrupt_audio");
+        assert!(error.to_string().contains("truncated"));
+
+        let mut corrupt = audio.clone();
+        corrupt[834] = 0x00;
+        let error = validate(&corrupt).unwrap_err();
+        assert!(error.to_string().contains("byte 834"), "{}", error);
+
+        let mut validator = Mp3Validator::new();
+        assert!(validator.push(b"<html>").is_err());
+        assert!(validate(b"").is_err());
+    }
+}
@@ -0,0 +1,138 @@
+//! Transparent iteration over cursor-paginated list endpoints
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use crate::error::ElevenLabsTTDError;
+
+/// One page of a list endpoint
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    // Cursor for the next page, `None` on the last page.
+    pub next_cursor: Option<String>,
+}
+
+type PageFuture<T> = Pin<Box<dyn Future<Output = Result<Page<T>, ElevenLabsTTDError>> + Send>>;
+type PageFetcher<T> = Box<dyn FnMut(Option<String>) -> PageFuture<T> + Send>;
+
+/// A stream of items that fetches the next page whenever the current one runs out.
+///
+/// Use it as a [`Stream`], or call [`Paginated::next`] / [`Paginated::collect_all`] directly.
+pub struct Paginated<T> {
+    fetch: PageFetcher<T>,
+    buffer: VecDeque<T>,
+    cursor: Option<String>,
+    in_flight: Option<PageFuture<T>>,
+    done: bool,
+}
+
+impl<T> Paginated<T> {
+    /// Build a stream from a function fetching the page after `cursor` (`None` for the first page)
+    pub fn new<F, Fut>(mut fetch: F) -> Self
+    where
+        F: FnMut(Option<String>) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Page<T>, ElevenLabsTTDError>> + Send + 'static,
+    {
+        Self {
+            fetch: Box::new(move |cursor| Box::pin(fetch(cursor))),
+            buffer: VecDeque::new(),
+            cursor: None,
+            in_flight: None,
+            done: false,
+        }
+    }
+}
+
+impl<T: Unpin> Paginated<T> {
+    /// The next item, fetching another page if needed
+    pub async fn next(&mut self) -> Option<Result<T, ElevenLabsTTDError>> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+
+    /// Fetch every remaining page and return all items
+    pub async fn collect_all(mut self) -> Result<Vec<T>, ElevenLabsTTDError> {
+        let mut items = Vec::new();
+        while let Some(item) = self.next().await {
+            items.push(item?);
+        }
+        Ok(items)
+    }
+}
+
+impl<T: Unpin> Stream for Paginated<T> {
+    type Item = Result<T, ElevenLabsTTDError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(item) = this.buffer.pop_front() {
+                return Poll::Ready(Some(Ok(item)));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            let cursor = this.cursor.take();
+            let fetch = &mut this.fetch;
+            let page = this.in_flight.get_or_insert_with(|| fetch(cursor));
+
+            match page.as_mut().poll(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(result) => {
+                    this.in_flight = None;
+                    match result {
+                        Ok(page) => {
+                            this.buffer.extend(page.items);
+                            this.cursor = page.next_cursor;
+                            this.done = this.cursor.is_none();
+                        }
+                        Err(e) => {
+                            this.done = true;
+                            return Poll::Ready(Some(Err(e)));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers() -> Paginated<u32> {
+        // Pages of three numbers, 0..8, with the page start as cursor
+        Paginated::new(|cursor: Option<String>| async move {
+            let start: u32 = cursor.map(|c| c.parse().unwrap()).unwrap_or(0);
+            let end = (start + 3).min(8);
+            Ok(Page {
+                items: (start..end).collect(),
+                next_cursor: (end < 8).then(|| end.to_string()),
+            })
+        })
+    }
+
+    #[tokio::test]
+    async fn test_collect_all_follows_cursors() {
+        assert_eq!(
+            numbers().collect_all().await.unwrap(),
+            (0..8).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_ends_stream() {
+        let mut pages = Paginated::<u32>::new(|_| async {
+            Err(ElevenLabsTTDError::ValidationError("boom".to_string()))
+        });
+
+        assert!(pages.next().await.unwrap().is_err());
+        assert!(pages.next().await.is_none());
+    }
+}
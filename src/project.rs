@@ -0,0 +1,2676 @@
+//! Long-form productions made of many dialogues, rendered to a set of files,
+//! and [`Project`]s saving scripts and renders across sessions
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::audio;
+use crate::dialogue::{Dialogue, DialogueLine, SpeakerMap};
+use crate::error::ElevenLabsTTDError;
+use crate::formats::{Codec, OutputFormat};
+use crate::retry::RetryPolicy;
+use crate::writer::ChunkedWriter;
+use crate::{ElevenLabsTTDClient, RequestOptions, TTDAudio, TTDSettings, VoiceRef, Warning};
+
+pub use crate::retry::{DEFAULT_MAX_ATTEMPTS, DEFAULT_RETRY_DELAY};
+
+/// One chapter of an [`Audiobook`]
+#[derive(Debug, Clone)]
+pub struct BookChapter {
+    pub title: String,
+    pub dialogue: Dialogue,
+}
+
+/// An ordered list of chapters, each a [`Dialogue`], rendered to one file per chapter
+/// plus a combined file and chapter timings.
+///
+/// Each chapter is generated on its own (see [`ElevenLabsTTDClient::render_dialogue`]),
+/// so a failure only retries that chapter, and chapters already on disk from an
+/// earlier run are reused instead of generated (and billed) again, unless their
+/// script changed since (see [`Audiobook::changes`]). The chapter is the unit of
+/// reuse: a chapter with any edited line is generated again as a whole, since
+/// its turns share requests.
+#[derive(Debug, Clone)]
+pub struct Audiobook {
+    pub title: String,
+    pub chapters: Vec<BookChapter>,
+    pub output_format: OutputFormat,
+    pub retry: RetryPolicy,
+    // Seed for every request; also allows retrying errors that may have been billed.
+    pub seed: Option<u32>,
+    // Prefix of the idempotency keys of every request; likewise allows retrying.
+    pub idempotency_key: Option<String>,
+}
+
+/// Where a chapter ended up and when it plays in the combined file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChapterTiming {
+    pub title: String,
+    // File name of the chapter, relative to the output directory.
+    pub file: PathBuf,
+    // Position in the combined file, in seconds.
+    pub start: f64,
+    pub end: f64,
+    // Characters billed, `None` when reused from disk or not reported.
+    pub character_count: Option<u64>,
+    // Whether the chapter was reused from an earlier run.
+    pub cached: bool,
+    // Hash of the chapter's script, voices and output format.
+    #[serde(default)]
+    pub fingerprint: String,
+    // Hash of each line of the script, to tell which lines changed.
+    #[serde(default)]
+    pub line_fingerprints: Vec<String>,
+    // Size of the chapter file in bytes, to tell a complete file from a cut-short one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
+/// Files written by [`Audiobook::render`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudiobookOutput {
+    pub title: String,
+    pub output_format: String,
+    // The combined file, relative to the output directory.
+    pub combined: PathBuf,
+    pub chapters: Vec<ChapterTiming>,
+    // Estimates and adjustments that went into the render.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Warning>,
+}
+
+/// How a chapter differs from the one in an earlier [`AudiobookOutput`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChapterChange {
+    // Not part of the earlier render.
+    Added,
+    // Same script, voices and output format; the rendered file is reused.
+    Unchanged,
+    // Regenerated as a whole; `lines` are the indices of the lines that were
+    // edited or inserted, e.g. to review them.
+    Changed { lines: Vec<usize> },
+}
+
+impl AudiobookOutput {
+    /// Read the chapter timings written by an earlier [`Audiobook::render`]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ElevenLabsTTDError> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+}
+
+impl Audiobook {
+    pub fn new<S: Into<String>>(title: S) -> Self {
+        Self {
+            title: title.into(),
+            chapters: Vec::new(),
+            output_format: OutputFormat::default(),
+            retry: RetryPolicy::default(),
+            seed: None,
+            idempotency_key: None,
+        }
+    }
+
+    /// Add a chapter
+    pub fn chapter<S: Into<String>>(mut self, title: S, dialogue: Dialogue) -> Self {
+        self.chapters.push(BookChapter {
+            title: title.into(),
+            dialogue,
+        });
+        self
+    }
+
+    /// Set the output format of every file
+    pub fn output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+
+    /// Set how many times a chapter is attempted before giving up
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.retry = self.retry.max_attempts(max_attempts);
+        self
+    }
+
+    /// Set the wait before the first retry of a chapter
+    pub fn retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry = self.retry.base_delay(retry_delay);
+        self
+    }
+
+    /// Set the backoff, jitter and time budget of retries
+    pub fn retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Generate with a seed, which also lets timeouts and server errors be retried
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Send idempotency keys starting with `key`, followed by the chapter's
+    /// number, which also lets timeouts and server errors be retried. Use a new
+    /// key for each render, or edited chapters get their earlier audio back.
+    pub fn idempotency_key<S: Into<String>>(mut self, key: S) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// File name of the `index`th chapter, e.g. `02-the-storm.mp3`
+    pub fn chapter_file_name(&self, index: usize) -> PathBuf {
+        let title = self
+            .chapters
+            .get(index)
+            .map(|c| c.title.as_str())
+            .unwrap_or_default();
+        PathBuf::from(format!(
+            "{:02}-{}.{}",
+            index + 1,
+            slug(title),
+            self.output_format.extension()
+        ))
+    }
+
+    /// Path of the chapter timings [`Self::render`] writes into `dir`
+    pub fn timings_path<P: AsRef<Path>>(&self, dir: P) -> PathBuf {
+        dir.as_ref()
+            .join(format!("{}.chapters.json", slug(&self.title)))
+    }
+
+    /// How each chapter differs from an earlier render, matched by file name.
+    /// Lines are compared by content, so inserting a line only marks that line,
+    /// though [`Self::render`] still regenerates the whole chapter.
+    pub fn changes(&self, previous: &AudiobookOutput) -> Vec<ChapterChange> {
+        (0..self.chapters.len())
+            .map(|index| {
+                let file = self.chapter_file_name(index);
+                let Some(earlier) = previous.chapters.iter().find(|c| c.file == file) else {
+                    return ChapterChange::Added;
+                };
+                if earlier.fingerprint == self.fingerprint(index) {
+                    return ChapterChange::Unchanged;
+                }
+                ChapterChange::Changed {
+                    lines: changed_lines(
+                        &self.line_fingerprints(index),
+                        &earlier.line_fingerprints,
+                    ),
+                }
+            })
+            .collect()
+    }
+
+    fn line_fingerprints(&self, index: usize) -> Vec<String> {
+        self.chapters[index]
+            .dialogue
+            .lines
+            .iter()
+            .map(|line| {
+                let canonical = match line {
+                    DialogueLine::Turn(input) => {
+                        format!("turn\0{}\0{}", input.voice_id, input.text)
+                    }
+                    DialogueLine::LocalizedTurn(input, language) => {
+                        format!("turn\0{}\0{}\0{}", input.voice_id, input.text, language)
+                    }
+                    DialogueLine::Sfx(cue) => {
+                        format!("sfx\0{}\0{:?}", cue.description, cue.duration)
+                    }
+                    DialogueLine::Pause(duration) => format!("pause\0{:?}", duration),
+                };
+                hash(canonical.as_bytes())
+            })
+            .collect()
+    }
+
+    fn fingerprint(&self, index: usize) -> String {
+        let lines = self.line_fingerprints(index).join(",");
+        hash(format!("{}\0{}", self.output_format.as_str(), lines).as_bytes())
+    }
+
+    /// Render every chapter into `dir`, then write the combined file
+    /// (`<title>.<ext>`) and the chapter timings (`<title>.chapters.json`).
+    ///
+    /// The timings are also saved as each chapter is generated, so a run that
+    /// fails part-way doesn't generate the chapters it finished again.
+    pub async fn render<P: AsRef<Path>>(
+        &self,
+        client: &ElevenLabsTTDClient,
+        dir: P,
+    ) -> Result<AudiobookOutput, ElevenLabsTTDError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+
+        let combined_file = PathBuf::from(format!(
+            "{}.{}",
+            slug(&self.title),
+            self.output_format.extension()
+        ));
+        let timings_path = self.timings_path(dir);
+        let previous = AudiobookOutput::load(&timings_path).ok();
+        let changes = match &previous {
+            Some(previous) => self.changes(previous),
+            None => vec![ChapterChange::Added; self.chapters.len()],
+        };
+        // The earlier timings with every chapter generated so far swapped in
+        let mut progress = previous.unwrap_or_else(|| AudiobookOutput {
+            title: self.title.clone(),
+            output_format: self.output_format.as_str().to_string(),
+            combined: combined_file.clone(),
+            chapters: Vec::new(),
+            warnings: Vec::new(),
+        });
+
+        let mut combined = Vec::new();
+        let mut chapters = Vec::new();
+        let mut warnings = Vec::new();
+        let mut position = Duration::ZERO;
+        for (index, chapter) in self.chapters.iter().enumerate() {
+            let file = self.chapter_file_name(index);
+            let path = dir.join(&file);
+
+            // Files of chapters not in the timings are from an interrupted run
+            let reusable = !matches!(changes[index], ChapterChange::Changed { .. });
+            let size = progress
+                .chapters
+                .iter()
+                .find(|c| c.file == file)
+                .and_then(|c| c.size);
+            let intact = |audio: &[u8]| {
+                !audio.is_empty()
+                    && size.is_none_or(|size| size == audio.len() as u64)
+                    && audio::strip_container(audio, self.output_format).is_ok()
+            };
+            let (audio, character_count, cached) = match std::fs::read(&path) {
+                Ok(audio) if reusable && intact(&audio) => {
+                    tracing::debug!(chapter = %chapter.title, "reusing rendered chapter");
+                    (audio, None, true)
+                }
+                _ => {
+                    let rendered = render_with_retries(
+                        client,
+                        &chapter.dialogue,
+                        self.output_format,
+                        &self.retry,
+                        self.seed,
+                        item_key(&self.idempotency_key, index).as_deref(),
+                        &chapter.title,
+                    )
+                    .await?;
+                    write_atomically(&path, &rendered.audio)?;
+                    warnings.extend(rendered.warnings);
+                    (rendered.audio, rendered.character_count, false)
+                }
+            };
+
+            let length = audio::duration(&audio, self.output_format).unwrap_or_else(|| {
+                let turns: Vec<_> = chapter
+                    .dialogue
+                    .lines
+                    .iter()
+                    .filter_map(|line| match line {
+                        crate::DialogueLine::Turn(input)
+                        | crate::DialogueLine::LocalizedTurn(input, _) => Some(input.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                warnings.push(Warning::EstimatedTiming {
+                    item: format!("chapter \"{}\"", chapter.title),
+                });
+                crate::estimate::dialogue_duration(&turns)
+            });
+            let timing = ChapterTiming {
+                title: chapter.title.clone(),
+                file,
+                start: position.as_secs_f64(),
+                end: (position + length).as_secs_f64(),
+                character_count,
+                cached,
+                fingerprint: self.fingerprint(index),
+                line_fingerprints: self.line_fingerprints(index),
+                size: Some(audio.len() as u64),
+            };
+            if !cached {
+                match progress.chapters.iter_mut().find(|c| c.file == timing.file) {
+                    Some(earlier) => *earlier = timing.clone(),
+                    None => progress.chapters.push(timing.clone()),
+                }
+                write_atomically(
+                    &timings_path,
+                    serde_json::to_string_pretty(&progress)?.as_bytes(),
+                )?;
+            }
+            chapters.push(timing);
+            position += length;
+            combined.extend_from_slice(audio::strip_container(&audio, self.output_format)?);
+        }
+
+        if let OutputFormat::Wav { sample_rate } = self.output_format {
+            combined = audio::wav(&combined, sample_rate);
+        }
+        write_atomically(&dir.join(&combined_file), &combined)?;
+
+        let output = AudiobookOutput {
+            title: self.title.clone(),
+            output_format: self.output_format.as_str().to_string(),
+            combined: combined_file,
+            chapters,
+            warnings,
+        };
+        write_atomically(
+            &timings_path,
+            serde_json::to_string_pretty(&output)?.as_bytes(),
+        )?;
+        Ok(output)
+    }
+}
+
+/// Write to a temporary file next to `path` first, then move it into place,
+/// so a crash never leaves a truncated file that a later run would reuse
+fn write_atomically(path: &Path, contents: &[u8]) -> Result<(), ElevenLabsTTDError> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    std::fs::write(&tmp_path, contents)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Indices in `lines` outside the prefix and suffix shared with `earlier`
+fn changed_lines(lines: &[String], earlier: &[String]) -> Vec<usize> {
+    let prefix = lines
+        .iter()
+        .zip(earlier)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = lines[prefix..]
+        .iter()
+        .rev()
+        .zip(earlier[prefix.min(earlier.len())..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    (prefix..lines.len() - suffix).collect()
+}
+
+/// First 16 hex digits of the SHA-256 of `bytes`
+fn hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .take(8)
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Render a dialogue, retrying errors that may go away as `retry` says.
+///
+/// Only errors where nothing was generated are retried, so a retry can't bill
+/// twice, unless a seed is set and a repeat generation yields the same audio, or
+/// an idempotency key is set and a repeat returns the original.
+async fn render_with_retries(
+    client: &ElevenLabsTTDClient,
+    dialogue: &Dialogue,
+    output_format: OutputFormat,
+    retry: &RetryPolicy,
+    seed: Option<u32>,
+    idempotency_key: Option<&str>,
+    label: &str,
+) -> Result<TTDAudio, ElevenLabsTTDError> {
+    let repeatable = seed.is_some() || idempotency_key.is_some();
+    let started = std::time::Instant::now();
+    let mut attempt = 1;
+    loop {
+        let options = RequestOptions {
+            idempotency_key: idempotency_key.map(str::to_string),
+            ..RequestOptions::default()
+        };
+        let error = match client
+            .render_dialogue_with(dialogue, output_format, None, seed, &options)
+            .await
+        {
+            Err(e) if e.is_safe_to_retry() || (repeatable && e.is_retryable()) => e,
+            Err(e) => {
+                client.report_error(&e, "render_dialogue", Some(label), attempt);
+                return Err(e);
+            }
+            result => return result,
+        };
+        let delay = error
+            .retry_after()
+            .map(Duration::from_secs)
+            .unwrap_or_else(|| retry.delay(attempt));
+        if !retry.should_retry(attempt, started.elapsed(), delay) {
+            client.report_error(&error, "render_dialogue", Some(label), attempt);
+            return Err(error);
+        }
+        tracing::warn!(part = %label, attempt, %error, "retrying render");
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Idempotency key of the `index`th item rendered under the prefix `key`
+fn item_key(key: &Option<String>, index: usize) -> Option<String> {
+    key.as_ref().map(|key| format!("{}-{}", key, index + 1))
+}
+
+/// Many independent dialogues, e.g. from [`Dialogue::batch_from_csv`] or
+/// [`Dialogue::scenes_from_markdown`], rendered one after the other.
+///
+/// A clone is a separate batch with its own [`BatchControl`].
+#[derive(Debug)]
+pub struct Batch {
+    pub items: Vec<(String, Dialogue)>,
+    pub output_format: OutputFormat,
+    pub retry: RetryPolicy,
+    // Seed for every request; also allows retrying errors that may have been billed.
+    pub seed: Option<u32>,
+    // Prefix of the idempotency keys of every request; likewise allows retrying.
+    pub idempotency_key: Option<String>,
+    control: BatchControl,
+}
+
+impl Clone for Batch {
+    fn clone(&self) -> Self {
+        Self {
+            items: self.items.clone(),
+            output_format: self.output_format,
+            retry: self.retry,
+            seed: self.seed,
+            idempotency_key: self.idempotency_key.clone(),
+            control: BatchControl::default(),
+        }
+    }
+}
+
+/// Pauses, resumes and inspects a rendering [`Batch`] from another task.
+/// Clones control the same batch.
+#[derive(Debug, Clone)]
+pub struct BatchControl {
+    paused: Arc<tokio::sync::watch::Sender<bool>>,
+    progress: Arc<Mutex<Progress>>,
+}
+
+/// Snapshot of a batch's progress, from [`BatchControl::status`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchStatus {
+    pub queued: usize,
+    pub running: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    // Characters the API reported billing for the rendered items.
+    pub characters: u64,
+    // Text length of rendered items the API reported no count for, e.g. ones
+    // served from the dedupe store or the sandbox.
+    pub estimated_characters: u64,
+    // Time left at the average pace of the items finished so far, `None` until
+    // one has finished.
+    pub eta: Option<Duration>,
+}
+
+#[derive(Debug, Default)]
+struct Progress {
+    queued: usize,
+    running: usize,
+    succeeded: usize,
+    failed: usize,
+    characters: u64,
+    estimated_characters: u64,
+    // Time spent rendering finished items, excluding pauses.
+    busy: Duration,
+    // Items finished in `busy`.
+    timed: u32,
+}
+
+impl Default for BatchControl {
+    fn default() -> Self {
+        Self {
+            paused: Arc::new(tokio::sync::watch::Sender::new(false)),
+            progress: Arc::default(),
+        }
+    }
+}
+
+impl BatchControl {
+    /// Hold the queued items once the running one finishes, e.g. when the
+    /// monthly quota is nearly exhausted
+    pub fn pause(&self) {
+        self.paused.send_replace(true);
+    }
+
+    /// Continue with the queued items
+    pub fn resume(&self) {
+        self.paused.send_replace(false);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+
+    /// Number of items waiting to start
+    pub fn queued(&self) -> usize {
+        self.progress.lock().unwrap().queued
+    }
+
+    /// Counts, characters consumed and estimated time left
+    pub fn status(&self) -> BatchStatus {
+        let progress = self.progress.lock().unwrap();
+        let remaining = (progress.queued + progress.running) as u32;
+        BatchStatus {
+            queued: progress.queued,
+            running: progress.running,
+            succeeded: progress.succeeded,
+            failed: progress.failed,
+            characters: progress.characters,
+            estimated_characters: progress.estimated_characters,
+            eta: (progress.timed > 0).then(|| progress.busy / progress.timed * remaining),
+        }
+    }
+
+    fn start_item(&self) {
+        let mut progress = self.progress.lock().unwrap();
+        progress.queued = progress.queued.saturating_sub(1);
+        progress.running += 1;
+    }
+
+    /// Count an item as done, with its audio and script when it rendered
+    fn finish_item(&self, took: Duration, rendered: Option<(&TTDAudio, &Dialogue)>) {
+        let mut progress = self.progress.lock().unwrap();
+        progress.running -= 1;
+        progress.busy += took;
+        progress.timed += 1;
+        match rendered {
+            Some((audio, dialogue)) => {
+                progress.succeeded += 1;
+                match audio.character_count {
+                    Some(billed) => progress.characters += billed,
+                    None => progress.estimated_characters += billable_characters(dialogue),
+                }
+            }
+            None => progress.failed += 1,
+        }
+    }
+
+    /// Wait until the batch isn't paused
+    async fn wait_until_resumed(&self) {
+        let mut paused = self.paused.subscribe();
+        // The sender lives as long as `self`, so this can't fail
+        let _ = paused.wait_for(|paused| !paused).await;
+    }
+}
+
+/// A dialogue of a [`Batch`] that rendered
+#[derive(Debug, Clone)]
+pub struct CompletedItem {
+    // Position in the batch.
+    pub index: usize,
+    pub name: String,
+    pub audio: TTDAudio,
+}
+
+/// A dialogue of a [`Batch`] that failed after every attempt
+#[derive(Debug)]
+pub struct FailedItem {
+    // Position in the batch.
+    pub index: usize,
+    pub name: String,
+    pub dialogue: Dialogue,
+    pub error: ElevenLabsTTDError,
+}
+
+/// Outcome of [`Batch::render`]: what rendered and what failed, in batch order
+#[derive(Debug)]
+pub struct BatchResult {
+    pub completed: Vec<CompletedItem>,
+    pub failed: Vec<FailedItem>,
+    output_format: OutputFormat,
+    retry: RetryPolicy,
+    seed: Option<u32>,
+    idempotency_key: Option<String>,
+    control: BatchControl,
+}
+
+impl Batch {
+    pub fn new<I, S>(items: I) -> Self
+    where
+        I: IntoIterator<Item = (S, Dialogue)>,
+        S: Into<String>,
+    {
+        Self {
+            items: items
+                .into_iter()
+                .map(|(name, dialogue)| (name.into(), dialogue))
+                .collect(),
+            output_format: OutputFormat::default(),
+            retry: RetryPolicy::default(),
+            seed: None,
+            idempotency_key: None,
+            control: BatchControl::default(),
+        }
+    }
+
+    /// Handle to pause and resume the batch while it renders, also used by
+    /// [`BatchResult::resume`]
+    pub fn control(&self) -> BatchControl {
+        self.control.clone()
+    }
+
+    /// Set the output format of every item
+    pub fn output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+
+    /// Set how many times an item is attempted before it counts as failed
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.retry = self.retry.max_attempts(max_attempts);
+        self
+    }
+
+    /// Set the wait before the first retry of an item
+    pub fn retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry = self.retry.base_delay(retry_delay);
+        self
+    }
+
+    /// Set the backoff, jitter and time budget of retries
+    pub fn retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Generate with a seed, which also lets timeouts and server errors be retried
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Send idempotency keys starting with `key`, followed by the item's number,
+    /// which also lets timeouts and server errors be retried, here and by
+    /// [`BatchResult::resume`]. Use a new key for each batch.
+    pub fn idempotency_key<S: Into<String>>(mut self, key: S) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Render every item. A failing item doesn't stop the batch: it ends up in
+    /// [`BatchResult::failed`], to be retried with [`BatchResult::resume`].
+    pub async fn render(self, client: &ElevenLabsTTDClient) -> BatchResult {
+        let pending = self
+            .items
+            .into_iter()
+            .enumerate()
+            .map(|(index, (name, dialogue))| (index, name, dialogue))
+            .collect();
+        BatchResult {
+            completed: Vec::new(),
+            failed: Vec::new(),
+            output_format: self.output_format,
+            retry: self.retry,
+            seed: self.seed,
+            idempotency_key: self.idempotency_key,
+            control: self.control,
+        }
+        .render(client, pending)
+        .await
+    }
+}
+
+impl BatchResult {
+    /// Whether every item rendered
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Render the failed items again, keeping the completed ones
+    pub async fn resume(mut self, client: &ElevenLabsTTDClient) -> Self {
+        let pending = std::mem::take(&mut self.failed)
+            .into_iter()
+            .map(|item| (item.index, item.name, item.dialogue))
+            .collect();
+        self.render(client, pending).await
+    }
+
+    /// The completed items, or the first failure if any item failed
+    pub fn into_result(self) -> Result<Vec<CompletedItem>, ElevenLabsTTDError> {
+        match self.failed.into_iter().next() {
+            Some(item) => Err(item.error),
+            None => Ok(self.completed),
+        }
+    }
+
+    async fn render(
+        mut self,
+        client: &ElevenLabsTTDClient,
+        pending: Vec<(usize, String, Dialogue)>,
+    ) -> Self {
+        {
+            let mut progress = self.control.progress.lock().unwrap();
+            progress.queued = pending.len();
+            progress.succeeded = self.completed.len();
+            progress.failed = self.failed.len();
+        }
+        for (index, name, dialogue) in pending {
+            if self.control.is_paused() {
+                tracing::info!(queued = self.control.queued(), "batch paused");
+                self.control.wait_until_resumed().await;
+            }
+            self.control.start_item();
+            let started = Instant::now();
+            let rendered = render_with_retries(
+                client,
+                &dialogue,
+                self.output_format,
+                &self.retry,
+                self.seed,
+                item_key(&self.idempotency_key, index).as_deref(),
+                &name,
+            )
+            .await;
+            match rendered {
+                Ok(audio) => {
+                    self.control
+                        .finish_item(started.elapsed(), Some((&audio, &dialogue)));
+                    self.completed.push(CompletedItem { index, name, audio });
+                }
+                Err(error) => {
+                    self.control.finish_item(started.elapsed(), None);
+                    tracing::warn!(item = %name, %error, "batch item failed");
+                    self.failed.push(FailedItem {
+                        index,
+                        name,
+                        dialogue,
+                        error,
+                    });
+                }
+            }
+        }
+        self.completed.sort_by_key(|item| item.index);
+        self
+    }
+}
+
+/// Default sample rate episodes are rendered and mixed at
+pub const DEFAULT_EPISODE_SAMPLE_RATE: u32 = 24000;
+
+/// Role of a part in an [`Episode`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PartKind {
+    Intro,
+    Segment,
+    Ad,
+    Outro,
+}
+
+/// Where a part's audio comes from
+#[derive(Debug, Clone)]
+pub enum PartSource {
+    /// Generated with [`ElevenLabsTTDClient::render_dialogue`]
+    Dialogue(Dialogue),
+    /// An audio file, e.g. a pre-recorded ad or jingle: a 16-bit PCM WAV file, or
+    /// with the `ffmpeg` feature any file ffmpeg reads, such as MP3
+    File(PathBuf),
+    /// A file in one of the API's output formats, e.g. a headerless `pcm_24000`
+    /// or μ-law recording; MP3 and Opus need the `ffmpeg` feature
+    Audio(PathBuf, OutputFormat),
+}
+
+impl From<Dialogue> for PartSource {
+    fn from(dialogue: Dialogue) -> Self {
+        PartSource::Dialogue(dialogue)
+    }
+}
+
+impl From<PathBuf> for PartSource {
+    fn from(path: PathBuf) -> Self {
+        PartSource::File(path)
+    }
+}
+
+impl From<&Path> for PartSource {
+    fn from(path: &Path) -> Self {
+        PartSource::File(path.to_path_buf())
+    }
+}
+
+/// One part of an [`Episode`]
+#[derive(Debug, Clone)]
+pub struct EpisodePart {
+    pub kind: PartKind,
+    pub label: String,
+    pub source: PartSource,
+}
+
+/// A podcast episode: intro, dialogue segments, ad slots and outro in order,
+/// rendered at one sample rate, each part normalized to the same level, and
+/// stitched into one file with a timing manifest.
+///
+/// [`Self::render_wav`] writes the episode with no other requirements; MP3
+/// output ([`Self::render_mp3`]) needs the `ffmpeg` feature and an ffmpeg binary.
+#[derive(Debug, Clone)]
+pub struct Episode {
+    pub title: String,
+    pub parts: Vec<EpisodePart>,
+    pub sample_rate: u32,
+    // Loudness every part is normalized to, in dBFS RMS.
+    pub level_db: f32,
+    // Silence between consecutive parts.
+    pub gap: Duration,
+    pub retry: RetryPolicy,
+    // Seed for every request; also allows retrying errors that may have been billed.
+    pub seed: Option<u32>,
+    // Prefix of the idempotency keys of every request; likewise allows retrying.
+    pub idempotency_key: Option<String>,
+}
+
+/// Position of one part in a rendered [`Episode`]
+#[derive(Debug, Clone, Serialize)]
+pub struct PartTiming {
+    pub kind: PartKind,
+    pub label: String,
+    // Position in the episode, in seconds.
+    pub start: f64,
+    pub end: f64,
+}
+
+/// Timing manifest written next to a rendered [`Episode`]
+#[derive(Debug, Clone, Serialize)]
+pub struct EpisodeManifest {
+    pub title: String,
+    pub sample_rate: u32,
+    pub duration: f64,
+    pub parts: Vec<PartTiming>,
+}
+
+impl Episode {
+    pub fn new<S: Into<String>>(title: S) -> Self {
+        Self {
+            title: title.into(),
+            parts: Vec::new(),
+            sample_rate: DEFAULT_EPISODE_SAMPLE_RATE,
+            level_db: audio::DEFAULT_NORMALIZE_DB,
+            gap: Duration::ZERO,
+            retry: RetryPolicy::default(),
+            seed: None,
+            idempotency_key: None,
+        }
+    }
+
+    fn part<S: Into<String>, P: Into<PartSource>>(
+        mut self,
+        kind: PartKind,
+        label: S,
+        source: P,
+    ) -> Self {
+        self.parts.push(EpisodePart {
+            kind,
+            label: label.into(),
+            source: source.into(),
+        });
+        self
+    }
+
+    /// Add the intro
+    pub fn intro<P: Into<PartSource>>(self, source: P) -> Self {
+        self.part(PartKind::Intro, "Intro", source)
+    }
+
+    /// Add a dialogue segment
+    pub fn segment<S: Into<String>, P: Into<PartSource>>(self, label: S, source: P) -> Self {
+        self.part(PartKind::Segment, label, source)
+    }
+
+    /// Add an ad slot
+    pub fn ad<S: Into<String>, P: Into<PartSource>>(self, label: S, source: P) -> Self {
+        self.part(PartKind::Ad, label, source)
+    }
+
+    /// Add the outro
+    pub fn outro<P: Into<PartSource>>(self, source: P) -> Self {
+        self.part(PartKind::Outro, "Outro", source)
+    }
+
+    /// Set the sample rate parts are rendered at; one of the `pcm_*` rates
+    pub fn sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    /// Set the level every part is normalized to, in dBFS RMS
+    pub fn level_db(mut self, level_db: f32) -> Self {
+        self.level_db = level_db;
+        self
+    }
+
+    /// Set the silence between consecutive parts
+    pub fn gap(mut self, gap: Duration) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Generate with a seed, which also lets timeouts and server errors be retried
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Send idempotency keys starting with `key`, followed by the part's number,
+    /// which also lets timeouts and server errors be retried. Use a new key for
+    /// each render.
+    pub fn idempotency_key<S: Into<String>>(mut self, key: S) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Render every part into mono samples at [`Self::sample_rate`] with their timings
+    pub async fn render(
+        &self,
+        client: &ElevenLabsTTDClient,
+    ) -> Result<(Vec<i16>, EpisodeManifest), ElevenLabsTTDError> {
+        let format = OutputFormat::ALL
+            .into_iter()
+            .find(|f| f.codec() == Codec::Pcm && f.sample_rate() == self.sample_rate)
+            .ok_or_else(|| {
+                ElevenLabsTTDError::ValidationError(format!(
+                    "No PCM output format at {} Hz",
+                    self.sample_rate
+                ))
+            })?;
+
+        let gap = vec![0i16; (self.gap.as_secs_f64() * self.sample_rate as f64).round() as usize];
+        let seconds = |samples: usize| samples as f64 / self.sample_rate as f64;
+        let mut samples = Vec::new();
+        let mut parts = Vec::new();
+        for (index, part) in self.parts.iter().enumerate() {
+            let mut part_samples = match &part.source {
+                PartSource::Dialogue(dialogue) => {
+                    let rendered = render_with_retries(
+                        client,
+                        dialogue,
+                        format,
+                        &self.retry,
+                        self.seed,
+                        item_key(&self.idempotency_key, index).as_deref(),
+                        &part.label,
+                    )
+                    .await?;
+                    audio::i16_samples(&rendered.audio, format)?
+                }
+                PartSource::File(path) => self.read_file(path).await?,
+                PartSource::Audio(path, format)
+                    if matches!(format.codec(), Codec::Mp3 | Codec::Opus) =>
+                {
+                    self.read_file(path).await?
+                }
+                PartSource::Audio(path, format) => {
+                    let file = std::fs::read(path)?;
+                    let samples = audio::i16_samples(&file, *format)?;
+                    self.at_sample_rate(samples, format.sample_rate(), path)?
+                }
+            };
+            audio::normalize(&mut part_samples, self.level_db);
+
+            if index > 0 {
+                samples.extend_from_slice(&gap);
+            }
+            let start = seconds(samples.len());
+            samples.extend(part_samples);
+            parts.push(PartTiming {
+                kind: part.kind,
+                label: part.label.clone(),
+                start,
+                end: seconds(samples.len()),
+            });
+        }
+
+        let manifest = EpisodeManifest {
+            title: self.title.clone(),
+            sample_rate: self.sample_rate,
+            duration: seconds(samples.len()),
+            parts,
+        };
+        Ok((samples, manifest))
+    }
+
+    /// Render the episode to a WAV file at `path`, with its manifest next to it
+    /// (`episode.wav` → `episode.timing.json`)
+    pub async fn render_wav<P: AsRef<Path>>(
+        &self,
+        client: &ElevenLabsTTDClient,
+        path: P,
+    ) -> Result<EpisodeManifest, ElevenLabsTTDError> {
+        let (samples, manifest) = self.render(client).await?;
+        let pcm: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        std::fs::write(&path, audio::wav(&pcm, self.sample_rate))?;
+        write_timing(&manifest, path.as_ref())?;
+        Ok(manifest)
+    }
+
+    /// Render the episode to a 128 kbps MP3 file at `path` by piping it through
+    /// `ffmpeg`, with its manifest next to it (`episode.mp3` → `episode.timing.json`)
+    #[cfg(feature = "ffmpeg")]
+    pub async fn render_mp3<P: AsRef<Path>>(
+        &self,
+        client: &ElevenLabsTTDClient,
+        path: P,
+    ) -> Result<EpisodeManifest, ElevenLabsTTDError> {
+        use tokio::io::AsyncWriteExt;
+
+        let (samples, manifest) = self.render(client).await?;
+        let pcm: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+
+        let mut ffmpeg = tokio::process::Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error", "-f", "s16le", "-ac", "1", "-ar"])
+            .arg(self.sample_rate.to_string())
+            .args(["-i", "pipe:0", "-c:a", "libmp3lame", "-b:a", "128k"])
+            .arg(path.as_ref())
+            .stdin(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = ffmpeg.stdin.take() {
+            stdin.write_all(&pcm).await?;
+        }
+        let output = ffmpeg.wait_with_output().await?;
+        if !output.status.success() {
+            return Err(std::io::Error::other(format!(
+                "ffmpeg failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+            .into());
+        }
+
+        write_timing(&manifest, path.as_ref())?;
+        Ok(manifest)
+    }
+
+    /// Samples of a file part at the episode's rate: WAV files are read natively,
+    /// anything else is decoded with ffmpeg
+    async fn read_file(&self, path: &Path) -> Result<Vec<i16>, ElevenLabsTTDError> {
+        let file = std::fs::read(path)?;
+        if file.starts_with(b"RIFF") {
+            let (samples, sample_rate) = audio::read_wav(&file)?;
+            return self.at_sample_rate(samples, sample_rate, path);
+        }
+
+        #[cfg(feature = "ffmpeg")]
+        {
+            let output = tokio::process::Command::new("ffmpeg")
+                .args(["-loglevel", "error", "-i"])
+                .arg(path)
+                .args(["-f", "s16le", "-ac", "1", "-ar"])
+                .arg(self.sample_rate.to_string())
+                .arg("pipe:1")
+                .output()
+                .await?;
+            if !output.status.success() {
+                return Err(std::io::Error::other(format!(
+                    "ffmpeg failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ))
+                .into());
+            }
+            Ok(output
+                .stdout
+                .chunks_exact(2)
+                .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+                .collect())
+        }
+        #[cfg(not(feature = "ffmpeg"))]
+        Err(ElevenLabsTTDError::ValidationError(format!(
+            "{} isn't a 16-bit PCM WAV file; reading other formats needs the `ffmpeg` feature",
+            path.display()
+        )))
+    }
+
+    /// `samples` of a file part, resampled to the episode's rate when that's enabled
+    fn at_sample_rate(
+        &self,
+        samples: Vec<i16>,
+        sample_rate: u32,
+        path: &Path,
+    ) -> Result<Vec<i16>, ElevenLabsTTDError> {
+        if sample_rate == self.sample_rate {
+            return Ok(samples);
+        }
+
+        #[cfg(feature = "resample")]
+        {
+            tracing::debug!(
+                path = %path.display(),
+                from = sample_rate,
+                to = self.sample_rate,
+                "resampling part"
+            );
+            let pcm: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+            let resampled = audio::resample(&pcm, sample_rate, self.sample_rate)?;
+            Ok(resampled
+                .chunks_exact(2)
+                .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+                .collect())
+        }
+        #[cfg(not(feature = "resample"))]
+        Err(ElevenLabsTTDError::ValidationError(format!(
+            "{} is {} Hz, the episode is {} Hz",
+            path.display(),
+            sample_rate,
+            self.sample_rate
+        )))
+    }
+}
+
+fn write_timing(manifest: &EpisodeManifest, audio_path: &Path) -> Result<(), ElevenLabsTTDError> {
+    std::fs::write(
+        audio_path.with_extension("timing.json"),
+        serde_json::to_string_pretty(manifest)?,
+    )?;
+    Ok(())
+}
+
+#[cfg(feature = "id3")]
+impl AudiobookOutput {
+    /// ID3 tags with the book title and a chapter marker per chapter,
+    /// for the combined MP3 file
+    pub fn id3_tags(&self) -> crate::id3::Tags {
+        self.chapters.iter().fold(
+            crate::id3::Tags::new()
+                .title(&self.title)
+                .album(&self.title),
+            |tags, chapter| {
+                tags.chapter(
+                    &chapter.title,
+                    Duration::from_secs_f64(chapter.start),
+                    Duration::from_secs_f64(chapter.end),
+                )
+            },
+        )
+    }
+}
+
+#[cfg(feature = "ffmpeg")]
+impl AudiobookOutput {
+    /// Chapter markers in ffmpeg's `FFMETADATA1` format
+    pub fn ffmetadata(&self) -> String {
+        let escape = |text: &str| {
+            text.chars().fold(String::new(), |mut out, c| {
+                if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+                    out.push('\\');
+                }
+                out.push(c);
+                out
+            })
+        };
+
+        let mut metadata = format!(
+            ";FFMETADATA1\ntitle={}\nalbum={}\n",
+            escape(&self.title),
+            escape(&self.title)
+        );
+        for chapter in &self.chapters {
+            metadata.push_str(&format!(
+                "\n[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+                (chapter.start * 1000.0).round() as u64,
+                (chapter.end * 1000.0).round() as u64,
+                escape(&chapter.title)
+            ));
+        }
+        metadata
+    }
+
+    /// Mux the combined file in `dir` (where [`Audiobook::render`] wrote it) into an
+    /// AAC `.m4b` audiobook with embedded chapter markers, by running `ffmpeg`
+    pub async fn export_m4b<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        dir: P,
+        path: Q,
+    ) -> Result<(), ElevenLabsTTDError> {
+        let dir = dir.as_ref();
+        let format: OutputFormat = self.output_format.parse()?;
+
+        let metadata = dir.join(format!("{}.ffmetadata", slug(&self.title)));
+        std::fs::write(&metadata, self.ffmetadata())?;
+
+        // Headerless formats need their layout spelled out
+        let mut command = tokio::process::Command::new("ffmpeg");
+        command.arg("-y").arg("-loglevel").arg("error");
+        let raw = match format.codec() {
+            Codec::Pcm if !format.is_wav() => Some("s16le"),
+            Codec::Ulaw => Some("mulaw"),
+            Codec::Alaw => Some("alaw"),
+            _ => None,
+        };
+        if let Some(raw) = raw {
+            command
+                .args(["-f", raw, "-ac", "1", "-ar"])
+                .arg(format.sample_rate().to_string());
+        }
+        command
+            .arg("-i")
+            .arg(dir.join(&self.combined))
+            .arg("-i")
+            .arg(&metadata)
+            .args(["-map", "0:a", "-map_metadata", "1", "-map_chapters", "1"])
+            .args(["-c:a", "aac", "-b:a", "64k", "-f", "ipod"])
+            .arg(path.as_ref());
+
+        let output = command.output().await?;
+        std::fs::remove_file(&metadata)?;
+        if !output.status.success() {
+            return Err(std::io::Error::other(format!(
+                "ffmpeg failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Settings a [`Scene`] renders with in place of its project's
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SceneOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<TTDSettings>,
+    // Output format name, e.g. "mp3_44100_128".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u32>,
+}
+
+impl SceneOverrides {
+    /// Whether nothing is overridden
+    pub fn is_empty(&self) -> bool {
+        self.settings.is_none() && self.output_format.is_none() && self.seed.is_none()
+    }
+}
+
+/// A named dialogue of an episode or production, with its own overrides of the
+/// [`Project`] settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scene {
+    pub name: String,
+    pub dialogue: Dialogue,
+    #[serde(default, skip_serializing_if = "SceneOverrides::is_empty")]
+    pub overrides: SceneOverrides,
+}
+
+impl Scene {
+    pub fn new<S: Into<String>, D: Into<Dialogue>>(name: S, dialogue: D) -> Self {
+        Self {
+            name: name.into(),
+            dialogue: dialogue.into(),
+            overrides: SceneOverrides::default(),
+        }
+    }
+
+    /// Generate this scene with `settings` instead of the project's
+    pub fn settings(mut self, settings: TTDSettings) -> Self {
+        self.overrides.settings = Some(settings);
+        self
+    }
+
+    /// Render this scene in `output_format` instead of the project's
+    pub fn output_format(mut self, output_format: OutputFormat) -> Self {
+        self.overrides.output_format = Some(output_format.as_str().to_string());
+        self
+    }
+
+    /// Generate every turn of this scene with `seed`
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.overrides.seed = Some(seed);
+        self
+    }
+}
+
+/// Record of a scene render, written next to its audio
+/// (`01-cold-open.mp3` → `01-cold-open.manifest.json`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SceneManifest {
+    pub project: String,
+    pub scene: String,
+    pub output_format: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<TTDSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u32>,
+    // Voice IDs of the speakers, in order of first line.
+    pub speakers: Vec<String>,
+    // Same as the scene artifact's fingerprint.
+    pub fingerprint: String,
+    pub rendered_at: String,
+    pub character_count: Option<u64>,
+}
+
+impl SceneManifest {
+    /// Read a manifest written by [`Project::render`]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ElevenLabsTTDError> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+}
+
+/// A scene of a [`Project`] and the audio rendered for it, if any
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectScene {
+    #[serde(flatten)]
+    pub scene: Scene,
+    // The last render of this scene.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<Artifact>,
+}
+
+/// A rendered file and what it was rendered from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Artifact {
+    pub path: PathBuf,
+    // Hash of the dialogue, settings and output format the file was rendered from.
+    pub fingerprint: String,
+    // RFC 3339 timestamp of the render.
+    pub rendered_at: String,
+    pub character_count: Option<u64>,
+    // Where each turn's take sits in the file, for projects rendered per turn.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub turns: Vec<TurnTake>,
+}
+
+/// One turn's audio in a scene file rendered with [`Project::per_turn`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TurnTake {
+    // Index of the turn's line in the scene's dialogue.
+    pub line: usize,
+    // Byte range of the take in the file.
+    pub offset: u64,
+    pub length: u64,
+    // Seed the take was generated with, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u32>,
+}
+
+/// Audio of a rendered scene and what it took to generate it
+struct SceneAudio {
+    audio: Vec<u8>,
+    character_count: Option<u64>,
+    turns: Vec<TurnTake>,
+}
+
+/// Where a scene of a [`Project`] stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SceneStatus {
+    // Never rendered, or its file is gone.
+    Pending,
+    // Rendered, and the script hasn't changed since.
+    Rendered,
+    // Rendered, but the script, settings or output format changed since.
+    Stale,
+}
+
+/// A dialogue production saved as JSON: the scenes' scripts, the cast used to
+/// import them, generation settings and the files rendered so far, so work can
+/// be picked up where it was left.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Project {
+    pub name: String,
+    // Speaker names to voice IDs, used by `Project::script`.
+    #[serde(default)]
+    pub cast: SpeakerMap,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<TTDSettings>,
+    pub output_format: String,
+    // Generate every turn in its own request, see `Project::per_turn`.
+    #[serde(default)]
+    pub per_turn: bool,
+    // Write a turn index next to scene files, see `Project::turn_index`.
+    #[serde(default)]
+    pub turn_index: bool,
+    #[serde(default)]
+    pub scenes: Vec<ProjectScene>,
+}
+
+impl Project {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            cast: SpeakerMap::new(),
+            settings: None,
+            output_format: OutputFormat::default().as_str().to_string(),
+            per_turn: false,
+            turn_index: false,
+            scenes: Vec::new(),
+        }
+    }
+
+    /// Read a project saved with [`Self::save`]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ElevenLabsTTDError> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    /// Write the project as pretty-printed JSON
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ElevenLabsTTDError> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Cast a speaker name to a voice
+    pub fn cast<S: Into<String>, V: VoiceRef>(mut self, speaker: S, voice: V) -> Self {
+        self.cast
+            .insert(speaker.into(), voice.voice_id().to_string());
+        self
+    }
+
+    /// Set the settings every scene is generated with
+    pub fn settings(mut self, settings: TTDSettings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    /// Set the output format of every scene
+    pub fn output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format.as_str().to_string();
+        self
+    }
+
+    /// Generate each turn in its own request rather than runs of turns together,
+    /// recording where each take lands so bad takes can be replaced with
+    /// [`Self::regenerate_turns`]. Costs one request per turn, and turns lose the
+    /// context of their neighbours.
+    pub fn per_turn(mut self, enabled: bool) -> Self {
+        self.per_turn = enabled;
+        self
+    }
+
+    /// Write scene files rendered [`Self::per_turn`] with a
+    /// [`crate::writer::TurnIndex`] of where each turn starts next to them
+    /// (`01-intro.index.json`), so a server can answer range requests that start
+    /// at a given line. Kept up to date by [`Self::regenerate_turns`].
+    pub fn turn_index(mut self, enabled: bool) -> Self {
+        self.turn_index = enabled;
+        self
+    }
+
+    /// Add a scene, or replace the dialogue of the scene with that name
+    pub fn scene<S: Into<String>>(mut self, name: S, dialogue: Dialogue) -> Self {
+        self.set_scene(name, dialogue);
+        self
+    }
+
+    /// Add or replace a scene from a `SPEAKER: text` script, using the project's cast
+    pub fn script<S: Into<String>>(
+        mut self,
+        name: S,
+        script: &str,
+    ) -> Result<Self, ElevenLabsTTDError> {
+        let dialogue = Dialogue::parse(script, &self.cast)?;
+        self.set_scene(name, dialogue);
+        Ok(self)
+    }
+
+    /// Add a [`Scene`] with its overrides, or replace the scene with that name
+    pub fn add_scene(mut self, scene: Scene) -> Self {
+        self.set_scene_with(scene);
+        self
+    }
+
+    /// Add a scene, or replace the dialogue of the scene with that name, keeping
+    /// its artifact so the change shows as [`SceneStatus::Stale`]
+    pub fn set_scene<S: Into<String>>(&mut self, name: S, dialogue: Dialogue) {
+        let name = name.into();
+        match self
+            .scenes
+            .iter_mut()
+            .find(|entry| entry.scene.name == name)
+        {
+            Some(entry) => entry.scene.dialogue = dialogue,
+            None => self.scenes.push(ProjectScene {
+                scene: Scene::new(name, dialogue),
+                artifact: None,
+            }),
+        }
+    }
+
+    /// Add a [`Scene`], or replace the dialogue and overrides of the scene with
+    /// that name, keeping its artifact like [`Self::set_scene`]
+    pub fn set_scene_with(&mut self, scene: Scene) {
+        match self
+            .scenes
+            .iter_mut()
+            .find(|entry| entry.scene.name == scene.name)
+        {
+            Some(entry) => entry.scene = scene,
+            None => self.scenes.push(ProjectScene {
+                scene,
+                artifact: None,
+            }),
+        }
+    }
+
+    /// The scenes, in order
+    pub fn scenes(&self) -> impl Iterator<Item = &Scene> {
+        self.scenes.iter().map(|entry| &entry.scene)
+    }
+
+    /// Where the scene with that name stands, or `None` if there's no such scene
+    pub fn scene_status(&self, name: &str) -> Option<SceneStatus> {
+        let entry = self.scenes.iter().find(|entry| entry.scene.name == name)?;
+        Some(self.status_of(entry))
+    }
+
+    /// Name and status of every scene, in order
+    pub fn status(&self) -> Vec<(&str, SceneStatus)> {
+        self.scenes
+            .iter()
+            .map(|entry| (entry.scene.name.as_str(), self.status_of(entry)))
+            .collect()
+    }
+
+    /// Whether every scene is rendered and up to date
+    pub fn is_complete(&self) -> bool {
+        self.status()
+            .iter()
+            .all(|(_, status)| *status == SceneStatus::Rendered)
+    }
+
+    /// Render every scene that isn't [`SceneStatus::Rendered`] into `dir`
+    /// (`NN-<scene>.<ext>`, with a [`SceneManifest`] next to it), recording each
+    /// file as the scene's artifact. Returns the names of the scenes rendered.
+    ///
+    /// Artifacts are recorded as scenes finish, so on error the project still
+    /// holds the scenes done so far and can be saved and rendered again later.
+    pub async fn render<P: AsRef<Path>>(
+        &mut self,
+        client: &ElevenLabsTTDClient,
+        dir: P,
+    ) -> Result<Vec<String>, ElevenLabsTTDError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+
+        let mut rendered = Vec::new();
+        for index in 0..self.scenes.len() {
+            if self.status_of(&self.scenes[index]) == SceneStatus::Rendered {
+                continue;
+            }
+            let scene = &self.scenes[index].scene;
+            let output_format = self.format_of(scene)?;
+            let settings = self.settings_of(scene);
+            let seed = scene.overrides.seed;
+            let audio = if self.per_turn {
+                render_turns(client, &scene.dialogue, output_format, settings, seed).await
+            } else {
+                client
+                    .render_dialogue_with(
+                        &scene.dialogue,
+                        output_format,
+                        settings,
+                        seed,
+                        &RequestOptions::default(),
+                    )
+                    .await
+                    .map(|audio| SceneAudio {
+                        character_count: audio.character_count,
+                        audio: audio.audio,
+                        turns: Vec::new(),
+                    })
+            }
+            .inspect_err(|e| client.report_error(e, "render_dialogue", Some(&scene.name), 1))?;
+
+            let path = dir.join(format!(
+                "{:02}-{}.{}",
+                index + 1,
+                slug(&scene.name),
+                output_format.extension()
+            ));
+            self.write_scene(&path, &audio.audio, &audio.turns, scene, output_format)?;
+            let artifact = Artifact {
+                path,
+                fingerprint: self.fingerprint(scene),
+                rendered_at: chrono::Utc::now().to_rfc3339(),
+                character_count: audio.character_count,
+                turns: audio.turns,
+            };
+            self.write_manifest(scene, &artifact)?;
+            rendered.push(scene.name.clone());
+            self.scenes[index].artifact = Some(artifact);
+        }
+        Ok(rendered)
+    }
+
+    /// Generate the given turns of a scene rendered [`Self::per_turn`] again and
+    /// splice the new takes into its file in place of the old ones. `turns` index
+    /// the scene's turns (see [`Artifact::turns`]); each is generated with `seed`,
+    /// or else the seed of its last take.
+    pub async fn regenerate_turns(
+        &mut self,
+        client: &ElevenLabsTTDClient,
+        scene: &str,
+        turns: &[usize],
+        seed: Option<u32>,
+    ) -> Result<(), ElevenLabsTTDError> {
+        self.splice_turns(client, scene, turns, seed)
+            .await
+            .inspect_err(|e| client.report_error(e, "regenerate_turns", Some(scene), 1))
+    }
+
+    /// Turns of a scene rendered [`Self::per_turn`] whose take shows an
+    /// [`audio::AudioAnomaly`], for PCM, WAV, μ-law or A-law output
+    pub fn flagged_turns(&self, scene: &str) -> Result<Vec<usize>, ElevenLabsTTDError> {
+        let (entry, artifact) = self.rendered_per_turn(scene)?;
+        let api_format = self.format_of(&entry.scene)?.api_format();
+        let file = std::fs::read(&artifact.path)?;
+
+        let mut flagged = Vec::new();
+        for (index, take) in artifact.turns.iter().enumerate() {
+            let range = take.offset as usize..(take.offset + take.length) as usize;
+            let samples = audio::i16_samples(slice_of(&file, range, artifact)?, api_format)?;
+            if !audio::detect_anomalies(&samples, api_format.sample_rate()).is_empty() {
+                flagged.push(index);
+            }
+        }
+        Ok(flagged)
+    }
+
+    async fn splice_turns(
+        &mut self,
+        client: &ElevenLabsTTDClient,
+        scene: &str,
+        turns: &[usize],
+        seed: Option<u32>,
+    ) -> Result<(), ElevenLabsTTDError> {
+        let (entry, artifact) = self.rendered_per_turn(scene)?;
+        if let Some(index) = turns.iter().find(|index| **index >= artifact.turns.len()) {
+            return Err(ElevenLabsTTDError::ValidationError(format!(
+                "Scene {} has {} turns, no turn {}",
+                scene,
+                artifact.turns.len(),
+                index
+            )));
+        }
+        let output_format = self.format_of(&entry.scene)?;
+        let api_format = output_format.api_format();
+        let settings = self.settings_of(&entry.scene);
+        let client = client.without_error_sink();
+
+        let file = std::fs::read(&artifact.path)?;
+        let header = if output_format.is_wav() {
+            WAV_HEADER_LEN
+        } else {
+            0
+        };
+        let mut audio = Vec::with_capacity(file.len());
+        let mut takes = artifact.turns.clone();
+        let mut character_count = artifact.character_count;
+        // Copied up to here from the old file
+        let mut copied = header;
+        for (index, take) in takes.iter_mut().enumerate() {
+            let start = take.offset as usize;
+            audio.extend_from_slice(slice_of(&file, copied..start, artifact)?);
+            copied = start + take.length as usize;
+
+            let offset = (audio.len() + header) as u64;
+            if turns.contains(&index) {
+                take.seed = seed.or(take.seed);
+                let (part, count) = generate_turn(
+                    &client,
+                    &entry.scene.dialogue.lines[take.line],
+                    api_format,
+                    settings,
+                    take.seed,
+                )
+                .await?;
+                if let Some(count) = count {
+                    *character_count.get_or_insert(0) += count;
+                }
+                take.length = part.len() as u64;
+                audio.extend_from_slice(&part);
+            } else {
+                audio.extend_from_slice(slice_of(&file, start..copied, artifact)?);
+            }
+            take.offset = offset;
+        }
+        audio.extend_from_slice(slice_of(&file, copied..file.len(), artifact)?);
+        let audio = match output_format {
+            OutputFormat::Wav { sample_rate } => audio::wav(&audio, sample_rate),
+            _ => audio,
+        };
+        self.write_scene(&artifact.path, &audio, &takes, &entry.scene, output_format)?;
+
+        let index = self
+            .scenes
+            .iter()
+            .position(|entry| entry.scene.name == scene)
+            .expect("scene exists");
+        let artifact = self.scenes[index]
+            .artifact
+            .as_mut()
+            .expect("scene rendered");
+        artifact.turns = takes;
+        artifact.character_count = character_count;
+        artifact.rendered_at = chrono::Utc::now().to_rfc3339();
+        let entry = &self.scenes[index];
+        self.write_manifest(
+            &entry.scene,
+            entry.artifact.as_ref().expect("scene rendered"),
+        )
+    }
+
+    /// Write the file of `scene`, through a [`ChunkedWriter`] indexing its takes
+    /// when [`Self::turn_index`] is set and the scene was rendered per turn
+    fn write_scene(
+        &self,
+        path: &Path,
+        audio: &[u8],
+        takes: &[TurnTake],
+        scene: &Scene,
+        output_format: OutputFormat,
+    ) -> Result<(), ElevenLabsTTDError> {
+        let Some(first) = takes.first().filter(|_| self.turn_index) else {
+            std::fs::write(path, audio)?;
+            return Ok(());
+        };
+
+        let mut writer = ChunkedWriter::create(path)?.with_index();
+        let mut header = 0;
+        if let OutputFormat::Wav { sample_rate } = output_format {
+            writer = writer.wav(sample_rate)?;
+            header = WAV_HEADER_LEN;
+        }
+        // Effects and pauses before the first turn belong to no turn
+        writer.write_chunk(&audio[header..first.offset as usize])?;
+        for (index, take) in takes.iter().enumerate() {
+            let end = takes
+                .get(index + 1)
+                .map_or(audio.len(), |next| next.offset as usize);
+            let label = match &scene.dialogue.lines[take.line] {
+                DialogueLine::Turn(input) | DialogueLine::LocalizedTurn(input, _) => {
+                    Some(input.text.as_str())
+                }
+                _ => None,
+            };
+            writer.write_turn(label, &audio[take.offset as usize..end])?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Write the [`SceneManifest`] of `scene` next to its audio
+    fn write_manifest(&self, scene: &Scene, artifact: &Artifact) -> Result<(), ElevenLabsTTDError> {
+        let manifest = SceneManifest {
+            project: self.name.clone(),
+            scene: scene.name.clone(),
+            output_format: self.format_of(scene)?.as_str().to_string(),
+            settings: self.settings_of(scene).cloned(),
+            seed: scene.overrides.seed,
+            speakers: speakers(&scene.dialogue),
+            fingerprint: artifact.fingerprint.clone(),
+            rendered_at: artifact.rendered_at.clone(),
+            character_count: artifact.character_count,
+        };
+        std::fs::write(
+            artifact.path.with_extension("manifest.json"),
+            serde_json::to_vec_pretty(&manifest)?,
+        )?;
+        Ok(())
+    }
+
+    /// The scene with that name and its artifact, if it is up to date and was
+    /// rendered per turn
+    fn rendered_per_turn(
+        &self,
+        scene: &str,
+    ) -> Result<(&ProjectScene, &Artifact), ElevenLabsTTDError> {
+        let entry = self
+            .scenes
+            .iter()
+            .find(|entry| entry.scene.name == scene)
+            .ok_or_else(|| {
+                ElevenLabsTTDError::ValidationError(format!("No scene named {}", scene))
+            })?;
+        match &entry.artifact {
+            Some(artifact) if self.status_of(entry) == SceneStatus::Rendered => {
+                if artifact.turns.is_empty() {
+                    return Err(ElevenLabsTTDError::ValidationError(format!(
+                        "Scene {} was not rendered per turn",
+                        scene
+                    )));
+                }
+                Ok((entry, artifact))
+            }
+            _ => Err(ElevenLabsTTDError::ValidationError(format!(
+                "Scene {} must be rendered and up to date",
+                scene
+            ))),
+        }
+    }
+
+    /// Output format of `scene`, its override or else the project's
+    fn format_of(&self, scene: &Scene) -> Result<OutputFormat, ElevenLabsTTDError> {
+        let name = scene
+            .overrides
+            .output_format
+            .as_deref()
+            .unwrap_or(&self.output_format);
+        name.parse()
+    }
+
+    fn settings_of<'a>(&'a self, scene: &'a Scene) -> Option<&'a TTDSettings> {
+        scene.overrides.settings.as_ref().or(self.settings.as_ref())
+    }
+
+    fn status_of(&self, entry: &ProjectScene) -> SceneStatus {
+        match &entry.artifact {
+            Some(artifact) if artifact.path.exists() => {
+                if artifact.fingerprint == self.fingerprint(&entry.scene) {
+                    SceneStatus::Rendered
+                } else {
+                    SceneStatus::Stale
+                }
+            }
+            _ => SceneStatus::Pending,
+        }
+    }
+
+    fn fingerprint(&self, scene: &Scene) -> String {
+        let output_format = scene
+            .overrides
+            .output_format
+            .as_ref()
+            .unwrap_or(&self.output_format);
+        let mut inputs = vec![
+            serde_json::json!(scene.dialogue),
+            serde_json::json!(self.settings_of(scene)),
+            serde_json::json!(output_format),
+        ];
+        // Only appended when set, so renders from before seeds could be
+        // overridden keep their fingerprints
+        if let Some(seed) = scene.overrides.seed {
+            inputs.push(serde_json::json!(seed));
+        }
+        if self.per_turn {
+            inputs.push(serde_json::json!({ "per_turn": true }));
+        }
+        hash(serde_json::Value::Array(inputs).to_string().as_bytes())
+    }
+}
+
+/// Size of the header [`audio::wav`] writes
+const WAV_HEADER_LEN: usize = 44;
+
+/// `range` of a scene file, or an error if the file was cut short or edited
+/// since `artifact` recorded where its takes sit
+fn slice_of<'a>(
+    file: &'a [u8],
+    range: std::ops::Range<usize>,
+    artifact: &Artifact,
+) -> Result<&'a [u8], ElevenLabsTTDError> {
+    file.get(range).ok_or_else(|| {
+        ElevenLabsTTDError::ValidationError(format!(
+            "{} no longer matches its artifact; render the scene again",
+            artifact.path.display()
+        ))
+    })
+}
+
+/// Render `dialogue` with one request per turn, recording where each take lands
+async fn render_turns(
+    client: &ElevenLabsTTDClient,
+    dialogue: &Dialogue,
+    output_format: OutputFormat,
+    settings: Option<&TTDSettings>,
+    seed: Option<u32>,
+) -> Result<SceneAudio, ElevenLabsTTDError> {
+    // Failures are reported once, by the caller
+    let client = client.without_error_sink();
+    let api_format = output_format.api_format();
+    let gap = audio::silence(client.segment_gap, api_format);
+
+    let mut out = Vec::new();
+    let mut turns = Vec::new();
+    let mut character_count = None;
+    for (line, entry) in dialogue.lines.iter().enumerate() {
+        if line > 0 && !client.segment_gap.is_zero() {
+            out.extend_from_slice(&gap);
+        }
+        let part = match entry {
+            DialogueLine::Turn(_) | DialogueLine::LocalizedTurn(..) => {
+                let (part, count) =
+                    generate_turn(&client, entry, api_format, settings, seed).await?;
+                if let Some(count) = count {
+                    *character_count.get_or_insert(0) += count;
+                }
+                turns.push(TurnTake {
+                    line,
+                    offset: out.len() as u64,
+                    length: part.len() as u64,
+                    seed,
+                });
+                part
+            }
+            DialogueLine::Sfx(cue) => client.sound_effect(cue, api_format).await?,
+            DialogueLine::Pause(duration) => audio::silence(*duration, api_format),
+        };
+        out.extend_from_slice(&part);
+    }
+
+    if let OutputFormat::Wav { sample_rate } = output_format {
+        out = audio::wav(&out, sample_rate);
+        for take in &mut turns {
+            take.offset += WAV_HEADER_LEN as u64;
+        }
+    }
+    Ok(SceneAudio {
+        audio: out,
+        character_count,
+        turns,
+    })
+}
+
+/// Generate the turn on `line` on its own, returning its audio and billed characters
+async fn generate_turn(
+    client: &ElevenLabsTTDClient,
+    line: &DialogueLine,
+    api_format: OutputFormat,
+    settings: Option<&TTDSettings>,
+    seed: Option<u32>,
+) -> Result<(Vec<u8>, Option<u64>), ElevenLabsTTDError> {
+    let (input, language) = match line {
+        DialogueLine::Turn(input) => (input, None),
+        DialogueLine::LocalizedTurn(input, language) => (input, Some(language)),
+        _ => {
+            return Err(ElevenLabsTTDError::ValidationError(
+                "Only turns can be regenerated".to_string(),
+            ));
+        }
+    };
+
+    let mut builder = client
+        .text_to_dialogue(vec![input.clone()])
+        .output_format(api_format);
+    if let Some(language) = language {
+        builder = builder.language_code(language.as_str());
+    }
+    if let Some(settings) = settings {
+        builder = builder.settings(settings.clone());
+    }
+    if let Some(seed) = seed {
+        builder = builder.seed(seed);
+    }
+    let audio = builder.execute().await?;
+    let character_count = audio.character_count;
+    Ok((audio.into_bytes(), character_count))
+}
+
+/// Voice IDs of the turns of `dialogue`, in order of first line
+fn speakers(dialogue: &Dialogue) -> Vec<String> {
+    let mut speakers: Vec<String> = Vec::new();
+    for line in &dialogue.lines {
+        if let DialogueLine::Turn(input) | DialogueLine::LocalizedTurn(input, _) = line
+            && !speakers.contains(&input.voice_id)
+        {
+            speakers.push(input.voice_id.clone());
+        }
+    }
+    speakers
+}
+
+/// Billable characters of the turns of `dialogue`
+fn billable_characters(dialogue: &Dialogue) -> u64 {
+    dialogue
+        .lines
+        .iter()
+        .map(|line| match line {
+            DialogueLine::Turn(input) | DialogueLine::LocalizedTurn(input, _) => {
+                crate::text::billable_len(&input.text)
+            }
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Lowercase ASCII letters and digits joined by dashes
+fn slug(title: &str) -> String {
+    let slug = title
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        "untitled".to_string()
+    } else {
+        slug
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::{MockResponse, TTDInput};
+
+    fn book() -> Audiobook {
+        Audiobook::new("The Long Night")
+            .chapter(
+                "Dusk",
+                Dialogue::new().line("voice-a", "It's getting dark."),
+            )
+            .chapter(
+                "The Storm!",
+                Dialogue::new()
+                    .line("voice-a", "Listen.")
+                    .line("voice-b", "Thunder."),
+            )
+            .output_format(OutputFormat::Wav { sample_rate: 16000 })
+    }
+
+    #[tokio::test]
+    async fn test_render_writes_chapters_and_timings() {
+        let dir = std::env::temp_dir().join(format!("ttd-book-{}", uuid::Uuid::new_v4()));
+        let client = ElevenLabsTTDClient::sandbox();
+
+        let output = book().render(&client, &dir).await.unwrap();
+
+        assert_eq!(output.chapters[1].file, PathBuf::from("02-the-storm.wav"));
+        assert_eq!(output.chapters[0].start, 0.0);
+        assert_eq!(output.chapters[1].start, output.chapters[0].end);
+        assert!(!output.chapters[0].cached);
+
+        let combined = std::fs::read(dir.join("the-long-night.wav")).unwrap();
+        let length = audio::duration(&combined, OutputFormat::Wav { sample_rate: 16000 });
+        assert!((length.unwrap().as_secs_f64() - output.chapters[1].end).abs() < 0.001);
+        assert!(dir.join("the-long-night.chapters.json").exists());
+
+        // A second run reuses the chapters already on disk
+        let again = book().render(&client, &dir).await.unwrap();
+        assert!(again.chapters.iter().all(|c| c.cached));
+
+        // ...unless one was cut short or doesn't decode
+        let first = dir.join(&output.chapters[0].file);
+        let audio = std::fs::read(&first).unwrap();
+        std::fs::write(&first, &audio[..audio.len() - 10]).unwrap();
+        std::fs::write(dir.join(&output.chapters[1].file), b"garbage").unwrap();
+        let repaired = book().render(&client, &dir).await.unwrap();
+        assert!(repaired.chapters.iter().all(|c| !c.cached));
+        assert_eq!(std::fs::read(&first).unwrap(), audio);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_render_regenerates_changed_chapters() {
+        let dir = std::env::temp_dir().join(format!("ttd-book-{}", uuid::Uuid::new_v4()));
+        let client = ElevenLabsTTDClient::sandbox();
+        book().render(&client, &dir).await.unwrap();
+
+        let mut edited = book();
+        edited.chapters[1].dialogue = Dialogue::new()
+            .line("voice-a", "Listen.")
+            .line("voice-b", "Did you hear that?")
+            .line("voice-b", "Thunder.");
+        edited = edited.chapter("Dawn", Dialogue::new().line("voice-a", "Morning."));
+
+        let previous = AudiobookOutput::load(edited.timings_path(&dir)).unwrap();
+        assert_eq!(
+            edited.changes(&previous),
+            vec![
+                ChapterChange::Unchanged,
+                ChapterChange::Changed { lines: vec![1] },
+                ChapterChange::Added,
+            ]
+        );
+
+        let output = edited.render(&client, &dir).await.unwrap();
+        let cached: Vec<bool> = output.chapters.iter().map(|c| c.cached).collect();
+        assert_eq!(cached, [true, false, false]);
+        assert!(
+            edited
+                .changes(&output)
+                .iter()
+                .all(|c| *c == ChapterChange::Unchanged)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_project_persists_scenes_and_status() {
+        let dir = std::env::temp_dir().join(format!("ttd-project-{}", uuid::Uuid::new_v4()));
+        let client = ElevenLabsTTDClient::sandbox();
+        let mut project = Project::new("Pilot")
+            .cast("Ann", "voice-a")
+            .cast("Bob", "voice-b")
+            .output_format(OutputFormat::Pcm16000)
+            .script("Cold open", "Ann: Hello?\nBob: Over here.")
+            .unwrap()
+            .scene("Finale", Dialogue::new().line("voice-a", "Goodbye."));
+        assert_eq!(project.scene_status("Finale"), Some(SceneStatus::Pending));
+
+        let rendered = project.render(&client, &dir).await.unwrap();
+        assert_eq!(rendered, ["Cold open", "Finale"]);
+        assert!(project.is_complete());
+        assert!(dir.join("01-cold-open.pcm").exists());
+
+        let path = dir.join("pilot.json");
+        project.save(&path).unwrap();
+        let mut project = Project::load(&path).unwrap();
+        assert!(project.is_complete());
+
+        project.set_scene("Finale", Dialogue::new().line("voice-a", "Farewell."));
+        assert_eq!(
+            project.status(),
+            [
+                ("Cold open", SceneStatus::Rendered),
+                ("Finale", SceneStatus::Stale)
+            ]
+        );
+        assert_eq!(project.render(&client, &dir).await.unwrap(), ["Finale"]);
+    }
+
+    #[tokio::test]
+    async fn test_scene_overrides_and_manifests() {
+        let dir = std::env::temp_dir().join(format!("ttd-scenes-{}", uuid::Uuid::new_v4()));
+        let client = ElevenLabsTTDClient::sandbox();
+        let turns = vec![
+            TTDInput {
+                voice_id: "voice-b".to_string(),
+                text: "Ready?".to_string(),
+            },
+            TTDInput {
+                voice_id: "voice-a".to_string(),
+                text: "Always.".to_string(),
+            },
+        ];
+        let mut project = Project::new("Season")
+            .output_format(OutputFormat::Pcm16000)
+            .add_scene(Scene::new("Episode 1", turns))
+            .add_scene(
+                Scene::new("Episode 2", Dialogue::new().line("voice-a", "Again."))
+                    .output_format(OutputFormat::Wav { sample_rate: 16000 })
+                    .seed(7),
+            );
+        let names: Vec<_> = project.scenes().map(|scene| scene.name.as_str()).collect();
+        assert_eq!(names, ["Episode 1", "Episode 2"]);
+
+        project.render(&client, &dir).await.unwrap();
+        assert!(dir.join("01-episode-1.pcm").exists());
+        assert!(dir.join("02-episode-2.wav").exists());
+        let manifest = SceneManifest::load(dir.join("01-episode-1.manifest.json")).unwrap();
+        assert_eq!(manifest.scene, "Episode 1");
+        assert_eq!(manifest.speakers, ["voice-b", "voice-a"]);
+        let manifest = SceneManifest::load(dir.join("02-episode-2.manifest.json")).unwrap();
+        assert_eq!(manifest.output_format, "wav_16000");
+        assert_eq!(manifest.seed, Some(7));
+
+        // Overrides survive a save and count towards staleness
+        let path = dir.join("season.json");
+        project.save(&path).unwrap();
+        let mut project = Project::load(&path).unwrap();
+        assert!(project.is_complete());
+        project.set_scene_with(
+            Scene::new("Episode 2", Dialogue::new().line("voice-a", "Again.")).seed(8),
+        );
+        assert_eq!(project.scene_status("Episode 2"), Some(SceneStatus::Stale));
+    }
+
+    #[tokio::test]
+    async fn test_regenerate_flagged_turn() {
+        let dir = std::env::temp_dir().join(format!("ttd-takes-{}", uuid::Uuid::new_v4()));
+        let tone = |len: usize| -> Vec<u8> {
+            (0..len / 2)
+                .flat_map(|i| if i % 2 == 0 { 8000i16 } else { -8000 }.to_le_bytes())
+                .collect()
+        };
+        // The first take of the second turn comes back silent
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .mock_transport(move |request| {
+                let audio = match (request.inputs[0].text.as_str(), request.seed) {
+                    ("Still there?", None) => vec![0u8; 3200],
+                    ("Still there?", Some(_)) => tone(4000),
+                    _ => tone(3200),
+                };
+                Ok(MockResponse::audio(audio))
+            })
+            .build();
+        let mut project = Project::new("Takes")
+            .output_format(OutputFormat::Wav { sample_rate: 16000 })
+            .per_turn(true)
+            .scene(
+                "Call",
+                Dialogue::new()
+                    .line("voice-a", "Hello?")
+                    .pause(100)
+                    .line("voice-b", "Still there?"),
+            );
+        project.render(&client, &dir).await.unwrap();
+
+        let takes = project.scenes[0].artifact.as_ref().unwrap().turns.clone();
+        assert_eq!(takes.len(), 2);
+        assert_eq!((takes[0].line, takes[0].offset), (0, 44));
+        assert_eq!(takes[1].offset, 44 + 3200 + 3200);
+        assert_eq!(project.flagged_turns("Call").unwrap(), [1]);
+
+        project
+            .regenerate_turns(&client, "Call", &[1], Some(9))
+            .await
+            .unwrap();
+        assert!(project.flagged_turns("Call").unwrap().is_empty());
+        let artifact = project.scenes[0].artifact.clone().unwrap();
+        assert_eq!(artifact.turns[1].length, 4000);
+        assert_eq!(artifact.turns[1].seed, Some(9));
+        assert_eq!(artifact.turns[0], takes[0]);
+        let (samples, _) = audio::read_wav(&std::fs::read(&artifact.path).unwrap()).unwrap();
+        assert_eq!(samples.len(), (3200 + 3200 + 4000) / 2);
+        assert!(project.is_complete());
+
+        assert!(
+            project
+                .regenerate_turns(&client, "Call", &[2], None)
+                .await
+                .is_err()
+        );
+        let manifest = SceneManifest::load(artifact.path.with_extension("manifest.json")).unwrap();
+        assert_eq!(manifest.rendered_at, artifact.rendered_at);
+
+        // A file cut short no longer holds the recorded takes
+        std::fs::write(&artifact.path, vec![0u8; 100]).unwrap();
+        assert!(project.flagged_turns("Call").is_err());
+        let truncated = project.regenerate_turns(&client, "Call", &[0], None).await;
+        assert_eq!(truncated.unwrap_err().kind(), "validation_error");
+
+        project.per_turn = false;
+        assert_eq!(project.scene_status("Call"), Some(SceneStatus::Stale));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_turn_index_written_next_to_scene() {
+        use crate::writer::TurnIndex;
+
+        let dir = std::env::temp_dir().join(format!("ttd-index-{}", uuid::Uuid::new_v4()));
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .mock_transport(|request| {
+                let len = if request.seed.is_some() { 600 } else { 400 };
+                Ok(MockResponse::audio(vec![1u8; len]))
+            })
+            .build();
+        let mut project = Project::new("Indexed")
+            .output_format(OutputFormat::Wav { sample_rate: 16000 })
+            .per_turn(true)
+            .turn_index(true)
+            .scene(
+                "Call",
+                Dialogue::new()
+                    .pause(100)
+                    .line("voice-a", "Hello?")
+                    .line("voice-b", "Still there?"),
+            );
+        project.render(&client, &dir).await.unwrap();
+
+        let artifact = project.scenes[0].artifact.clone().unwrap();
+        let index = TurnIndex::load(TurnIndex::sidecar_path(&artifact.path)).unwrap();
+        // The pause before the first turn belongs to no turn
+        assert_eq!(index.range(0), Some(44 + 3200..44 + 3200 + 400));
+        assert_eq!(index.turns[1].label.as_deref(), Some("Still there?"));
+        assert_eq!(index.size, 44 + 3200 + 800);
+        let file = std::fs::read(&artifact.path).unwrap();
+        assert_eq!(file.len() as u64, index.size);
+        assert_eq!(audio::read_wav(&file).unwrap().0.len(), (3200 + 800) / 2);
+
+        project
+            .regenerate_turns(&client, "Call", &[0], Some(3))
+            .await
+            .unwrap();
+        let index = TurnIndex::load(TurnIndex::sidecar_path(&artifact.path)).unwrap();
+        assert_eq!(index.range(1), Some(44 + 3200 + 600..44 + 3200 + 1000));
+        assert_eq!(
+            std::fs::read(&artifact.path).unwrap().len() as u64,
+            index.size
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_paused_batch_keeps_items_queued() {
+        let client = ElevenLabsTTDClient::sandbox();
+        let batch = Batch::new([
+            ("a", Dialogue::new().line("voice-a", "One.")),
+            ("b", Dialogue::new().line("voice-a", "Two.")),
+        ]);
+        let control = batch.control();
+        control.pause();
+
+        let render = tokio::spawn(async move { batch.render(&client).await });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(control.is_paused());
+        assert_eq!(control.queued(), 2);
+        assert!(!render.is_finished());
+        assert_eq!(
+            control.status(),
+            BatchStatus {
+                queued: 2,
+                running: 0,
+                succeeded: 0,
+                failed: 0,
+                characters: 0,
+                estimated_characters: 0,
+                eta: None,
+            }
+        );
+
+        control.resume();
+        let result = render.await.unwrap();
+        assert!(result.is_complete());
+        assert_eq!(result.completed.len(), 2);
+        let status = control.status();
+        assert_eq!(status.queued, 0);
+        assert_eq!(status.succeeded, 2);
+        // The sandbox bills nothing, so its text length is only an estimate
+        assert_eq!(status.characters, 0);
+        assert_eq!(status.estimated_characters, 8);
+        assert_eq!(status.eta, Some(Duration::ZERO));
+    }
+
+    #[tokio::test]
+    async fn test_batch_resumes_failed_items() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        // The second request fails once, then succeeds
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .mock_transport(move |_| match counter.fetch_add(1, Ordering::SeqCst) {
+                1 => Ok(MockResponse::error(400, "bad luck")),
+                _ => Ok(MockResponse::audio(b"audio".to_vec())),
+            })
+            .build();
+        let batch = Batch::new([
+            ("a", Dialogue::new().line("voice-a", "One.")),
+            ("b", Dialogue::new().line("voice-a", "Two.")),
+            ("c", Dialogue::new().line("voice-a", "Three.")),
+        ]);
+
+        let control = batch.control();
+        let result = batch.render(&client).await;
+        assert!(!result.is_complete());
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(
+            (control.status().succeeded, control.status().failed),
+            (2, 1)
+        );
+        assert_eq!(result.failed[0].name, "b");
+        assert_eq!(result.failed[0].error.status(), Some(400));
+
+        let result = result.resume(&client).await;
+        assert!(result.is_complete());
+        assert_eq!(
+            (control.status().succeeded, control.status().failed),
+            (3, 0)
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        let names: Vec<_> = result
+            .completed
+            .iter()
+            .map(|item| item.name.as_str())
+            .collect();
+        assert_eq!(names, ["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_batch_clone_has_own_control() {
+        let client = ElevenLabsTTDClient::sandbox();
+        let batch = Batch::new([("a", Dialogue::new().line("voice-a", "One."))]);
+        let copy = batch.clone();
+        batch.control().pause();
+        assert!(!copy.control().is_paused());
+
+        let control = copy.control();
+        assert!(copy.render(&client).await.is_complete());
+        assert_eq!(control.status().succeeded, 1);
+        assert_eq!(batch.control().status().succeeded, 0);
+    }
+
+    #[cfg(feature = "ffmpeg")]
+    #[test]
+    fn test_ffmetadata_chapters() {
+        let output = AudiobookOutput {
+            title: "A=B".to_string(),
+            output_format: "mp3_44100_128".to_string(),
+            combined: PathBuf::from("a-b.mp3"),
+            chapters: vec![ChapterTiming {
+                title: "One".to_string(),
+                file: PathBuf::from("01-one.mp3"),
+                start: 0.0,
+                end: 1.5,
+                character_count: None,
+                cached: false,
+                fingerprint: String::new(),
+                line_fingerprints: Vec::new(),
+                size: None,
+            }],
+            warnings: Vec::new(),
+        };
+
+        assert_eq!(
+            output.ffmetadata(),
+            ";FFMETADATA1\ntitle=A\\=B\nalbum=A\\=B\n\n[CHAPTER]\nTIMEBASE=1/1000\nSTART=0\nEND=1500\ntitle=One\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_episode_sequences_parts() {
+        let dir = std::env::temp_dir().join(format!("ttd-episode-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let jingle = dir.join("jingle.wav");
+        let tone: Vec<u8> = [1000i16, -1000]
+            .repeat(8000)
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+        std::fs::write(&jingle, audio::wav(&tone, 16000)).unwrap();
+
+        let episode = Episode::new("Pilot")
+            .sample_rate(16000)
+            .gap(Duration::from_millis(500))
+            .intro(jingle.as_path())
+            .segment(
+                "Chat",
+                Dialogue::new().line("voice-a", "Welcome to the show."),
+            )
+            .ad("Sponsor", jingle.clone())
+            .outro(jingle.as_path());
+
+        let manifest = episode
+            .render_wav(&ElevenLabsTTDClient::sandbox(), dir.join("pilot.wav"))
+            .await
+            .unwrap();
+
+        let kinds: Vec<_> = manifest.parts.iter().map(|p| p.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                PartKind::Intro,
+                PartKind::Segment,
+                PartKind::Ad,
+                PartKind::Outro
+            ]
+        );
+        assert_eq!(manifest.parts[0].end, 1.0);
+        assert_eq!(manifest.parts[1].start, 1.5);
+        assert_eq!(manifest.duration, manifest.parts[3].end);
+        assert!(dir.join("pilot.timing.json").exists());
+
+        // Every part is normalized to the same level
+        let (samples, _) = audio::read_wav(&std::fs::read(dir.join("pilot.wav")).unwrap()).unwrap();
+        assert_eq!(samples[0], 3277);
+    }
+
+    #[tokio::test]
+    async fn test_episode_reads_raw_parts() {
+        let dir = std::env::temp_dir().join(format!("ttd-episode-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let ad = dir.join("ad.pcm");
+        let tone: Vec<u8> = [1000i16, -1000]
+            .repeat(800)
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+        std::fs::write(&ad, tone).unwrap();
+        let jingle = dir.join("jingle.mp3");
+        std::fs::write(&jingle, b"ID3not-really-mp3").unwrap();
+
+        let (samples, manifest) = Episode::new("Pilot")
+            .sample_rate(16000)
+            .ad("Sponsor", PartSource::Audio(ad, OutputFormat::Pcm16000))
+            .render(&ElevenLabsTTDClient::sandbox())
+            .await
+            .unwrap();
+        assert_eq!(samples.len(), 1600);
+        assert_eq!(manifest.parts[0].end, 0.1);
+
+        // Without a WAV header, a file part is decoded with ffmpeg
+        let error = Episode::new("Pilot")
+            .sample_rate(16000)
+            .intro(jingle.as_path())
+            .render(&ElevenLabsTTDClient::sandbox())
+            .await
+            .unwrap_err();
+        #[cfg(not(feature = "ffmpeg"))]
+        assert!(error.to_string().contains("`ffmpeg` feature"));
+        #[cfg(feature = "ffmpeg")]
+        assert!(matches!(error, ElevenLabsTTDError::IoError(_)));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_episode_renders_with_seed() {
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .mock_transport(|request| {
+                assert_eq!(request.seed, Some(3));
+                Ok(MockResponse::audio(vec![0; 320]))
+            })
+            .build();
+
+        let (samples, _) = Episode::new("Pilot")
+            .sample_rate(16000)
+            .seed(3)
+            .segment("Chat", Dialogue::new().line("voice-a", "Hello."))
+            .render(&client)
+            .await
+            .unwrap();
+        assert_eq!(samples.len(), 160);
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_render_keeps_finished_chapters() {
+        let dir = std::env::temp_dir().join(format!("ttd-book-{}", uuid::Uuid::new_v4()));
+        book()
+            .render(&ElevenLabsTTDClient::sandbox(), &dir)
+            .await
+            .unwrap();
+
+        let mut edited = book();
+        edited.chapters[0].dialogue = Dialogue::new().line("voice-a", "It's dark now.");
+        edited.chapters[1].dialogue = Dialogue::new().line("voice-b", "Thunder!");
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        // The second chapter fails for good on the first run
+        let failing = ElevenLabsTTDClient::builder("test-key")
+            .mock_transport(move |request| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(match request.inputs[0].text.as_str() {
+                    "Thunder!" => MockResponse::error(400, "bad request"),
+                    _ => MockResponse::audio(vec![0; 320]),
+                })
+            })
+            .build();
+        assert!(edited.render(&failing, &dir).await.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let previous = AudiobookOutput::load(edited.timings_path(&dir)).unwrap();
+        assert_eq!(edited.changes(&previous)[0], ChapterChange::Unchanged);
+        let output = edited
+            .render(&ElevenLabsTTDClient::sandbox(), &dir)
+            .await
+            .unwrap();
+        let cached: Vec<bool> = output.chapters.iter().map(|c| c.cached).collect();
+        assert_eq!(cached, [true, false]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_render_retries_failed_chapter() {
+        let dir = std::env::temp_dir().join(format!("ttd-book-{}", uuid::Uuid::new_v4()));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .mock_transport(move |_| {
+                Ok(match counter.fetch_add(1, Ordering::SeqCst) {
+                    0 => MockResponse::error(503, "overloaded"),
+                    _ => MockResponse::audio(vec![0; 320]),
+                })
+            })
+            .build();
+
+        let output = book()
+            .retry_delay(Duration::ZERO)
+            .render(&client, &dir)
+            .await
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(output.chapters[1].end, 0.02);
+    }
+
+    #[tokio::test]
+    async fn test_ambiguous_errors_retried_only_with_seed_or_key() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        // A 500 may come after the audio was generated and billed
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .mock_transport(move |_| {
+                Ok(match counter.fetch_add(1, Ordering::SeqCst) {
+                    2 | 4 => MockResponse::audio(vec![0; 320]),
+                    _ => MockResponse::error(500, "internal error"),
+                })
+            })
+            .build();
+        let batch = Batch::new([("a", Dialogue::new().line("voice-a", "One."))])
+            .retry_delay(Duration::ZERO);
+
+        let result = batch.clone().render(&client).await;
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let result = batch.clone().idempotency_key("run-1").render(&client).await;
+        assert!(result.is_complete());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let result = batch.seed(7).render(&client).await;
+        assert!(result.is_complete());
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn test_retried_failure_reported_once() {
+        let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        let client = ElevenLabsTTDClient::builder("test-key")
+            .error_sink(
+                move |_: &ElevenLabsTTDError, context: &crate::ErrorContext| {
+                    sink.lock().unwrap().push(context.clone())
+                },
+            )
+            .mock_transport(|_| Ok(MockResponse::error(503, "overloaded")))
+            .build();
+
+        let result = Batch::new([("intro", Dialogue::new().line("voice-a", "One."))])
+            .max_attempts(2)
+            .retry_delay(Duration::ZERO)
+            .render(&client)
+            .await;
+
+        assert_eq!(result.failed.len(), 1);
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].item.as_deref(), Some("intro"));
+        assert_eq!(reports[0].attempts, 2);
+    }
+}
@@ -0,0 +1,75 @@
+//! Rate-limit and concurrency headers reported by the API
+
+use std::time::Duration;
+
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+
+/// Rate-limit state reported in the headers of a response
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RateLimitInfo {
+    // Requests currently in flight for the account (`current-concurrent-requests`).
+    pub current_concurrent_requests: Option<u32>,
+    // Concurrency allowed by the account's tier (`maximum-concurrent-requests`).
+    pub maximum_concurrent_requests: Option<u32>,
+    // Requests allowed in the current window (`x-ratelimit-limit`).
+    pub limit: Option<u32>,
+    // Requests left in the current window (`x-ratelimit-remaining`).
+    pub remaining: Option<u32>,
+    // Time until the window resets (`x-ratelimit-reset`, or `retry-after`).
+    pub reset_after: Option<Duration>,
+}
+
+impl RateLimitInfo {
+    /// Parse rate-limit headers, or `None` if the response carried none
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let number =
+            |name: &str| -> Option<u64> { headers.get(name)?.to_str().ok()?.trim().parse().ok() };
+        let count = |name: &str| number(name).and_then(|n| u32::try_from(n).ok());
+
+        let info = RateLimitInfo {
+            current_concurrent_requests: count("current-concurrent-requests"),
+            maximum_concurrent_requests: count("maximum-concurrent-requests"),
+            limit: count("x-ratelimit-limit"),
+            remaining: count("x-ratelimit-remaining"),
+            reset_after: number("x-ratelimit-reset")
+                .or_else(|| number("retry-after"))
+                .map(Duration::from_secs),
+        };
+
+        (info != RateLimitInfo::default()).then_some(info)
+    }
+
+    /// Concurrent request slots still free, if both counts are known
+    pub fn available_concurrency(&self) -> Option<u32> {
+        Some(
+            self.maximum_concurrent_requests?
+                .saturating_sub(self.current_concurrent_requests?),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_known_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("current-concurrent-requests", "3".parse().unwrap());
+        headers.insert("maximum-concurrent-requests", "5".parse().unwrap());
+        headers.insert("retry-after", "12".parse().unwrap());
+
+        let info = RateLimitInfo::from_headers(&headers).unwrap();
+        assert_eq!(info.available_concurrency(), Some(2));
+        assert_eq!(info.reset_after, Some(Duration::from_secs(12)));
+        assert_eq!(info.remaining, None);
+    }
+
+    #[test]
+    fn test_no_headers_is_none() {
+        let mut headers = HeaderMap::new();
+        headers.insert("content-type", "audio/mpeg".parse().unwrap());
+        assert_eq!(RateLimitInfo::from_headers(&headers), None);
+    }
+}
@@ -0,0 +1,164 @@
+//! Low-level access to ElevenLabs endpoints this crate doesn't wrap yet
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::audit::{ErrorContext, ErrorSink};
+use crate::error::ElevenLabsTTDError;
+use crate::rate_limit::RateLimitInfo;
+
+/// A request to an arbitrary endpoint, with authentication and base URL already applied.
+/// Created by [`crate::ElevenLabsTTDClient::request`].
+pub struct RawRequestBuilder {
+    inner: reqwest::RequestBuilder,
+    // Returned by `send` instead of sending, e.g. for a URL on another host.
+    rejected: Option<ElevenLabsTTDError>,
+    // The client's error sink, and the operation errors are reported under.
+    error_sink: Option<Arc<dyn ErrorSink>>,
+    operation: &'static str,
+}
+
+/// Status, headers and body of a successful raw request
+#[derive(Debug, Clone)]
+pub struct RawResponse {
+    pub status: u16,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl RawResponse {
+    /// Parse the body as JSON
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, ElevenLabsTTDError> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+
+    /// Rate-limit state reported in the response headers
+    pub fn rate_limit(&self) -> Option<RateLimitInfo> {
+        RateLimitInfo::from_headers(&self.headers)
+    }
+}
+
+impl RawRequestBuilder {
+    pub(crate) fn new(inner: reqwest::RequestBuilder) -> Self {
+        Self {
+            inner,
+            rejected: None,
+            error_sink: None,
+            operation: "request",
+        }
+    }
+
+    /// A request that fails with `error` when sent
+    pub(crate) fn rejected(inner: reqwest::RequestBuilder, error: ElevenLabsTTDError) -> Self {
+        Self {
+            inner,
+            rejected: Some(error),
+            error_sink: None,
+            operation: "request",
+        }
+    }
+
+    /// Report the error the request fails with to `sink`
+    pub(crate) fn error_sink(mut self, sink: Option<Arc<dyn ErrorSink>>) -> Self {
+        self.error_sink = sink;
+        self
+    }
+
+    /// Name the operation errors are reported under, "request" by default
+    pub(crate) fn operation(mut self, operation: &'static str) -> Self {
+        self.operation = operation;
+        self
+    }
+
+    /// Add URL query parameters
+    pub fn query<T: Serialize + ?Sized>(mut self, query: &T) -> Self {
+        self.inner = self.inner.query(query);
+        self
+    }
+
+    /// Send a JSON body
+    pub fn json<T: Serialize + ?Sized>(mut self, body: &T) -> Self {
+        self.inner = self.inner.json(body);
+        self
+    }
+
+    /// Send a multipart form body
+    pub fn multipart(mut self, form: reqwest::multipart::Form) -> Self {
+        self.inner = self.inner.multipart(form);
+        self
+    }
+
+    /// Send a raw body
+    pub fn body<B: Into<reqwest::Body>>(mut self, body: B) -> Self {
+        self.inner = self.inner.body(body);
+        self
+    }
+
+    /// Add a header
+    pub fn header<V: AsRef<str>>(mut self, name: &str, value: V) -> Self {
+        self.inner = self.inner.header(name, value.as_ref());
+        self
+    }
+
+    /// Send the request and return the raw response.
+    /// Non-success statuses become errors: 401, 402 and 429 their dedicated
+    /// variants, 5xx [`ElevenLabsTTDError::ServerError`], others [`ElevenLabsTTDError::ApiError`].
+    pub async fn send(mut self) -> Result<RawResponse, ElevenLabsTTDError> {
+        let report = self.reporter();
+        self.send_unreported().await.inspect_err(report)
+    }
+
+    /// Send the request and parse the response body as JSON
+    pub async fn send_json<T: DeserializeOwned>(mut self) -> Result<T, ElevenLabsTTDError> {
+        let report = self.reporter();
+        self.send_unreported()
+            .await
+            .and_then(|response| response.json())
+            .inspect_err(report)
+    }
+
+    /// Passes an error to the error sink, taken out of the builder
+    fn reporter(&mut self) -> impl FnOnce(&ElevenLabsTTDError) + use<> {
+        let sink = self.error_sink.take();
+        let operation = self.operation;
+        move |error| {
+            if let Some(sink) = sink {
+                sink.report(error, &ErrorContext::new(error, operation, None, 1));
+            }
+        }
+    }
+
+    async fn send_unreported(self) -> Result<RawResponse, ElevenLabsTTDError> {
+        if let Some(error) = self.rejected {
+            return Err(error);
+        }
+        let started = Instant::now();
+        let response = self
+            .inner
+            .send()
+            .await
+            .map_err(|e| ElevenLabsTTDError::from_transport(e, started))?;
+
+        if !response.status().is_success() {
+            return Err(ElevenLabsTTDError::from_response(response, None).await);
+        }
+
+        let status = response.status().as_u16();
+        let headers = response.headers().clone();
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| ElevenLabsTTDError::from_transport(e, started))?
+            .to_vec();
+
+        Ok(RawResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+}
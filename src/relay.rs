@@ -0,0 +1,426 @@
+//! Re-packetizing Ogg Opus output into fixed-duration, sequence-numbered
+//! packets for RTP-style forwarding (RFC 7587), e.g. by telephony or WebRTC gateways
+
+use std::time::Duration;
+
+use crate::error::ElevenLabsTTDError;
+
+/// Opus RTP timestamps always run at 48kHz
+const CLOCK_RATE: u64 = 48000;
+
+/// Longest packet Opus allows
+const MAX_PACKET: Duration = Duration::from_millis(120);
+
+/// One packet ready to forward, holding exactly `duration` of audio unless the
+/// stream ended or changed mode mid-packet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayPacket {
+    // Increments by one per packet, wrapping; gaps tell the receiver what was lost.
+    pub sequence: u16,
+    // Position of the packet's first sample on the 48kHz RTP clock.
+    pub timestamp: u32,
+    // Set on the first packet of each relayed output, the start of a talkspurt.
+    pub marker: bool,
+    // Samples at 48kHz the packet decodes to.
+    pub samples: u32,
+    // An Opus packet (RFC 6716), without any container.
+    pub payload: Vec<u8>,
+}
+
+impl RelayPacket {
+    /// The packet with a 12 byte RTP header (RFC 3550) prepended
+    pub fn to_rtp(&self, payload_type: u8, ssrc: u32) -> Vec<u8> {
+        let mut out = Vec::with_capacity(12 + self.payload.len());
+        out.push(0x80); // version 2, no padding, extension or CSRCs
+        out.push(((self.marker as u8) << 7) | (payload_type & 0x7F));
+        out.extend_from_slice(&self.sequence.to_be_bytes());
+        out.extend_from_slice(&self.timestamp.to_be_bytes());
+        out.extend_from_slice(&ssrc.to_be_bytes());
+        out.extend_from_slice(&self.payload);
+        out
+    }
+}
+
+/// Splits Ogg Opus audio into packets of a fixed duration, merging or splitting
+/// the encoder's frames without re-encoding. Sequence numbers and timestamps
+/// carry on across calls, so consecutive dialogue renders form one stream.
+#[derive(Debug, Clone)]
+pub struct OpusRelay {
+    packet_samples: u32,
+    sequence: u16,
+    timestamp: u32,
+}
+
+impl OpusRelay {
+    /// Relay packets of `packet_duration`, a multiple of 2.5ms up to 120ms;
+    /// 20ms is what most gateways expect
+    pub fn new(packet_duration: Duration) -> Result<Self, ElevenLabsTTDError> {
+        let samples = packet_duration.as_micros() as u64 * CLOCK_RATE / 1_000_000;
+        if packet_duration.is_zero() || packet_duration > MAX_PACKET || !samples.is_multiple_of(120)
+        {
+            return Err(ElevenLabsTTDError::ValidationError(format!(
+                "Opus packet duration must be a multiple of 2.5ms up to 120ms, got {:?}",
+                packet_duration
+            )));
+        }
+        Ok(Self {
+            packet_samples: samples as u32,
+            sequence: 0,
+            timestamp: 0,
+        })
+    }
+
+    /// Start numbering at `sequence` and `timestamp` instead of zero, as RTP
+    /// recommends random initial values
+    pub fn starting_at(mut self, sequence: u16, timestamp: u32) -> Self {
+        self.sequence = sequence;
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Sequence number the next packet will get
+    pub fn next_sequence(&self) -> u16 {
+        self.sequence
+    }
+
+    /// Re-packetize one Ogg Opus output, e.g. a [`crate::TTDAudio`] in an `opus_*` format
+    pub fn packets(&mut self, ogg: &[u8]) -> Result<Vec<RelayPacket>, ElevenLabsTTDError> {
+        let mut frames = Vec::new();
+        for packet in ogg_packets(ogg)? {
+            if packet.starts_with(b"OpusHead") || packet.starts_with(b"OpusTags") {
+                continue;
+            }
+            frames.extend(split_frames(&packet)?);
+        }
+
+        let mut out = Vec::new();
+        let mut group: Vec<Frame> = Vec::new();
+        let mut group_samples = 0;
+        for frame in frames {
+            let samples = frame.samples();
+            if samples > self.packet_samples || !self.packet_samples.is_multiple_of(samples) {
+                return Err(invalid(&format!(
+                    "{}us frames don't fit {}us packets",
+                    samples as u64 * 1_000_000 / CLOCK_RATE,
+                    self.packet_samples as u64 * 1_000_000 / CLOCK_RATE
+                )));
+            }
+            // A mode change can't share a packet with the frames before it
+            if group.first().is_some_and(|first| first.toc != frame.toc) {
+                self.emit(&mut out, std::mem::take(&mut group), group_samples);
+                group_samples = 0;
+            }
+            group_samples += samples;
+            group.push(frame);
+            if group_samples == self.packet_samples {
+                self.emit(&mut out, std::mem::take(&mut group), group_samples);
+                group_samples = 0;
+            }
+        }
+        if !group.is_empty() {
+            self.emit(&mut out, group, group_samples);
+        }
+
+        if let Some(first) = out.first_mut() {
+            first.marker = true;
+        }
+        Ok(out)
+    }
+
+    fn emit(&mut self, out: &mut Vec<RelayPacket>, frames: Vec<Frame>, samples: u32) {
+        out.push(RelayPacket {
+            sequence: self.sequence,
+            timestamp: self.timestamp,
+            marker: false,
+            samples,
+            payload: join_frames(&frames),
+        });
+        self.sequence = self.sequence.wrapping_add(1);
+        self.timestamp = self.timestamp.wrapping_add(samples);
+    }
+}
+
+/// One Opus frame and the TOC byte (minus the frame count code) it was coded with
+#[derive(Debug, Clone)]
+struct Frame {
+    toc: u8,
+    data: Vec<u8>,
+}
+
+impl Frame {
+    /// Samples at 48kHz, from the configuration in the TOC byte (RFC 6716 §3.1)
+    fn samples(&self) -> u32 {
+        let config = self.toc >> 3;
+        match config {
+            // SILK: 10, 20, 40, 60ms
+            0..=11 => [480, 960, 1920, 2880][(config % 4) as usize],
+            // Hybrid: 10, 20ms
+            12..=15 => [480, 960][(config % 2) as usize],
+            // CELT: 2.5, 5, 10, 20ms
+            _ => [120, 240, 480, 960][(config % 4) as usize],
+        }
+    }
+}
+
+/// Split an Opus packet into its frames (RFC 6716 §3.2)
+fn split_frames(packet: &[u8]) -> Result<Vec<Frame>, ElevenLabsTTDError> {
+    let (&toc, rest) = packet
+        .split_first()
+        .ok_or_else(|| invalid("empty packet"))?;
+    let frame = |data: &[u8]| Frame {
+        toc: toc & 0xFC,
+        data: data.to_vec(),
+    };
+
+    match toc & 0x03 {
+        0 => Ok(vec![frame(rest)]),
+        1 => {
+            if rest.len() % 2 != 0 {
+                return Err(invalid("odd-length two-frame packet"));
+            }
+            let (a, b) = rest.split_at(rest.len() / 2);
+            Ok(vec![frame(a), frame(b)])
+        }
+        2 => {
+            let (len, used) = read_frame_len(rest)?;
+            let rest = &rest[used..];
+            if len > rest.len() {
+                return Err(invalid("frame length past end of packet"));
+            }
+            let (a, b) = rest.split_at(len);
+            Ok(vec![frame(a), frame(b)])
+        }
+        _ => {
+            let (&count_byte, mut rest) = rest
+                .split_first()
+                .ok_or_else(|| invalid("missing frame count"))?;
+            let count = (count_byte & 0x3F) as usize;
+            if count == 0 {
+                return Err(invalid("zero frames in packet"));
+            }
+
+            let mut padding = 0;
+            if count_byte & 0x40 != 0 {
+                loop {
+                    let (&byte, after) = rest
+                        .split_first()
+                        .ok_or_else(|| invalid("truncated padding length"))?;
+                    rest = after;
+                    padding += if byte == 255 { 254 } else { byte as usize };
+                    if byte != 255 {
+                        break;
+                    }
+                }
+            }
+
+            let mut lens = Vec::with_capacity(count);
+            if count_byte & 0x80 != 0 {
+                for _ in 0..count - 1 {
+                    let (len, used) = read_frame_len(rest)?;
+                    lens.push(len);
+                    rest = &rest[used..];
+                }
+            }
+            let body = rest
+                .len()
+                .checked_sub(padding)
+                .ok_or_else(|| invalid("padding past end of packet"))?;
+            let rest = &rest[..body];
+            if count_byte & 0x80 != 0 {
+                let used: usize = lens.iter().sum();
+                lens.push(
+                    body.checked_sub(used)
+                        .ok_or_else(|| invalid("frame lengths past end of packet"))?,
+                );
+            } else {
+                if body % count != 0 {
+                    return Err(invalid("uneven constant-bitrate frames"));
+                }
+                lens = vec![body / count; count];
+            }
+
+            let mut frames = Vec::with_capacity(count);
+            let mut start = 0;
+            for len in lens {
+                frames.push(frame(&rest[start..start + len]));
+                start += len;
+            }
+            Ok(frames)
+        }
+    }
+}
+
+/// Build one packet from frames sharing a TOC configuration
+fn join_frames(frames: &[Frame]) -> Vec<u8> {
+    let toc = frames[0].toc;
+    let mut out = Vec::new();
+    match frames {
+        [only] => {
+            out.push(toc);
+            out.extend_from_slice(&only.data);
+        }
+        [a, b] if a.data.len() == b.data.len() => {
+            out.push(toc | 1);
+            out.extend_from_slice(&a.data);
+            out.extend_from_slice(&b.data);
+        }
+        [a, b] => {
+            out.push(toc | 2);
+            write_frame_len(&mut out, a.data.len());
+            out.extend_from_slice(&a.data);
+            out.extend_from_slice(&b.data);
+        }
+        frames => {
+            let constant = frames.iter().all(|f| f.data.len() == frames[0].data.len());
+            out.push(toc | 3);
+            out.push(frames.len() as u8 | if constant { 0 } else { 0x80 });
+            if !constant {
+                for frame in &frames[..frames.len() - 1] {
+                    write_frame_len(&mut out, frame.data.len());
+                }
+            }
+            for frame in frames {
+                out.extend_from_slice(&frame.data);
+            }
+        }
+    }
+    out
+}
+
+/// Read a one or two byte frame length, returning it and the bytes used
+fn read_frame_len(data: &[u8]) -> Result<(usize, usize), ElevenLabsTTDError> {
+    match data {
+        [first, ..] if *first < 252 => Ok((*first as usize, 1)),
+        [first, second, ..] => Ok((*second as usize * 4 + *first as usize, 2)),
+        _ => Err(invalid("truncated frame length")),
+    }
+}
+
+fn write_frame_len(out: &mut Vec<u8>, len: usize) {
+    if len < 252 {
+        out.push(len as u8);
+    } else {
+        let first = 252 + (len & 0x03);
+        out.push(first as u8);
+        out.push(((len - first) >> 2) as u8);
+    }
+}
+
+/// Packets of an Ogg stream, in order, across page boundaries and chained streams
+fn ogg_packets(ogg: &[u8]) -> Result<Vec<Vec<u8>>, ElevenLabsTTDError> {
+    let mut packets = Vec::new();
+    let mut packet = Vec::new();
+    let mut pos = 0;
+    while pos < ogg.len() {
+        let header = ogg
+            .get(pos..pos + 27)
+            .filter(|header| header.starts_with(b"OggS"))
+            .ok_or_else(|| invalid(&format!("no Ogg page at byte {}", pos)))?;
+        let segments = header[26] as usize;
+        let lacing = ogg
+            .get(pos + 27..pos + 27 + segments)
+            .ok_or_else(|| invalid("truncated page header"))?;
+        let mut data = pos + 27 + segments;
+        for &len in lacing {
+            let segment = ogg
+                .get(data..data + len as usize)
+                .ok_or_else(|| invalid("truncated page"))?;
+            packet.extend_from_slice(segment);
+            data += len as usize;
+            // A 255 byte segment continues the packet, possibly on the next page
+            if len < 255 {
+                packets.push(std::mem::take(&mut packet));
+            }
+        }
+        pos = data;
+    }
+    Ok(packets)
+}
+
+fn invalid(reason: &str) -> ElevenLabsTTDError {
+    ElevenLabsTTDError::ValidationError(format!("Invalid Ogg Opus audio: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::OutputFormat;
+
+    fn opus(duration_ms: u64) -> Vec<u8> {
+        crate::sandbox::encode(
+            &[(Duration::from_millis(duration_ms), None)],
+            OutputFormat::Opus48000_64,
+        )
+    }
+
+    #[test]
+    fn test_packets_are_numbered_across_outputs() {
+        let mut relay = OpusRelay::new(Duration::from_millis(20))
+            .unwrap()
+            .starting_at(u16::MAX, 1000);
+        let first = relay.packets(&opus(100)).unwrap();
+        let second = relay.packets(&opus(40)).unwrap();
+
+        assert_eq!(first.len(), 5);
+        assert_eq!(second.len(), 2);
+        assert!(first[0].marker && !first[1].marker && second[0].marker);
+        assert_eq!(first[1].sequence, 0);
+        assert_eq!(second[0].sequence, 4);
+        assert_eq!(second[0].timestamp, 1000 + 5 * 960);
+        assert_eq!(first[0].payload, [0xF8, 0xFF, 0xFE]);
+    }
+
+    #[test]
+    fn test_frames_are_merged_into_longer_packets() {
+        let mut relay = OpusRelay::new(Duration::from_millis(60)).unwrap();
+        let packets = relay.packets(&opus(140)).unwrap();
+
+        // 7 frames of 20ms: two full 60ms packets and a short last one
+        assert_eq!(packets.len(), 3);
+        assert_eq!(packets[0].samples, 2880);
+        assert_eq!(
+            packets[0].payload,
+            [0xFB, 0x03, 0xFF, 0xFE, 0xFF, 0xFE, 0xFF, 0xFE]
+        );
+        assert_eq!(packets[2].payload, [0xF8, 0xFF, 0xFE]);
+        assert_eq!(split_frames(&packets[0].payload).unwrap().len(), 3);
+
+        let rtp = packets[1].to_rtp(111, 0xDEADBEEF);
+        assert_eq!(&rtp[..4], &[0x80, 111, 0, 1]);
+        assert_eq!(&rtp[4..8], &2880u32.to_be_bytes());
+        assert_eq!(&rtp[12..], &packets[1].payload[..]);
+    }
+
+    #[test]
+    fn test_rejects_durations_frames_cant_fill() {
+        assert!(OpusRelay::new(Duration::from_millis(7)).is_err());
+        assert!(OpusRelay::new(Duration::from_millis(240)).is_err());
+        let mut relay = OpusRelay::new(Duration::from_millis(10)).unwrap();
+        assert!(relay.packets(&opus(40)).is_err());
+        assert!(relay.packets(b"not ogg").is_err());
+    }
+
+    #[test]
+    fn test_variable_frame_lengths_round_trip() {
+        let frames = [
+            Frame {
+                toc: 0xF8,
+                data: vec![1; 300],
+            },
+            Frame {
+                toc: 0xF8,
+                data: vec![2; 10],
+            },
+            Frame {
+                toc: 0xF8,
+                data: vec![3; 20],
+            },
+        ];
+        for count in 2..=3 {
+            let packet = join_frames(&frames[..count]);
+            let split = split_frames(&packet).unwrap();
+            let lens: Vec<usize> = split.iter().map(|f| f.data.len()).collect();
+            assert_eq!(lens, [300, 10, 20][..count]);
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! Backoff between attempts of long renders ([`crate::project`])
+
+use std::time::Duration;
+
+/// Attempts per chapter or batch item before a render gives up
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry, doubled for each further retry
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// How much randomness is added to each backoff delay, so many workers failing
+/// at once don't all retry at the same moment
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Jitter {
+    // Wait exactly the exponential backoff.
+    #[default]
+    None,
+    // Wait a random time between zero and the backoff.
+    Full,
+    // Wait half the backoff plus a random time up to the other half.
+    Equal,
+}
+
+/// Exponential backoff with optional jitter, a cap on each delay and a budget
+/// for the total time spent on one item
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    // Backoff before the first retry, doubled for each further retry.
+    pub base_delay: Duration,
+    // Longest backoff between two attempts, before jitter.
+    pub max_delay: Option<Duration>,
+    pub jitter: Jitter,
+    // Give up once the next retry would start after this much time since the first attempt.
+    pub budget: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay: DEFAULT_RETRY_DELAY,
+            max_delay: None,
+            jitter: Jitter::None,
+            budget: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how many times an item is attempted before giving up
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the backoff before the first retry
+    pub fn base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Cap the backoff between two attempts
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = Some(max_delay);
+        self
+    }
+
+    /// Set the jitter strategy
+    pub fn jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Limit the total time spent on one item, waits included
+    pub fn budget(mut self, budget: Duration) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Backoff before retrying after the `attempt`th failed attempt (from 1), with jitter
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)));
+        let backoff = self.max_delay.map_or(backoff, |max| backoff.min(max));
+        match self.jitter {
+            Jitter::None => backoff,
+            Jitter::Full => backoff.mul_f64(random_fraction()),
+            Jitter::Equal => backoff / 2 + (backoff / 2).mul_f64(random_fraction()),
+        }
+    }
+
+    /// Whether to retry after the `attempt`th failed attempt, given the time
+    /// since the first attempt and the wait before the next one
+    pub fn should_retry(&self, attempt: u32, elapsed: Duration, delay: Duration) -> bool {
+        attempt < self.max_attempts && self.budget.is_none_or(|budget| elapsed + delay <= budget)
+    }
+}
+
+/// Uniformly distributed in `[0, 1)`
+fn random_fraction() -> f64 {
+    (uuid::Uuid::new_v4().as_u64_pair().0 >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_backoff_and_jitter() {
+        let policy = RetryPolicy::new()
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_millis(300));
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(300));
+
+        let full = policy.jitter(Jitter::Full);
+        let equal = policy.jitter(Jitter::Equal);
+        for _ in 0..100 {
+            assert!(full.delay(2) < Duration::from_millis(200));
+            let delay = equal.delay(2);
+            assert!(delay >= Duration::from_millis(100) && delay < Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn test_budget_limits_retries() {
+        let policy = RetryPolicy::new()
+            .max_attempts(5)
+            .budget(Duration::from_secs(10));
+        assert!(policy.should_retry(1, Duration::from_secs(2), Duration::from_secs(8)));
+        assert!(!policy.should_retry(1, Duration::from_secs(3), Duration::from_secs(8)));
+        assert!(!policy.should_retry(5, Duration::ZERO, Duration::ZERO));
+    }
+}
@@ -0,0 +1,290 @@
+//! Deterministic placeholder audio for clients running in sandbox mode.
+//! PCM formats get a quiet tone per turn (one pitch per speaker) so turn
+//! boundaries are visible in a waveform; other formats get valid silence.
+
+use std::time::Duration;
+
+use crate::error::ElevenLabsTTDError;
+use crate::estimate;
+use crate::formats::{Codec, OutputFormat};
+use crate::types::TTDRequest;
+
+/// Gap left between consecutive turns in placeholder audio
+const TURN_GAP: Duration = Duration::from_millis(250);
+
+/// Pitches assigned to speakers in order of first appearance
+const SPEAKER_TONES_HZ: [f64; 4] = [330.0, 440.0, 550.0, 660.0];
+
+/// Build placeholder audio for a request, in the request's output format
+pub(crate) fn placeholder_audio(request: &TTDRequest) -> Result<Vec<u8>, ElevenLabsTTDError> {
+    let name = request
+        .output_format
+        .as_deref()
+        .unwrap_or(OutputFormat::default().as_str());
+    let format: OutputFormat = name.parse()?;
+
+    // (duration, tone) per turn, with silent gaps in between
+    let mut voices: Vec<&str> = Vec::new();
+    let mut segments = Vec::new();
+    for (i, input) in request.inputs.iter().enumerate() {
+        if i > 0 {
+            segments.push((TURN_GAP, None));
+        }
+        let speaker = match voices.iter().position(|v| *v == input.voice_id) {
+            Some(index) => index,
+            None => {
+                voices.push(&input.voice_id);
+                voices.len() - 1
+            }
+        };
+        let tone = SPEAKER_TONES_HZ[speaker % SPEAKER_TONES_HZ.len()];
+        segments.push((estimate::turn_duration(&input.text), Some(tone)));
+    }
+
+    Ok(encode(&segments, format))
+}
+
+/// Encode segments of tone (or silence for `None`) in `format`
+pub(crate) fn encode(segments: &[(Duration, Option<f64>)], format: OutputFormat) -> Vec<u8> {
+    let total: Duration = segments.iter().map(|(duration, _)| *duration).sum();
+
+    match format.codec() {
+        Codec::Pcm => pcm_tones(segments, format.sample_rate()),
+        // Silence in the companded 8-bit encodings
+        Codec::Ulaw => vec![0xFF; samples_for(total, format.sample_rate())],
+        Codec::Alaw => vec![0xD5; samples_for(total, format.sample_rate())],
+        Codec::Mp3 => mp3_silence(total, format),
+        Codec::Opus => opus_silence(total),
+    }
+}
+
+fn samples_for(duration: Duration, sample_rate: u32) -> usize {
+    (duration.as_secs_f64() * sample_rate as f64).round() as usize
+}
+
+/// 16-bit little-endian mono PCM
+fn pcm_tones(segments: &[(Duration, Option<f64>)], sample_rate: u32) -> Vec<u8> {
+    let amplitude = i16::MAX as f64 * 0.1;
+    let mut out = Vec::new();
+
+    for (duration, tone) in segments {
+        for n in 0..samples_for(*duration, sample_rate) {
+            let sample = match tone {
+                Some(hz) => {
+                    let t = n as f64 / sample_rate as f64;
+                    (amplitude * (2.0 * std::f64::consts::PI * hz * t).sin()) as i16
+                }
+                None => 0,
+            };
+            out.extend_from_slice(&sample.to_le_bytes());
+        }
+    }
+
+    out
+}
+
+/// Silent mono MPEG Layer III frames: a valid header followed by zeroed side
+/// info and main data, which decoders play back as silence
+fn mp3_silence(duration: Duration, format: OutputFormat) -> Vec<u8> {
+    let sample_rate = format.sample_rate();
+    let kbps = format.bitrate_kbps().unwrap_or(128);
+
+    // MPEG-1 covers 32/44.1/48kHz; lower rates are MPEG-2
+    let mpeg1 = sample_rate >= 32000;
+    let (version_byte, samples_per_frame, frame_factor, bitrates, sample_rates): (
+        u8,
+        u32,
+        u32,
+        [u32; 15],
+        [u32; 3],
+    ) = if mpeg1 {
+        (
+            0xFB,
+            1152,
+            144_000,
+            [
+                0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+            ],
+            [44100, 48000, 32000],
+        )
+    } else {
+        (
+            0xF3,
+            576,
+            72_000,
+            [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+            [22050, 24000, 16000],
+        )
+    };
+
+    let bitrate_index = bitrates.iter().position(|b| *b == kbps).unwrap_or(9) as u8;
+    let rate_index = sample_rates
+        .iter()
+        .position(|r| *r == sample_rate)
+        .unwrap_or(0) as u8;
+
+    let frame_len = (frame_factor * bitrates[bitrate_index as usize] / sample_rate) as usize;
+    let mut frame = vec![0u8; frame_len];
+    frame[0] = 0xFF;
+    frame[1] = version_byte;
+    frame[2] = (bitrate_index << 4) | (rate_index << 2);
+    frame[3] = 0xC0; // mono
+
+    let frames = samples_for(duration, sample_rate).div_ceil(samples_per_frame as usize);
+    frame.repeat(frames)
+}
+
+/// Ogg Opus stream of 20ms silence packets
+fn opus_silence(duration: Duration) -> Vec<u8> {
+    const SERIAL: u32 = 0x5454_4453;
+    const PRE_SKIP: u16 = 312;
+    const SAMPLES_PER_PACKET: u64 = 960;
+    const PACKETS_PER_PAGE: usize = 50;
+    // CELT-only fullband 20ms mono frame that decodes to silence
+    const SILENCE_PACKET: [u8; 3] = [0xF8, 0xFF, 0xFE];
+
+    let mut out = Vec::new();
+    let mut sequence = 0;
+
+    let mut head = b"OpusHead".to_vec();
+    head.push(1); // version
+    head.push(1); // channels
+    head.extend_from_slice(&PRE_SKIP.to_le_bytes());
+    head.extend_from_slice(&48000u32.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+    head.push(0); // channel mapping family
+    write_ogg_page(&mut out, 0x02, 0, SERIAL, &mut sequence, &[&head]);
+
+    let vendor = b"elevenlabs_ttd";
+    let mut tags = b"OpusTags".to_vec();
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor);
+    tags.extend_from_slice(&0u32.to_le_bytes());
+    write_ogg_page(&mut out, 0, 0, SERIAL, &mut sequence, &[&tags]);
+
+    let packets = (samples_for(duration, 48000) as u64).div_ceil(SAMPLES_PER_PACKET) as usize;
+    let mut written = 0;
+    while written < packets || written == 0 {
+        let count = PACKETS_PER_PAGE.min(packets.saturating_sub(written)).max(1);
+        written += count;
+        let granule = PRE_SKIP as u64 + written as u64 * SAMPLES_PER_PACKET;
+        let header_type = if written >= packets { 0x04 } else { 0 };
+        let page_packets = vec![&SILENCE_PACKET[..]; count];
+        write_ogg_page(
+            &mut out,
+            header_type,
+            granule,
+            SERIAL,
+            &mut sequence,
+            &page_packets,
+        );
+    }
+
+    out
+}
+
+/// Append one Ogg page holding the given (each < 255 byte) packets
+fn write_ogg_page(
+    out: &mut Vec<u8>,
+    header_type: u8,
+    granule: u64,
+    serial: u32,
+    sequence: &mut u32,
+    packets: &[&[u8]],
+) {
+    let start = out.len();
+    out.extend_from_slice(b"OggS");
+    out.push(0); // stream structure version
+    out.push(header_type);
+    out.extend_from_slice(&granule.to_le_bytes());
+    out.extend_from_slice(&serial.to_le_bytes());
+    out.extend_from_slice(&sequence.to_le_bytes());
+    out.extend_from_slice(&[0; 4]); // checksum, filled in below
+    out.push(packets.len() as u8);
+    for packet in packets {
+        out.push(packet.len() as u8);
+    }
+    for packet in packets {
+        out.extend_from_slice(packet);
+    }
+
+    let crc = ogg_crc(&out[start..]);
+    out[start + 22..start + 26].copy_from_slice(&crc.to_le_bytes());
+    *sequence += 1;
+}
+
+/// CRC-32 as used by Ogg (polynomial 0x04C11DB7, no reflection, zero init)
+fn ogg_crc(data: &[u8]) -> u32 {
+    let mut crc = 0u32;
+    for byte in data {
+        crc ^= (*byte as u32) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TTDInput;
+
+    fn request(output_format: &str) -> TTDRequest {
+        TTDRequest {
+            output_format: Some(output_format.to_string()),
+            inputs: vec![
+                TTDInput {
+                    text: "Fifteen chars!!".to_string(),
+                    voice_id: "voice-a".to_string(),
+                },
+                TTDInput {
+                    text: "Fifteen chars!!".to_string(),
+                    voice_id: "voice-b".to_string(),
+                },
+            ],
+            model_id: Some("eleven_v3".to_string()),
+            settings: None,
+            pronunciation_dictionary_locators: None,
+            seed: None,
+            language_code: None,
+        }
+    }
+
+    #[test]
+    fn test_pcm_placeholder_matches_estimated_duration() {
+        // Two 1s turns plus one 250ms gap, 16-bit samples at 16kHz
+        let audio = placeholder_audio(&request("pcm_16000")).unwrap();
+        assert_eq!(audio.len(), 2 * 16000 * 2250 / 1000);
+        assert_eq!(audio, placeholder_audio(&request("pcm_16000")).unwrap());
+    }
+
+    #[test]
+    fn test_mp3_placeholder_is_framed() {
+        let audio = placeholder_audio(&request("mp3_44100_128")).unwrap();
+        assert_eq!(&audio[..4], &[0xFF, 0xFB, 0x90, 0xC0]);
+        assert_eq!(audio.len() % 417, 0);
+    }
+
+    #[test]
+    fn test_opus_placeholder_is_ogg() {
+        let audio = placeholder_audio(&request("opus_48000_64")).unwrap();
+        assert_eq!(&audio[..4], b"OggS");
+        assert_eq!(&audio[28..36], b"OpusHead");
+    }
+
+    #[test]
+    fn test_unknown_format_is_rejected() {
+        assert!(placeholder_audio(&request("flac_44100")).is_err());
+    }
+
+    #[test]
+    fn test_ogg_crc_check_value() {
+        // CRC-32/POSIX check value (0x765E7680) without its final XOR
+        assert_eq!(ogg_crc(b"123456789"), 0x89A1_897F);
+    }
+}
@@ -0,0 +1,136 @@
+//! Graceful shutdown: stop taking new requests and let running ones finish
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+
+use crate::error::ElevenLabsTTDError;
+
+/// Outcome of [`crate::ElevenLabsTTDClient::shutdown`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShutdownReport {
+    // Requests that were running when shutdown started and finished in time.
+    pub drained: usize,
+    // Requests still running when the deadline passed.
+    pub abandoned: usize,
+}
+
+impl ShutdownReport {
+    /// Whether every running request finished before the deadline
+    pub fn is_clean(&self) -> bool {
+        self.abandoned == 0
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    closed: AtomicBool,
+    in_flight: AtomicUsize,
+    idle: Notify,
+}
+
+/// Whether the client still accepts requests, and how many are running.
+/// Shared by every clone of a client.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Lifecycle {
+    state: Arc<State>,
+}
+
+/// Marks a request as running until dropped
+#[derive(Debug)]
+pub(crate) struct InFlight {
+    state: Arc<State>,
+}
+
+impl Lifecycle {
+    /// Count a request as running, or fail once shutdown has started
+    pub(crate) fn enter(&self) -> Result<InFlight, ElevenLabsTTDError> {
+        self.state.in_flight.fetch_add(1, Ordering::AcqRel);
+        // Checked after counting, so `close` never misses a request it let through
+        if self.is_closed() {
+            drop(InFlight {
+                state: self.state.clone(),
+            });
+            return Err(ElevenLabsTTDError::ShuttingDown);
+        }
+        Ok(InFlight {
+            state: self.state.clone(),
+        })
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.state.closed.load(Ordering::Acquire)
+    }
+
+    /// Refuse new requests and wait up to `deadline` for running ones to finish
+    pub(crate) async fn close(&self, deadline: Duration) -> ShutdownReport {
+        self.state.closed.store(true, Ordering::Release);
+        let running = self.state.in_flight.load(Ordering::Acquire);
+
+        let drained = tokio::time::timeout(deadline, async {
+            loop {
+                let idle = self.state.idle.notified();
+                if self.state.in_flight.load(Ordering::Acquire) == 0 {
+                    return;
+                }
+                idle.await;
+            }
+        })
+        .await;
+
+        let abandoned = match drained {
+            Ok(()) => 0,
+            Err(_) => self.state.in_flight.load(Ordering::Acquire).min(running),
+        };
+        ShutdownReport {
+            drained: running - abandoned,
+            abandoned,
+        }
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if self.state.in_flight.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.state.idle.notify_waiters();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_close_drains_running_requests() {
+        let lifecycle = Lifecycle::default();
+        let first = lifecycle.enter().unwrap();
+        let second = lifecycle.enter().unwrap();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drop(first);
+            drop(second);
+        });
+
+        let report = lifecycle.close(Duration::from_secs(5)).await;
+        assert_eq!(
+            report,
+            ShutdownReport {
+                drained: 2,
+                abandoned: 0
+            }
+        );
+        assert_eq!(lifecycle.enter().unwrap_err().kind(), "shutting_down");
+    }
+
+    #[tokio::test]
+    async fn test_close_gives_up_at_deadline() {
+        let lifecycle = Lifecycle::default();
+        let _stuck = lifecycle.enter().unwrap();
+        let report = lifecycle.close(Duration::from_millis(10)).await;
+        assert_eq!(report.abandoned, 1);
+        assert!(!report.is_clean());
+    }
+}
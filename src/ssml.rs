@@ -0,0 +1,363 @@
+//! Translation of a small SSML subset into plain text with eleven_v3 audio tags,
+//! for scripts written for other TTS providers.
+//!
+//! | SSML | Result |
+//! |------|--------|
+//! | `<break time="200ms"/>`, `strength="weak"` | `[short pause]` |
+//! | `<break time="700ms"/>`, `strength="medium"` | `[pause]` |
+//! | `<break time="2s"/>`, `strength="strong"` | `[long pause]` |
+//! | `<emphasis level="strong">` | the text in capitals |
+//! | `<emphasis>`, `level="moderate"` | `[emphasized]` before the text |
+//! | `<emphasis level="reduced">` | `[softly]` before the text |
+//! | `<say-as interpret-as="characters">` | letters spelled out, e.g. `A B C` |
+//! | `<say-as interpret-as="cardinal">` | the number in words |
+//! | `<say-as interpret-as="ordinal">` | the ordinal in words, e.g. `twenty-first` |
+//! | `<say-as interpret-as="telephone">` | digits read one by one |
+//!
+//! Other elements (`<speak>`, `<p>`, `<s>`, `<prosody>`, ...) are dropped and
+//! their text kept.
+
+use crate::error::ElevenLabsTTDError;
+
+/// Translate SSML into text for eleven_v3
+pub fn translate(ssml: &str) -> Result<String, ElevenLabsTTDError> {
+    // Open elements, each with the text collected inside it
+    let mut stack: Vec<(Element, String)> = vec![(Element::Other(String::new()), String::new())];
+    let mut rest = ssml;
+
+    while let Some(start) = rest.find('<') {
+        stack
+            .last_mut()
+            .unwrap()
+            .1
+            .push_str(&decode_entities(&rest[..start]));
+        let end = rest[start..]
+            .find('>')
+            .map(|end| start + end)
+            .ok_or_else(|| invalid("unterminated tag"))?;
+        let tag = rest[start + 1..end].trim();
+        rest = &rest[end + 1..];
+
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            let (element, text) = stack
+                .pop()
+                .ok_or_else(|| invalid("unexpected closing tag"))?;
+            if element.name() != name.trim() || stack.is_empty() {
+                return Err(invalid(&format!("unexpected </{}>", name.trim())));
+            }
+            let rendered = element.render(&text);
+            push_text(&mut stack.last_mut().unwrap().1, &rendered);
+            continue;
+        }
+
+        let self_closing = tag.ends_with('/');
+        let element = Element::parse(tag.trim_end_matches('/'))?;
+        if self_closing {
+            let rendered = element.render("");
+            push_text(&mut stack.last_mut().unwrap().1, &rendered);
+        } else {
+            stack.push((element, String::new()));
+        }
+    }
+    stack.last_mut().unwrap().1.push_str(&decode_entities(rest));
+
+    if stack.len() > 1 {
+        return Err(invalid(&format!("unclosed <{}>", stack[1].0.name())));
+    }
+    let text = stack.pop().unwrap().1;
+    Ok(text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+fn invalid(reason: &str) -> ElevenLabsTTDError {
+    ElevenLabsTTDError::ValidationError(format!("Invalid SSML: {}", reason))
+}
+
+/// Append `text`, keeping tags separated from neighbouring words
+fn push_text(out: &mut String, text: &str) {
+    if text.starts_with('[') && !out.is_empty() && !out.ends_with(char::is_whitespace) {
+        out.push(' ');
+    }
+    out.push_str(text);
+    if text.ends_with(']') {
+        out.push(' ');
+    }
+}
+
+enum Element {
+    Break(&'static str),
+    Emphasis(String),
+    SayAs(String),
+    Other(String),
+}
+
+impl Element {
+    fn parse(tag: &str) -> Result<Self, ElevenLabsTTDError> {
+        let (name, attributes) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        let attribute = |key: &str| attribute(attributes, key);
+
+        Ok(match name {
+            "break" => Element::Break(match (attribute("time"), attribute("strength")) {
+                (Some(time), _) => pause_for_millis(parse_time(&time)?),
+                (None, Some(strength)) => match strength.as_str() {
+                    "none" => "",
+                    "x-weak" | "weak" => "[short pause]",
+                    "strong" | "x-strong" => "[long pause]",
+                    _ => "[pause]",
+                },
+                (None, None) => "[pause]",
+            }),
+            "emphasis" => Element::Emphasis(attribute("level").unwrap_or_default()),
+            "say-as" => Element::SayAs(attribute("interpret-as").unwrap_or_default()),
+            _ => Element::Other(name.to_string()),
+        })
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Element::Break(_) => "break",
+            Element::Emphasis(_) => "emphasis",
+            Element::SayAs(_) => "say-as",
+            Element::Other(name) => name,
+        }
+    }
+
+    fn render(&self, text: &str) -> String {
+        match self {
+            Element::Break(tag) => tag.to_string(),
+            Element::Emphasis(level) => match level.as_str() {
+                "strong" => text.to_uppercase(),
+                "reduced" => format!("[softly] {}", text.trim()),
+                "none" => text.to_string(),
+                _ => format!("[emphasized] {}", text.trim()),
+            },
+            Element::SayAs(interpret_as) => say_as(interpret_as, text.trim()),
+            // Paragraphs and sentences still separate words
+            Element::Other(_) => format!(" {} ", text),
+        }
+    }
+}
+
+/// Value of `key="value"` (or single-quoted) in a tag's attributes
+fn attribute(attributes: &str, key: &str) -> Option<String> {
+    let mut rest = attributes;
+    while let Some(eq) = rest.find('=') {
+        let name = rest[..eq].trim();
+        let value = rest[eq + 1..].trim_start();
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let end = value[1..].find(quote)? + 1;
+        if name == key {
+            return Some(decode_entities(&value[1..end]));
+        }
+        rest = &value[end + 1..];
+    }
+    None
+}
+
+/// Milliseconds in an SSML time such as `500ms` or `1.5s`
+fn parse_time(time: &str) -> Result<f64, ElevenLabsTTDError> {
+    let time = time.trim();
+    let (number, scale) = match time.strip_suffix("ms") {
+        Some(ms) => (ms, 1.0),
+        None => (time.strip_suffix('s').unwrap_or(time), 1000.0),
+    };
+    number
+        .trim()
+        .parse::<f64>()
+        .map(|n| n * scale)
+        .map_err(|_| invalid(&format!("bad break time {:?}", time)))
+}
+
+/// The eleven_v3 pause tag closest to `millis`, or nothing for no pause
+pub(crate) fn pause_for_millis(millis: f64) -> &'static str {
+    match millis {
+        m if m <= 0.0 => "",
+        m if m < 500.0 => "[short pause]",
+        m if m < 1500.0 => "[pause]",
+        _ => "[long pause]",
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn say_as(interpret_as: &str, text: &str) -> String {
+    let number = || text.replace(',', "").parse::<i64>().ok();
+    match interpret_as {
+        "characters" | "spell-out" => text
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| c.to_uppercase().to_string())
+            .collect::<Vec<_>>()
+            .join(" "),
+        "cardinal" | "number" => number()
+            .map(number_words)
+            .unwrap_or_else(|| text.to_string()),
+        "ordinal" => number()
+            .map(|n| ordinal_words(&number_words(n)))
+            .unwrap_or_else(|| text.to_string()),
+        "telephone" | "digits" => text
+            .chars()
+            .filter(|c| c.is_ascii_digit())
+            .map(|c| number_words(c.to_digit(10).unwrap() as i64))
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => text.to_string(),
+    }
+}
+
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+const SCALES: [(u64, &str); 6] = [
+    (1_000_000_000_000_000_000, "quintillion"),
+    (1_000_000_000_000_000, "quadrillion"),
+    (1_000_000_000_000, "trillion"),
+    (1_000_000_000, "billion"),
+    (1_000_000, "million"),
+    (1_000, "thousand"),
+];
+
+/// An integer in English words, e.g. `one hundred twenty-three`
+fn number_words(n: i64) -> String {
+    if n < 0 {
+        return format!("minus {}", number_words_unsigned(n.unsigned_abs()));
+    }
+    number_words_unsigned(n as u64)
+}
+
+fn number_words_unsigned(mut n: u64) -> String {
+    if n == 0 {
+        return ONES[0].to_string();
+    }
+
+    let mut words = Vec::new();
+    for (scale, name) in SCALES {
+        if n >= scale {
+            words.push(format!("{} {}", below_thousand(n / scale), name));
+            n %= scale;
+        }
+    }
+    if n > 0 {
+        words.push(below_thousand(n));
+    }
+    words.join(" ")
+}
+
+fn below_thousand(n: u64) -> String {
+    let mut words = Vec::new();
+    if n >= 100 {
+        words.push(format!("{} hundred", ONES[(n / 100) as usize]));
+    }
+    match n % 100 {
+        0 => {}
+        r if r < 20 => words.push(ONES[r as usize].to_string()),
+        r if r % 10 == 0 => words.push(TENS[(r / 10) as usize].to_string()),
+        r => words.push(format!(
+            "{}-{}",
+            TENS[(r / 10) as usize],
+            ONES[(r % 10) as usize]
+        )),
+    }
+    words.join(" ")
+}
+
+/// Turn the last word of a cardinal into its ordinal, e.g. `twenty-one` → `twenty-first`
+fn ordinal_words(cardinal: &str) -> String {
+    let split = cardinal.rfind([' ', '-']).map(|i| i + 1).unwrap_or(0);
+    let (head, last) = cardinal.split_at(split);
+    let ordinal = match last {
+        "one" => "first".to_string(),
+        "two" => "second".to_string(),
+        "three" => "third".to_string(),
+        "five" => "fifth".to_string(),
+        "eight" => "eighth".to_string(),
+        "nine" => "ninth".to_string(),
+        "twelve" => "twelfth".to_string(),
+        word if word.ends_with('y') => format!("{}ieth", &word[..word.len() - 1]),
+        word => format!("{}th", word),
+    };
+    format!("{}{}", head, ordinal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breaks_and_emphasis() {
+        assert_eq!(
+            translate(r#"<speak>Wait<break time="200ms"/>for it. <break strength="x-strong"/>Now!</speak>"#)
+                .unwrap(),
+            "Wait [short pause] for it. [long pause] Now!"
+        );
+        assert_eq!(
+            translate(r#"I <emphasis level="strong">really</emphasis> mean it, <emphasis>truly</emphasis>."#)
+                .unwrap(),
+            "I REALLY mean it, [emphasized] truly."
+        );
+        assert_eq!(
+            translate("<p>Tom &amp; Jerry</p><p><emphasis level='reduced'>quiet</emphasis></p>")
+                .unwrap(),
+            "Tom & Jerry [softly] quiet"
+        );
+    }
+
+    #[test]
+    fn test_say_as() {
+        assert_eq!(
+            translate(r#"Call <say-as interpret-as="telephone">555-01</say-as>"#).unwrap(),
+            "Call five five five zero one"
+        );
+        assert_eq!(
+            translate(r#"<say-as interpret-as="characters">abc</say-as>"#).unwrap(),
+            "A B C"
+        );
+        assert_eq!(
+            translate(r#"<say-as interpret-as="cardinal">1,234,005</say-as>"#).unwrap(),
+            "one million two hundred thirty-four thousand five"
+        );
+        assert_eq!(
+            translate(r#"the <say-as interpret-as="ordinal">21</say-as> and <say-as interpret-as="ordinal">40</say-as>"#)
+                .unwrap(),
+            "the twenty-first and fortieth"
+        );
+    }
+
+    #[test]
+    fn test_rejects_malformed_ssml() {
+        assert!(translate("<speak>open").is_err());
+        assert!(translate("<speak>text</p>").is_err());
+        assert!(translate("<break time=\"soon\"/>").is_err());
+        assert!(translate("a < b").is_err());
+    }
+}
@@ -0,0 +1,156 @@
+//! In-process latency histograms, see [`crate::ElevenLabsTTDClient::stats`]
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Upper bound of the first bucket
+const FIRST_BUCKET_MS: f64 = 1.0;
+
+/// Each bucket is this much wider than the one before, bounding percentile error to ~10%
+const BUCKET_GROWTH: f64 = 1.2;
+
+/// Buckets up to ~1 hour; slower requests land in the last one
+const BUCKETS: usize = 84;
+
+/// Latency of successful requests to one endpoint in one output format
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LatencyStats {
+    // "text_to_dialogue" or "text_to_speech".
+    pub endpoint: &'static str,
+    // Output format requested, `None` when the API default was used.
+    pub output_format: Option<String>,
+    pub count: u64,
+    pub mean: Duration,
+    // Percentiles are the upper bound of the histogram bucket they fall in.
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+#[derive(Debug, Clone)]
+struct Histogram {
+    buckets: Vec<u64>,
+    count: u64,
+    total: Duration,
+    max: Duration,
+}
+
+impl Histogram {
+    fn new() -> Self {
+        Self {
+            buckets: vec![0; BUCKETS],
+            count: 0,
+            total: Duration::ZERO,
+            max: Duration::ZERO,
+        }
+    }
+
+    fn record(&mut self, latency: Duration) {
+        let ms = latency.as_secs_f64() * 1000.0;
+        let bucket = if ms <= FIRST_BUCKET_MS {
+            0
+        } else {
+            ((ms / FIRST_BUCKET_MS).ln() / BUCKET_GROWTH.ln()).ceil() as usize
+        };
+        self.buckets[bucket.min(BUCKETS - 1)] += 1;
+        self.count += 1;
+        self.total += latency;
+        self.max = self.max.max(latency);
+    }
+
+    fn percentile(&self, percentile: f64) -> Duration {
+        let rank = ((percentile * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let bound = FIRST_BUCKET_MS * BUCKET_GROWTH.powi(bucket as i32);
+                return Duration::from_secs_f64(bound / 1000.0).min(self.max);
+            }
+        }
+        self.max
+    }
+}
+
+/// Endpoint and output format a histogram is kept for
+type Key = (&'static str, Option<String>);
+
+/// Latency histograms per endpoint and output format, shared by every clone of a client
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LatencyRecorder {
+    histograms: Arc<Mutex<HashMap<Key, Histogram>>>,
+}
+
+impl LatencyRecorder {
+    pub(crate) fn record(
+        &self,
+        endpoint: &'static str,
+        output_format: Option<&str>,
+        latency: Duration,
+    ) {
+        self.histograms
+            .lock()
+            .unwrap()
+            .entry((endpoint, output_format.map(str::to_string)))
+            .or_insert_with(Histogram::new)
+            .record(latency);
+    }
+
+    pub(crate) fn stats(&self) -> Vec<LatencyStats> {
+        let histograms = self.histograms.lock().unwrap();
+        let mut stats: Vec<LatencyStats> = histograms
+            .iter()
+            .map(|((endpoint, output_format), histogram)| LatencyStats {
+                endpoint,
+                output_format: output_format.clone(),
+                count: histogram.count,
+                mean: histogram.total / histogram.count as u32,
+                p50: histogram.percentile(0.50),
+                p95: histogram.percentile(0.95),
+                p99: histogram.percentile(0.99),
+                max: histogram.max,
+            })
+            .collect();
+        stats.sort_by(|a, b| (a.endpoint, &a.output_format).cmp(&(b.endpoint, &b.output_format)));
+        stats
+    }
+
+    pub(crate) fn reset(&self) {
+        self.histograms.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_within_bucket_error() {
+        let recorder = LatencyRecorder::default();
+        for ms in 1..=100 {
+            recorder.record(
+                "text_to_dialogue",
+                Some("mp3_44100_128"),
+                Duration::from_millis(ms * 10),
+            );
+        }
+        recorder.record("text_to_speech", None, Duration::from_millis(5));
+
+        let stats = recorder.stats();
+        assert_eq!(stats.len(), 2);
+        let dialogue = &stats[0];
+        assert_eq!(dialogue.count, 100);
+        assert_eq!(dialogue.mean, Duration::from_millis(505));
+        assert_eq!(dialogue.max, Duration::from_secs(1));
+        let close = |actual: Duration, expected: f64| {
+            (actual.as_secs_f64() - expected).abs() / expected <= 0.2
+        };
+        assert!(close(dialogue.p50, 0.5), "{:?}", dialogue.p50);
+        assert!(close(dialogue.p95, 0.95), "{:?}", dialogue.p95);
+        assert_eq!(stats[1].p99, Duration::from_millis(5));
+    }
+}
@@ -0,0 +1,220 @@
+//! Auditioning a sample line across a grid of generation settings, so settings
+//! can be compared side by side instead of tried one at a time
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::ElevenLabsTTDClient;
+use crate::error::ElevenLabsTTDError;
+use crate::formats::OutputFormat;
+use crate::types::{TTDAudio, TTDInput, TTDSettings};
+use crate::validation::STABILITY_VALUES;
+
+/// Name of the index [`write_samples`] writes next to the samples
+pub const SWEEP_INDEX_NAME: &str = "sweep.json";
+
+/// A sample line rendered once per combination of stability and speaker boost.
+/// By default every accepted stability is tried with speaker boost off and on.
+#[derive(Debug, Clone)]
+pub struct SettingsSweep {
+    pub inputs: Vec<TTDInput>,
+    pub stability: Vec<f32>,
+    pub speaker_boost: Vec<bool>,
+    pub output_format: OutputFormat,
+    // Seed every sample is generated with, so only the settings differ.
+    pub seed: Option<u32>,
+}
+
+/// One rendered combination of a [`SettingsSweep`]
+#[derive(Debug, Clone)]
+pub struct SweepSample {
+    // e.g. "stability-0.5_boost-on", also the sample's file name.
+    pub label: String,
+    pub settings: TTDSettings,
+    pub audio: TTDAudio,
+}
+
+/// Entry of the [`SWEEP_INDEX_NAME`] index
+#[derive(Debug, Clone, Serialize)]
+struct IndexEntry<'a> {
+    label: &'a str,
+    file: String,
+    settings: &'a TTDSettings,
+    seed: Option<u32>,
+}
+
+impl SettingsSweep {
+    pub fn new<I: Into<Vec<TTDInput>>>(inputs: I) -> Self {
+        Self {
+            inputs: inputs.into(),
+            stability: STABILITY_VALUES.to_vec(),
+            speaker_boost: vec![false, true],
+            output_format: OutputFormat::default(),
+            seed: None,
+        }
+    }
+
+    /// Stability values to try
+    pub fn stability<I: IntoIterator<Item = f32>>(mut self, values: I) -> Self {
+        self.stability = values.into_iter().collect();
+        self
+    }
+
+    /// Speaker boost settings to try
+    pub fn speaker_boost<I: IntoIterator<Item = bool>>(mut self, values: I) -> Self {
+        self.speaker_boost = values.into_iter().collect();
+        self
+    }
+
+    pub fn output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+
+    /// Generate every sample with `seed`
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Every combination of the settings, with its label, in render order.
+    /// Labels are unique: a combination listed twice gets a `-2`, `-3`... suffix.
+    pub fn grid(&self) -> Vec<(String, TTDSettings)> {
+        let mut seen = HashSet::new();
+        self.stability
+            .iter()
+            .flat_map(|stability| {
+                self.speaker_boost.iter().map(move |boost| {
+                    // Debug keeps every digit needed to tell values apart, e.g. 0.25
+                    let label = format!(
+                        "stability-{:?}_boost-{}",
+                        stability,
+                        if *boost { "on" } else { "off" }
+                    );
+                    let settings = TTDSettings::new()
+                        .stability(*stability)
+                        .speaker_boost(*boost);
+                    (label, settings)
+                })
+            })
+            .map(|(label, settings)| {
+                let unique = (1..)
+                    .map(|n| match n {
+                        1 => label.clone(),
+                        n => format!("{}-{}", label, n),
+                    })
+                    .find(|candidate| seen.insert(candidate.clone()))
+                    .unwrap();
+                (unique, settings)
+            })
+            .collect()
+    }
+
+    /// Render the sample line with every combination, one after the other
+    pub async fn render(
+        &self,
+        client: &ElevenLabsTTDClient,
+    ) -> Result<Vec<SweepSample>, ElevenLabsTTDError> {
+        let mut samples = Vec::new();
+        for (label, settings) in self.grid() {
+            let mut builder = client
+                .text_to_dialogue(self.inputs.clone())
+                .output_format(self.output_format)
+                .settings(settings.clone());
+            if let Some(seed) = self.seed {
+                builder = builder.seed(seed);
+            }
+            let audio = builder.execute().await?;
+            samples.push(SweepSample {
+                label,
+                settings,
+                audio,
+            });
+        }
+        Ok(samples)
+    }
+}
+
+/// Write each sample to `dir` as `<label>.<ext>`, plus a [`SWEEP_INDEX_NAME`]
+/// index of labels, files and settings. Returns the paths of the samples.
+pub fn write_samples<P: AsRef<Path>>(
+    samples: &[SweepSample],
+    dir: P,
+) -> Result<Vec<PathBuf>, ElevenLabsTTDError> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+
+    let mut paths = Vec::new();
+    let mut index = Vec::new();
+    for sample in samples {
+        let extension = sample
+            .audio
+            .output_format
+            .map_or("mp3", |format| format.extension());
+        let file = format!("{}.{}", sample.label, extension);
+        let path = dir.join(&file);
+        sample.audio.save(&path)?;
+        paths.push(path);
+        index.push(IndexEntry {
+            label: &sample.label,
+            file,
+            settings: &sample.settings,
+            seed: sample.audio.manifest.as_ref().and_then(|m| m.seed),
+        });
+    }
+    std::fs::write(
+        dir.join(SWEEP_INDEX_NAME),
+        serde_json::to_vec_pretty(&index)?,
+    )?;
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sweep_renders_and_writes_grid() {
+        let dir = std::env::temp_dir().join(format!("ttd-sweep-{}", uuid::Uuid::new_v4()));
+        let client = ElevenLabsTTDClient::sandbox();
+        let sweep = SettingsSweep::new(vec![TTDInput {
+            voice_id: "voice-a".to_string(),
+            text: "How does this sound?".to_string(),
+        }])
+        .stability([0.0, 1.0])
+        .output_format(OutputFormat::Pcm16000)
+        .seed(3);
+        assert_eq!(sweep.grid().len(), 4);
+
+        let samples = sweep.render(&client).await.unwrap();
+        assert_eq!(samples[0].label, "stability-0.0_boost-off");
+        assert_eq!(samples[3].label, "stability-1.0_boost-on");
+        assert_eq!(samples[3].settings.use_speaker_boost, Some(true));
+
+        let paths = write_samples(&samples, &dir).unwrap();
+        assert_eq!(paths[1], dir.join("stability-0.0_boost-on.pcm"));
+        let index: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(dir.join(SWEEP_INDEX_NAME)).unwrap()).unwrap();
+        assert_eq!(index[2]["settings"]["stability"], 1.0);
+        assert_eq!(index[2]["seed"], 3);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_grid_labels_are_unique() {
+        let sweep = SettingsSweep::new(Vec::new())
+            .stability([0.2, 0.25, 0.2])
+            .speaker_boost([false]);
+        let labels: Vec<String> = sweep.grid().into_iter().map(|(label, _)| label).collect();
+        assert_eq!(
+            labels,
+            [
+                "stability-0.2_boost-off",
+                "stability-0.25_boost-off",
+                "stability-0.2_boost-off-2",
+            ]
+        );
+    }
+}
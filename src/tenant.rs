@@ -0,0 +1,132 @@
+//! Per-tenant API keys for clients shared by many customers
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// How long a key returned by a [`KeyProvider`] is reused before asking again
+pub const DEFAULT_KEY_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Identifies the customer a request is made for
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(transparent)]
+pub struct TenantId(pub String);
+
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<S: Into<String>> From<S> for TenantId {
+    fn from(id: S) -> Self {
+        TenantId(id.into())
+    }
+}
+
+/// An API key that is never printed by `Debug` or `Display`
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new<S: Into<String>>(secret: S) -> Self {
+        Secret(secret.into())
+    }
+
+    /// The key itself, for sending
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(***)")
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+/// Looks up the ElevenLabs API key of a tenant, consulted by the client for
+/// requests made with [`crate::TextToDialogueBuilder::tenant`]
+pub trait KeyProvider: Send + Sync {
+    /// The tenant's key, or `None` if the tenant is unknown
+    fn key_for(&self, tenant: &TenantId) -> Option<Secret>;
+}
+
+impl<F> KeyProvider for F
+where
+    F: Fn(&TenantId) -> Option<Secret> + Send + Sync,
+{
+    fn key_for(&self, tenant: &TenantId) -> Option<Secret> {
+        self(tenant)
+    }
+}
+
+/// A [`KeyProvider`] with its answers cached for a TTL. Clones share the cache.
+#[derive(Clone)]
+pub(crate) struct CachedKeyProvider {
+    provider: Arc<dyn KeyProvider>,
+    ttl: Duration,
+    cache: Arc<Mutex<HashMap<TenantId, (Secret, Instant)>>>,
+}
+
+impl CachedKeyProvider {
+    pub(crate) fn new(provider: Arc<dyn KeyProvider>, ttl: Duration) -> Self {
+        Self {
+            provider,
+            ttl,
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub(crate) fn key_for(&self, tenant: &TenantId) -> Option<Secret> {
+        let mut cache = self.cache.lock().unwrap();
+        if let Some((secret, fetched_at)) = cache.get(tenant)
+            && fetched_at.elapsed() < self.ttl
+        {
+            return Some(secret.clone());
+        }
+
+        let secret = self.provider.key_for(tenant)?;
+        cache.insert(tenant.clone(), (secret.clone(), Instant::now()));
+        Some(secret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_provider_answers_are_cached() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let provider = move |tenant: &TenantId| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (tenant.0 == "acme").then(|| Secret::new("sk_acme"))
+        };
+        let cached = CachedKeyProvider::new(Arc::new(provider), DEFAULT_KEY_CACHE_TTL);
+
+        let acme = TenantId::from("acme");
+        assert_eq!(cached.key_for(&acme).unwrap().expose(), "sk_acme");
+        assert_eq!(cached.key_for(&acme).unwrap().expose(), "sk_acme");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        assert!(cached.key_for(&TenantId::from("unknown")).is_none());
+    }
+
+    #[test]
+    fn test_secret_is_redacted() {
+        let secret = Secret::new("sk_live_123");
+        assert_eq!(format!("{:?} {}", secret, secret), "Secret(***) ***");
+    }
+}
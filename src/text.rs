@@ -0,0 +1,221 @@
+//! Text helpers shared by validation, estimates and usage accounting
+
+/// Characters ElevenLabs bills for a piece of text:
+///
+/// - bracketed audio tags such as `[laughs]` are directions, not speech, and aren't counted
+/// - leading and trailing whitespace isn't counted, and runs of whitespace count once
+/// - every other Unicode scalar value counts as one character, so an emoji made of
+///   several code points counts several times
+pub fn billable_len(text: &str) -> u64 {
+    let mut count = 0;
+    let mut pending_space = false;
+    let mut in_tag = false;
+    // A `[` after the last `]` is never closed
+    let last_close = text.rfind(']');
+
+    for (i, c) in text.char_indices() {
+        match c {
+            // Unclosed brackets are plain text
+            '[' if !in_tag && last_close.is_some_and(|close| close > i) => in_tag = true,
+            ']' if in_tag => in_tag = false,
+            _ if in_tag => {}
+            c if c.is_whitespace() => pending_space = count > 0,
+            _ => {
+                if pending_space {
+                    count += 1;
+                    pending_space = false;
+                }
+                count += 1;
+            }
+        }
+    }
+
+    count
+}
+
+/// Clean up text pasted from word processors: strips control and zero-width
+/// characters, replaces smart quotes, ellipses and unusual spaces with plain ASCII,
+/// and collapses runs of spaces. Line breaks are kept, but at most one blank line.
+pub fn sanitize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut newlines = 0;
+    let mut space = false;
+    let mut buf = [0u8; 4];
+
+    for c in text.chars() {
+        let visible = match c {
+            '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{2032}' => "'",
+            '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{2033}' => "\"",
+            '\u{2026}' => "...",
+            // Zero-width characters, byte order mark and soft hyphen
+            '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{FEFF}' | '\u{00AD}' => {
+                continue;
+            }
+            '\n' => {
+                newlines += 1;
+                continue;
+            }
+            c if c.is_whitespace() => {
+                space = true;
+                continue;
+            }
+            c if c.is_control() => continue,
+            c => c.encode_utf8(&mut buf),
+        };
+
+        // Whitespace seen since the last visible character, dropped at the start
+        if !out.is_empty() {
+            match newlines {
+                0 if space => out.push(' '),
+                0 => {}
+                1 => out.push('\n'),
+                _ => out.push_str("\n\n"),
+            }
+        }
+        newlines = 0;
+        space = false;
+        out.push_str(visible);
+    }
+
+    out
+}
+
+/// Split text into chunks of at most `max_chars` characters, audio tags included,
+/// breaking at sentence ends where possible, then at spaces, then anywhere
+pub fn split_sentences(text: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks: Vec<String> = Vec::new();
+    let mut current = String::new();
+
+    for sentence in sentences(text) {
+        let candidate = if current.is_empty() {
+            sentence.to_string()
+        } else {
+            format!("{} {}", current, sentence)
+        };
+        if candidate.chars().count() <= max_chars {
+            current = candidate;
+            continue;
+        }
+
+        if !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if sentence.chars().count() <= max_chars {
+            current = sentence.to_string();
+        } else {
+            let mut pieces = split_words(sentence, max_chars);
+            current = pieces.pop().unwrap_or_default();
+            chunks.extend(pieces);
+        }
+    }
+
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Trimmed sentences, each ending at `.`, `!` or `?` followed by whitespace
+fn sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((_, c)) = chars.next() {
+        if matches!(c, '.' | '!' | '?')
+            && let Some(&(next, following)) = chars.peek()
+            && following.is_whitespace()
+        {
+            sentences.push(text[start..next].trim());
+            start = next;
+        }
+    }
+    sentences.push(text[start..].trim());
+    sentences.retain(|s| !s.is_empty());
+    sentences
+}
+
+/// Split a single overlong sentence at spaces, or mid-word for words longer than `max_chars`
+fn split_words(sentence: &str, max_chars: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+
+    for word in sentence.split_whitespace() {
+        let candidate = if current.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", current, word)
+        };
+        if candidate.chars().count() <= max_chars {
+            current = candidate;
+            continue;
+        }
+
+        if !current.is_empty() {
+            pieces.push(std::mem::take(&mut current));
+        }
+        let chars: Vec<char> = word.chars().collect();
+        let mut parts: Vec<String> = chars
+            .chunks(max_chars.max(1))
+            .map(|part| part.iter().collect())
+            .collect();
+        current = parts.pop().unwrap_or_default();
+        pieces.extend(parts);
+    }
+
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_sentences_respects_limit() {
+        assert_eq!(
+            split_sentences("One two. Three four! Five?", 20),
+            vec!["One two. Three four!", "Five?"]
+        );
+        assert_eq!(split_sentences("Short.", 20), vec!["Short."]);
+        assert_eq!(
+            split_sentences("aaaa bbbb cccc.", 9),
+            vec!["aaaa bbbb", "cccc."]
+        );
+        assert_eq!(split_sentences("abcdefgh", 3), vec!["abc", "def", "gh"]);
+        // Decimals aren't sentence ends
+        assert_eq!(
+            split_sentences("Pi is 3.14. Yes.", 11),
+            vec!["Pi is 3.14.", "Yes."]
+        );
+    }
+
+    #[test]
+    fn test_sanitize_cleans_pasted_text() {
+        assert_eq!(
+            sanitize("\u{FEFF}\u{201C}Don\u{2019}t\u{201D}\u{00A0}\u{00A0}go\u{2026}\t\u{0007}"),
+            "\"Don't\" go..."
+        );
+        assert_eq!(sanitize("One.  \r\n\n\n\nTwo."), "One.\n\nTwo.");
+        assert_eq!(sanitize("zero\u{200B}width"), "zerowidth");
+    }
+
+    #[test]
+    fn test_tags_and_whitespace_are_not_billed() {
+        assert_eq!(billable_len("Hello there."), 12);
+        assert_eq!(billable_len("  [laughs] Hello   there. "), 12);
+        assert_eq!(billable_len("[whispers]"), 0);
+        assert_eq!(billable_len("a [sighs] b"), 3);
+    }
+
+    #[test]
+    fn test_unicode_counts_scalar_values() {
+        assert_eq!(billable_len("héllo"), 5);
+        assert_eq!(billable_len("日本語"), 3);
+        assert_eq!(billable_len("[unclosed"), 9);
+        assert_eq!(billable_len("] [unclosed"), 11);
+        assert_eq!(billable_len("[a] b [unclosed"), 11);
+    }
+}
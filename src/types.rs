@@ -1,6 +1,14 @@
+use std::collections::HashMap;
+
+use reqwest::header::HeaderMap;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
-#[derive(Debug, Clone, Serialize)]
+use crate::formats::OutputFormat;
+use crate::manifest::GenerationManifest;
+use crate::rate_limit::RateLimitInfo;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TTDInput {
     // The text to be converted into speech.
     pub text: String,
@@ -8,7 +16,17 @@ pub struct TTDInput {
     pub voice_id: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+impl TTDInput {
+    /// A turn spoken by `voice`, given as any [`VoiceRef`]
+    pub fn new<V: VoiceRef, T: Into<String>>(voice: V, text: T) -> Self {
+        Self {
+            text: text.into(),
+            voice_id: voice.voice_id().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TTDSettings {
     // Determines how stable the voice is and the randomness between each generation.
     // Lower values introduce broader emotional range for the voice.
@@ -20,7 +38,7 @@ pub struct TTDSettings {
     pub use_speaker_boost: Option<bool>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TTDPronunciationDictionaryLocators {
     // The ID of the pronunciation dictionary.
     pub pronunciation_dictionary_id: String,
@@ -37,6 +55,7 @@ pub struct TTDRequest {
     // Possible values are: mp3_22050_32 | mp3_44100_32 | mp3_44100_64 | mp3_44100_96 | mp3_44100_128 | mp3_44100_192 | pcm_8000 | pcm_16000 | pcm_22050 | pcm_24000 | pcm_44100 | pcm_48000 | ulaw_8000 | alaw_8000 | opus_48000_32 | opus_48000_64 | opus_48000_96
     // Default to: mp3_44100_128
     // This goes in the URL path, not in the body.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub output_format: Option<String>,
 
     // A list of dialogue inputs, each containing text and a voice ID which will be converted into speech.
@@ -44,7 +63,9 @@ pub struct TTDRequest {
 
     // Identifier of the model that will be used.
     // Only Eleven V3 Family Supported for now.
-    pub model_id: String,
+    // When not set, the API's default model is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_id: Option<String>,
 
     // Settings controlling the dialogue generation.
     pub settings: Option<TTDSettings>,
@@ -56,6 +77,281 @@ pub struct TTDRequest {
     // If specified, our system will make a best effort to sample deterministically, such that repeated requests with the same seed and parameters should return the same result.
     // Determinism is not guaranteed. Must be integer between 0 and 4294967295.
     pub seed: Option<u32>,
+
+    // ISO 639-1 code of the language the text is in, e.g. "fr", to improve pronunciation.
+    // When not set, the model detects the language.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_code: Option<String>,
+}
+
+/// Every option of a [`TTDRequest`] except its inputs, e.g. loaded from a config
+/// file and combined with each dialogue:
+/// `template.with_inputs(inputs).execute(&client)`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TTDRequestTemplate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings: Option<TTDSettings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pronunciation_dictionary_locators: Option<TTDPronunciationDictionaryLocators>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_code: Option<String>,
+    // Leave an unset output format and model to the API instead of filling in
+    // `mp3_44100_128` / `eleven_v3`.
+    pub use_server_defaults: bool,
+}
+
+impl TTDRequestTemplate {
+    /// A template with no options set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the output format to use
+    pub fn output_format<S: Into<String>>(mut self, output_format: S) -> Self {
+        self.output_format = Some(output_format.into());
+        self
+    }
+
+    /// Set the model to use
+    pub fn model<S: Into<String>>(mut self, model_id: S) -> Self {
+        self.model_id = Some(model_id.into());
+        self
+    }
+
+    /// Set the settings to use
+    pub fn settings(mut self, settings: TTDSettings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    /// Set the pronunciation dictionary locators to use
+    pub fn pronunciation_dictionary_locators(
+        mut self,
+        pronunciation_dictionary_locators: TTDPronunciationDictionaryLocators,
+    ) -> Self {
+        self.pronunciation_dictionary_locators = Some(pronunciation_dictionary_locators);
+        self
+    }
+
+    /// Set seeds to use
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Set the language of the text as an ISO 639-1 code, e.g. "fr"
+    pub fn language_code<S: Into<String>>(mut self, language_code: S) -> Self {
+        self.language_code = Some(language_code.into());
+        self
+    }
+
+    /// Don't fill in `mp3_44100_128` / `eleven_v3` when no output format or model
+    /// was set; leave them out and let ElevenLabs apply its current defaults
+    pub fn use_server_defaults(mut self) -> Self {
+        self.use_server_defaults = true;
+        self
+    }
+
+    /// The request for `inputs` with this template's options
+    pub fn with_inputs<I: Into<Vec<TTDInput>>>(&self, inputs: I) -> TTDRequest {
+        let mut output_format = self.output_format.clone();
+        let mut model_id = self.model_id.clone();
+        if !self.use_server_defaults {
+            output_format.get_or_insert_with(|| OutputFormat::default().into()); // Default to: mp3_44100_128
+            model_id.get_or_insert_with(|| {
+                crate::models::elevanlabs_models::ELEVEN_V3.to_string() // Default to: eleven_v3
+            });
+        }
+
+        TTDRequest {
+            inputs: inputs.into(),
+            output_format,
+            seed: self.seed,
+            language_code: self.language_code.clone(),
+            model_id,
+            settings: self.settings.clone(),
+            pronunciation_dictionary_locators: self.pronunciation_dictionary_locators.clone(),
+        }
+    }
+}
+
+/// One field that differs between two [`TTDRequest`]s
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    // Path of the field, e.g. "inputs[1].text" or "settings.stability".
+    pub field: String,
+    // JSON value in the first request, "(unset)" if absent.
+    pub left: String,
+    // JSON value in the second request, "(unset)" if absent.
+    pub right: String,
+}
+
+impl std::fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} -> {}", self.field, self.left, self.right)
+    }
+}
+
+impl TTDRequest {
+    /// Send this request with `client`, going through the same validation and
+    /// checks as [`crate::TextToDialogueBuilder::execute`]
+    pub async fn execute(
+        self,
+        client: &crate::ElevenLabsTTDClient,
+    ) -> Result<TTDAudio, crate::ElevenLabsTTDError> {
+        let template = TTDRequestTemplate {
+            output_format: self.output_format,
+            model_id: self.model_id,
+            settings: self.settings,
+            pronunciation_dictionary_locators: self.pronunciation_dictionary_locators,
+            seed: self.seed,
+            language_code: self.language_code,
+            // Defaults were filled in when the request was made
+            use_server_defaults: true,
+        };
+        client
+            .text_to_dialogue(self.inputs)
+            .apply_template(template)
+            .execute()
+            .await
+    }
+
+    /// Field-by-field differences from `other`, empty when both would send the
+    /// same request. Strings are shown JSON-escaped, so stray newlines and tabs
+    /// are visible.
+    pub fn diff(&self, other: &TTDRequest) -> Vec<FieldChange> {
+        let left = serde_json::to_value(self).unwrap_or_default();
+        let right = serde_json::to_value(other).unwrap_or_default();
+        let mut changes = Vec::new();
+        diff_values(String::new(), Some(&left), Some(&right), &mut changes);
+        changes
+    }
+
+    /// Hex-encoded SHA-256 of the request's content, stable across crate versions
+    /// and field order: object keys are sorted, unset fields and explicit nulls are
+    /// equivalent, and texts have line endings, trailing and repeated spaces normalized.
+    /// Used as the key of [`crate::DedupeStore`] entries, hashed again with the
+    /// tenant or API key when the request overrides either.
+    pub fn canonical_hash(&self) -> String {
+        let mut request = self.clone();
+        for input in &mut request.inputs {
+            input.text = canonical_text(&input.text);
+        }
+        let mut canonical = String::new();
+        canonical_json(
+            &serde_json::to_value(&request).unwrap_or_default(),
+            &mut canonical,
+        );
+        Sha256::digest(canonical.as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+/// `text` with CRLF line endings, runs of spaces and tabs, and whitespace at the
+/// ends of lines and of the text collapsed away
+fn canonical_text(text: &str) -> String {
+    text.trim()
+        .lines()
+        .map(|line| {
+            line.split([' ', '\t'])
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// JSON with sorted object keys and without null fields
+fn canonical_json(value: &serde_json::Value, out: &mut String) {
+    use serde_json::Value;
+
+    match value {
+        Value::Object(map) => {
+            let mut fields: Vec<(&String, &Value)> =
+                map.iter().filter(|(_, value)| !value.is_null()).collect();
+            fields.sort_by_key(|(key, _)| *key);
+            out.push('{');
+            for (i, (key, value)) in fields.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                canonical_json(value, out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                canonical_json(item, out);
+            }
+            out.push(']');
+        }
+        value => out.push_str(&value.to_string()),
+    }
+}
+
+fn diff_values(
+    field: String,
+    left: Option<&serde_json::Value>,
+    right: Option<&serde_json::Value>,
+    changes: &mut Vec<FieldChange>,
+) {
+    use serde_json::Value;
+
+    let join = |key: &str| {
+        if field.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", field, key)
+        }
+    };
+    match (left, right) {
+        (Some(Value::Object(left)), Some(Value::Object(right))) => {
+            let mut keys: Vec<&String> = left.keys().chain(right.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                diff_values(join(key), left.get(key), right.get(key), changes);
+            }
+        }
+        (Some(Value::Array(left)), Some(Value::Array(right))) => {
+            for index in 0..left.len().max(right.len()) {
+                diff_values(
+                    format!("{}[{}]", field, index),
+                    left.get(index),
+                    right.get(index),
+                    changes,
+                );
+            }
+        }
+        (left, right) if left != right => {
+            let show = |value: Option<&Value>| match value {
+                None | Some(Value::Null) => "(unset)".to_string(),
+                Some(value) => value.to_string(),
+            };
+            let (left, right) = (show(left), show(right));
+            // A missing field and an explicit null send the same request
+            if left != right {
+                changes.push(FieldChange { field, left, right });
+            }
+        }
+        _ => {}
+    }
 }
 
 impl Default for TTDSettings {
@@ -88,27 +384,787 @@ impl TTDSettings {
         self.use_speaker_boost = Some(enabled);
         self
     }
+
+    /// Settings matching what ElevenLabs currently uses as voice defaults
+    pub async fn from_api_defaults(
+        client: &crate::ElevenLabsTTDClient,
+    ) -> Result<Self, crate::ElevenLabsTTDError> {
+        Ok(client.voices().default_settings().await?.into())
+    }
+}
+
+/// The stability values eleven_v3 accepts, by the names the ElevenLabs UI uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stability {
+    // 0.0, most expressive but prone to hallucinations.
+    Creative,
+    // 0.5, balanced.
+    Natural,
+    // 1.0, most consistent but less responsive to directions.
+    Robust,
+}
+
+impl Stability {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Stability::Creative => "creative",
+            Stability::Natural => "natural",
+            Stability::Robust => "robust",
+        }
+    }
+
+    /// The value sent as `settings.stability`
+    pub const fn value(&self) -> f32 {
+        match self {
+            Stability::Creative => 0.0,
+            Stability::Natural => 0.5,
+            Stability::Robust => 1.0,
+        }
+    }
+
+    /// The named value equal to `value`, if it is one the API accepts
+    pub fn from_value(value: f32) -> Option<Self> {
+        [Stability::Creative, Stability::Natural, Stability::Robust]
+            .into_iter()
+            .find(|stability| stability.value() == value)
+    }
+}
+
+impl std::fmt::Display for Stability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Stability {
+    type Err = crate::ElevenLabsTTDError;
+
+    /// Accepts a name ("robust", any case) or an accepted value ("1.0")
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.to_ascii_lowercase().as_str() {
+            "creative" => Ok(Stability::Creative),
+            "natural" => Ok(Stability::Natural),
+            "robust" => Ok(Stability::Robust),
+            _ => s.parse().ok().and_then(Self::from_value).ok_or_else(|| {
+                crate::ElevenLabsTTDError::ValidationError(format!(
+                    "Stability must be creative, natural, robust, 0.0, 0.5 or 1.0, got {}",
+                    s
+                ))
+            }),
+        }
+    }
+}
+
+impl From<Stability> for f32 {
+    fn from(stability: Stability) -> Self {
+        stability.value()
+    }
+}
+
+/// Voice settings as reported by the voice settings endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceSettings {
+    pub stability: Option<f32>,
+    pub similarity_boost: Option<f32>,
+    pub style: Option<f32>,
+    pub use_speaker_boost: Option<bool>,
+    pub speed: Option<f32>,
+}
+
+impl From<VoiceSettings> for TTDSettings {
+    fn from(settings: VoiceSettings) -> Self {
+        Self {
+            stability: settings.stability,
+            use_speaker_boost: settings.use_speaker_boost,
+        }
+    }
+}
+
+/// Subscription details of the account that owns the API key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subscription {
+    // Subscription tier, e.g. "free", "starter", "creator", "pro".
+    pub tier: String,
+    // Characters used in the current billing period.
+    pub character_count: u64,
+    // Characters available in the current billing period.
+    pub character_limit: u64,
+    // Unix timestamp of the next character count reset.
+    pub next_character_count_reset_unix: Option<i64>,
+    // Whether the character limit can be extended with usage-based billing.
+    pub can_extend_character_limit: Option<bool>,
+    // Maximum number of custom voices.
+    pub voice_limit: Option<u32>,
+    // Subscription status, e.g. "active".
+    pub status: Option<String>,
+}
+
+/// Subscription tiers, ordered from least to most capable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionTier {
+    Free,
+    Starter,
+    Creator,
+    Pro,
+    Scale,
+    Business,
+    Enterprise,
+}
+
+impl SubscriptionTier {
+    /// Parse the `tier` field of a [`Subscription`], ignoring variants such as
+    /// `creator_new` or `growing_business`
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        [
+            ("enterprise", SubscriptionTier::Enterprise),
+            ("business", SubscriptionTier::Business),
+            ("scale", SubscriptionTier::Scale),
+            ("pro", SubscriptionTier::Pro),
+            ("creator", SubscriptionTier::Creator),
+            ("starter", SubscriptionTier::Starter),
+            ("free", SubscriptionTier::Free),
+        ]
+        .into_iter()
+        .find(|(key, _)| name.split('_').any(|part| part == *key))
+        .map(|(_, tier)| tier)
+    }
+}
+
+impl Subscription {
+    /// The parsed subscription tier, or `None` if it isn't a known tier
+    pub fn tier_level(&self) -> Option<SubscriptionTier> {
+        SubscriptionTier::from_name(&self.tier)
+    }
+}
+
+/// Outcome of [`crate::ElevenLabsTTDClient::health_check`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    // The API answered and accepted the API key.
+    Healthy,
+    // The API answered but rejected the API key.
+    Unauthorized,
+    // The API answered but is rate limiting the account.
+    RateLimited,
+    // The API answered with a server error.
+    Degraded,
+    // No answer: DNS, connection, TLS or timeout failure.
+    Unreachable,
+}
+
+/// Result of a connectivity probe against the configured base URL
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthCheck {
+    pub status: HealthStatus,
+    // Round trip time of the probe.
+    pub latency: std::time::Duration,
+    // Why the probe wasn't healthy, redacted.
+    pub error: Option<String>,
+}
+
+impl HealthCheck {
+    /// Whether the API is reachable and accepts the API key
+    pub fn is_healthy(&self) -> bool {
+        self.status == HealthStatus::Healthy
+    }
+}
+
+/// A voice available to the account, as returned by the voices endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Voice {
+    pub voice_id: String,
+    pub name: String,
+    // Voice category, e.g. "premade", "cloned", "generated", "professional".
+    pub category: Option<String>,
+    pub description: Option<String>,
+    // Free-form labels such as gender, accent or age.
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+    // URL of a short audio preview.
+    pub preview_url: Option<String>,
+    // Models this voice has been fine-tuned for.
+    #[serde(default)]
+    pub high_quality_base_model_ids: Vec<String>,
+}
+
+/// A model available to the account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Model {
+    pub model_id: String,
+    pub name: Option<String>,
+    // Whether the model can generate speech.
+    #[serde(default)]
+    pub can_do_text_to_speech: bool,
+    #[serde(default)]
+    pub languages: Vec<ModelLanguage>,
+}
+
+impl Model {
+    /// Whether this model can generate speech with `voice`, see [`Voice::supports_model`]
+    pub fn supports_voice(&self, voice: &Voice) -> bool {
+        self.can_do_text_to_speech && voice.supports_model(&self.model_id)
+    }
+}
+
+/// A language a [`Model`] speaks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelLanguage {
+    // ISO 639 code, e.g. "en".
+    pub language_id: String,
+    pub name: String,
+}
+
+/// A voice shared in the public voice library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedVoice {
+    pub public_owner_id: String,
+    pub voice_id: String,
+    pub name: String,
+    pub category: Option<String>,
+    pub description: Option<String>,
+    pub gender: Option<String>,
+    pub age: Option<String>,
+    pub accent: Option<String>,
+    pub language: Option<String>,
+    pub preview_url: Option<String>,
+}
+
+/// A past generation in the account's history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryItem {
+    pub history_item_id: String,
+    pub request_id: Option<String>,
+    pub voice_id: Option<String>,
+    pub voice_name: Option<String>,
+    pub model_id: Option<String>,
+    pub text: Option<String>,
+    // Unix timestamp of the generation.
+    pub date_unix: i64,
+    pub content_type: Option<String>,
+    // Generation state, e.g. "created".
+    pub state: Option<String>,
+    // Where the generation came from, e.g. "TTS" or "API".
+    pub source: Option<String>,
+}
+
+/// ID of a voice, as returned when a voice is created
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct VoiceId(pub String);
+
+impl VoiceId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for VoiceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for VoiceId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
 }
 
-/// Represents a static voice
-#[derive(Debug, Clone, Deserialize)]
+impl From<VoiceId> for String {
+    fn from(id: VoiceId) -> Self {
+        id.0
+    }
+}
+
+impl std::str::FromStr for VoiceId {
+    type Err = crate::ElevenLabsTTDError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        non_empty_id("voice", s).map(VoiceId)
+    }
+}
+
+/// ID of a model, e.g. "eleven_v3"
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ModelId(pub String);
+
+impl ModelId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for ModelId {
+    fn default() -> Self {
+        ModelId(crate::models::elevanlabs_models::ELEVEN_V3.to_string())
+    }
+}
+
+impl std::fmt::Display for ModelId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for ModelId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<ModelId> for String {
+    fn from(id: ModelId) -> Self {
+        id.0
+    }
+}
+
+impl std::str::FromStr for ModelId {
+    type Err = crate::ElevenLabsTTDError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        non_empty_id("model", s).map(ModelId)
+    }
+}
+
+/// `id` without surrounding whitespace, failing when nothing is left
+fn non_empty_id(kind: &str, id: &str) -> Result<String, crate::ElevenLabsTTDError> {
+    let id = id.trim();
+    if id.is_empty() {
+        return Err(crate::ElevenLabsTTDError::ValidationError(format!(
+            "Empty {} ID",
+            kind
+        )));
+    }
+    Ok(id.to_string())
+}
+
+/// An audio sample uploaded to clone a voice
+#[derive(Debug, Clone)]
+pub struct VoiceSample {
+    // File name sent with the upload, its extension tells the API the audio type.
+    pub file_name: String,
+    pub data: Vec<u8>,
+}
+
+impl VoiceSample {
+    pub fn new<S: Into<String>>(file_name: S, data: Vec<u8>) -> Self {
+        Self {
+            file_name: file_name.into(),
+            data,
+        }
+    }
+
+    /// Read a sample from an audio file
+    pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "sample".to_string());
+        Ok(Self::new(file_name, std::fs::read(path)?))
+    }
+}
+
+/// A voice generated from a text description, not yet saved to the account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoicePreview {
+    // Pass to `VoicesApi::create_from_preview` to keep this voice.
+    pub generated_voice_id: String,
+    // Base64-encoded sample of the voice speaking the preview text.
+    pub audio_base_64: String,
+    // MIME type of the sample, e.g. "audio/mpeg".
+    pub media_type: Option<String>,
+    pub duration_secs: Option<f64>,
+}
+
+impl VoicePreview {
+    /// Decode the preview sample
+    pub fn audio(&self) -> Result<Vec<u8>, crate::ElevenLabsTTDError> {
+        use base64::Engine;
+
+        base64::engine::general_purpose::STANDARD
+            .decode(&self.audio_base_64)
+            .map_err(|e| {
+                crate::ElevenLabsTTDError::ValidationError(format!("Invalid preview audio: {}", e))
+            })
+    }
+}
+
+/// Gender of a voice
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Gender {
+    Male,
+    Female,
+    Neutral,
+    // Not reported, or a value this crate doesn't know.
+    #[serde(other)]
+    Unknown,
+}
+
+impl Gender {
+    /// Parse a gender label as reported by the API, e.g. in [`Voice::labels`]
+    pub fn from_label(label: &str) -> Self {
+        match label.trim().to_ascii_lowercase().as_str() {
+            "male" => Gender::Male,
+            "female" => Gender::Female,
+            "neutral" | "non-binary" | "nonbinary" | "non_binary" => Gender::Neutral,
+            _ => Gender::Unknown,
+        }
+    }
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Gender::Male => "male",
+            Gender::Female => "female",
+            Gender::Neutral => "neutral",
+            Gender::Unknown => "unknown",
+        }
+    }
+}
+
+impl std::fmt::Display for Gender {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Voice {
+    /// Gender from the voice's `gender` label
+    pub fn gender(&self) -> Gender {
+        self.labels
+            .get("gender")
+            .map_or(Gender::Unknown, |label| Gender::from_label(label))
+    }
+
+    /// Models this voice is limited to: the ones a professional clone is fine-tuned
+    /// for, or `None` when it works with every model
+    pub fn supported_models(&self) -> Option<&[String]> {
+        (self.category.as_deref() == Some("professional")
+            && !self.high_quality_base_model_ids.is_empty())
+        .then_some(self.high_quality_base_model_ids.as_slice())
+    }
+
+    /// Whether this voice can be used with `model_id`
+    pub fn supports_model(&self, model_id: &str) -> bool {
+        self.supported_models()
+            .is_none_or(|models| models.iter().any(|id| id == model_id))
+    }
+}
+
+/// A pre-built voice of the catalog in [`crate::voices::all_voices`].
+///
+/// Serializes as `{"voice_id", "name", "gender"}`. Its fields borrow from the
+/// catalog, so it can't be deserialized from owned data: deserialize into
+/// `&'static StaticVoice` to look the entry up by `voice_id`, or into the owned
+/// [`Voice`] for voices that may not be in the catalog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub struct StaticVoice {
     pub voice_id: &'static str,
     pub name: &'static str,
-    pub gender: &'static str,
+    pub gender: Gender,
 }
 
 impl StaticVoice {
-    pub const fn new(voice_id: &'static str, name: &'static str, gender: &'static str) -> Self {
+    pub const fn new(voice_id: &'static str, name: &'static str, gender: Gender) -> Self {
         Self {
-            voice_id: voice_id,
-            name: name,
-            gender: gender,
+            voice_id,
+            name,
+            gender,
         }
     }
 
     /// Get the voice ID for API calls
     pub fn id(&self) -> &str {
+        self.voice_id
+    }
+}
+
+impl<'de> Deserialize<'de> for &'static StaticVoice {
+    /// Accepts a voice ID or a serialized [`StaticVoice`], failing for voices
+    /// outside the catalog
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Id(String),
+            Entry { voice_id: String },
+        }
+
+        let voice_id = match Repr::deserialize(deserializer)? {
+            Repr::Id(voice_id) | Repr::Entry { voice_id } => voice_id,
+        };
+        crate::voices::all_voices::find_by_id(&voice_id).ok_or_else(|| {
+            serde::de::Error::custom(format!("{} is not a pre-built voice", voice_id))
+        })
+    }
+}
+
+impl From<&StaticVoice> for Voice {
+    /// An owned copy of a catalog voice, with its gender as the `gender` label
+    fn from(voice: &StaticVoice) -> Self {
+        let mut labels = HashMap::new();
+        if voice.gender != Gender::Unknown {
+            labels.insert("gender".to_string(), voice.gender.as_str().to_string());
+        }
+        Self {
+            voice_id: voice.voice_id.to_string(),
+            name: voice.name.to_string(),
+            category: Some("premade".to_string()),
+            description: None,
+            labels,
+            preview_url: None,
+            high_quality_base_model_ids: Vec::new(),
+        }
+    }
+}
+
+impl From<StaticVoice> for Voice {
+    fn from(voice: StaticVoice) -> Self {
+        Self::from(&voice)
+    }
+}
+
+impl TryFrom<&Voice> for &'static StaticVoice {
+    type Error = crate::ElevenLabsTTDError;
+
+    /// The catalog entry with the voice's ID
+    fn try_from(voice: &Voice) -> Result<Self, Self::Error> {
+        crate::voices::all_voices::find_by_id(&voice.voice_id).ok_or_else(|| {
+            crate::ElevenLabsTTDError::ValidationError(format!(
+                "{} is not a pre-built voice",
+                voice.voice_id
+            ))
+        })
+    }
+}
+
+/// Anything that names a voice: a catalog [`StaticVoice`], a [`Voice`] from the
+/// API, a [`VoiceId`], or a plain ID string. Accepted wherever a voice is needed.
+pub trait VoiceRef {
+    /// ID of the voice, as sent to the API
+    fn voice_id(&self) -> &str;
+
+    /// Name to show for the voice: the catalog name for IDs of pre-built voices,
+    /// the ID itself otherwise
+    fn display_name(&self) -> &str {
+        crate::voices::all_voices::name_for_id(self.voice_id()).unwrap_or(self.voice_id())
+    }
+}
+
+impl VoiceRef for StaticVoice {
+    fn voice_id(&self) -> &str {
+        self.voice_id
+    }
+
+    fn display_name(&self) -> &str {
+        self.name
+    }
+}
+
+impl VoiceRef for Voice {
+    fn voice_id(&self) -> &str {
         &self.voice_id
     }
+
+    fn display_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl VoiceRef for VoiceId {
+    fn voice_id(&self) -> &str {
+        &self.0
+    }
+}
+
+impl VoiceRef for str {
+    fn voice_id(&self) -> &str {
+        self
+    }
+}
+
+impl VoiceRef for String {
+    fn voice_id(&self) -> &str {
+        self
+    }
+}
+
+impl<V: VoiceRef + ?Sized> VoiceRef for &V {
+    fn voice_id(&self) -> &str {
+        (**self).voice_id()
+    }
+
+    fn display_name(&self) -> &str {
+        (**self).display_name()
+    }
+}
+
+/// Audio returned by a Text-to-Dialogue request, along with response metadata
+#[derive(Debug, Clone)]
+pub struct TTDAudio {
+    // The raw audio bytes, encoded in the requested output format.
+    pub audio: Vec<u8>,
+    // The client-generated correlation ID sent with the request.
+    pub correlation_id: String,
+    // The history item ID returned by ElevenLabs, if the response carried one.
+    pub history_item_id: Option<String>,
+    // Characters billed for this generation, if the response reported it.
+    pub character_count: Option<u64>,
+    // Rate-limit and concurrency state reported with the response.
+    pub rate_limit: Option<RateLimitInfo>,
+    // All response headers, including ones this crate doesn't model.
+    // Empty for sandbox responses and ones loaded from a DedupeStore.
+    pub headers: HeaderMap,
+    // Parameters of the request that produced this audio.
+    // `None` for responses loaded from a DedupeStore.
+    pub manifest: Option<GenerationManifest>,
+    // Format of `audio`, `None` when the server default was used.
+    pub output_format: Option<OutputFormat>,
+    // Problems found in the audio when the client checks for them.
+    pub anomalies: Vec<crate::audio::AudioAnomaly>,
+    // Adjustments, estimates and fallbacks that went into this response.
+    pub warnings: Vec<crate::Warning>,
+}
+
+impl TTDAudio {
+    pub(crate) fn new(audio: Vec<u8>, correlation_id: String) -> Self {
+        Self {
+            audio,
+            correlation_id,
+            history_item_id: None,
+            character_count: None,
+            rate_limit: None,
+            headers: HeaderMap::new(),
+            manifest: None,
+            output_format: None,
+            anomalies: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Scan PCM, WAV, μ-law or A-law audio for clipping, long silences and near-zero energy
+    pub fn detect_anomalies(
+        &self,
+    ) -> Result<Vec<crate::audio::AudioAnomaly>, crate::ElevenLabsTTDError> {
+        let format = self.known_format()?;
+        let samples = crate::audio::i16_samples(&self.audio, format)?;
+        Ok(crate::audio::detect_anomalies(
+            &samples,
+            format.sample_rate(),
+        ))
+    }
+
+    /// Decode PCM, WAV, μ-law or A-law audio into 16-bit samples
+    pub fn as_i16_samples(&self) -> Result<Vec<i16>, crate::ElevenLabsTTDError> {
+        crate::audio::i16_samples(&self.audio, self.known_format()?)
+    }
+
+    /// Decode PCM, WAV, μ-law or A-law audio into samples between -1.0 and 1.0
+    pub fn as_f32_samples(&self) -> Result<Vec<f32>, crate::ElevenLabsTTDError> {
+        crate::audio::f32_samples(&self.audio, self.known_format()?)
+    }
+
+    /// Split PCM, WAV, μ-law or A-law audio into mono 48kHz frames of `duration`
+    /// for WebRTC or LiveKit audio tracks, see [`crate::audio::frames`]. Without
+    /// the `resample` feature only `pcm_48000` audio can be split, and MP3 or
+    /// Opus audio never can
+    pub fn frames(
+        &self,
+        duration: std::time::Duration,
+    ) -> Result<crate::audio::AudioFrames, crate::ElevenLabsTTDError> {
+        crate::audio::frames(&self.audio, self.known_format()?, duration)
+    }
+
+    fn known_format(&self) -> Result<OutputFormat, crate::ElevenLabsTTDError> {
+        self.output_format.ok_or_else(|| {
+            crate::ElevenLabsTTDError::ValidationError(
+                "Output format of the audio is unknown".to_string(),
+            )
+        })
+    }
+
+    /// Write the audio to a file
+    pub fn save<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<(), crate::ElevenLabsTTDError> {
+        std::fs::write(path, &self.audio)?;
+        Ok(())
+    }
+
+    /// Write the audio to a file, plus its [`GenerationManifest`] next to it
+    /// (`scene.mp3` → `scene.manifest.json`) when one is available
+    pub fn save_with_manifest<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<(), crate::ElevenLabsTTDError> {
+        self.save(&path)?;
+        if let Some(manifest) = &self.manifest {
+            manifest.write_sidecar(&path)?;
+        }
+        Ok(())
+    }
+
+    /// Write MP3 audio to a file with ID3 `tags` in front of it
+    #[cfg(feature = "id3")]
+    pub fn write_mp3_with_tags<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        tags: &crate::id3::Tags,
+    ) -> Result<(), crate::ElevenLabsTTDError> {
+        if let Some(format) = self.output_format
+            && format.codec() != crate::formats::Codec::Mp3
+        {
+            return Err(crate::ElevenLabsTTDError::ValidationError(format!(
+                "ID3 tags need MP3 audio, got {}",
+                format.as_str()
+            )));
+        }
+
+        let mut file = tags.to_bytes();
+        file.extend_from_slice(&self.audio);
+        std::fs::write(path, file)?;
+        Ok(())
+    }
+
+    /// Consume the response and return the raw audio bytes
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.audio
+    }
+}
+
+/// One of several renders of the same dialogue, see
+/// [`crate::TextToDialogueBuilder::takes`]
+#[derive(Debug, Clone)]
+pub struct Take {
+    // Seed the take was generated with; set it on the builder to reproduce it.
+    pub seed: u32,
+    pub audio: TTDAudio,
+}
+
+impl std::ops::Deref for TTDAudio {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.audio
+    }
+}
+
+impl AsRef<[u8]> for TTDAudio {
+    fn as_ref(&self) -> &[u8] {
+        &self.audio
+    }
+}
+
+impl From<TTDAudio> for Vec<u8> {
+    fn from(audio: TTDAudio) -> Self {
+        audio.audio
+    }
 }
@@ -0,0 +1,242 @@
+//! Opt-in accounting of what a client has consumed during a session
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::error::ElevenLabsTTDError;
+
+/// Totals recorded by a client since it was built (or last reset)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct UsageSummary {
+    // Requests sent to the API, successful or not.
+    pub requests: u64,
+    // Requests that failed.
+    pub errors: u64,
+    // Characters billed for successful requests.
+    pub characters: u64,
+    // Estimated cost of the billed characters, if a price was configured.
+    pub estimated_cost: Option<f64>,
+}
+
+impl fmt::Display for UsageSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} requests ({} failed), {} characters billed",
+            self.requests, self.errors, self.characters
+        )?;
+        if let Some(cost) = self.estimated_cost {
+            write!(f, ", estimated cost {:.4}", cost)?;
+        }
+        Ok(())
+    }
+}
+
+/// Usage counters shared by every clone of a client
+#[derive(Debug, Clone)]
+pub(crate) struct UsageTracker {
+    summary: Arc<Mutex<UsageSummary>>,
+    cost_per_thousand_characters: Option<f64>,
+}
+
+impl UsageTracker {
+    pub(crate) fn new(cost_per_thousand_characters: Option<f64>) -> Self {
+        Self {
+            summary: Arc::new(Mutex::new(Self::empty(cost_per_thousand_characters))),
+            cost_per_thousand_characters,
+        }
+    }
+
+    fn empty(cost_per_thousand_characters: Option<f64>) -> UsageSummary {
+        UsageSummary {
+            estimated_cost: cost_per_thousand_characters.map(|_| 0.0),
+            ..UsageSummary::default()
+        }
+    }
+
+    pub(crate) fn record_success(&self, characters: u64) {
+        let mut summary = self.summary.lock().unwrap();
+        summary.requests += 1;
+        summary.characters += characters;
+        if let Some(price) = self.cost_per_thousand_characters {
+            summary.estimated_cost = Some(summary.characters as f64 / 1000.0 * price);
+        }
+    }
+
+    pub(crate) fn record_error(&self) {
+        let mut summary = self.summary.lock().unwrap();
+        summary.requests += 1;
+        summary.errors += 1;
+    }
+
+    pub(crate) fn summary(&self) -> UsageSummary {
+        self.summary.lock().unwrap().clone()
+    }
+
+    pub(crate) fn reset(&self) {
+        *self.summary.lock().unwrap() = Self::empty(self.cost_per_thousand_characters);
+    }
+}
+
+/// Characters and requests counted against the daily ceiling for the current UTC day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DailyUsage {
+    pub date: NaiveDate,
+    pub requests: u64,
+    // Characters billed, plus characters of requests still in flight.
+    pub characters: u64,
+    pub limit: u64,
+}
+
+/// A per-day character ceiling shared by every clone of a client, e.g. across a worker pool.
+/// Characters are reserved before sending so concurrent requests can't overshoot it.
+#[derive(Debug, Clone)]
+pub(crate) struct DailyCeiling {
+    state: Arc<Mutex<DailyUsage>>,
+}
+
+impl DailyCeiling {
+    pub(crate) fn new(limit: u64) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(DailyUsage {
+                date: today(),
+                requests: 0,
+                characters: 0,
+                limit,
+            })),
+        }
+    }
+
+    /// Count `characters` against today's ceiling, or fail if they don't fit.
+    /// The reservation is released unless [`Reservation::settle`] is called.
+    pub(crate) fn reserve(&self, characters: u64) -> Result<Reservation, ElevenLabsTTDError> {
+        let mut usage = self.state.lock().unwrap();
+        roll_over(&mut usage);
+        if usage.characters + characters > usage.limit {
+            return Err(ElevenLabsTTDError::QuotaExceededError {
+                message: format!(
+                    "Daily character ceiling of {} reached ({} used, {} requested)",
+                    usage.limit, usage.characters, characters
+                ),
+                correlation_id: None,
+                response: None,
+            });
+        }
+        usage.characters += characters;
+        usage.requests += 1;
+        Ok(Reservation {
+            ceiling: self.clone(),
+            characters,
+            settled: false,
+        })
+    }
+
+    /// Replace a reservation with the characters actually billed
+    fn settle(&self, reserved: u64, billed: u64) {
+        let mut usage = self.state.lock().unwrap();
+        usage.characters = (usage.characters + billed).saturating_sub(reserved);
+    }
+
+    pub(crate) fn usage(&self) -> DailyUsage {
+        let mut usage = self.state.lock().unwrap();
+        roll_over(&mut usage);
+        usage.clone()
+    }
+}
+
+/// Characters held against a [`DailyCeiling`] while a request runs. Dropping it
+/// unsettled, e.g. when the request is cancelled, releases the characters.
+#[derive(Debug)]
+#[must_use]
+pub(crate) struct Reservation {
+    ceiling: DailyCeiling,
+    characters: u64,
+    settled: bool,
+}
+
+impl Reservation {
+    /// Replace the reservation with the characters actually billed (0 for failed requests)
+    pub(crate) fn settle(mut self, billed: u64) {
+        self.settled = true;
+        self.ceiling.settle(self.characters, billed);
+    }
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        if !self.settled {
+            self.ceiling.settle(self.characters, 0);
+        }
+    }
+}
+
+fn today() -> NaiveDate {
+    chrono::Utc::now().date_naive()
+}
+
+fn roll_over(usage: &mut DailyUsage) {
+    let today = today();
+    if usage.date != today {
+        usage.date = today;
+        usage.requests = 0;
+        usage.characters = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_accumulates_and_resets() {
+        let tracker = UsageTracker::new(Some(0.3));
+        tracker.record_success(1500);
+        tracker.clone().record_success(500);
+        tracker.record_error();
+
+        let summary = tracker.summary();
+        assert_eq!(summary.requests, 3);
+        assert_eq!(summary.errors, 1);
+        assert_eq!(summary.characters, 2000);
+        assert!((summary.estimated_cost.unwrap() - 0.6).abs() < 1e-9);
+
+        tracker.reset();
+        assert_eq!(tracker.summary().requests, 0);
+        assert_eq!(tracker.summary().estimated_cost, Some(0.0));
+    }
+
+    #[test]
+    fn test_daily_ceiling_is_shared_and_enforced() {
+        let ceiling = DailyCeiling::new(1000);
+        let worker = ceiling.clone();
+
+        let reservation = worker.reserve(600).unwrap();
+        let refused = ceiling.reserve(600).unwrap_err();
+        assert_eq!(refused.kind(), "quota_exceeded_error");
+        // Refused locally, not by the API
+        assert_eq!(refused.status(), None);
+
+        // Billed fewer characters than reserved
+        reservation.settle(400);
+        ceiling.reserve(600).unwrap().settle(600);
+
+        let usage = ceiling.usage();
+        assert_eq!(usage.characters, 1000);
+        assert_eq!(usage.requests, 2);
+    }
+
+    #[test]
+    fn test_dropped_reservation_is_released() {
+        let ceiling = DailyCeiling::new(1000);
+        let cancelled = ceiling.reserve(800).unwrap();
+        assert!(ceiling.reserve(800).is_err());
+        drop(cancelled);
+
+        assert_eq!(ceiling.usage().characters, 0);
+        ceiling.reserve(800).unwrap().settle(800);
+        assert_eq!(ceiling.usage().characters, 800);
+    }
+}
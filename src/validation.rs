@@ -0,0 +1,312 @@
+//! Pre-flight checks run on every request before it is sent
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ElevenLabsTTDError;
+use crate::text::split_sentences;
+use crate::types::{TTDInput, TTDRequest};
+
+/// Maximum characters in a single turn accepted by eleven_v3, audio tags and
+/// whitespace included (unlike [`crate::text::billable_len`])
+pub const MAX_TURN_CHARACTERS: usize = 3_000;
+
+/// Stability values accepted by the API
+pub const STABILITY_VALUES: [f32; 3] = [0.0, 0.5, 1.0];
+
+/// How the client reacts to requests that would be rejected by the API
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ValidationMode {
+    /// Fail fast with a [`ElevenLabsTTDError::ValidationError`] on any violation
+    #[default]
+    Strict,
+    /// Fix what can be fixed safely (drop empty turns, snap stability,
+    /// truncate overlong text) and report each fix as a [`ValidationWarning`]
+    Lenient,
+}
+
+/// A fix applied to a request in [`ValidationMode::Lenient`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "warning", rename_all = "snake_case")]
+pub enum ValidationWarning {
+    /// A turn with no text (after trimming) was dropped
+    EmptyTurnRemoved { index: usize },
+    /// Stability was snapped to the nearest accepted value
+    StabilitySnapped { from: f32, to: f32 },
+    /// A turn was truncated to [`MAX_TURN_CHARACTERS`]
+    TextTruncated {
+        index: usize,
+        from_chars: usize,
+        to_chars: usize,
+    },
+    /// A turn's voice isn't supported by the request's model, per the voice
+    /// metadata given to the builder. The request is sent unchanged.
+    UnsupportedVoice {
+        index: usize,
+        voice_id: String,
+        model_id: String,
+    },
+}
+
+impl fmt::Display for ValidationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationWarning::EmptyTurnRemoved { index } => {
+                write!(f, "Removed empty turn at index {}", index)
+            }
+            ValidationWarning::StabilitySnapped { from, to } => {
+                write!(f, "Stability {} snapped to {}", from, to)
+            }
+            ValidationWarning::TextTruncated {
+                index,
+                from_chars,
+                to_chars,
+            } => write!(
+                f,
+                "Truncated turn {} from {} to {} characters",
+                index, from_chars, to_chars
+            ),
+            ValidationWarning::UnsupportedVoice {
+                index,
+                voice_id,
+                model_id,
+            } => write!(
+                f,
+                "Voice {} of turn {} isn't supported by {}",
+                voice_id, index, model_id
+            ),
+        }
+    }
+}
+
+/// Check a request, fixing it in place in lenient mode.
+/// Returns the fixes applied, or the first violation in strict mode.
+pub fn validate(
+    request: &mut TTDRequest,
+    mode: ValidationMode,
+) -> Result<Vec<ValidationWarning>, ElevenLabsTTDError> {
+    let strict = mode == ValidationMode::Strict;
+    let mut warnings = Vec::new();
+
+    // Indices refer to the inputs as submitted, before any were removed
+    let mut index = 0;
+    let mut violation = None;
+    // Violations that can't be fixed, even in lenient mode
+    let mut unfixable = None;
+    request.inputs.retain_mut(|input| {
+        let current = index;
+        index += 1;
+
+        if input.voice_id.trim().is_empty() && unfixable.is_none() {
+            unfixable = Some(format!("Turn {} has no voice_id", current));
+        }
+
+        if input.text.trim().is_empty() {
+            if strict && violation.is_none() {
+                violation = Some(format!("Turn {} has no text", current));
+            }
+            warnings.push(ValidationWarning::EmptyTurnRemoved { index: current });
+            return strict;
+        }
+
+        let chars = input.text.chars().count();
+        if chars > MAX_TURN_CHARACTERS {
+            if strict && violation.is_none() {
+                violation = Some(format!(
+                    "Turn {} has {} characters, the maximum is {}",
+                    current, chars, MAX_TURN_CHARACTERS
+                ));
+            }
+            input.text = truncate(&input.text, MAX_TURN_CHARACTERS);
+            warnings.push(ValidationWarning::TextTruncated {
+                index: current,
+                from_chars: chars,
+                to_chars: input.text.chars().count(),
+            });
+        }
+
+        true
+    });
+
+    if let Some(stability) = request.settings.as_mut().and_then(|s| s.stability.as_mut())
+        && !STABILITY_VALUES.contains(stability)
+    {
+        if strict && violation.is_none() {
+            violation = Some(format!(
+                "Stability must be one of 0.0, 0.5 or 1.0, got {}",
+                stability
+            ));
+        }
+        let snapped = nearest_stability(*stability);
+        warnings.push(ValidationWarning::StabilitySnapped {
+            from: *stability,
+            to: snapped,
+        });
+        *stability = snapped;
+    }
+
+    if request.inputs.is_empty() && unfixable.is_none() {
+        unfixable = Some("At least one input is required".to_string());
+    }
+
+    let error = if strict {
+        violation.or(unfixable)
+    } else {
+        unfixable
+    };
+
+    match error {
+        Some(message) => Err(ElevenLabsTTDError::ValidationError(message)),
+        None => Ok(warnings),
+    }
+}
+
+/// Split every turn over [`MAX_TURN_CHARACTERS`] at sentence boundaries into
+/// consecutive turns with the same voice, keeping the order of the dialogue
+pub fn split_long_turns(request: &mut TTDRequest) {
+    if request
+        .inputs
+        .iter()
+        .all(|input| input.text.chars().count() <= MAX_TURN_CHARACTERS)
+    {
+        return;
+    }
+
+    request.inputs = std::mem::take(&mut request.inputs)
+        .into_iter()
+        .flat_map(|input| {
+            if input.text.chars().count() <= MAX_TURN_CHARACTERS {
+                return vec![input];
+            }
+            split_sentences(&input.text, MAX_TURN_CHARACTERS)
+                .into_iter()
+                .map(|text| TTDInput {
+                    text,
+                    voice_id: input.voice_id.clone(),
+                })
+                .collect()
+        })
+        .collect();
+}
+
+fn nearest_stability(value: f32) -> f32 {
+    STABILITY_VALUES
+        .into_iter()
+        .min_by(|a, b| (a - value).abs().total_cmp(&(b - value).abs()))
+        .unwrap_or(0.5)
+}
+
+/// Cut text to at most `max_chars`, preferring the last word boundary
+fn truncate(text: &str, max_chars: usize) -> String {
+    let cut: String = text.chars().take(max_chars).collect();
+    match cut.rfind(char::is_whitespace) {
+        Some(boundary) if boundary > cut.len() / 2 => cut[..boundary].trim_end().to_string(),
+        _ => cut,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TTDSettings;
+
+    fn request(texts: &[&str], stability: f32) -> TTDRequest {
+        TTDRequest {
+            output_format: None,
+            inputs: texts
+                .iter()
+                .map(|text| TTDInput {
+                    text: text.to_string(),
+                    voice_id: "voice".to_string(),
+                })
+                .collect(),
+            model_id: Some("eleven_v3".to_string()),
+            settings: Some(TTDSettings::new().stability(stability)),
+            pronunciation_dictionary_locators: None,
+            seed: None,
+            language_code: None,
+        }
+    }
+
+    #[test]
+    fn test_strict_rejects_violations() {
+        let mut empty_turn = request(&["Hello", "   "], 0.5);
+        assert!(validate(&mut empty_turn, ValidationMode::Strict).is_err());
+
+        let mut bad_stability = request(&["Hello"], 0.3);
+        assert!(validate(&mut bad_stability, ValidationMode::Strict).is_err());
+
+        let mut valid = request(&["Hello"], 1.0);
+        assert!(
+            validate(&mut valid, ValidationMode::Strict)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_lenient_fixes_request() {
+        let long = "word ".repeat(MAX_TURN_CHARACTERS);
+        let mut request = request(&["Hello", " ", &long], 0.3);
+
+        let warnings = validate(&mut request, ValidationMode::Lenient).unwrap();
+
+        assert_eq!(request.inputs.len(), 2);
+        assert!(request.inputs[1].text.chars().count() <= MAX_TURN_CHARACTERS);
+        assert_eq!(request.settings.unwrap().stability, Some(0.5));
+        assert_eq!(
+            warnings[0],
+            ValidationWarning::EmptyTurnRemoved { index: 1 }
+        );
+        assert!(matches!(
+            warnings[1],
+            ValidationWarning::TextTruncated { index: 2, .. }
+        ));
+        assert_eq!(
+            warnings[2],
+            ValidationWarning::StabilitySnapped { from: 0.3, to: 0.5 }
+        );
+    }
+
+    #[test]
+    fn test_split_long_turns_keeps_order_and_voice() {
+        let long = "This is one sentence. ".repeat(200);
+        let mut request = request(&["Before", &long, "After"], 0.5);
+        request.inputs[1].voice_id = "narrator".to_string();
+
+        split_long_turns(&mut request);
+
+        let inputs = &request.inputs;
+        assert!(inputs.len() > 3);
+        assert_eq!(inputs.first().unwrap().text, "Before");
+        assert_eq!(inputs.last().unwrap().text, "After");
+        for input in &inputs[1..inputs.len() - 1] {
+            assert_eq!(input.voice_id, "narrator");
+            assert!(input.text.chars().count() <= MAX_TURN_CHARACTERS);
+            assert!(input.text.ends_with('.'));
+        }
+        assert!(validate(&mut request, ValidationMode::Strict).is_ok());
+    }
+
+    #[test]
+    fn test_turn_limit_counts_audio_tags() {
+        // Under the limit in billable characters, over it as sent
+        let tagged = "[laughs] ha ".repeat(300);
+        assert!(crate::text::billable_len(&tagged) as usize <= MAX_TURN_CHARACTERS);
+        let mut request = request(&[&tagged], 0.5);
+
+        let error = validate(&mut request.clone(), ValidationMode::Strict).unwrap_err();
+        assert!(error.to_string().contains("3600 characters"));
+
+        split_long_turns(&mut request);
+        assert!(request.inputs.len() > 1);
+        assert!(validate(&mut request, ValidationMode::Strict).is_ok());
+    }
+
+    #[test]
+    fn test_lenient_still_rejects_empty_dialogue() {
+        let mut request = request(&["  "], 0.5);
+        assert!(validate(&mut request, ValidationMode::Lenient).is_err());
+    }
+}
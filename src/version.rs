@@ -0,0 +1,217 @@
+//! API version selection per endpoint family
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Version segment of ElevenLabs API paths
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    /// The path segment, e.g. "v1"
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "v1",
+            ApiVersion::V2 => "v2",
+        }
+    }
+
+    /// Parse a path segment such as "v1"
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "v1" => Some(ApiVersion::V1),
+            "v2" => Some(ApiVersion::V2),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Groups of endpoints that move between API versions together
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndpointFamily {
+    // `text-to-dialogue`
+    TextToDialogue,
+    // `text-to-speech`
+    TextToSpeech,
+    // `voices`: listing is on v2, managing voices on v1.
+    Voices,
+    // `text-to-voice`
+    VoiceDesign,
+    // `shared-voices`
+    SharedVoices,
+    // `history`
+    History,
+    // `sound-generation`
+    SoundEffects,
+    // `forced-alignment`
+    ForcedAlignment,
+}
+
+/// Splits a base URL into its root and trailing version segment, if any:
+/// `https://api.elevenlabs.io/v1` becomes (`https://api.elevenlabs.io`, `Some(V1)`)
+pub(crate) fn split_base_url(base_url: &str) -> (&str, Option<ApiVersion>) {
+    let base_url = base_url.trim_end_matches('/');
+    if let Some((root, segment)) = base_url.rsplit_once('/')
+        && let Some(version) = ApiVersion::from_name(segment)
+    {
+        return (root, Some(version));
+    }
+    (base_url, None)
+}
+
+/// Root URL and versions a client sends requests with.
+///
+/// A base URL without a version segment, e.g. a gateway that adds it itself,
+/// is taken to stand for v1: v1 paths are appended to it as they are, and only
+/// other versions get a segment, unless a version was set on the builder.
+#[derive(Debug, Clone)]
+pub(crate) struct ApiVersions {
+    root: String,
+    default: ApiVersion,
+    // Whether the version was set on the builder rather than read from the base URL.
+    explicit: bool,
+    // Whether v1 paths get a version segment under `root`.
+    versioned: bool,
+    overrides: Arc<HashMap<EndpointFamily, ApiVersion>>,
+}
+
+impl ApiVersions {
+    pub(crate) fn new(
+        base_url: &str,
+        default: Option<ApiVersion>,
+        overrides: HashMap<EndpointFamily, ApiVersion>,
+    ) -> Self {
+        let (root, version) = split_base_url(base_url);
+        Self {
+            root: root.to_string(),
+            default: default.or(version).unwrap_or(ApiVersion::V1),
+            explicit: default.is_some(),
+            versioned: default.is_some() || version.is_some(),
+            overrides: Arc::new(overrides),
+        }
+    }
+
+    /// Base URL relative paths of raw requests and [`crate::Endpoint`]s resolve against
+    pub(crate) fn base_url(&self) -> String {
+        join(&self.root, self.versioned, self.default, "")
+    }
+
+    /// URL of `path` in `family`, at the configured version or else `version`,
+    /// the one this crate targets for the endpoint
+    pub(crate) fn url(&self, family: EndpointFamily, version: ApiVersion, path: &str) -> String {
+        self.url_at(None, family, version, path)
+    }
+
+    /// Like [`Self::url`], against another base URL if one is given
+    pub(crate) fn url_at(
+        &self,
+        base_url: Option<&str>,
+        family: EndpointFamily,
+        version: ApiVersion,
+        path: &str,
+    ) -> String {
+        let (root, versioned) = match base_url {
+            Some(base_url) => {
+                let (root, version) = split_base_url(base_url);
+                (root, self.explicit || version.is_some())
+            }
+            None => (self.root.as_str(), self.versioned),
+        };
+        match self.overrides.get(&family) {
+            Some(version) => format!("{}/{}{}", root, version, path),
+            None => join(root, versioned, version, path),
+        }
+    }
+}
+
+/// `root` and `path` with the version segment between them, left out for v1
+/// under an unversioned root
+fn join(root: &str, versioned: bool, version: ApiVersion, path: &str) -> String {
+    if versioned || version != ApiVersion::V1 {
+        format!("{}/{}{}", root, version, path)
+    } else {
+        format!("{}{}", root, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_base_url() {
+        assert_eq!(
+            split_base_url("https://api.elevenlabs.io/v1/"),
+            ("https://api.elevenlabs.io", Some(ApiVersion::V1))
+        );
+        assert_eq!(
+            split_base_url("https://proxy.example.com/elevenlabs"),
+            ("https://proxy.example.com/elevenlabs", None)
+        );
+    }
+
+    #[test]
+    fn test_overrides_apply_per_family() {
+        let mut overrides = HashMap::new();
+        overrides.insert(EndpointFamily::TextToDialogue, ApiVersion::V2);
+        let versions = ApiVersions::new("https://api.elevenlabs.io/v1", None, overrides);
+
+        assert_eq!(versions.base_url(), "https://api.elevenlabs.io/v1");
+        assert_eq!(
+            versions.url(
+                EndpointFamily::TextToDialogue,
+                ApiVersion::V1,
+                "/text-to-dialogue"
+            ),
+            "https://api.elevenlabs.io/v2/text-to-dialogue"
+        );
+        assert_eq!(
+            versions.url(EndpointFamily::Voices, ApiVersion::V2, "/voices"),
+            "https://api.elevenlabs.io/v2/voices"
+        );
+        assert_eq!(
+            versions.url_at(
+                Some("http://127.0.0.1:9"),
+                EndpointFamily::TextToSpeech,
+                ApiVersion::V1,
+                "/text-to-speech/voice"
+            ),
+            "http://127.0.0.1:9/text-to-speech/voice"
+        );
+    }
+
+    #[test]
+    fn test_unversioned_base_url_is_kept() {
+        let gateway = ApiVersions::new("https://gw.example/elevenlabs", None, HashMap::new());
+        assert_eq!(gateway.base_url(), "https://gw.example/elevenlabs");
+        assert_eq!(
+            gateway.url(EndpointFamily::History, ApiVersion::V1, "/history"),
+            "https://gw.example/elevenlabs/history"
+        );
+        assert_eq!(
+            gateway.url(EndpointFamily::Voices, ApiVersion::V2, "/voices"),
+            "https://gw.example/elevenlabs/v2/voices"
+        );
+
+        let opted_in = ApiVersions::new(
+            "https://gw.example/elevenlabs",
+            Some(ApiVersion::V1),
+            HashMap::new(),
+        );
+        assert_eq!(opted_in.base_url(), "https://gw.example/elevenlabs/v1");
+        assert_eq!(
+            opted_in.url(EndpointFamily::History, ApiVersion::V1, "/history"),
+            "https://gw.example/elevenlabs/v1/history"
+        );
+    }
+}
@@ -1,356 +1,429 @@
-use crate::types::StaticVoice;
+use crate::types::{Gender, StaticVoice};
+
+/// How a voice name matched a search query, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchKind {
+    Exact,
+    Prefix,
+    Substring,
+    // Edit distance between the query and the closest part of the name.
+    Fuzzy { distance: usize },
+}
+
+/// A voice returned by [`all_voices::search`]
+#[derive(Debug, Clone)]
+pub struct VoiceMatch {
+    pub voice: &'static StaticVoice,
+    pub kind: MatchKind,
+}
+
+/// Match a query against a voice name, case-insensitively.
+/// Fuzzy matches compare the query with the name's prefix of the same length,
+/// so typos are tolerated while typing, and allow one edit per three characters.
+pub(crate) fn match_name(query: &str, name: &str) -> Option<MatchKind> {
+    let query = query.trim().to_lowercase();
+    let name = name.to_lowercase();
+    if query.is_empty() {
+        return None;
+    }
+
+    if name == query {
+        return Some(MatchKind::Exact);
+    }
+    if name.starts_with(&query) {
+        return Some(MatchKind::Prefix);
+    }
+    if name.contains(&query) {
+        return Some(MatchKind::Substring);
+    }
+
+    let query: Vec<char> = query.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let prefix = &name[..name.len().min(query.len())];
+    let distance = edit_distance(&query, &name).min(edit_distance(&query, prefix));
+    let allowed = (query.len() / 3).max(1);
+    (distance <= allowed).then_some(MatchKind::Fuzzy { distance })
+}
+
+/// Levenshtein distance
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
 
 /// Elevanlabs common voice IDs as constants
 pub mod all_voices {
-    use super::StaticVoice;
+    use super::{Gender, StaticVoice, VoiceMatch, match_name};
 
     // Pre-made voices from ElevenLabs
     pub static WILL: StaticVoice = StaticVoice {
         voice_id: "bIHbv24MWmeRgasZH58o",
         name: "Will",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static THOMAS: StaticVoice = StaticVoice {
         voice_id: "GBv7mTt0atIp3Br8iCZE",
         name: "Thomas",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static CHARLIE: StaticVoice = StaticVoice {
         voice_id: "IKne3meq5aSn9XLyUdCD",
         name: "Charlie",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static GEORGE: StaticVoice = StaticVoice {
         voice_id: "JBFqnCBsd6RMkjVDRZzb",
         name: "George",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static CALLUM: StaticVoice = StaticVoice {
         voice_id: "N2lVS1w4EtoT3dr4eOWO",
         name: "Callum",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static LIAM: StaticVoice = StaticVoice {
         voice_id: "TX3LPaxmHKxFdv7VOQHJ",
         name: "Liam",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static CHARLOTTE: StaticVoice = StaticVoice {
         voice_id: "XB0fDUnXU5powFXDhCwa",
         name: "Charlotte",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static ALICE: StaticVoice = StaticVoice {
         voice_id: "Xb7hH8MSUJpSbSDYk0k2",
         name: "Alice",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static MATILDA: StaticVoice = StaticVoice {
         voice_id: "XrExE9yKIg1WjnnlVkGX",
         name: "Matilda",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static RACHEL: StaticVoice = StaticVoice {
         voice_id: "21m00Tcm4TlvDq8ikWAM",
         name: "Rachel",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static DOMI: StaticVoice = StaticVoice {
         voice_id: "AZnzlk1XvdvUeBnXmlld",
         name: "Domi",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static BELLA: StaticVoice = StaticVoice {
         voice_id: "EXAVITQu4vr4xnSDxMaL",
         name: "Bella",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static ANTONI: StaticVoice = StaticVoice {
         voice_id: "ErXwobaYiN019PkySvjV",
         name: "Antoni",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static ELLI: StaticVoice = StaticVoice {
         voice_id: "MF3mGyEYCl7XYWbV9V6O",
         name: "Elli",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static JOSH: StaticVoice = StaticVoice {
         voice_id: "TxGEqnHWrfWFTfGW9XjX",
         name: "Josh",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static ARNOLD: StaticVoice = StaticVoice {
         voice_id: "VR6AewLTigWG4xSOukaG",
         name: "Arnold",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static ADAM: StaticVoice = StaticVoice {
         voice_id: "pNInz6obpgDQGcFmaJgB",
         name: "Adam",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static SAM: StaticVoice = StaticVoice {
         voice_id: "yoZ06aMxZJJ28mfd3POQ",
         name: "Sam",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static SERENA: StaticVoice = StaticVoice {
         voice_id: "pMsXgVXv3BLzUgSXRplE",
         name: "Serena",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static ROGER: StaticVoice = StaticVoice {
         voice_id: "CwhRBWXzGAHq8TQ4Fs17",
         name: "Roger",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static RIVER: StaticVoice = StaticVoice {
         voice_id: "SAz9YHcvj6GT2YYXdXww",
         name: "River",
-        gender: "neutral",
+        gender: Gender::Neutral,
     };
 
     pub static PAUL: StaticVoice = StaticVoice {
         voice_id: "5Q0t7uMcjvnagumLfvZi",
         name: "Paul",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static PATRICK: StaticVoice = StaticVoice {
         voice_id: "ODq5zmih8GrVes37Dizd",
         name: "Patrick",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static NICOLE: StaticVoice = StaticVoice {
         voice_id: "piTKgcLEGmPE4e6mEKli",
         name: "Nicole",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static MIMI: StaticVoice = StaticVoice {
         voice_id: "zrHiDhphv9ZnVXBqCLjz",
         name: "Mimi",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static MICHAEL: StaticVoice = StaticVoice {
         voice_id: "flq6f7yk4E4fJM5XTYuZ",
         name: "Michael",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static MARK: StaticVoice = StaticVoice {
         voice_id: "UgBBYS2sOqTuMpoF3BR0",
         name: "Mark",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static LILY: StaticVoice = StaticVoice {
         voice_id: "pFZP5JQG7iQjIQuC4Bku",
         name: "Lily",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static LAURA: StaticVoice = StaticVoice {
         voice_id: "FGY2WhTYpPnrIDTdsKH5",
         name: "Laura",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static JOSEPH: StaticVoice = StaticVoice {
         voice_id: "Zlb1dXrM653N07WRdFW3",
         name: "Joseph",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static JESSIE: StaticVoice = StaticVoice {
         voice_id: "t0jbNlBVZ17f02VDIeMI",
         name: "Jessie",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static JESSICA: StaticVoice = StaticVoice {
         voice_id: "cgSgspJ2msm6clMCkdW9",
         name: "Jessica",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static JEREMY: StaticVoice = StaticVoice {
         voice_id: "bVMeCyTHy58xNoL34h3p",
         name: "Jeremy",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static JAMES: StaticVoice = StaticVoice {
         voice_id: "ZQe5CZNOzWyzPSCn5a3c",
         name: "James",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static IVANA: StaticVoice = StaticVoice {
         voice_id: "4NejU5DwQjevnR6mh3mb",
         name: "Ivanna",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static HARRY: StaticVoice = StaticVoice {
         voice_id: "SOYHLrjzK2X1ezoPC6cr",
         name: "Harry",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static GRACE: StaticVoice = StaticVoice {
         voice_id: "oWAxZDx7w5VEj9dCyTzz",
         name: "Grace",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static GLINDA: StaticVoice = StaticVoice {
         voice_id: "z9fAnlkpzviPz146aGWa",
         name: "Glinda",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static GIOVANNI: StaticVoice = StaticVoice {
         voice_id: "zcAOhNBS3c14rBihAFp1",
         name: "Giovanni",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static GIGI: StaticVoice = StaticVoice {
         voice_id: "jBpfuIE2acCO8z3wKNLl",
         name: "Gigi",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static FREYA: StaticVoice = StaticVoice {
         voice_id: "jsCqWAovK2LkecY7zXl4",
         name: "Freya",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static FIN: StaticVoice = StaticVoice {
         voice_id: "D38z5RcWu1voky8WS1ja",
         name: "Fin",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static ETHAN: StaticVoice = StaticVoice {
         voice_id: "g5CIjZEefAph4nQFvHAz",
         name: "Ethan",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static ERIC: StaticVoice = StaticVoice {
         voice_id: "cjVigY5qzO86Huf0OWal",
         name: "Eric",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static EMILY: StaticVoice = StaticVoice {
         voice_id: "LcfcDJNUP1GQjkzn1xUU",
         name: "Emily",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static DREW: StaticVoice = StaticVoice {
         voice_id: "29vD33N1CtxCmqQRPOHJ",
         name: "Drew",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static DOROTHY: StaticVoice = StaticVoice {
         voice_id: "ThT5KcBeYPX3keUQqHPh",
         name: "Dorothy",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static DAVE: StaticVoice = StaticVoice {
         voice_id: "CYw3kZ02Hs0563khs1Fj",
         name: "Dave",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static DANIEL: StaticVoice = StaticVoice {
         voice_id: "onwK4e9ZLuTAKqWW03F9",
         name: "Daniel",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static CLYDE: StaticVoice = StaticVoice {
         voice_id: "2EiwWnXFnvU5JabPnv8n",
         name: "Clyde",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static CHRIS: StaticVoice = StaticVoice {
         voice_id: "iP95p4xoKVk53GoZ742B",
         name: "Chris",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static CASSIDY: StaticVoice = StaticVoice {
         voice_id: "56AoDkrOh6qfVPDXZ7Pt",
         name: "Cassidy",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     pub static BRIAN: StaticVoice = StaticVoice {
         voice_id: "nPczCjzI2devNBz1zQrb",
         name: "Brian",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static BILL: StaticVoice = StaticVoice {
         voice_id: "pqHfZKP75CvOlQylNhV4",
         name: "Bill",
-        gender: "male",
+        gender: Gender::Male,
     };
 
     pub static ARIA: StaticVoice = StaticVoice {
         voice_id: "9BWtsMINqrJLrRacOk9x",
         name: "Aria",
-        gender: "female",
+        gender: Gender::Female,
     };
 
     /// Get all available pre-built voices as a vector
     pub fn all() -> Vec<&'static StaticVoice> {
         vec![
             &WILL, &THOMAS, &CHARLIE, &GEORGE, &CALLUM, &LIAM, &CHARLOTTE, &ALICE, &MATILDA,
-            &RACHEL, &DOMI, &BELLA, &ANTONI, &ELLI, &JOSH, &ARNOLD, &ADAM, &SAM,
+            &RACHEL, &DOMI, &BELLA, &ANTONI, &ELLI, &JOSH, &ARNOLD, &ADAM, &SAM, &SERENA, &ROGER,
+            &RIVER, &PAUL, &PATRICK, &NICOLE, &MIMI, &MICHAEL, &MARK, &LILY, &LAURA, &JOSEPH,
+            &JESSIE, &JESSICA, &JEREMY, &JAMES, &IVANA, &HARRY, &GRACE, &GLINDA, &GIOVANNI, &GIGI,
+            &FREYA, &FIN, &ETHAN, &ERIC, &EMILY, &DREW, &DOROTHY, &DAVE, &DANIEL, &CLYDE, &CHRIS,
+            &CASSIDY, &BRIAN, &BILL, &ARIA,
         ]
     }
 
+    /// Get all voices of the given gender
+    pub fn by_gender(gender: Gender) -> Vec<&'static StaticVoice> {
+        all().into_iter().filter(|v| v.gender == gender).collect()
+    }
+
     /// Get all male voices
     pub fn male() -> Vec<&'static StaticVoice> {
-        all().into_iter().filter(|v| v.gender == "male").collect()
+        by_gender(Gender::Male)
     }
 
     /// Get all female voices
     pub fn female() -> Vec<&'static StaticVoice> {
-        all().into_iter().filter(|v| v.gender == "female").collect()
+        by_gender(Gender::Female)
+    }
+
+    /// Get all gender-neutral voices
+    pub fn neutral() -> Vec<&'static StaticVoice> {
+        by_gender(Gender::Neutral)
     }
 
     /// Find a voice by name (case-insensitive)
@@ -359,4 +432,36 @@ pub mod all_voices {
             .into_iter()
             .find(|v| v.name.to_lowercase() == name.to_lowercase())
     }
+
+    /// Search voices by name: exact, prefix, substring, then fuzzy matches,
+    /// ranked best first (ties broken alphabetically)
+    pub fn search(query: &str) -> Vec<VoiceMatch> {
+        let mut matches: Vec<VoiceMatch> = all()
+            .into_iter()
+            .filter_map(|voice| {
+                match_name(query, voice.name).map(|kind| VoiceMatch { voice, kind })
+            })
+            .collect();
+        matches.sort_by(|a, b| a.kind.cmp(&b.kind).then(a.voice.name.cmp(b.voice.name)));
+        matches
+    }
+
+    /// Find a voice by its voice ID
+    pub fn find_by_id(voice_id: &str) -> Option<&'static StaticVoice> {
+        all().into_iter().find(|v| v.voice_id == voice_id)
+    }
+
+    /// Name of the voice with this ID, if it is a pre-built voice
+    pub fn name_for_id(voice_id: &str) -> Option<&'static str> {
+        find_by_id(voice_id).map(|v| v.name)
+    }
+
+    /// Human-readable label for a voice ID, e.g. for logs: the voice name
+    /// for pre-built voices, the ID itself otherwise
+    pub fn display_name(voice_id: &str) -> String {
+        match name_for_id(voice_id) {
+            Some(name) => format!("{} ({})", name, voice_id),
+            None => voice_id.to_string(),
+        }
+    }
 }
@@ -0,0 +1,43 @@
+//! Soft problems collected on responses, for pipelines to surface instead of
+//! finding them in logs
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::validation::ValidationWarning;
+
+/// Something that didn't fail a call but made its result differ from what was
+/// asked for: an adjusted request, an estimate standing in for a measurement,
+/// or a degraded fallback
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Warning {
+    /// The request was adjusted, or found wanting, by validation
+    Validation(ValidationWarning),
+    /// Timings were estimated from the text because the audio couldn't be measured
+    EstimatedTiming { item: String },
+    /// The request was served by a fallback instead of the endpoint asked for
+    FallbackUsed { fallback: String, reason: String },
+}
+
+impl From<ValidationWarning> for Warning {
+    fn from(warning: ValidationWarning) -> Self {
+        Warning::Validation(warning)
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::Validation(warning) => warning.fmt(f),
+            Warning::EstimatedTiming { item } => {
+                write!(f, "Timing of {} estimated from its text", item)
+            }
+            Warning::FallbackUsed { fallback, reason } => {
+                write!(f, "Used {} instead: {}", fallback, reason)
+            }
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! Incremental writing of large outputs to disk, optionally with an index of the
+//! byte offset each dialogue turn starts at, so a server can answer range
+//! requests that start at a given line
+
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio;
+use crate::error::ElevenLabsTTDError;
+
+/// Where one turn's audio sits in a file written by [`ChunkedWriter`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TurnOffset {
+    // Position of the turn among the indexed turns, from 0.
+    pub turn: usize,
+    // Caller-supplied label, e.g. the speaker or the line's text.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    // Byte offset of the turn's first byte in the file.
+    pub offset: u64,
+    pub length: u64,
+}
+
+/// Byte-offset index of the turn boundaries in an audio file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TurnIndex {
+    // Total size of the audio file in bytes.
+    pub size: u64,
+    pub turns: Vec<TurnOffset>,
+}
+
+impl TurnIndex {
+    /// Read an index written next to an audio file by [`ChunkedWriter::finish`]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ElevenLabsTTDError> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    /// Path of the index for an audio file (`scene.mp3` → `scene.index.json`)
+    pub fn sidecar_path<P: AsRef<Path>>(audio_path: P) -> PathBuf {
+        audio_path.as_ref().with_extension("index.json")
+    }
+
+    /// Bytes of `turn` in the file
+    pub fn range(&self, turn: usize) -> Option<Range<u64>> {
+        let entry = self.turns.get(turn)?;
+        Some(entry.offset..entry.offset + entry.length)
+    }
+
+    /// `Range` header value requesting the file from the start of `turn` to the end,
+    /// e.g. "bytes=4096-"
+    pub fn range_header(&self, turn: usize) -> Option<String> {
+        self.range(turn)
+            .map(|range| format!("bytes={}-", range.start))
+    }
+}
+
+/// Writes audio to a file chunk by chunk as it is produced, so long outputs never
+/// have to be held in memory, recording where each turn starts when indexing
+#[derive(Debug)]
+pub struct ChunkedWriter {
+    file: BufWriter<File>,
+    path: PathBuf,
+    // Bytes written so far.
+    offset: u64,
+    // Turns seen so far, when indexing.
+    turns: Option<Vec<TurnOffset>>,
+    // Sample rate of the WAV header to patch on finish, when writing WAV.
+    wav_sample_rate: Option<u32>,
+}
+
+impl ChunkedWriter {
+    /// Create or truncate the file at `path`
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, ElevenLabsTTDError> {
+        let path = path.as_ref().to_path_buf();
+        Ok(Self {
+            file: BufWriter::new(File::create(&path)?),
+            path,
+            offset: 0,
+            turns: None,
+            wav_sample_rate: None,
+        })
+    }
+
+    /// Also write a [`TurnIndex`] next to the file when it is finished
+    pub fn with_index(mut self) -> Self {
+        self.turns.get_or_insert_with(Vec::new);
+        self
+    }
+
+    /// Write a WAV header for 16-bit mono PCM at `sample_rate` before the audio,
+    /// so the chunks that follow are raw PCM. Its sizes aren't known yet, so
+    /// [`Self::finish`] fills them in. Must come before any audio is written.
+    pub fn wav(mut self, sample_rate: u32) -> Result<Self, ElevenLabsTTDError> {
+        if self.offset > 0 {
+            return Err(ElevenLabsTTDError::ValidationError(
+                "The WAV header must be written before any audio".to_string(),
+            ));
+        }
+        self.write_chunk(&audio::wav_header(0, sample_rate, 1))?;
+        self.wav_sample_rate = Some(sample_rate);
+        Ok(self)
+    }
+
+    /// Bytes written so far
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Mark the start of the next turn at the current offset. Bytes written before
+    /// the first turn, such as a WAV header, belong to no turn.
+    pub fn start_turn<S: Into<String>>(&mut self, label: Option<S>) {
+        let offset = self.offset;
+        if let Some(turns) = &mut self.turns {
+            Self::close_last(turns, offset);
+            turns.push(TurnOffset {
+                turn: turns.len(),
+                label: label.map(Into::into),
+                offset,
+                length: 0,
+            });
+        }
+    }
+
+    /// Append a chunk of audio to the current turn
+    pub fn write_chunk(&mut self, chunk: &[u8]) -> Result<(), ElevenLabsTTDError> {
+        self.file.write_all(chunk)?;
+        self.offset += chunk.len() as u64;
+        Ok(())
+    }
+
+    /// Start a turn and write all of its audio
+    pub fn write_turn<S: Into<String>>(
+        &mut self,
+        label: Option<S>,
+        audio: &[u8],
+    ) -> Result<(), ElevenLabsTTDError> {
+        self.start_turn(label);
+        self.write_chunk(audio)
+    }
+
+    /// Flush the file, patch the sizes into its WAV header if it has one and,
+    /// when indexing, write its [`TurnIndex`] to [`TurnIndex::sidecar_path`].
+    /// Returns the index.
+    pub fn finish(mut self) -> Result<Option<TurnIndex>, ElevenLabsTTDError> {
+        if let Some(sample_rate) = self.wav_sample_rate {
+            let header = audio::wav_header(0, sample_rate, 1);
+            let data_len = u32::try_from(self.offset - header.len() as u64).map_err(|_| {
+                ElevenLabsTTDError::ValidationError(
+                    "Audio is too long for a WAV file (over 4 GiB)".to_string(),
+                )
+            })?;
+            self.file.seek(SeekFrom::Start(0))?;
+            self.file
+                .write_all(&audio::wav_header(data_len, sample_rate, 1))?;
+        }
+        self.file.flush()?;
+        let Some(mut turns) = self.turns.take() else {
+            return Ok(None);
+        };
+
+        Self::close_last(&mut turns, self.offset);
+        let index = TurnIndex {
+            size: self.offset,
+            turns,
+        };
+        std::fs::write(
+            TurnIndex::sidecar_path(&self.path),
+            serde_json::to_vec_pretty(&index)?,
+        )?;
+        Ok(Some(index))
+    }
+
+    /// The previous turn runs up to `offset`
+    fn close_last(turns: &mut [TurnOffset], offset: u64) {
+        if let Some(last) = turns.last_mut() {
+            last.length = offset - last.offset;
+        }
+    }
+}
+
+impl Write for ChunkedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.file.write(buf)?;
+        self.offset += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_records_turn_offsets() {
+        let path = std::env::temp_dir().join(format!("ttd-writer-{}.wav", uuid::Uuid::new_v4()));
+        let mut writer = ChunkedWriter::create(&path).unwrap().with_index();
+        writer.write_chunk(&[0u8; 44]).unwrap();
+        writer.write_turn(Some("Alice"), &[1u8; 100]).unwrap();
+        writer.start_turn(None::<String>);
+        writer.write_chunk(&[2u8; 30]).unwrap();
+        writer.write_chunk(&[2u8; 20]).unwrap();
+        let index = writer.finish().unwrap().unwrap();
+
+        assert_eq!(index.size, 194);
+        assert_eq!(index.range(0), Some(44..144));
+        assert_eq!(index.range(1), Some(144..194));
+        assert_eq!(index.turns[0].label.as_deref(), Some("Alice"));
+        assert_eq!(index.range_header(1).as_deref(), Some("bytes=144-"));
+        assert_eq!(index.range(2), None);
+
+        let sidecar = TurnIndex::sidecar_path(&path);
+        assert_eq!(TurnIndex::load(&sidecar).unwrap(), index);
+        assert_eq!(std::fs::read(&path).unwrap().len(), 194);
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&sidecar).unwrap();
+    }
+
+    #[test]
+    fn test_wav_header_is_patched_on_finish() {
+        let path = std::env::temp_dir().join(format!("ttd-writer-{}.wav", uuid::Uuid::new_v4()));
+        let mut writer = ChunkedWriter::create(&path)
+            .unwrap()
+            .with_index()
+            .wav(16000)
+            .unwrap();
+        writer.write_turn(Some("Alice"), &[1u8; 100]).unwrap();
+        writer.write_turn(Some("Bob"), &[2u8; 50]).unwrap();
+        let index = writer.finish().unwrap().unwrap();
+
+        let mut pcm = vec![1u8; 100];
+        pcm.extend([2u8; 50]);
+        assert_eq!(std::fs::read(&path).unwrap(), audio::wav(&pcm, 16000));
+        assert_eq!(index.range(1), Some(144..194));
+
+        let mut late = ChunkedWriter::create(&path).unwrap();
+        late.write_chunk(b"audio").unwrap();
+        assert!(late.wav(16000).is_err());
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(TurnIndex::sidecar_path(&path)).unwrap();
+    }
+
+    #[test]
+    fn test_no_index_by_default() {
+        let path = std::env::temp_dir().join(format!("ttd-writer-{}.mp3", uuid::Uuid::new_v4()));
+        let mut writer = ChunkedWriter::create(&path).unwrap();
+        writer.write_turn(Some("Bob"), b"audio").unwrap();
+        assert!(writer.finish().unwrap().is_none());
+        assert!(!TurnIndex::sidecar_path(&path).exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
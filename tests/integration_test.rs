@@ -1,4 +1,6 @@
-use elevenlabs_ttd::{ElevenLabsTTDClient, ElevenLabsTTDError, models, voices};
+use elevenlabs_ttd::{
+    ElevenLabsTTDClient, ElevenLabsTTDError, Gender, OutputFormat, SubscriptionTier, models, voices,
+};
 
 #[tokio::test]
 async fn test_client_creation() {
@@ -31,7 +33,7 @@ fn test_static_voices() {
     // Test voice constants
     assert_eq!(voices::all_voices::RACHEL.voice_id, "21m00Tcm4TlvDq8ikWAM");
     assert_eq!(voices::all_voices::RACHEL.name, "Rachel");
-    assert_eq!(voices::all_voices::RACHEL.gender, "female");
+    assert_eq!(voices::all_voices::RACHEL.gender, Gender::Female);
 
     // Test voice ID access
     assert_eq!(voices::all_voices::ANTONI.id(), "ErXwobaYiN019PkySvjV");
@@ -42,22 +44,63 @@ fn test_voice_filtering() {
     let all_voices = voices::all_voices::all();
     let male_voices = voices::all_voices::male();
     let female_voices = voices::all_voices::female();
+    let neutral_voices = voices::all_voices::neutral();
 
-    assert!(all_voices.len() > 0);
-    assert!(male_voices.len() > 0);
-    assert!(female_voices.len() > 0);
-    assert_eq!(all_voices.len(), male_voices.len() + female_voices.len());
+    assert!(!all_voices.is_empty());
+    assert!(!male_voices.is_empty());
+    assert!(!female_voices.is_empty());
+    assert!(!neutral_voices.is_empty());
+    assert_eq!(
+        all_voices.len(),
+        male_voices.len() + female_voices.len() + neutral_voices.len()
+    );
 
     // Check that filtering works correctly
     for voice in male_voices {
-        assert_eq!(voice.gender, "male");
+        assert_eq!(voice.gender, Gender::Male);
     }
 
     for voice in female_voices {
-        assert_eq!(voice.gender, "female");
+        assert_eq!(voice.gender, Gender::Female);
     }
 }
 
+#[test]
+fn test_voice_fuzzy_search() {
+    use voices::MatchKind;
+
+    let results = voices::all_voices::search("ja");
+    assert_eq!(results[0].voice.name, "James");
+    assert_eq!(results[0].kind, MatchKind::Prefix);
+
+    let results = voices::all_voices::search("rachle");
+    assert_eq!(results[0].voice.name, "Rachel");
+    assert!(matches!(results[0].kind, MatchKind::Fuzzy { .. }));
+
+    assert_eq!(
+        voices::all_voices::search("Rachel")[0].kind,
+        MatchKind::Exact
+    );
+    assert!(voices::all_voices::search("zzzz").is_empty());
+}
+
+#[test]
+fn test_voice_lookup_by_id() {
+    let rachel = voices::all_voices::RACHEL.voice_id;
+
+    assert_eq!(
+        voices::all_voices::find_by_id(rachel).unwrap().name,
+        "Rachel"
+    );
+    assert_eq!(voices::all_voices::name_for_id(rachel), Some("Rachel"));
+    assert_eq!(
+        voices::all_voices::display_name(rachel),
+        format!("Rachel ({})", rachel)
+    );
+    assert_eq!(voices::all_voices::display_name("custom-id"), "custom-id");
+    assert!(voices::all_voices::find_by_id("custom-id").is_none());
+}
+
 #[test]
 fn test_voice_search() {
     let found = voices::all_voices::find_by_name("Rachel");
@@ -100,3 +143,143 @@ mod mock_tests {
         assert_eq!(true, true);
     }
 }
+
+#[test]
+fn test_api_error_carries_correlation_id() {
+    let error = ElevenLabsTTDError::ApiError {
+        status: 500,
+        message: "Internal error".to_string(),
+        correlation_id: Some("abc-123".to_string()),
+        response: None,
+    };
+    assert_eq!(error.correlation_id(), Some("abc-123"));
+    assert!(format!("{}", error).contains("abc-123"));
+}
+
+#[tokio::test]
+async fn test_sandbox_client_returns_placeholder_audio() {
+    let client = ElevenLabsTTDClient::sandbox();
+    let inputs = vec![elevenlabs_ttd::TTDInput {
+        text: "Hello from the sandbox".to_string(),
+        voice_id: voices::all_voices::RACHEL.voice_id.to_string(),
+    }];
+
+    let first = client
+        .text_to_dialogue(inputs.clone())
+        .output_format(elevenlabs_ttd::OutputFormat::Pcm16000)
+        .execute()
+        .await
+        .unwrap();
+    let second = client
+        .text_to_dialogue(inputs)
+        .output_format(elevenlabs_ttd::OutputFormat::Pcm16000)
+        .execute()
+        .await
+        .unwrap();
+
+    assert!(!first.is_empty());
+    assert_eq!(first.audio, second.audio);
+}
+
+#[test]
+fn test_error_source_chain() {
+    use std::error::Error;
+
+    let io = std::io::Error::new(std::io::ErrorKind::NotFound, "missing index");
+    let error = ElevenLabsTTDError::from(io);
+    assert!(error.source().is_some());
+    assert!(format!("{}", error).contains("missing index"));
+
+    let rate_limited = ElevenLabsTTDError::RateLimitError {
+        retry_after: Some(30),
+        message: "Too many requests".to_string(),
+        correlation_id: None,
+        response: None,
+    };
+    assert_eq!(
+        format!("{}", rate_limited),
+        "Rate limit exceeded (retry in 30s): Too many requests"
+    );
+    assert!(rate_limited.source().is_none());
+}
+
+#[test]
+fn test_error_serializes_redacted() {
+    let error = ElevenLabsTTDError::ApiError {
+        status: 401,
+        message: r#"{"detail":{"status":"invalid_api_key","message":"Key sk_abc123 is invalid"}}"#
+            .to_string(),
+        correlation_id: Some("corr-1".to_string()),
+        response: None,
+    };
+
+    let json = serde_json::to_value(&error).unwrap();
+    assert_eq!(json["kind"], "api_error");
+    assert_eq!(json["status"], 401);
+    assert_eq!(json["code"], "invalid_api_key");
+    assert_eq!(json["correlation_id"], "corr-1");
+    assert!(!json["message"].as_str().unwrap().contains("abc123"));
+    assert!(json.get("retry_after").is_none());
+}
+
+#[test]
+fn test_timeout_error() {
+    let error = ElevenLabsTTDError::Timeout {
+        elapsed: std::time::Duration::from_millis(1500),
+    };
+    assert_eq!(error.kind(), "timeout");
+    assert_eq!(format!("{}", error), "Request timed out after 1500ms");
+}
+
+#[test]
+fn test_format_tier_requirements() {
+    assert_eq!(
+        SubscriptionTier::from_name("growing_business"),
+        Some(SubscriptionTier::Business)
+    );
+    assert_eq!(SubscriptionTier::from_name("unknown"), None);
+
+    assert_eq!(
+        OutputFormat::Pcm44100.required_tier(),
+        SubscriptionTier::Pro
+    );
+    assert!(SubscriptionTier::Creator >= OutputFormat::Mp3_44100_192.required_tier());
+    assert!(SubscriptionTier::Free < OutputFormat::Mp3_44100_192.required_tier());
+}
+
+#[test]
+fn test_wav_formats() {
+    let wav = OutputFormat::Wav { sample_rate: 44100 };
+    assert_eq!(wav.as_str(), "wav_44100");
+    assert_eq!(OutputFormat::from_name("wav_44100"), Some(wav));
+    assert_eq!(wav.api_format(), OutputFormat::Pcm44100);
+    assert_eq!(wav.required_tier(), SubscriptionTier::Pro);
+    assert_eq!(OutputFormat::from_name("wav_11025"), None);
+}
+
+#[tokio::test]
+async fn test_save_with_manifest() {
+    let client = ElevenLabsTTDClient::sandbox();
+    let audio = client
+        .text_to_dialogue(vec![elevenlabs_ttd::TTDInput {
+            text: "Hello".to_string(),
+            voice_id: voices::all_voices::RACHEL.voice_id.to_string(),
+        }])
+        .seed(7)
+        .execute()
+        .await
+        .unwrap();
+
+    let dir = std::env::temp_dir().join(format!("ttd-manifest-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    audio.save_with_manifest(dir.join("scene.mp3")).unwrap();
+
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(dir.join("scene.manifest.json")).unwrap()).unwrap();
+    assert_eq!(manifest["seed"], 7);
+    assert_eq!(manifest["model_id"], "eleven_v3");
+    assert_eq!(manifest["crate_version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(manifest["inputs_hash"].as_str().unwrap().len(), 64);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}